@@ -0,0 +1,14 @@
+-- Add migration script here
+BEGIN;
+
+ALTER TABLE users ADD COLUMN auth_provider VARCHAR(16) NOT NULL DEFAULT 'local';
+
+CREATE TABLE user_departments (
+    user_id INT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    department_id SMALLINT NOT NULL REFERENCES departments(id) ON DELETE CASCADE,
+    PRIMARY KEY (user_id, department_id)
+);
+
+CREATE INDEX idx_user_departments_department_id ON user_departments (department_id);
+
+COMMIT;
@@ -0,0 +1,94 @@
+-- Add migration script here
+BEGIN;
+
+CREATE TABLE roles (
+    id SMALLINT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+    name VARCHAR(32) NOT NULL UNIQUE,
+    -- Place on the old role ladder, still used for ticket visibility
+    -- and for deciding whose role a user may change.
+    level SMALLINT NOT NULL CHECK (level BETWEEN 0 AND 4),
+    is_system BOOLEAN NOT NULL DEFAULT FALSE
+);
+
+INSERT INTO roles (id, name, level, is_system) VALUES
+    (0, 'anonym', 0, TRUE),
+    (1, 'client', 1, TRUE),
+    (2, 'employee', 2, TRUE),
+    (3, 'moderator', 3, TRUE),
+    (4, 'admin', 4, TRUE);
+
+ALTER TABLE roles ALTER COLUMN id RESTART WITH 5;
+
+CREATE TABLE role_permissions (
+    role_id SMALLINT NOT NULL REFERENCES roles(id) ON DELETE CASCADE,
+    permission VARCHAR(64) NOT NULL,
+    PRIMARY KEY (role_id, permission)
+);
+
+INSERT INTO role_permissions (role_id, permission)
+SELECT r.id, p.permission
+FROM roles r
+JOIN (VALUES
+    ('tickets.read', 1),
+    ('messages.write', 1),
+    ('profile.update', 1),
+    ('tokens.manage', 1),
+    ('notifications.read', 1),
+    ('tickets.update', 2),
+    ('tickets.assign', 2),
+    ('tickets.metrics', 2),
+    ('messages.delete', 2),
+    ('ticket_assets.manage', 2),
+    ('users.read', 2),
+    ('users.stats', 2),
+    ('users.change_status', 2),
+    ('pages.write', 2),
+    ('pages.publish', 2),
+    ('assets.read', 2),
+    ('assets.write', 2),
+    ('reports.generate', 2),
+    ('tickets.assign_others', 3),
+    ('users.manage_status', 3),
+    ('tags.write', 3),
+    ('tickets.delete', 4),
+    ('users.invite', 4),
+    ('users.manage', 4),
+    ('users.change_role', 4),
+    ('users.transfer_admin', 4),
+    ('roles.manage', 4),
+    ('departments.write', 4),
+    ('buildings.write', 4),
+    ('system_notifications.write', 4)
+) AS p(permission, min_level) ON r.level >= p.min_level
+WHERE r.is_system;
+
+ALTER TABLE users ADD COLUMN role_id SMALLINT REFERENCES roles(id);
+
+UPDATE users SET role_id = role;
+
+ALTER TABLE users ALTER COLUMN role_id SET NOT NULL;
+
+CREATE INDEX idx_users_role_id ON users (role_id);
+
+-- Keeps users.role equal to the level of users.role_id, so code that
+-- only sets one of them stays correct.
+CREATE OR REPLACE FUNCTION sync_user_role()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'INSERT' AND NEW.role_id IS NULL THEN
+        NEW.role_id := NEW.role;
+    ELSIF TG_OP = 'INSERT' OR NEW.role_id IS DISTINCT FROM OLD.role_id THEN
+        SELECT level INTO NEW.role FROM roles WHERE id = NEW.role_id;
+    ELSIF NEW.role IS DISTINCT FROM OLD.role THEN
+        NEW.role_id := NEW.role;
+    END IF;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trg_sync_user_role
+BEFORE INSERT OR UPDATE OF role, role_id ON users
+FOR EACH ROW EXECUTE FUNCTION sync_user_role();
+
+COMMIT;
@@ -0,0 +1,18 @@
+-- Add migration script here
+BEGIN;
+
+-- 0 - member, 1 - moderator
+ALTER TABLE user_departments ADD COLUMN role SMALLINT NOT NULL DEFAULT 0;
+
+-- Staff without memberships keep access to the tickets they could see before.
+INSERT INTO user_departments (user_id, department_id)
+SELECT u.id, d.id
+FROM users u
+CROSS JOIN departments d
+WHERE u.role IN (2, 3)
+    AND NOT EXISTS (SELECT 1 FROM user_departments ud WHERE ud.user_id = u.id)
+ON CONFLICT DO NOTHING;
+
+INSERT INTO role_permissions (role_id, permission) VALUES (4, 'tickets.all_departments');
+
+COMMIT;
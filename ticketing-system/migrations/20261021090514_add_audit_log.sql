@@ -0,0 +1,38 @@
+-- Add migration script here
+BEGIN;
+
+-- No foreign keys on users: entries have to outlive the accounts they mention.
+CREATE TABLE audit_log (
+    id BIGINT PRIMARY KEY GENERATED ALWAYS AS IDENTITY,
+    actor_id INT,
+    target_id INT,
+    action VARCHAR(64) NOT NULL,
+    ip VARCHAR(64),
+    user_agent VARCHAR(256),
+    details JSONB NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_audit_log_created_at ON audit_log (created_at);
+CREATE INDEX idx_audit_log_actor_id ON audit_log (actor_id);
+CREATE INDEX idx_audit_log_target_id ON audit_log (target_id);
+CREATE INDEX idx_audit_log_action ON audit_log (action);
+
+CREATE FUNCTION audit_log_append_only() RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION 'audit_log is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER audit_log_append_only
+BEFORE UPDATE OR DELETE ON audit_log
+FOR EACH ROW EXECUTE FUNCTION audit_log_append_only();
+
+CREATE TRIGGER audit_log_no_truncate
+BEFORE TRUNCATE ON audit_log
+FOR EACH STATEMENT EXECUTE FUNCTION audit_log_append_only();
+
+INSERT INTO role_permissions (role_id, permission)
+VALUES (4, 'audit.read');
+
+COMMIT;
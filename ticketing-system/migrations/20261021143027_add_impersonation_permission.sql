@@ -0,0 +1,7 @@
+-- Add migration script here
+BEGIN;
+
+INSERT INTO role_permissions (role_id, permission)
+VALUES (4, 'users.impersonate');
+
+COMMIT;
@@ -0,0 +1,13 @@
+-- Add migration script here
+BEGIN;
+
+CREATE TABLE app_settings (
+    key VARCHAR(64) PRIMARY KEY,
+    value JSONB NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+INSERT INTO app_settings (key, value)
+VALUES ('signup_enabled', 'false');
+
+COMMIT;
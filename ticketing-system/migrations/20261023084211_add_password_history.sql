@@ -0,0 +1,16 @@
+-- Add migration script here
+BEGIN;
+
+ALTER TABLE users
+ADD COLUMN password_changed_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+
+CREATE TABLE password_history (
+    id BIGINT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    user_id INT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    password_hash VARCHAR(100) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX password_history_user_id_idx ON password_history (user_id, id DESC);
+
+COMMIT;
@@ -0,0 +1,23 @@
+-- Add migration script here
+BEGIN;
+
+CREATE TABLE user_absences (
+    id INT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    user_id INT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    -- users.status while the absence lasts: sick, vacation or busy
+    kind SMALLINT NOT NULL CHECK (kind BETWEEN 1 AND 3),
+    starts_at TIMESTAMPTZ NOT NULL,
+    ends_at TIMESTAMPTZ NOT NULL,
+    deputy_id INT REFERENCES users(id) ON DELETE SET NULL,
+    reassign_tickets BOOLEAN NOT NULL DEFAULT FALSE,
+    started BOOLEAN NOT NULL DEFAULT FALSE,
+    finished BOOLEAN NOT NULL DEFAULT FALSE,
+    created_by INT REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (starts_at < ends_at)
+);
+
+CREATE INDEX idx_user_absences_user_id ON user_absences (user_id, starts_at DESC);
+CREATE INDEX idx_user_absences_pending ON user_absences (starts_at, ends_at) WHERE NOT finished;
+
+COMMIT;
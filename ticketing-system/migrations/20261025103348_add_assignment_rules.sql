@@ -0,0 +1,33 @@
+-- Add migration script here
+BEGIN;
+
+CREATE TABLE assignment_rules (
+    id INT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    name VARCHAR(64) NOT NULL,
+    -- Rules are tried in ascending order, the first one with an available assignee wins
+    position INT NOT NULL DEFAULT 0,
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    department_id SMALLINT REFERENCES departments(id) ON DELETE CASCADE,
+    building_id SMALLINT REFERENCES buildings(id) ON DELETE CASCADE,
+    priority SMALLINT CHECK (priority BETWEEN 0 AND 3),
+    -- Matched case-insensitively against the title and description, any keyword is enough
+    keywords TEXT[] NOT NULL DEFAULT '{}',
+    strategy SMALLINT NOT NULL CHECK (strategy BETWEEN 0 AND 1),
+    -- Round-robin position
+    last_assigned_to INT REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_assignment_rules_position ON assignment_rules (position, id) WHERE is_active;
+
+-- Candidates of a rule. Without rows every member of the ticket department is a candidate.
+CREATE TABLE assignment_rule_users (
+    rule_id INT NOT NULL REFERENCES assignment_rules(id) ON DELETE CASCADE,
+    user_id INT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    PRIMARY KEY (rule_id, user_id)
+);
+
+INSERT INTO role_permissions (role_id, permission)
+VALUES (4, 'assignment_rules.manage');
+
+COMMIT;
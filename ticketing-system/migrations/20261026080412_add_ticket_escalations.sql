@@ -0,0 +1,15 @@
+-- Add migration script here
+BEGIN;
+
+-- Every escalation policy fires once per ticket, `policy` is its name from the configuration.
+CREATE TABLE ticket_escalations (
+    ticket_id BIGINT NOT NULL REFERENCES tickets(id) ON DELETE CASCADE,
+    policy VARCHAR(64) NOT NULL,
+    escalated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (ticket_id, policy)
+);
+
+CREATE INDEX idx_tickets_first_response_pending ON tickets (created_at)
+WHERE first_response_at IS NULL;
+
+COMMIT;
@@ -0,0 +1,23 @@
+-- Add migration script here
+BEGIN;
+
+-- `ticket_id` duplicates, relates to or blocks `linked_ticket_id`.
+CREATE TABLE ticket_links (
+    ticket_id BIGINT NOT NULL REFERENCES tickets(id) ON DELETE CASCADE,
+    linked_ticket_id BIGINT NOT NULL REFERENCES tickets(id) ON DELETE CASCADE,
+    kind SMALLINT NOT NULL CHECK (kind BETWEEN 0 AND 2),
+    created_by INT REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (ticket_id, linked_ticket_id),
+    CHECK (ticket_id <> linked_ticket_id)
+);
+
+-- A pair of tickets has at most one link, whatever its direction.
+CREATE UNIQUE INDEX idx_ticket_links_pair ON ticket_links (
+    LEAST(ticket_id, linked_ticket_id),
+    GREATEST(ticket_id, linked_ticket_id)
+);
+
+CREATE INDEX idx_ticket_links_linked_ticket_id ON ticket_links (linked_ticket_id);
+
+COMMIT;
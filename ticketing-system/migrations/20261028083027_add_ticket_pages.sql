@@ -0,0 +1,17 @@
+-- Add migration script here
+BEGIN;
+
+-- Knowledge base articles that solve a ticket.
+CREATE TABLE ticket_pages (
+    ticket_id BIGINT NOT NULL REFERENCES tickets(id) ON DELETE CASCADE,
+    page_id INT NOT NULL REFERENCES pages(id) ON DELETE CASCADE,
+    added_by INT REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (ticket_id, page_id)
+);
+
+CREATE INDEX idx_ticket_pages_page_id ON ticket_pages (page_id);
+
+CREATE INDEX idx_pages_text_fts ON pages USING GIN (to_tsvector('russian', text));
+
+COMMIT;
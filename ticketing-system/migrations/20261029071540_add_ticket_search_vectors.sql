@@ -0,0 +1,18 @@
+-- Add migration script here
+BEGIN;
+
+ALTER TABLE tickets ADD COLUMN search_vector tsvector GENERATED ALWAYS AS (
+    setweight(to_tsvector('russian', title), 'A')
+    || setweight(to_tsvector('russian', description), 'B')
+    || setweight(to_tsvector('russian', author || ' ' || COALESCE(cabinet, '')), 'C')
+) STORED;
+
+CREATE INDEX idx_tickets_search_vector ON tickets USING GIN (search_vector);
+
+ALTER TABLE ticket_messages ADD COLUMN search_vector tsvector GENERATED ALWAYS AS (
+    to_tsvector('russian', message_text)
+) STORED;
+
+CREATE INDEX idx_ticket_messages_search_vector ON ticket_messages USING GIN (search_vector);
+
+COMMIT;
@@ -0,0 +1,25 @@
+-- Add migration script here
+BEGIN;
+
+-- Saved `GET /v1/tickets/` filters, optionally shared with a department.
+CREATE TABLE ticket_views (
+    id INT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    owner_id INT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name VARCHAR(64) NOT NULL,
+    filters JSONB NOT NULL,
+    department_id SMALLINT REFERENCES departments(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_ticket_views_owner_id ON ticket_views (owner_id);
+CREATE INDEX idx_ticket_views_department_id ON ticket_views (department_id) WHERE department_id IS NOT NULL;
+
+-- When each user last opened a view, for the count of new tickets.
+CREATE TABLE ticket_view_visits (
+    view_id INT NOT NULL REFERENCES ticket_views(id) ON DELETE CASCADE,
+    user_id INT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    visited_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (view_id, user_id)
+);
+
+COMMIT;
@@ -0,0 +1,13 @@
+-- Add migration script here
+BEGIN;
+
+INSERT INTO role_permissions (role_id, permission)
+SELECT r.id, p.permission
+FROM roles r
+JOIN (VALUES
+    ('messages.read_internal', 2),
+    ('messages.delete_others', 2),
+    ('pages.read_private', 2)
+) AS p(permission, min_level) ON r.level >= p.min_level;
+
+COMMIT;
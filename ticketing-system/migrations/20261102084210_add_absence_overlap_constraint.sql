@@ -0,0 +1,12 @@
+-- Add migration script here
+BEGIN;
+
+CREATE EXTENSION IF NOT EXISTS btree_gist;
+
+-- Concurrent requests can both pass the overlap check in the handler.
+ALTER TABLE user_absences
+    ADD CONSTRAINT user_absences_no_overlap
+    EXCLUDE USING gist (user_id WITH =, tstzrange(starts_at, ends_at) WITH &&)
+    WHERE (NOT finished);
+
+COMMIT;
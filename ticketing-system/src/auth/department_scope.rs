@@ -0,0 +1,116 @@
+use sqlx::PgPool;
+
+use crate::{auth::{permission::{Permission, Permissions}, types::DepartmentRole}, schema::{common::UserId, tickets::TicketId}};
+
+pub type DepartmentId = i16;
+
+/// Departments whose tickets a staff member works with.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DepartmentScope {
+    All,
+    Departments(Vec<DepartmentId>),
+}
+
+impl DepartmentScope {
+    #[tracing::instrument(name = "Resolve department scope", skip(pool, permissions))]
+    pub async fn resolve(
+        pool: &PgPool,
+        user_id: UserId,
+        permissions: &Permissions,
+    ) -> Result<Self, sqlx::Error> {
+        if permissions.has(Permission::TicketsAllDepartments) {
+            return Ok(Self::All);
+        }
+
+        let departments = sqlx::query_scalar!(
+            "SELECT department_id FROM user_departments WHERE user_id = $1",
+            user_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(Self::Departments(departments))
+    }
+
+    pub fn contains(&self, department_id: DepartmentId) -> bool {
+        match self {
+            Self::All => true,
+            Self::Departments(departments) => departments.contains(&department_id),
+        }
+    }
+}
+
+/// Tickets outside of the scope stay available to their authors and assignees.
+#[tracing::instrument(name = "Check ticket access", skip(pool))]
+pub async fn can_access_ticket(
+    pool: &PgPool,
+    scope: &DepartmentScope,
+    user_id: UserId,
+    ticket_id: TicketId,
+) -> Result<Option<bool>, sqlx::Error> {
+    let ticket = sqlx::query!(
+        r#"
+            SELECT
+                department_id,
+                author_id IS NOT DISTINCT FROM $2
+                    OR EXISTS (
+                        SELECT 1 FROM tickets_users
+                        WHERE ticket_id = t.id AND assigned_to = $2
+                    ) AS "is_involved!"
+            FROM tickets t
+            WHERE id = $1
+        "#,
+        ticket_id,
+        user_id
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(ticket.map(|ticket| ticket.is_involved || scope.contains(ticket.department_id)))
+}
+
+#[tracing::instrument(name = "Get ticket department", skip(pool))]
+pub async fn get_ticket_department(
+    pool: &PgPool,
+    ticket_id: TicketId,
+) -> Result<Option<DepartmentId>, sqlx::Error> {
+    sqlx::query_scalar!(
+        "SELECT department_id FROM tickets WHERE id = $1",
+        ticket_id
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+#[tracing::instrument(name = "Get department role", skip(pool))]
+pub async fn get_department_role(
+    pool: &PgPool,
+    user_id: UserId,
+    department_id: DepartmentId,
+) -> Result<Option<DepartmentRole>, sqlx::Error> {
+    let role = sqlx::query_scalar!(
+        "SELECT role FROM user_departments WHERE user_id = $1 AND department_id = $2",
+        user_id,
+        department_id
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(role.map(DepartmentRole::from))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DepartmentScope;
+
+    #[test]
+    fn scope_contains_member_departments() {
+        let scope = DepartmentScope::Departments(vec![1, 3]);
+
+        assert!(scope.contains(1));
+        assert!(scope.contains(3));
+        assert!(!scope.contains(2));
+        assert!(DepartmentScope::All.contains(2));
+        assert!(!DepartmentScope::Departments(vec![]).contains(1));
+    }
+}
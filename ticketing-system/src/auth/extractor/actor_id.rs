@@ -0,0 +1,23 @@
+use std::future::{ready, Ready};
+
+use actix_web::{FromRequest, HttpMessage, HttpRequest};
+
+use crate::{auth::{extractor::JwtExtractorError, jwt::Claims}, schema::common::UserId};
+
+/// Id of the admin impersonating the user, if any.
+pub struct ActorIdExtractor(pub Option<UserId>);
+
+impl FromRequest for ActorIdExtractor {
+    type Error = JwtExtractorError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut actix_web::dev::Payload) -> Self::Future {
+        let result = req
+            .extensions()
+            .get::<Claims>()
+            .ok_or(JwtExtractorError::MissingClaims)
+            .map(|claims| Self(claims.actor_id()));
+
+        ready(result)
+    }
+}
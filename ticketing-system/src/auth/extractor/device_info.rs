@@ -0,0 +1,104 @@
+use std::{convert::Infallible, future::{ready, Ready}, net::IpAddr};
+
+use actix_web::{http::header::USER_AGENT, web::Data, FromRequest, HttpRequest};
+use sqlx::types::ipnetwork::IpNetwork;
+
+use crate::startup::TrustedProxies;
+
+/// Client metadata stored with refresh tokens.
+#[derive(Debug, Clone, Default)]
+pub struct DeviceInfo {
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+}
+
+impl FromRequest for DeviceInfo {
+    type Error = Infallible;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut actix_web::dev::Payload) -> Self::Future {
+        let user_agent = req
+            .headers()
+            .get(USER_AGENT)
+            .and_then(|h| h.to_str().ok())
+            .map(|s| s.chars().take(256).collect());
+
+        let trusted_proxies = req
+            .app_data::<Data<TrustedProxies>>()
+            .map(|proxies| proxies.0.as_slice())
+            .unwrap_or_default();
+
+        let forwarded_for = req
+            .headers()
+            .get_all("X-Forwarded-For")
+            .filter_map(|h| h.to_str().ok())
+            .collect::<Vec<_>>()
+            .join(",");
+
+        let ip = req
+            .peer_addr()
+            .map(|peer| client_ip(peer.ip(), &forwarded_for, trusted_proxies).to_string());
+
+        ready(Ok(DeviceInfo { user_agent, ip }))
+    }
+}
+
+/// The peer address, unless it is a trusted proxy. Then `X-Forwarded-For` is read
+/// from the right, skipping trusted hops, so a client cannot prepend its own entries.
+fn client_ip(peer: IpAddr, forwarded_for: &str, trusted_proxies: &[IpNetwork]) -> IpAddr {
+    let is_trusted = |ip: IpAddr| trusted_proxies.iter().any(|network| network.contains(ip));
+
+    let mut client = peer;
+
+    if !is_trusted(client) {
+        return client;
+    }
+
+    for hop in forwarded_for.rsplit(',').map(str::trim).filter(|hop| !hop.is_empty()) {
+        let Ok(hop) = hop.parse::<IpAddr>() else {
+            break;
+        };
+
+        client = hop;
+
+        if !is_trusted(client) {
+            break;
+        }
+    }
+
+    client
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    fn network(s: &str) -> IpNetwork {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn forwarded_for_is_ignored_from_untrusted_peer() {
+        let client = client_ip(ip("203.0.113.7"), "10.0.0.1", &[network("127.0.0.1")]);
+
+        assert_eq!(client, ip("203.0.113.7"));
+    }
+
+    #[test]
+    fn forwarded_for_is_read_from_the_right_behind_trusted_proxies() {
+        let trusted = [network("127.0.0.1"), network("172.16.0.0/12")];
+
+        let client = client_ip(ip("127.0.0.1"), "1.1.1.1, 203.0.113.7, 172.18.0.2", &trusted);
+        assert_eq!(client, ip("203.0.113.7"));
+
+        let client = client_ip(ip("127.0.0.1"), "", &trusted);
+        assert_eq!(client, ip("127.0.0.1"));
+
+        let client = client_ip(ip("127.0.0.1"), "garbage, 172.18.0.2", &trusted);
+        assert_eq!(client, ip("172.18.0.2"));
+    }
+}
@@ -1,8 +1,16 @@
 pub mod user_id;
 pub mod user_role;
+pub mod session_id;
+pub mod device_info;
+pub mod permissions;
+pub mod actor_id;
 
 pub use user_id::UserIdExtractor;
 pub use user_role::UserRoleExtractor;
+pub use session_id::SessionIdExtractor;
+pub use device_info::DeviceInfo;
+pub use permissions::PermissionsExtractor;
+pub use actor_id::ActorIdExtractor;
 
 use actix_web::{http::StatusCode, ResponseError};
 
@@ -0,0 +1,34 @@
+use std::future::{ready, Ready};
+
+use actix_web::{FromRequest, HttpMessage, HttpRequest};
+
+use crate::auth::{extractor::JwtExtractorError, permission::Permissions};
+
+pub struct PermissionsExtractor(pub Permissions);
+
+impl FromRequest for PermissionsExtractor {
+    type Error = JwtExtractorError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut actix_web::dev::Payload) -> Self::Future {
+        let result = req
+            .extensions()
+            .get::<Permissions>()
+            .cloned()
+            .ok_or(Self::Error::MissingClaims)
+            .map(Self);
+
+        ready(result)
+    }
+}
+
+pub struct OptionalPermissionsExtractor(pub Option<Permissions>);
+
+impl FromRequest for OptionalPermissionsExtractor {
+    type Error = JwtExtractorError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut actix_web::dev::Payload) -> Self::Future {
+        ready(Ok(Self(req.extensions().get::<Permissions>().cloned())))
+    }
+}
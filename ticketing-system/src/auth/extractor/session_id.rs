@@ -0,0 +1,24 @@
+use std::future::{ready, Ready};
+
+use actix_web::{FromRequest, HttpMessage, HttpRequest};
+
+use crate::auth::{extractor::JwtExtractorError, jwt::Claims};
+
+/// Session of the refresh token the access token was issued with.
+/// `None` for tokens that are not bound to a session.
+pub struct SessionIdExtractor(pub Option<String>);
+
+impl FromRequest for SessionIdExtractor {
+    type Error = JwtExtractorError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut actix_web::dev::Payload) -> Self::Future {
+        let result = req
+            .extensions()
+            .get::<Claims>()
+            .ok_or(JwtExtractorError::MissingClaims)
+            .map(|claims| SessionIdExtractor(claims.sid.clone()));
+
+        ready(result)
+    }
+}
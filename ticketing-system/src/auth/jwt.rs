@@ -1,12 +1,16 @@
-use std::fs;
+use std::{collections::HashMap, fs, path::Path};
 
 use anyhow::Context as _;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
 use chrono::{Duration, Utc};
-use jsonwebtoken::{decode_header, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use jsonwebtoken::{decode_header, jwk::{AlgorithmParameters, CommonParameters, Jwk, JwkSet, KeyAlgorithm, PublicKeyUse, RSAKeyParameters, RSAKeyType}, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use rsa::{pkcs1::DecodeRsaPublicKey, pkcs8::DecodePublicKey, traits::PublicKeyParts, RsaPublicKey};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::{auth::types::UserRole, config::AuthSettings};
+use crate::{auth::types::UserRole, config::AuthSettings, schema::common::UserId};
+
+const IMPERSONATION_TOKEN_LIFETIME: Duration = Duration::minutes(15);
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Claims {
@@ -16,11 +20,41 @@ pub struct Claims {
     pub iss: String,
     pub jti: String,
     pub role: UserRole,
+    /// Role the permissions are taken from. Tokens issued before roles
+    /// were editable only carry `role`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rid: Option<i16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<String>,
+    /// The admin acting as `sub`, see RFC 8693.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub act: Option<Actor>,
+    /// Set when the request is authenticated with a personal access token.
+    #[serde(skip)]
+    pub token_id: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Actor {
+    pub sub: String,
+}
+
+impl Claims {
+    /// Built-in roles share their id with the `UserRole` value.
+    pub fn role_id(&self) -> i16 {
+        self.rid.unwrap_or(self.role as i16)
+    }
+
+    pub fn actor_id(&self) -> Option<UserId> {
+        self.act.as_ref().and_then(|act| act.sub.parse().ok())
+    }
 }
 
 pub struct JwtService {
+    key_id: String,
     encoding_key: EncodingKey,
-    decoding_key: DecodingKey,
+    decoding_keys: HashMap<String, DecodingKey>,
+    jwks: JwkSet,
     issuer: String,
     pub access_token_lifetime: Duration,
     validation: Validation,
@@ -32,14 +66,38 @@ impl JwtService {
         
         let public_key = fs::read(&auth_settings.public_key_path)?;
 
+        let mut decoding_keys = HashMap::new();
+        let mut jwks = Vec::new();
+
+        let (decoding_key, jwk) = load_public_key(&auth_settings.key_id, &public_key)?;
+        decoding_keys.insert(auth_settings.key_id.clone(), decoding_key);
+        jwks.push(jwk);
+
+        // Retired keys are kept only for verification, so tokens issued
+        // before the rotation stay valid until they expire.
+        if let Some(dir) = &auth_settings.verification_keys_dir {
+            for (kid, public_key) in read_verification_keys(Path::new(dir))? {
+                anyhow::ensure!(
+                    !decoding_keys.contains_key(&kid),
+                    "Duplicate key id: {}", kid
+                );
+
+                let (decoding_key, jwk) = load_public_key(&kid, &public_key)?;
+                decoding_keys.insert(kid, decoding_key);
+                jwks.push(jwk);
+            }
+        }
+
         let mut validation = Validation::new(Algorithm::RS256);
         validation.validate_exp = true;
         validation.validate_nbf = true;
         validation.set_issuer(&[&auth_settings.issuer]);
         
         Ok(Self {
+            key_id: auth_settings.key_id.clone(),
             encoding_key: EncodingKey::from_rsa_pem(&private_key)?,
-            decoding_key: DecodingKey::from_rsa_pem(&public_key)?,
+            decoding_keys,
+            jwks: JwkSet { keys: jwks },
             issuer: auth_settings.issuer.clone(),
             access_token_lifetime: Duration::from_std(auth_settings.access_token_lifetime)?,
             validation
@@ -50,6 +108,8 @@ impl JwtService {
         &self,
         user_id: i32,
         role: UserRole,
+        role_id: i16,
+        session_id: Option<&str>,
     ) -> Result<String, JwtError> {
         let now = Utc::now();
         let expiry = now + self.access_token_lifetime;
@@ -60,14 +120,54 @@ impl JwtService {
             iat: now.timestamp(),
             iss: self.issuer.clone(),
             jti: Uuid::new_v4().to_string(),
-            role
+            role,
+            rid: Some(role_id),
+            sid: session_id.map(ToOwned::to_owned),
+            act: None,
+            token_id: None,
+        };
+
+        self.encode(&claims)
+    }
+
+    /// Access token for `user_id` that keeps track of the admin using it.
+    /// It has no session, so it can not be refreshed.
+    pub fn create_impersonation_token(
+        &self,
+        user_id: i32,
+        role: UserRole,
+        role_id: i16,
+        actor_id: i32,
+    ) -> Result<String, JwtError> {
+        let now = Utc::now();
+        let expiry = now + self.impersonation_token_lifetime();
+
+        let claims = Claims {
+            sub: user_id.to_string(),
+            exp: expiry.timestamp(),
+            iat: now.timestamp(),
+            iss: self.issuer.clone(),
+            jti: Uuid::new_v4().to_string(),
+            role,
+            rid: Some(role_id),
+            sid: None,
+            act: Some(Actor { sub: actor_id.to_string() }),
+            token_id: None,
         };
 
+        self.encode(&claims)
+    }
+
+    pub fn impersonation_token_lifetime(&self) -> Duration {
+        self.access_token_lifetime.min(IMPERSONATION_TOKEN_LIFETIME)
+    }
+
+    fn encode(&self, claims: &Claims) -> Result<String, JwtError> {
         let mut header = Header::new(Algorithm::RS256);
-        header.kid = Some("default-key-1".to_string());
+        header.kid = Some(self.key_id.clone());
         
         Ok(
-            jsonwebtoken::encode(&header, &claims, &self.encoding_key)
+            jsonwebtoken::encode(&header, claims, &self.encoding_key)
                 .context("Failed to encode token.")?
         )
     }
@@ -76,11 +176,12 @@ impl JwtService {
         let header = decode_header(token)
             .map_err(|e| JwtError::InvalidToken(format!("Invalid header: {}", e)))?;
 
-        let _kid = header.kid
+        let kid = header.kid
             .ok_or_else(|| JwtError::InvalidToken("Missing kid in header".to_string()))?;
 
-        // TODO: Implement key rotation mechanism
-        let key = &self.decoding_key;
+        let key = self.decoding_keys
+            .get(&kid)
+            .ok_or(JwtError::KeyNotFound(kid))?;
 
         jsonwebtoken::decode::<Claims>(token, key, &self.validation)
             .map(|data| data.claims)
@@ -91,6 +192,68 @@ impl JwtService {
                 _ => JwtError::InvalidToken("Failed to decode token".to_string())
             })
     }
+
+    pub fn jwks(&self) -> &JwkSet {
+        &self.jwks
+    }
+}
+
+// Every `<kid>.pem` file in the directory is a verify-only public key.
+fn read_verification_keys(dir: &Path) -> anyhow::Result<Vec<(String, Vec<u8>)>> {
+    let mut keys = Vec::new();
+
+    let entries = fs::read_dir(dir)
+        .with_context(|| format!("Failed to read verification keys dir {}", dir.display()))?;
+
+    for entry in entries {
+        let path = entry?.path();
+
+        if path.extension().and_then(|ext| ext.to_str()) != Some("pem") {
+            continue;
+        }
+
+        let kid = path.file_stem()
+            .and_then(|stem| stem.to_str())
+            .with_context(|| format!("Invalid key file name {}", path.display()))?
+            .to_string();
+
+        keys.push((kid, fs::read(&path)?));
+    }
+
+    keys.sort_by(|a, b| a.0.cmp(&b.0));
+
+    Ok(keys)
+}
+
+fn load_public_key(kid: &str, pem: &[u8]) -> anyhow::Result<(DecodingKey, Jwk)> {
+    let decoding_key = DecodingKey::from_rsa_pem(pem)
+        .with_context(|| format!("Invalid public key for kid {}", kid))?;
+
+    // Same PEM flavours `from_rsa_pem` accepts: SPKI and PKCS#1.
+    let public_key = std::str::from_utf8(pem)
+        .ok()
+        .and_then(|pem| {
+            RsaPublicKey::from_public_key_pem(pem)
+                .or_else(|_| RsaPublicKey::from_pkcs1_pem(pem))
+                .ok()
+        })
+        .with_context(|| format!("Failed to parse public key for kid {}", kid))?;
+
+    let jwk = Jwk {
+        common: CommonParameters {
+            public_key_use: Some(PublicKeyUse::Signature),
+            key_algorithm: Some(KeyAlgorithm::RS256),
+            key_id: Some(kid.to_string()),
+            ..Default::default()
+        },
+        algorithm: AlgorithmParameters::RSA(RSAKeyParameters {
+            key_type: RSAKeyType::RSA,
+            n: URL_SAFE_NO_PAD.encode(public_key.n().to_bytes_be()),
+            e: URL_SAFE_NO_PAD.encode(public_key.e().to_bytes_be()),
+        }),
+    };
+
+    Ok((decoding_key, jwk))
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -105,7 +268,7 @@ pub enum JwtError {
 
 #[cfg(test)]
 mod tests {
-    use std::{fs, time::Duration as StdDuration};
+    use std::{fs, path::Path, time::Duration as StdDuration};
 
     use super::*;
     use crate::config::AuthSettings;
@@ -151,7 +314,11 @@ g/R07+N0Qsx1oPGBwU6LvOt9G0i9zSS8lDQL7mRfYYO+suqN0Sm3Hi2gPb3AGNwI
 9wIDAQAB
 -----END PUBLIC KEY-----"#;
 
-    fn create_test_service(access_token_lifetime: StdDuration) -> JwtService {
+    fn create_test_service_with_keys(
+        access_token_lifetime: StdDuration,
+        key_id: &str,
+        verification_keys_dir: Option<&Path>,
+    ) -> JwtService {
         let temp_dir = std::env::temp_dir().join(format!("ticketing-system-jwt-{}", Uuid::new_v4()));
         fs::create_dir_all(&temp_dir).unwrap();
 
@@ -167,24 +334,49 @@ g/R07+N0Qsx1oPGBwU6LvOt9G0i9zSS8lDQL7mRfYYO+suqN0Sm3Hi2gPb3AGNwI
             private_key_path: private_key_path.to_string_lossy().to_string(),
             public_key_path: public_key_path.to_string_lossy().to_string(),
             issuer: "test-issuer".to_string(),
+            key_id: key_id.to_string(),
+            verification_keys_dir: verification_keys_dir.map(|dir| dir.to_string_lossy().to_string()),
+            two_factor_required_role: None,
         };
 
         JwtService::new(&auth_settings).unwrap()
     }
 
+    fn create_test_service(access_token_lifetime: StdDuration) -> JwtService {
+        create_test_service_with_keys(access_token_lifetime, "test-key", None)
+    }
+
     #[test]
     fn create_and_validate_access_token_roundtrip() {
         let service = create_test_service(StdDuration::from_secs(60));
 
-        let token = service.create_access_token(42, UserRole::Admin).unwrap();
+        let token = service.create_access_token(42, UserRole::Admin, 7, Some("session")).unwrap();
         let claims = service.validate_token(&token).unwrap();
 
         assert_eq!(claims.sub, "42");
         assert_eq!(claims.iss, "test-issuer");
         assert_eq!(claims.role, UserRole::Admin);
+        assert_eq!(claims.rid, Some(7));
+        assert_eq!(claims.sid.as_deref(), Some("session"));
         assert!(claims.exp > claims.iat);
     }
 
+    #[test]
+    fn impersonation_token_carries_actor() {
+        let service = create_test_service(StdDuration::from_secs(60 * 60));
+
+        let token = service.create_impersonation_token(42, UserRole::Client, 1, 7).unwrap();
+        let claims = service.validate_token(&token).unwrap();
+
+        assert_eq!(claims.sub, "42");
+        assert_eq!(claims.actor_id(), Some(7));
+        assert_eq!(claims.sid, None);
+        assert_eq!(claims.exp - claims.iat, IMPERSONATION_TOKEN_LIFETIME.num_seconds());
+
+        let token = service.create_access_token(42, UserRole::Client, 1, None).unwrap();
+        assert_eq!(service.validate_token(&token).unwrap().actor_id(), None);
+    }
+
     #[test]
     fn validate_token_rejects_invalid_header() {
         let service = create_test_service(StdDuration::from_secs(60));
@@ -205,6 +397,10 @@ g/R07+N0Qsx1oPGBwU6LvOt9G0i9zSS8lDQL7mRfYYO+suqN0Sm3Hi2gPb3AGNwI
             iss: "test-issuer".to_string(),
             jti: Uuid::new_v4().to_string(),
             role: UserRole::Client,
+            rid: None,
+            sid: None,
+            act: None,
+            token_id: None,
         };
 
         let mut header = Header::new(Algorithm::RS256);
@@ -224,9 +420,53 @@ g/R07+N0Qsx1oPGBwU6LvOt9G0i9zSS8lDQL7mRfYYO+suqN0Sm3Hi2gPb3AGNwI
             ..service
         };
 
-        let token = expired_service.create_access_token(100, UserRole::Employee).unwrap();
+        let token = expired_service.create_access_token(100, UserRole::Employee, 2, None).unwrap();
         let err = expired_service.validate_token(&token).unwrap_err();
 
         assert!(matches!(err, JwtError::InvalidToken(message) if message.contains("expired")));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn validate_token_rejects_unknown_kid() {
+        let old_service = create_test_service_with_keys(StdDuration::from_secs(60), "old-key", None);
+        let new_service = create_test_service_with_keys(StdDuration::from_secs(60), "new-key", None);
+
+        let token = old_service.create_access_token(1, UserRole::Client, 1, None).unwrap();
+        let err = new_service.validate_token(&token).unwrap_err();
+
+        assert!(matches!(err, JwtError::KeyNotFound(kid) if kid == "old-key"));
+    }
+
+    #[test]
+    fn validate_token_accepts_verify_only_key() {
+        let keys_dir = std::env::temp_dir().join(format!("ticketing-system-jwks-{}", Uuid::new_v4()));
+        fs::create_dir_all(&keys_dir).unwrap();
+        fs::write(keys_dir.join("old-key.pem"), TEST_PUBLIC_KEY).unwrap();
+
+        let old_service = create_test_service_with_keys(StdDuration::from_secs(60), "old-key", None);
+        let new_service = create_test_service_with_keys(StdDuration::from_secs(60), "new-key", Some(&keys_dir));
+
+        let old_token = old_service.create_access_token(5, UserRole::Moderator, 3, None).unwrap();
+        let claims = new_service.validate_token(&old_token).unwrap();
+        assert_eq!(claims.sub, "5");
+
+        let new_token = new_service.create_access_token(6, UserRole::Moderator, 3, None).unwrap();
+        let header = decode_header(&new_token).unwrap();
+        assert_eq!(header.kid.as_deref(), Some("new-key"));
+        assert!(matches!(old_service.validate_token(&new_token), Err(JwtError::KeyNotFound(_))));
+    }
+
+    #[test]
+    fn jwks_contains_public_components_of_signing_key() {
+        let service = create_test_service(StdDuration::from_secs(60));
+        let encoding_key = EncodingKey::from_rsa_pem(TEST_PRIVATE_KEY.as_bytes()).unwrap();
+        let expected = Jwk::from_encoding_key(&encoding_key, Algorithm::RS256).unwrap();
+
+        let jwks = service.jwks();
+        assert_eq!(jwks.keys.len(), 1);
+
+        let jwk = jwks.find("test-key").unwrap();
+        assert_eq!(jwk.algorithm, expected.algorithm);
+        assert_eq!(jwk.common.public_key_use, Some(PublicKeyUse::Signature));
+    }
+}
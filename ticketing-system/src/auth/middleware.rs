@@ -3,19 +3,25 @@ use std::{future::{ready, Ready}, rc::Rc};
 use actix_web::{body::{EitherBody, MessageBody}, dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform}, http::{header::AUTHORIZATION, StatusCode}, web::Data, Error, HttpMessage as _, HttpResponse};
 use futures_util::future::LocalBoxFuture;
 
-use crate::auth::{jwt::JwtService, types::UserRole};
+use sqlx::PgPool;
+
+use crate::{auth::{jwt::JwtService, permission::Permission, personal_access_token::{self, is_personal_access_token, PersonalAccessTokenError}, types::UserRole}, services::permission::PermissionService};
 
 #[derive(Clone)]
 pub struct JwtConfig {
     pub min_role: UserRole,
+    pub permission: Option<Permission>,
     pub optional: bool,
+    pub jwt_only: bool,
 }
 
 impl Default for JwtConfig {
     fn default() -> Self {
         Self {
             min_role: UserRole::AnonymousClient,
+            permission: None,
             optional: false,
+            jwt_only: false,
         }
     }
 }
@@ -30,10 +36,21 @@ impl JwtConfig {
         self
     }
 
+    pub fn permission(mut self, permission: Permission) -> Self {
+        self.permission = Some(permission);
+        self
+    }
+
     pub fn optional(mut self) -> Self {
         self.optional = true;
         self
     }
+
+    /// Rejects personal access tokens, e.g. for managing the tokens themselves.
+    pub fn jwt_only(mut self) -> Self {
+        self.jwt_only = true;
+        self
+    }
 }
 
 pub struct JwtMiddleware {
@@ -57,9 +74,17 @@ impl JwtMiddleware {
         Self::new(JwtConfig::new().min_role(role))
     }
 
+    pub fn permission(permission: Permission) -> Self {
+        Self::new(JwtConfig::new().permission(permission))
+    }
+
     pub fn optional() -> Self {
         Self::new(JwtConfig::new().optional())
     }
+
+    pub fn jwt_only() -> Self {
+        Self::new(JwtConfig::new().jwt_only())
+    }
 }
 
 impl<S, B> Transform<S, ServiceRequest> for JwtMiddleware
@@ -137,11 +162,40 @@ where
                 return Ok(create_error_response(req, "Empty token", StatusCode::UNAUTHORIZED));
             }
 
-            let claims = match validator.validate_token(token) {
-                Ok(claims) => claims,
-                Err(e) => {
-                    tracing::warn!("JWT validation failed: {}", e);
-                    return Ok(create_error_response(req, &e.to_string(), StatusCode::UNAUTHORIZED));
+            let claims = if is_personal_access_token(token) {
+                if config.jwt_only {
+                    return Ok(create_error_response(req, "Personal access tokens are not allowed", StatusCode::FORBIDDEN));
+                }
+
+                let pool = match req.app_data::<Data<PgPool>>() {
+                    Some(pool) => pool,
+                    None => {
+                        tracing::error!("PgPool not found in app data");
+                        return Ok(create_error_response(req, "", StatusCode::INTERNAL_SERVER_ERROR));
+                    }
+                };
+
+                match personal_access_token::authenticate(pool, token, req.path()).await {
+                    Ok(claims) => claims,
+                    Err(PersonalAccessTokenError::Unexpected(e)) => {
+                        tracing::error!("Personal access token validation failed: {:?}", e);
+                        return Ok(create_error_response(req, "", StatusCode::INTERNAL_SERVER_ERROR));
+                    }
+                    Err(e @ PersonalAccessTokenError::OutOfScope) => {
+                        return Ok(create_error_response(req, &e.to_string(), StatusCode::FORBIDDEN));
+                    }
+                    Err(e) => {
+                        tracing::warn!("Personal access token validation failed: {}", e);
+                        return Ok(create_error_response(req, &e.to_string(), StatusCode::UNAUTHORIZED));
+                    }
+                }
+            } else {
+                match validator.validate_token(token) {
+                    Ok(claims) => claims,
+                    Err(e) => {
+                        tracing::warn!("JWT validation failed: {}", e);
+                        return Ok(create_error_response(req, &e.to_string(), StatusCode::UNAUTHORIZED));
+                    }
                 }
             };
 
@@ -150,7 +204,46 @@ where
                 return Ok(create_error_response(req, "Insufficient permissions", StatusCode::FORBIDDEN));
             }
 
+            let (permission_service, pool) = match (
+                req.app_data::<Data<PermissionService>>(),
+                req.app_data::<Data<PgPool>>(),
+            ) {
+                (Some(service), Some(pool)) => (service, pool),
+                _ => {
+                    tracing::error!("PermissionService or PgPool not found in app data");
+                    return Ok(create_error_response(req, "", StatusCode::INTERNAL_SERVER_ERROR));
+                }
+            };
+
+            let permissions = match permission_service.get_permissions(pool, claims.role_id()).await {
+                Ok(permissions) => permissions,
+                Err(e) => {
+                    tracing::error!("Failed to get permissions: {:?}", e);
+                    return Ok(create_error_response(req, "", StatusCode::INTERNAL_SERVER_ERROR));
+                }
+            };
+
+            if let Some(permission) = config.permission.filter(|p| !permissions.has(*p)) {
+                tracing::warn!("User {} lacks required permission: {}", claims.sub, permission);
+                return Ok(create_error_response(req, "Insufficient permissions", StatusCode::FORBIDDEN));
+            }
+
+            // Impersonation is for looking around, changes stay with the user.
+            if let Some(actor) = &claims.act {
+                if !req.method().is_safe() {
+                    tracing::warn!("Admin {} tried to {} {} as user {}", actor.sub, req.method(), req.path(), claims.sub);
+                    return Ok(create_error_response(req, "Write operations are not allowed while impersonating", StatusCode::FORBIDDEN));
+                }
+
+                tracing::info!(
+                    actor_id = %actor.sub,
+                    user_id = %claims.sub,
+                    "Impersonated request {} {}", req.method(), req.path()
+                );
+            }
+
             req.extensions_mut().insert(claims);
+            req.extensions_mut().insert(permissions);
 
             let res = service.call(req).await?;
             Ok(res.map_body(|_, body| EitherBody::left(body)))
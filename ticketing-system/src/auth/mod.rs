@@ -2,4 +2,8 @@ pub mod jwt;
 pub mod token_store;
 pub mod types;
 pub mod middleware;
-pub mod extractor;
\ No newline at end of file
+pub mod extractor;
+pub mod totp;
+pub mod personal_access_token;
+pub mod permission;
+pub mod department_scope;
\ No newline at end of file
@@ -0,0 +1,191 @@
+use std::{collections::HashSet, sync::Arc};
+
+use serde::{Deserialize, Serialize};
+use strum::{EnumIter, EnumString, IntoStaticStr};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, EnumString, EnumIter, IntoStaticStr)]
+pub enum Permission {
+    #[serde(rename = "tickets.read")]
+    #[strum(serialize = "tickets.read")]
+    TicketsRead,
+    #[serde(rename = "tickets.update")]
+    #[strum(serialize = "tickets.update")]
+    TicketsUpdate,
+    #[serde(rename = "tickets.delete")]
+    #[strum(serialize = "tickets.delete")]
+    TicketsDelete,
+    #[serde(rename = "tickets.assign")]
+    #[strum(serialize = "tickets.assign")]
+    TicketsAssign,
+    #[serde(rename = "tickets.assign_others")]
+    #[strum(serialize = "tickets.assign_others")]
+    TicketsAssignOthers,
+    /// Working with tickets of departments the user is not a member of.
+    #[serde(rename = "tickets.all_departments")]
+    #[strum(serialize = "tickets.all_departments")]
+    TicketsAllDepartments,
+    /// Routing rules that assign new tickets automatically.
+    #[serde(rename = "assignment_rules.manage")]
+    #[strum(serialize = "assignment_rules.manage")]
+    AssignmentRulesManage,
+    #[serde(rename = "tickets.metrics")]
+    #[strum(serialize = "tickets.metrics")]
+    TicketsMetrics,
+    #[serde(rename = "messages.write")]
+    #[strum(serialize = "messages.write")]
+    MessagesWrite,
+    #[serde(rename = "messages.delete")]
+    #[strum(serialize = "messages.delete")]
+    MessagesDelete,
+    /// Deleting messages written by other users.
+    #[serde(rename = "messages.delete_others")]
+    #[strum(serialize = "messages.delete_others")]
+    MessagesDeleteOthers,
+    /// Seeing messages marked as internal.
+    #[serde(rename = "messages.read_internal")]
+    #[strum(serialize = "messages.read_internal")]
+    MessagesReadInternal,
+    #[serde(rename = "ticket_assets.manage")]
+    #[strum(serialize = "ticket_assets.manage")]
+    TicketAssetsManage,
+    #[serde(rename = "users.read")]
+    #[strum(serialize = "users.read")]
+    UsersRead,
+    #[serde(rename = "users.stats")]
+    #[strum(serialize = "users.stats")]
+    UsersStats,
+    #[serde(rename = "users.invite")]
+    #[strum(serialize = "users.invite")]
+    UsersInvite,
+    #[serde(rename = "users.manage")]
+    #[strum(serialize = "users.manage")]
+    UsersManage,
+    #[serde(rename = "users.change_status")]
+    #[strum(serialize = "users.change_status")]
+    UsersChangeStatus,
+    /// Changing the status of other users.
+    #[serde(rename = "users.manage_status")]
+    #[strum(serialize = "users.manage_status")]
+    UsersManageStatus,
+    #[serde(rename = "users.change_role")]
+    #[strum(serialize = "users.change_role")]
+    UsersChangeRole,
+    #[serde(rename = "users.transfer_admin")]
+    #[strum(serialize = "users.transfer_admin")]
+    UsersTransferAdmin,
+    /// Acting as another user with a short-lived read-only token.
+    #[serde(rename = "users.impersonate")]
+    #[strum(serialize = "users.impersonate")]
+    UsersImpersonate,
+    #[serde(rename = "profile.update")]
+    #[strum(serialize = "profile.update")]
+    ProfileUpdate,
+    #[serde(rename = "tokens.manage")]
+    #[strum(serialize = "tokens.manage")]
+    TokensManage,
+    #[serde(rename = "roles.manage")]
+    #[strum(serialize = "roles.manage")]
+    RolesManage,
+    #[serde(rename = "pages.write")]
+    #[strum(serialize = "pages.write")]
+    PagesWrite,
+    /// Making pages visible to everyone.
+    #[serde(rename = "pages.publish")]
+    #[strum(serialize = "pages.publish")]
+    PagesPublish,
+    /// Seeing pages that are not public.
+    #[serde(rename = "pages.read_private")]
+    #[strum(serialize = "pages.read_private")]
+    PagesReadPrivate,
+    #[serde(rename = "tags.write")]
+    #[strum(serialize = "tags.write")]
+    TagsWrite,
+    #[serde(rename = "departments.write")]
+    #[strum(serialize = "departments.write")]
+    DepartmentsWrite,
+    #[serde(rename = "buildings.write")]
+    #[strum(serialize = "buildings.write")]
+    BuildingsWrite,
+    #[serde(rename = "system_notifications.write")]
+    #[strum(serialize = "system_notifications.write")]
+    SystemNotificationsWrite,
+    #[serde(rename = "notifications.read")]
+    #[strum(serialize = "notifications.read")]
+    NotificationsRead,
+    #[serde(rename = "assets.read")]
+    #[strum(serialize = "assets.read")]
+    AssetsRead,
+    #[serde(rename = "assets.write")]
+    #[strum(serialize = "assets.write")]
+    AssetsWrite,
+    #[serde(rename = "reports.generate")]
+    #[strum(serialize = "reports.generate")]
+    ReportsGenerate,
+    #[serde(rename = "audit.read")]
+    #[strum(serialize = "audit.read")]
+    AuditRead,
+}
+
+impl Permission {
+    pub fn as_str(&self) -> &'static str {
+        self.into()
+    }
+}
+
+impl std::fmt::Display for Permission {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Permissions granted to the authenticated user through their role.
+#[derive(Debug, Clone, Default)]
+pub struct Permissions(Arc<HashSet<Permission>>);
+
+impl Permissions {
+    pub fn new(permissions: HashSet<Permission>) -> Self {
+        Self(Arc::new(permissions))
+    }
+
+    pub fn has(&self, permission: Permission) -> bool {
+        self.0.contains(&permission)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Permission> {
+        self.0.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, str::FromStr as _};
+
+    use strum::IntoEnumIterator as _;
+
+    use super::{Permission, Permissions};
+
+    #[test]
+    fn permission_names_round_trip() {
+        for permission in Permission::iter() {
+            let name = permission.as_str();
+
+            assert_eq!(Permission::from_str(name).unwrap(), permission);
+            assert_eq!(
+                serde_json::to_string(&permission).unwrap(),
+                format!("\"{}\"", name)
+            );
+        }
+
+        assert_eq!(Permission::TicketsAssignOthers.as_str(), "tickets.assign_others");
+        assert!(Permission::from_str("tickets.fly").is_err());
+    }
+
+    #[test]
+    fn permissions_has() {
+        let permissions = Permissions::new(HashSet::from([Permission::AssetsRead]));
+
+        assert!(permissions.has(Permission::AssetsRead));
+        assert!(!permissions.has(Permission::AssetsWrite));
+        assert!(!Permissions::default().has(Permission::AssetsRead));
+    }
+}
@@ -0,0 +1,157 @@
+use anyhow::Context as _;
+use chrono::{DateTime, Utc};
+use rand::{RngExt as _, distr::Alphanumeric, rng};
+use ring::digest::{digest, SHA256};
+use sqlx::PgPool;
+
+use crate::auth::{jwt::Claims, types::UserRole};
+
+pub const TOKEN_PREFIX: &str = "pat_";
+const TOKEN_LEN: usize = 40;
+const ISSUER: &str = "personal_access_token";
+
+#[derive(thiserror::Error, Debug)]
+pub enum PersonalAccessTokenError {
+    #[error("Invalid token")]
+    InvalidToken,
+    #[error("Token is not allowed for this route")]
+    OutOfScope,
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+struct TokenRow {
+    id: i32,
+    user_id: i32,
+    role: i16,
+    user_role: i16,
+    user_role_id: i16,
+    scopes: Vec<String>,
+    expires_at: DateTime<Utc>,
+}
+
+pub fn is_personal_access_token(token: &str) -> bool {
+    token.starts_with(TOKEN_PREFIX)
+}
+
+pub fn generate_token() -> String {
+    let mut rng = rng();
+
+    let secret: String = std::iter::repeat_with(|| rng.sample(Alphanumeric))
+        .map(char::from)
+        .take(TOKEN_LEN)
+        .collect();
+
+    format!("{}{}", TOKEN_PREFIX, secret)
+}
+
+/// Tokens are random, so a plain SHA-256 is enough to store them.
+pub fn hash_token(token: &str) -> String {
+    hex::encode(digest(&SHA256, token.as_bytes()))
+}
+
+/// Scopes are path prefixes. A token without scopes may call any route
+/// its role allows.
+pub fn is_in_scope(scopes: &[String], path: &str) -> bool {
+    scopes.is_empty() || scopes.iter().any(|scope| {
+        let scope = scope.trim_end_matches('/');
+
+        path == scope
+            || path.strip_prefix(scope).is_some_and(|rest| rest.starts_with('/'))
+    })
+}
+
+/// Resolves the token into claims like the ones of an access token.
+/// The role is capped by the owner's current role.
+#[tracing::instrument(name = "Authenticate personal access token", skip_all)]
+pub async fn authenticate(
+    pool: &PgPool,
+    token: &str,
+    path: &str,
+) -> Result<Claims, PersonalAccessTokenError> {
+    let row = sqlx::query_as!(
+        TokenRow,
+        r#"
+            SELECT t.id, t.user_id, t.role, u.role AS user_role, u.role_id AS user_role_id, t.scopes, t.expires_at
+            FROM personal_access_tokens t
+            JOIN users u ON u.id = t.user_id
+            WHERE t.token_hash = $1
+                AND t.revoked_at IS NULL
+                AND t.expires_at > NOW()
+                AND u.is_active
+        "#,
+        hash_token(token)
+    )
+    .fetch_optional(pool)
+    .await
+    .context("Failed to get personal access token")?
+    .ok_or(PersonalAccessTokenError::InvalidToken)?;
+
+    if !is_in_scope(&row.scopes, path) {
+        return Err(PersonalAccessTokenError::OutOfScope);
+    }
+
+    // Written at most once a minute to spare the database.
+    sqlx::query!(
+        "UPDATE personal_access_tokens
+        SET last_used_at = NOW()
+        WHERE id = $1 AND (last_used_at IS NULL OR last_used_at < NOW() - INTERVAL '1 minute')",
+        row.id
+    )
+    .execute(pool)
+    .await
+    .context("Failed to update token last used time")?;
+
+    let role = UserRole::from(row.role);
+    let user_role = UserRole::from(row.user_role);
+
+    // A token with a lower role gets the built-in role of that level,
+    // otherwise the owner's own role with its permissions.
+    let (role, role_id) = if role < user_role {
+        (role, row.role)
+    } else {
+        (user_role, row.user_role_id)
+    };
+
+    Ok(Claims {
+        sub: row.user_id.to_string(),
+        iat: Utc::now().timestamp(),
+        exp: row.expires_at.timestamp(),
+        iss: ISSUER.to_string(),
+        jti: format!("{}{}", TOKEN_PREFIX, row.id),
+        role,
+        rid: Some(role_id),
+        sid: None,
+        act: None,
+        token_id: Some(row.id),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{generate_token, hash_token, is_in_scope, is_personal_access_token};
+
+    #[test]
+    fn generated_tokens_are_prefixed_and_unique() {
+        let first = generate_token();
+        let second = generate_token();
+
+        assert!(is_personal_access_token(&first));
+        assert_ne!(first, second);
+        assert_ne!(hash_token(&first), hash_token(&second));
+        assert_eq!(hash_token(&first).len(), 64);
+    }
+
+    #[test]
+    fn scopes_match_whole_path_segments() {
+        let scopes = vec!["/v1/tickets".to_string(), "/v1/assets/".to_string()];
+
+        assert!(is_in_scope(&scopes, "/v1/tickets"));
+        assert!(is_in_scope(&scopes, "/v1/tickets/5"));
+        assert!(is_in_scope(&scopes, "/v1/assets/models"));
+        assert!(!is_in_scope(&scopes, "/v1/ticketsx"));
+        assert!(!is_in_scope(&scopes, "/v1/user/list"));
+
+        assert!(is_in_scope(&[], "/v1/user/list"));
+    }
+}
@@ -17,8 +17,9 @@ pub enum TokenStoreError {
     #[error("Token not found")]
     TokenNotFound,
 
+    /// All sessions of the user have been revoked.
     #[error("Fingerprint mismatch")]
-    FingerprintMismatch,
+    FingerprintMismatch(i32),
 
     #[error(transparent)]
     Unexpected(#[from] anyhow::Error),
@@ -28,18 +29,17 @@ impl ResponseError for TokenStoreError {
     fn status_code(&self) -> StatusCode {
         match self {
             TokenStoreError::TokenNotFound => StatusCode::UNAUTHORIZED,
-            TokenStoreError::FingerprintMismatch => StatusCode::FORBIDDEN,
+            TokenStoreError::FingerprintMismatch(_) => StatusCode::FORBIDDEN,
             TokenStoreError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
 }
 
 impl TokenStore {
-    pub fn new(redis_pool: Pool<RedisConnectionManager>) -> Self {
-        // TODO: ttl from_config
+    pub fn new(redis_pool: Pool<RedisConnectionManager>, refresh_token_ttl: u64) -> Self {
         Self {
             redis_pool,
-            refresh_token_ttl: 60 * 60 * 24 * 30,
+            refresh_token_ttl,
         }
     }
 
@@ -123,7 +123,7 @@ impl TokenStore {
                 token.user_id, token.fingerprint, fingerprint
             );
 
-            return Err(TokenStoreError::FingerprintMismatch);
+            return Err(TokenStoreError::FingerprintMismatch(token.user_id));
         }
 
         conn.srem::<_, _, ()>(
@@ -164,11 +164,116 @@ impl TokenStore {
 
         Ok(())
     }
+
+    /// Returns the user's live sessions, dropping expired tokens from the set.
+    pub async fn get_user_sessions(&self, user_id: i32) -> Result<Vec<RefreshToken>, TokenStoreError> {
+        let mut conn = self.get_connection().await?;
+
+        let sessions = self.get_user_tokens(&mut conn, user_id)
+            .await?
+            .into_iter()
+            .map(|(_, token)| token)
+            .collect();
+
+        Ok(sessions)
+    }
+
+    /// Returns `false` if the user has no such session.
+    pub async fn revoke_session(&self, user_id: i32, session_id: &str) -> Result<bool, TokenStoreError> {
+        let mut conn = self.get_connection().await?;
+
+        let token_keys: Vec<String> = self.get_user_tokens(&mut conn, user_id)
+            .await?
+            .into_iter()
+            .filter(|(_, token)| token.session_id == session_id)
+            .map(|(key, _)| key)
+            .collect();
+
+        if token_keys.is_empty() {
+            return Ok(false);
+        }
+
+        self.remove_tokens(&mut conn, user_id, token_keys).await?;
+
+        Ok(true)
+    }
+
+    pub async fn revoke_other_sessions(&self, user_id: i32, except_session_id: Option<&str>) -> Result<(), TokenStoreError> {
+        let mut conn = self.get_connection().await?;
+
+        let token_keys: Vec<String> = self.get_user_tokens(&mut conn, user_id)
+            .await?
+            .into_iter()
+            .filter(|(_, token)| Some(token.session_id.as_str()) != except_session_id)
+            .map(|(key, _)| key)
+            .collect();
+
+        if !token_keys.is_empty() {
+            self.remove_tokens(&mut conn, user_id, token_keys).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn get_user_tokens(
+        &self,
+        conn: &mut PooledConnection<'_, RedisConnectionManager>,
+        user_id: i32
+    ) -> Result<Vec<(String, RefreshToken)>, TokenStoreError> {
+        let user_tokens_key = self.get_user_tokens_key(user_id);
+
+        let token_keys: Vec<String> = conn.smembers(&user_tokens_key)
+            .await
+            .context("Failed to get set members from Redis")?;
+
+        if token_keys.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let tokens: Vec<Option<RefreshToken>> = conn.mget(&token_keys)
+            .await
+            .context("Failed to get refresh tokens from Redis")?;
+
+        let (live, expired): (Vec<_>, Vec<_>) = token_keys
+            .into_iter()
+            .zip(tokens)
+            .partition(|(_, token)| token.is_some());
+
+        if !expired.is_empty() {
+            let expired: Vec<String> = expired.into_iter().map(|(key, _)| key).collect();
+
+            conn.srem::<_, _, ()>(&user_tokens_key, expired)
+                .await
+                .context("Failed to remove expired tokens from a set")?;
+        }
+
+        Ok(
+            live.into_iter()
+                .filter_map(|(key, token)| token.map(|token| (key, token)))
+                .collect()
+        )
+    }
+
+    async fn remove_tokens(
+        &self,
+        conn: &mut PooledConnection<'_, RedisConnectionManager>,
+        user_id: i32,
+        token_keys: Vec<String>
+    ) -> Result<(), TokenStoreError> {
+        redis::pipe()
+            .unlink(&token_keys)
+            .srem(self.get_user_tokens_key(user_id), &token_keys)
+            .exec_async(&mut **conn)
+            .await
+            .context("Failed to delete user tokens")?;
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::auth::{token_store::TokenStoreError, types::RefreshToken};
+    use crate::auth::{extractor::DeviceInfo, token_store::TokenStoreError, types::RefreshToken};
 
     use super::TokenStore;
     use bb8_redis::{RedisConnectionManager, bb8::Pool, redis::AsyncCommands};
@@ -196,14 +301,11 @@ mod tests {
     #[tokio::test]
     async fn test_revoke_all_except_one() {
         let pool = create_test_pool().await;
-        let store = TokenStore::new(pool.clone());
+        let store = TokenStore::new(pool.clone(), 60);
 
         cleanup_redis(&pool, 999999).await;
         
-        let token_data = RefreshToken {
-            user_id: 999999,
-            fingerprint: "fingerprint".to_string(),
-        };
+        let token_data = RefreshToken::new(999999, "fingerprint".to_string(), DeviceInfo::default());
 
         let token1 = store.generate_refresh_token(&token_data).await.unwrap();
         let token2 = store.generate_refresh_token(&token_data).await.unwrap();
@@ -226,14 +328,11 @@ mod tests {
     #[tokio::test]
     async fn test_get_del_removes_token_completely() {
         let pool = create_test_pool().await;
-        let store = TokenStore::new(pool.clone());
+        let store = TokenStore::new(pool.clone(), 60);
 
         cleanup_redis(&pool, 888888).await;
         
-        let token_data = RefreshToken {
-            user_id: 888888,
-            fingerprint: "fingerprint".to_string(),
-        };
+        let token_data = RefreshToken::new(888888, "fingerprint".to_string(), DeviceInfo::default());
 
         let token1 = store.generate_refresh_token(&token_data).await.unwrap();
         let token2 = store.generate_refresh_token(&token_data).await.unwrap();
@@ -273,13 +372,69 @@ mod tests {
         cleanup_redis(&pool, 888888).await;
     }
 
+    #[tokio::test]
+    async fn test_revoke_session_keeps_other_sessions() {
+        let pool = create_test_pool().await;
+        let store = TokenStore::new(pool.clone(), 60);
+
+        cleanup_redis(&pool, 777777).await;
+
+        let device = DeviceInfo {
+            user_agent: Some("Firefox".to_string()),
+            ip: Some("10.0.0.1".to_string()),
+        };
+
+        let first = RefreshToken::new(777777, "fingerprint".to_string(), device.clone());
+        let second = RefreshToken::new(777777, "fingerprint".to_string(), device);
+
+        let token1 = store.generate_refresh_token(&first).await.unwrap();
+        let token2 = store.generate_refresh_token(&second).await.unwrap();
+
+        let sessions = store.get_user_sessions(777777).await.unwrap();
+        assert_eq!(sessions.len(), 2);
+        assert!(sessions.iter().all(|s| s.user_agent.as_deref() == Some("Firefox")));
+
+        assert!(store.revoke_session(777777, &first.session_id).await.unwrap());
+        assert!(!store.revoke_session(777777, &first.session_id).await.unwrap());
+
+        let result = store.get_del_refresh_token(&token1, "fingerprint").await;
+        assert!(matches!(result, Err(TokenStoreError::TokenNotFound)));
+
+        let result = store.get_del_refresh_token(&token2, "fingerprint").await;
+        assert_eq!(result.unwrap().session_id, second.session_id);
+
+        cleanup_redis(&pool, 777777).await;
+    }
+
+    #[tokio::test]
+    async fn test_revoke_other_sessions() {
+        let pool = create_test_pool().await;
+        let store = TokenStore::new(pool.clone(), 60);
+
+        cleanup_redis(&pool, 666666).await;
+
+        let current = RefreshToken::new(666666, "fingerprint".to_string(), DeviceInfo::default());
+        let other = RefreshToken::new(666666, "fingerprint".to_string(), DeviceInfo::default());
+
+        store.generate_refresh_token(&current).await.unwrap();
+        store.generate_refresh_token(&other).await.unwrap();
+
+        store.revoke_other_sessions(666666, Some(&current.session_id)).await.unwrap();
+
+        let sessions = store.get_user_sessions(666666).await.unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].session_id, current.session_id);
+
+        cleanup_redis(&pool, 666666).await;
+    }
+
     #[test]
     fn token_store_error_status_codes() {
         use actix_web::http::StatusCode;
         use anyhow::anyhow;
 
         assert_eq!(TokenStoreError::TokenNotFound.status_code(), StatusCode::UNAUTHORIZED);
-        assert_eq!(TokenStoreError::FingerprintMismatch.status_code(), StatusCode::FORBIDDEN);
+        assert_eq!(TokenStoreError::FingerprintMismatch(1).status_code(), StatusCode::FORBIDDEN);
         let unexpected = TokenStoreError::Unexpected(anyhow!("test"));
         assert_eq!(unexpected.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
     }
@@ -0,0 +1,124 @@
+use data_encoding::BASE32_NOPAD;
+use ring::hmac;
+
+const STEP_SECONDS: i64 = 30;
+const DIGITS: u32 = 6;
+const SECRET_LEN: usize = 20;
+// Accept codes from the previous and the next step to tolerate clock drift.
+const ALLOWED_DRIFT: i64 = 1;
+
+pub fn generate_secret() -> String {
+    let mut secret = [0u8; SECRET_LEN];
+    rand::fill(&mut secret[..]);
+
+    BASE32_NOPAD.encode(&secret)
+}
+
+pub fn provisioning_uri(secret: &str, issuer: &str, account: &str) -> String {
+    format!(
+        "otpauth://totp/{issuer}:{account}?secret={secret}&issuer={issuer}&algorithm=SHA1&digits={DIGITS}&period={STEP_SECONDS}",
+        issuer = encode_uri_component(issuer),
+        account = encode_uri_component(account),
+    )
+}
+
+pub fn generate(secret: &str, unix_time: i64) -> Option<String> {
+    let key = BASE32_NOPAD.decode(secret.as_bytes()).ok()?;
+
+    Some(hotp(&key, (unix_time / STEP_SECONDS) as u64))
+}
+
+/// Returns the matched time step, so callers can reject reuse of the same code.
+pub fn verify(secret: &str, code: &str, unix_time: i64) -> Option<i64> {
+    if code.len() != DIGITS as usize || !code.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    let key = BASE32_NOPAD.decode(secret.as_bytes()).ok()?;
+    let current_step = unix_time / STEP_SECONDS;
+
+    (-ALLOWED_DRIFT..=ALLOWED_DRIFT)
+        .map(|drift| current_step + drift)
+        .find(|step| hotp(&key, *step as u64) == code)
+}
+
+fn hotp(key: &[u8], counter: u64) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY, key);
+    let tag = hmac::sign(&key, &counter.to_be_bytes());
+    let digest = tag.as_ref();
+
+    let offset = (digest[digest.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([
+        digest[offset] & 0x7f,
+        digest[offset + 1],
+        digest[offset + 2],
+        digest[offset + 3],
+    ]);
+
+    format!("{:0width$}", binary % 10u32.pow(DIGITS), width = DIGITS as usize)
+}
+
+fn encode_uri_component(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RFC 6238 appendix B, SHA1 secret "12345678901234567890"
+    const RFC_SECRET: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+
+    #[test]
+    fn hotp_matches_rfc_test_vectors() {
+        let key = BASE32_NOPAD.decode(RFC_SECRET.as_bytes()).unwrap();
+
+        assert_eq!(hotp(&key, 59 / 30), "287082");
+        assert_eq!(hotp(&key, 1111111109 / 30), "081804");
+        assert_eq!(hotp(&key, 1234567890 / 30), "005924");
+        assert_eq!(hotp(&key, 2000000000 / 30), "279037");
+    }
+
+    #[test]
+    fn verify_returns_matched_step() {
+        assert_eq!(verify(RFC_SECRET, "287082", 59), Some(1));
+    }
+
+    #[test]
+    fn verify_tolerates_one_step_of_drift() {
+        assert_eq!(verify(RFC_SECRET, "287082", 59 + 30), Some(1));
+        assert_eq!(verify(RFC_SECRET, "287082", 59 + 60), None);
+    }
+
+    #[test]
+    fn verify_rejects_malformed_codes() {
+        assert_eq!(verify(RFC_SECRET, "28708", 59), None);
+        assert_eq!(verify(RFC_SECRET, "28708a", 59), None);
+        assert_eq!(verify("not base32!", "287082", 59), None);
+    }
+
+    #[test]
+    fn generated_secret_roundtrips() {
+        let secret = generate_secret();
+        let key = BASE32_NOPAD.decode(secret.as_bytes()).unwrap();
+
+        assert_eq!(key.len(), SECRET_LEN);
+
+        let code = generate(&secret, 100 * 30).unwrap();
+        assert_eq!(code, hotp(&key, 100));
+        assert_eq!(verify(&secret, &code, 100 * 30), Some(100));
+    }
+
+    #[test]
+    fn provisioning_uri_escapes_account() {
+        let uri = provisioning_uri("ABC", "KFU OIT", "user@example.com");
+
+        assert!(uri.starts_with("otpauth://totp/KFU%20OIT:user%40example.com?secret=ABC&issuer=KFU%20OIT"));
+    }
+}
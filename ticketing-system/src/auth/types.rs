@@ -1,9 +1,13 @@
 use std::fmt::Debug;
 
 use bb8_redis::redis::{FromRedisValue, ParsingError, from_redis_value};
+use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use sqlx::Type;
 use strum::EnumString;
+use uuid::Uuid;
+
+use crate::auth::extractor::DeviceInfo;
 
 #[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, PartialOrd)]
 #[serde(rename_all = "lowercase")]
@@ -83,10 +87,74 @@ impl From<i16> for UserStatus {
     }
 }
 
+/// Role of a user inside one department.
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, PartialOrd)]
+#[serde(rename_all = "lowercase")]
+#[repr(i16)]
+pub enum DepartmentRole {
+    Member = 0,
+    /// Assigns tickets of the department to its members.
+    Moderator = 1,
+}
+
+impl From<i16> for DepartmentRole {
+    fn from(value: i16) -> Self {
+        match value {
+            0 => DepartmentRole::Member,
+            1 => DepartmentRole::Moderator,
+            _ => {
+                tracing::error!("Invalid DepartmentRole value: {}", value);
+                DepartmentRole::Member
+            }
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct RefreshToken {
     pub user_id: i32,
-    pub fingerprint: String
+    pub fingerprint: String,
+    // Tokens issued before sessions were tracked have no session fields.
+    #[serde(default = "new_session_id")]
+    pub session_id: String,
+    #[serde(default)]
+    pub user_agent: Option<String>,
+    #[serde(default)]
+    pub ip: Option<String>,
+    #[serde(default)]
+    pub created_at: i64,
+    #[serde(default)]
+    pub last_used_at: i64,
+}
+
+impl RefreshToken {
+    pub fn new(user_id: i32, fingerprint: String, device: DeviceInfo) -> Self {
+        let now = Utc::now().timestamp();
+
+        Self {
+            user_id,
+            fingerprint,
+            session_id: new_session_id(),
+            user_agent: device.user_agent,
+            ip: device.ip,
+            created_at: now,
+            last_used_at: now,
+        }
+    }
+
+    /// Token data for the next token of the same session.
+    pub fn rotate(self, device: DeviceInfo) -> Self {
+        Self {
+            user_agent: device.user_agent.or(self.user_agent),
+            ip: device.ip.or(self.ip),
+            last_used_at: Utc::now().timestamp(),
+            ..self
+        }
+    }
+}
+
+fn new_session_id() -> String {
+    Uuid::new_v4().to_string()
 }
 
 impl FromRedisValue for RefreshToken {
@@ -128,6 +196,14 @@ mod tests {
         assert!(UserRole::AnonymousClient.has_access(UserRole::AnonymousClient));
     }
 
+    #[test]
+    fn department_role_from_i16() {
+        assert_eq!(DepartmentRole::from(0i16), DepartmentRole::Member);
+        assert_eq!(DepartmentRole::from(1i16), DepartmentRole::Moderator);
+
+        assert_eq!(DepartmentRole::from(99i16), DepartmentRole::Member);
+    }
+
     #[test]
     fn user_status_from_i16() {
         assert_eq!(UserStatus::from(0i16), UserStatus::Available);
@@ -0,0 +1,41 @@
+//! Builds the leaked passwords filter for `password_policy.breached_passwords_path`
+//! from a text file with one password per line.
+//!
+//! cargo run --bin build_breached_passwords -- passwords.txt breached.bin
+
+use std::{env, fs};
+
+use anyhow::Context;
+use ticketing_system::services::password_policy::BreachedPasswords;
+
+fn main() -> Result<(), anyhow::Error> {
+    let mut args = env::args().skip(1);
+
+    let (Some(input), Some(output)) = (args.next(), args.next()) else {
+        anyhow::bail!("Usage: build_breached_passwords <passwords.txt> <output.bin>");
+    };
+
+    let bytes = fs::read(&input)
+        .with_context(|| format!("Failed to read {}", input))?;
+
+    // Leaked lists are not always valid UTF-8.
+    let list = String::from_utf8_lossy(&bytes);
+
+    let passwords = list.lines()
+        .map(str::trim)
+        .filter(|password| !password.is_empty())
+        .collect::<Vec<_>>();
+
+    let mut filter = BreachedPasswords::with_capacity(passwords.len());
+
+    for password in &passwords {
+        filter.insert(password);
+    }
+
+    fs::write(&output, filter.to_bytes())
+        .with_context(|| format!("Failed to write {}", output))?;
+
+    println!("Wrote {} passwords to {}", passwords.len(), output);
+
+    Ok(())
+}
@@ -3,9 +3,9 @@ use std::{sync::Arc, time::Duration};
 use secrecy::{ExposeSecret, SecretString};
 use serde::{Deserialize, Deserializer};
 use serde_aux::field_attributes::{deserialize_number_from_string, deserialize_bool_from_anything};
-use sqlx::{postgres::{PgConnectOptions, PgSslMode}, ConnectOptions};
+use sqlx::{postgres::{PgConnectOptions, PgSslMode}, types::ipnetwork::IpNetwork, ConnectOptions};
 
-use crate::{domain::email::Email, email_client::{EmailClient, mailersend::MailerSendClient, smtp::SmtpClient}, storage::Storage};
+use crate::{auth::types::UserRole, directory::{ldap::LdapDirectory, DirectoryProvider}, domain::email::Email, email_client::{EmailClient, mailersend::MailerSendClient, smtp::SmtpClient}, schema::tickets::{EscalationTrigger, TicketPriority}, storage::Storage};
 
 #[derive(Deserialize, Debug)]
 pub struct Settings {
@@ -16,6 +16,22 @@ pub struct Settings {
     pub storage: StorageSettings,
     pub email_client: EmailClientSettings,
     pub event_publisher: EventPublisherSettings,
+    #[serde(default)]
+    pub ldap: Option<LdapSettings>,
+    #[serde(default)]
+    pub oidc: Option<OidcSettings>,
+    #[serde(default)]
+    pub magic_link: MagicLinkSettings,
+    #[serde(default)]
+    pub rate_limit: RateLimitSettings,
+    #[serde(default)]
+    pub signup: SignupSettings,
+    #[serde(default)]
+    pub password_policy: PasswordPolicySettings,
+    #[serde(default)]
+    pub absences: AbsenceSettings,
+    #[serde(default)]
+    pub escalations: EscalationSettings,
 }
 
 #[derive(Deserialize, Debug)]
@@ -24,6 +40,9 @@ pub struct ApplicationSettings {
     pub port: u16,
     pub host: String,
     pub base_url: String,
+    /// Reverse proxy addresses or networks whose `X-Forwarded-For` header is trusted.
+    #[serde(default)]
+    pub trusted_proxies: Vec<IpNetwork>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -46,6 +65,16 @@ pub struct AuthSettings {
     pub private_key_path: String,
     pub public_key_path: String,
     pub issuer: String,
+    #[serde(default = "default_key_id")]
+    pub key_id: String,
+    #[serde(default)]
+    pub verification_keys_dir: Option<String>,
+    #[serde(default)]
+    pub two_factor_required_role: Option<UserRole>,
+}
+
+fn default_key_id() -> String {
+    "default-key-1".to_string()
 }
 
 #[derive(Deserialize, Debug)]
@@ -190,6 +219,196 @@ pub struct EventPublisherSettings {
     timeout_milliseconds: u64,
 }
 
+#[derive(Deserialize, Debug)]
+pub struct LdapSettings {
+    pub url: String,
+    /// `{login}` is replaced with the login entered by the user.
+    pub bind_dn_template: String,
+    pub base_dn: String,
+    #[serde(default = "default_ldap_user_filter")]
+    pub user_filter: String,
+    #[serde(default = "default_ldap_login_attribute")]
+    pub login_attribute: String,
+    #[serde(default = "default_ldap_name_attribute")]
+    pub name_attribute: String,
+    #[serde(default = "default_ldap_email_attribute")]
+    pub email_attribute: String,
+    #[serde(default = "default_ldap_groups_attribute")]
+    pub groups_attribute: String,
+    #[serde(default)]
+    pub group_mappings: Vec<LdapGroupMapping>,
+    #[serde(default)]
+    pub default_role: Option<UserRole>,
+    #[serde(default = "default_ldap_timeout")]
+    timeout_milliseconds: u64,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct LdapGroupMapping {
+    pub group: String,
+    #[serde(default)]
+    pub role: Option<UserRole>,
+    #[serde(default)]
+    pub department_id: Option<i16>,
+}
+
+fn default_ldap_user_filter() -> String {
+    "(sAMAccountName={login})".to_string()
+}
+
+fn default_ldap_login_attribute() -> String {
+    "sAMAccountName".to_string()
+}
+
+fn default_ldap_name_attribute() -> String {
+    "displayName".to_string()
+}
+
+fn default_ldap_email_attribute() -> String {
+    "mail".to_string()
+}
+
+fn default_ldap_groups_attribute() -> String {
+    "memberOf".to_string()
+}
+
+fn default_ldap_timeout() -> u64 {
+    5000
+}
+
+impl LdapSettings {
+    pub fn timeout(&self) -> Duration {
+        Duration::from_millis(self.timeout_milliseconds)
+    }
+
+    pub fn get_directory(&self) -> Arc<dyn DirectoryProvider> {
+        Arc::new(LdapDirectory::new(self))
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct OidcSettings {
+    /// Stored with linked identities, e.g. `kpfu`.
+    pub provider_name: String,
+    pub issuer_url: String,
+    pub client_id: String,
+    pub client_secret: SecretString,
+    /// Frontend page the provider redirects back to. It forwards the
+    /// query string to `/v1/auth/oidc/callback`.
+    pub redirect_url: String,
+    #[serde(default = "default_oidc_scopes")]
+    pub scopes: String,
+    #[serde(default = "default_oidc_timeout")]
+    pub timeout_milliseconds: u64,
+}
+
+fn default_oidc_scopes() -> String {
+    "openid email profile".to_string()
+}
+
+fn default_oidc_timeout() -> u64 {
+    10000
+}
+
+impl OidcSettings {
+    pub fn timeout(&self) -> Duration {
+        Duration::from_millis(self.timeout_milliseconds)
+    }
+}
+
+/// Passwordless sign in by a link sent to the email of an existing account.
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct MagicLinkSettings {
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct RateLimitSettings {
+    #[serde(default = "default_login_limits")]
+    pub login: RouteLimits,
+    #[serde(default = "default_account_recovery_limits")]
+    pub account_recovery: RouteLimits,
+    #[serde(default = "default_magic_link_limits")]
+    pub magic_link: RouteLimits,
+    #[serde(default = "default_signup_limits")]
+    pub signup: RouteLimits,
+    #[serde(default)]
+    pub lockout: LockoutSettings,
+}
+
+impl Default for RateLimitSettings {
+    fn default() -> Self {
+        Self {
+            login: default_login_limits(),
+            account_recovery: default_account_recovery_limits(),
+            magic_link: default_magic_link_limits(),
+            signup: default_signup_limits(),
+            lockout: LockoutSettings::default(),
+        }
+    }
+}
+
+/// Limits for one route: per submitted login or email, and per client IP.
+#[derive(Deserialize, Debug, Clone, Copy)]
+pub struct RouteLimits {
+    pub per_key: WindowLimit,
+    pub per_ip: WindowLimit,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy)]
+pub struct WindowLimit {
+    pub max_requests: u32,
+    pub window_seconds: u64,
+}
+
+fn default_login_limits() -> RouteLimits {
+    RouteLimits {
+        per_key: WindowLimit { max_requests: 10, window_seconds: 60 },
+        per_ip: WindowLimit { max_requests: 50, window_seconds: 60 },
+    }
+}
+
+fn default_account_recovery_limits() -> RouteLimits {
+    RouteLimits {
+        per_key: WindowLimit { max_requests: 3, window_seconds: 60 * 60 },
+        per_ip: WindowLimit { max_requests: 20, window_seconds: 60 * 60 },
+    }
+}
+
+fn default_magic_link_limits() -> RouteLimits {
+    RouteLimits {
+        per_key: WindowLimit { max_requests: 5, window_seconds: 60 * 60 },
+        per_ip: WindowLimit { max_requests: 20, window_seconds: 60 * 60 },
+    }
+}
+
+fn default_signup_limits() -> RouteLimits {
+    RouteLimits {
+        per_key: WindowLimit { max_requests: 3, window_seconds: 60 * 60 },
+        per_ip: WindowLimit { max_requests: 10, window_seconds: 60 * 60 },
+    }
+}
+
+/// After `threshold` failed logins in a row the login is locked for
+/// `base_delay_seconds`, doubled with every further failure up to `max_delay_seconds`.
+#[derive(Deserialize, Debug, Clone, Copy)]
+pub struct LockoutSettings {
+    pub threshold: u32,
+    pub base_delay_seconds: u64,
+    pub max_delay_seconds: u64,
+}
+
+impl Default for LockoutSettings {
+    fn default() -> Self {
+        Self {
+            threshold: 5,
+            base_delay_seconds: 30,
+            max_delay_seconds: 60 * 60,
+        }
+    }
+}
+
 impl EventPublisherSettings {
     pub fn timeout(&self) -> Duration {
         Duration::from_millis(self.timeout_milliseconds)
@@ -250,4 +469,166 @@ fn parse_duration(s: &str) -> Result<Duration, String> {
         "d" => Ok(Duration::from_secs(num * 86400)),
         _ => Err(format!("Unknown duration unit: {}", unit)),
     }
+}
+
+/// Self-registration is switched on and off at runtime by admins,
+/// these settings only restrict who can use it.
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct SignupSettings {
+    /// Email domains allowed to sign up, subdomains included.
+    /// An empty list allows no domain at all.
+    #[serde(default)]
+    pub allowed_domains: Vec<String>,
+    /// Checked in addition to the built-in list of disposable email providers.
+    #[serde(default)]
+    pub blocked_domains: Vec<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct PasswordPolicySettings {
+    #[serde(default = "default_min_entropy_bits")]
+    pub min_entropy_bits: f64,
+    /// How many recent passwords, the current one included, cannot be reused.
+    #[serde(default = "default_password_history_size")]
+    pub history_size: u32,
+    /// Bloom filter of leaked passwords, see `src/bin/build_breached_passwords.rs`.
+    #[serde(default)]
+    pub breached_passwords_path: Option<String>,
+    /// Admins are asked to change a password older than this.
+    #[serde(default)]
+    pub admin_max_age_days: Option<u32>,
+}
+
+impl Default for PasswordPolicySettings {
+    fn default() -> Self {
+        Self {
+            min_entropy_bits: default_min_entropy_bits(),
+            history_size: default_password_history_size(),
+            breached_passwords_path: None,
+            admin_max_age_days: None,
+        }
+    }
+}
+
+fn default_min_entropy_bits() -> f64 {
+    40.0
+}
+
+fn default_password_history_size() -> u32 {
+    5
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct AbsenceSettings {
+    /// How often absence start and end times are checked.
+    #[serde(default = "default_absence_check_interval")]
+    pub check_interval_seconds: u64,
+}
+
+impl Default for AbsenceSettings {
+    fn default() -> Self {
+        Self {
+            check_interval_seconds: default_absence_check_interval(),
+        }
+    }
+}
+
+fn default_absence_check_interval() -> u64 {
+    60
+}
+
+impl AbsenceSettings {
+    pub fn check_interval(&self) -> Duration {
+        Duration::from_secs(self.check_interval_seconds)
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct EscalationSettings {
+    /// Off by default, so the policies are reviewed before they raise priorities.
+    #[serde(default)]
+    pub enabled: bool,
+    /// How often tickets are checked against the policies.
+    #[serde(default = "default_escalation_check_interval")]
+    pub check_interval_seconds: u64,
+    #[serde(default = "default_escalation_policies")]
+    pub policies: Vec<EscalationPolicy>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct EscalationPolicy {
+    /// Identifies the policy in the escalation log, so it fires once per ticket.
+    /// Tickets that existed before the policies were introduced are recorded
+    /// for the built-in names only, a new name applies to them as well.
+    pub name: String,
+    pub trigger: EscalationTrigger,
+    /// Only tickets with this priority, all of them when unset.
+    #[serde(default)]
+    pub priority: Option<TicketPriority>,
+    /// Time since creation for `no_response`, since the planned date for `planned_at_passed`.
+    #[serde(default)]
+    pub after_minutes: u32,
+    /// Raises the priority by one step, up to critical. The other policies
+    /// of the same trigger no longer fire for the ticket.
+    #[serde(default)]
+    pub raise_priority: bool,
+    /// Notifies moderators of the ticket department, or all moderators
+    /// when the department has none.
+    #[serde(default)]
+    pub notify_moderators: bool,
+    /// Posts to the Telegram chat of the event publisher.
+    #[serde(default)]
+    pub notify_chat: bool,
+}
+
+impl Default for EscalationSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            check_interval_seconds: default_escalation_check_interval(),
+            policies: default_escalation_policies(),
+        }
+    }
+}
+
+fn default_escalation_check_interval() -> u64 {
+    60
+}
+
+fn default_escalation_policies() -> Vec<EscalationPolicy> {
+    vec![
+        EscalationPolicy {
+            name: "critical_no_response".to_string(),
+            trigger: EscalationTrigger::NoResponse,
+            priority: Some(TicketPriority::Critical),
+            after_minutes: 60,
+            raise_priority: false,
+            notify_moderators: true,
+            notify_chat: true,
+        },
+        EscalationPolicy {
+            name: "high_no_response".to_string(),
+            trigger: EscalationTrigger::NoResponse,
+            priority: Some(TicketPriority::High),
+            after_minutes: 240,
+            raise_priority: true,
+            notify_moderators: true,
+            notify_chat: false,
+        },
+        EscalationPolicy {
+            name: "planned_at_passed".to_string(),
+            trigger: EscalationTrigger::PlannedAtPassed,
+            priority: None,
+            after_minutes: 0,
+            raise_priority: true,
+            notify_moderators: true,
+            notify_chat: true,
+        },
+    ]
+}
+
+impl EscalationSettings {
+    pub fn check_interval(&self) -> Duration {
+        Duration::from_secs(self.check_interval_seconds)
+    }
 }
\ No newline at end of file
@@ -0,0 +1,221 @@
+use std::time::Duration;
+
+use anyhow::Context;
+use async_trait::async_trait;
+use ldap3::{dn_escape, ldap_escape, LdapConnAsync, LdapConnSettings, Scope, SearchEntry};
+
+use crate::{auth::types::UserRole, config::{LdapGroupMapping, LdapSettings}, directory::{DirectoryError, DirectoryProvider, DirectoryUser}};
+
+const INVALID_CREDENTIALS: u32 = 49;
+
+pub struct LdapDirectory {
+    url: String,
+    bind_dn_template: String,
+    base_dn: String,
+    user_filter: String,
+    login_attribute: String,
+    name_attribute: String,
+    email_attribute: String,
+    groups_attribute: String,
+    group_mappings: Vec<LdapGroupMapping>,
+    default_role: Option<UserRole>,
+    timeout: Duration,
+}
+
+impl LdapDirectory {
+    pub fn new(settings: &LdapSettings) -> Self {
+        Self {
+            url: settings.url.clone(),
+            bind_dn_template: settings.bind_dn_template.clone(),
+            base_dn: settings.base_dn.clone(),
+            user_filter: settings.user_filter.clone(),
+            login_attribute: settings.login_attribute.clone(),
+            name_attribute: settings.name_attribute.clone(),
+            email_attribute: settings.email_attribute.clone(),
+            groups_attribute: settings.groups_attribute.clone(),
+            group_mappings: settings.group_mappings.clone(),
+            default_role: settings.default_role,
+            timeout: settings.timeout(),
+        }
+    }
+}
+
+#[async_trait]
+impl DirectoryProvider for LdapDirectory {
+    #[tracing::instrument(name = "Authenticate with LDAP", skip(self, password))]
+    async fn authenticate(
+        &self,
+        login: &str,
+        password: &str
+    ) -> Result<Option<DirectoryUser>, DirectoryError> {
+        // An empty password would turn the bind into an unauthenticated one.
+        if login.is_empty() || password.is_empty() {
+            return Ok(None);
+        }
+
+        let settings = LdapConnSettings::new().set_conn_timeout(self.timeout);
+
+        let (conn, mut ldap) = LdapConnAsync::with_settings(settings, &self.url)
+            .await
+            .context("Failed to connect to LDAP server")?;
+
+        ldap3::drive!(conn);
+
+        let bind_dn = self.bind_dn_template.replace("{login}", &dn_escape(login));
+
+        let bind = ldap.with_timeout(self.timeout)
+            .simple_bind(&bind_dn, password)
+            .await
+            .context("Failed to bind to LDAP server")?;
+
+        if bind.rc == INVALID_CREDENTIALS {
+            let _ = ldap.unbind().await;
+            return Ok(None);
+        }
+
+        bind.success().context("LDAP bind failed")?;
+
+        let filter = self.user_filter.replace("{login}", &ldap_escape(login));
+
+        let (entries, _) = ldap.with_timeout(self.timeout)
+            .search(
+                &self.base_dn,
+                Scope::Subtree,
+                &filter,
+                vec![
+                    self.login_attribute.as_str(),
+                    self.name_attribute.as_str(),
+                    self.email_attribute.as_str(),
+                    self.groups_attribute.as_str(),
+                ],
+            )
+            .await
+            .context("Failed to search LDAP directory")?
+            .success()
+            .context("LDAP search failed")?;
+
+        let _ = ldap.unbind().await;
+
+        let entry = entries.into_iter()
+            .next()
+            .map(SearchEntry::construct)
+            .ok_or(DirectoryError::AccessDenied)?;
+
+        let first = |attribute: &str| entry.attrs
+            .get(attribute)
+            .and_then(|values| values.first())
+            .cloned();
+
+        let email = first(&self.email_attribute)
+            .context("Directory entry has no email")?;
+
+        let groups = entry.attrs
+            .get(&self.groups_attribute)
+            .cloned()
+            .unwrap_or_default();
+
+        let (role, department_ids) = map_groups(&self.group_mappings, self.default_role, &groups)
+            .ok_or(DirectoryError::AccessDenied)?;
+
+        Ok(Some(DirectoryUser {
+            login: first(&self.login_attribute).unwrap_or_else(|| login.to_string()),
+            name: first(&self.name_attribute).unwrap_or_else(|| login.to_string()),
+            email,
+            role,
+            department_ids,
+        }))
+    }
+}
+
+/// Picks the highest role among the matched groups. Users without
+/// a matching group get the default role, if one is configured.
+fn map_groups(
+    mappings: &[LdapGroupMapping],
+    default_role: Option<UserRole>,
+    groups: &[String],
+) -> Option<(UserRole, Vec<i16>)> {
+    let matched: Vec<&LdapGroupMapping> = mappings.iter()
+        .filter(|mapping| groups.iter().any(|group| group.eq_ignore_ascii_case(&mapping.group)))
+        .collect();
+
+    let role = matched.iter()
+        .filter_map(|mapping| mapping.role)
+        .fold(None, |acc: Option<UserRole>, role| match acc {
+            Some(current) if current.has_access(role) => Some(current),
+            _ => Some(role),
+        })
+        .or(default_role)?;
+
+    let mut department_ids: Vec<i16> = matched.iter()
+        .filter_map(|mapping| mapping.department_id)
+        .collect();
+
+    department_ids.sort_unstable();
+    department_ids.dedup();
+
+    Some((role, department_ids))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mappings() -> Vec<LdapGroupMapping> {
+        vec![
+            LdapGroupMapping {
+                group: "CN=OIT-Staff,OU=Groups,DC=kpfu,DC=ru".to_string(),
+                role: Some(UserRole::Employee),
+                department_id: Some(1),
+            },
+            LdapGroupMapping {
+                group: "CN=OIT-Admins,OU=Groups,DC=kpfu,DC=ru".to_string(),
+                role: Some(UserRole::Admin),
+                department_id: None,
+            },
+            LdapGroupMapping {
+                group: "CN=Network,OU=Groups,DC=kpfu,DC=ru".to_string(),
+                role: None,
+                department_id: Some(2),
+            },
+        ]
+    }
+
+    #[test]
+    fn highest_role_wins() {
+        let groups = vec![
+            "CN=OIT-Admins,OU=Groups,DC=kpfu,DC=ru".to_string(),
+            "CN=OIT-Staff,OU=Groups,DC=kpfu,DC=ru".to_string(),
+        ];
+
+        let (role, departments) = map_groups(&mappings(), None, &groups).unwrap();
+
+        assert_eq!(role, UserRole::Admin);
+        assert_eq!(departments, vec![1]);
+    }
+
+    #[test]
+    fn group_names_are_case_insensitive() {
+        let groups = vec!["cn=oit-staff,ou=groups,dc=kpfu,dc=ru".to_string()];
+
+        let (role, _) = map_groups(&mappings(), None, &groups).unwrap();
+
+        assert_eq!(role, UserRole::Employee);
+    }
+
+    #[test]
+    fn department_only_group_uses_default_role() {
+        let groups = vec!["CN=Network,OU=Groups,DC=kpfu,DC=ru".to_string()];
+
+        let (role, departments) = map_groups(&mappings(), Some(UserRole::Client), &groups).unwrap();
+
+        assert_eq!(role, UserRole::Client);
+        assert_eq!(departments, vec![2]);
+    }
+
+    #[test]
+    fn unmapped_user_without_default_role_is_denied() {
+        let groups = vec!["CN=Students,OU=Groups,DC=kpfu,DC=ru".to_string()];
+
+        assert!(map_groups(&mappings(), None, &groups).is_none());
+    }
+}
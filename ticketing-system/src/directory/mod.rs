@@ -0,0 +1,38 @@
+use async_trait::async_trait;
+
+use crate::{auth::types::UserRole, utils::error_chain_fmt};
+
+pub mod ldap;
+
+#[derive(Debug, Clone)]
+pub struct DirectoryUser {
+    pub login: String,
+    pub name: String,
+    pub email: String,
+    pub role: UserRole,
+    pub department_ids: Vec<i16>,
+}
+
+#[async_trait]
+pub trait DirectoryProvider: Send + Sync + 'static {
+    /// Returns `None` when the directory rejects the credentials.
+    async fn authenticate(
+        &self,
+        login: &str,
+        password: &str
+    ) -> Result<Option<DirectoryUser>, DirectoryError>;
+}
+
+#[derive(thiserror::Error)]
+pub enum DirectoryError {
+    #[error("User is not allowed to sign in")]
+    AccessDenied,
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error)
+}
+
+impl std::fmt::Debug for DirectoryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
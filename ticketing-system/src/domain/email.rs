@@ -12,6 +12,12 @@ impl Email {
         email.validate()?;
         Ok(email)
     }
+
+    pub fn domain(&self) -> &str {
+        self.0.rsplit_once('@')
+            .map(|(_, domain)| domain)
+            .unwrap_or_default()
+    }
 }
 
 string_newtype!(Email);
@@ -5,8 +5,9 @@ use chrono::Local;
 use reqwest::Client;
 use secrecy::{ExposeSecret, SecretString};
 
-use crate::{events::Event, startup::ApplicationBaseUrl};
+use crate::{events::Event, schema::tickets::{EscalationTrigger, TicketPriority}, startup::ApplicationBaseUrl};
 
+#[derive(Clone)]
 pub struct EventPublisher {
     http_client: Client,
     bot_token: SecretString,
@@ -93,6 +94,32 @@ impl EventPublisher {
                 result.push_str("</i>");
                 result
             },
+            Event::TicketEscalated { id, title, priority, trigger } => {
+                let reason = match trigger {
+                    EscalationTrigger::NoResponse => "нет ответа",
+                    EscalationTrigger::PlannedAtPassed => "прошла плановая дата",
+                };
+
+                let priority = match priority {
+                    TicketPriority::Low => "низкий",
+                    TicketPriority::Medium => "средний",
+                    TicketPriority::High => "высокий",
+                    TicketPriority::Critical => "критический",
+                };
+
+                format!(
+                    r#"<b>⏰ Эскалация заявки</b>
+<a href="{}/ticket/{}"><b>{}</b></a>
+
+<i>Причина: {}
+Приоритет: {}</i>"#,
+                    application_url.0,
+                    id,
+                    escape_html(&title),
+                    reason,
+                    priority
+                )
+            },
         }
     }
 }
@@ -2,7 +2,7 @@ pub mod event_publisher;
 
 use chrono::{DateTime, Utc};
 
-use crate::{domain::description::Description, schema::tickets::TicketId};
+use crate::{domain::description::Description, schema::tickets::{EscalationTrigger, TicketId, TicketPriority}};
 
 #[derive(Debug)]
 pub enum Event {
@@ -15,5 +15,11 @@ pub enum Event {
         planned_at: Option<DateTime<Utc>>,
         cabinet: Option<String>,
         building_name: String,
-    }
+    },
+    TicketEscalated {
+        id: TicketId,
+        title: String,
+        priority: TicketPriority,
+        trigger: EscalationTrigger,
+    },
 }
\ No newline at end of file
@@ -13,6 +13,7 @@ pub mod image;
 pub mod macros;
 pub mod domain;
 pub mod email_client;
+pub mod directory;
 pub mod cache_expiry;
 pub mod events;
 pub mod templates;
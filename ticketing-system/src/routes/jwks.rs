@@ -0,0 +1,11 @@
+use actix_web::{http::header::CACHE_CONTROL, web, HttpResponse};
+
+use crate::auth::jwt::JwtService;
+
+pub async fn jwks(
+    jwt_service: web::Data<JwtService>,
+) -> HttpResponse {
+    HttpResponse::Ok()
+        .insert_header((CACHE_CONTROL, "public, max-age=300"))
+        .json(jwt_service.jwks())
+}
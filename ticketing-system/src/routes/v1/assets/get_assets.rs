@@ -1,6 +1,6 @@
 use std::net::IpAddr;
 
-use actix_web::{HttpResponse, ResponseError, web};
+use actix_web::{HttpResponse, ResponseError, http::StatusCode, web};
 use anyhow::Context;
 use chrono::{DateTime, Utc};
 use garde::Validate;
@@ -9,7 +9,7 @@ use mac_address::MacAddress;
 use serde::{Deserialize, Serialize};
 use sqlx::{PgPool, Postgres, QueryBuilder, prelude::FromRow, types::Json};
 
-use crate::{build_where_condition, filters::IpFilter, schema::{assets::{AssetId, Model, ModelId, Status, StatusId}, common::{PaginationResult, SortOrder}}, utils::error_chain_fmt};
+use crate::{build_where_condition, filters::IpFilter, schema::{assets::{AssetId, Model, ModelId, Status, StatusId}, common::{Cursor, CursorPaginationResult, PaginationResult, SortOrder}}, utils::error_chain_fmt};
 
 fn default_page_size() -> i8 { 50 }
 
@@ -44,10 +44,26 @@ pub struct GetAssetsSchema {
     pub ip: Option<IpFilter>,
     #[garde(skip)]
     pub mac: Option<MacAddress>,
+    /// Switches to cursor pagination, empty for the first page.
+    #[garde(skip)]
+    pub cursor: Option<String>,
+    /// Counts the total in cursor pagination.
+    #[garde(skip)]
+    #[serde(default)]
+    pub with_total: bool,
+}
+
+#[derive(Debug)]
+enum Pagination {
+    Page,
+    /// Id of the last asset of the previous batch, `None` for the first one.
+    Cursor(Option<AssetId>),
 }
 
 #[derive(thiserror::Error)]
 pub enum GetAssetsError {
+    #[error("Invalid cursor")]
+    InvalidCursor,
     #[error(transparent)]
     Unexpected(#[from] anyhow::Error)
 }
@@ -58,7 +74,14 @@ impl std::fmt::Debug for GetAssetsError {
     }
 }
 
-impl ResponseError for GetAssetsError {}
+impl ResponseError for GetAssetsError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            GetAssetsError::InvalidCursor => StatusCode::BAD_REQUEST,
+            GetAssetsError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
 
 #[derive(Deserialize, FromRow, Serialize)]
 struct Asset {
@@ -82,24 +105,52 @@ pub async fn get_assets(
     pool: web::Data<PgPool>,
     QsQuery(schema): QsQuery<GetAssetsSchema>,
 ) -> Result<HttpResponse, GetAssetsError> {
+    let pagination = match schema.cursor.as_deref() {
+        Some("") => Pagination::Cursor(None),
+        Some(cursor) => {
+            let cursor = Cursor::<AssetId>::decode(cursor)
+                .ok_or(GetAssetsError::InvalidCursor)?;
+
+            Pagination::Cursor(Some(cursor.id))
+        },
+        None => Pagination::Page,
+    };
+
     let assets = fetch_assets(
         &pool,
+        &pagination,
         &schema
     )
     .await
     .context("Failed to fetch assets from database")?;
 
-    let total_items = get_assets_count(
-        &pool,
-        &schema
-    )
-    .await
-    .context("Failed to get assets count")?;
+    let total_items = match pagination {
+        Pagination::Cursor(_) if !schema.with_total => None,
+        _ => {
+            let count = get_assets_count(
+                &pool,
+                &schema
+            )
+            .await
+            .context("Failed to get assets count")?;
 
-    Ok(HttpResponse::Ok().json(PaginationResult::new_with_pagination(
-        total_items,
+            Some(count)
+        },
+    };
+
+    if let Pagination::Page = pagination {
+        return Ok(HttpResponse::Ok().json(PaginationResult::new_with_pagination(
+            total_items.unwrap_or_default(),
+            schema.page_size,
+            assets
+        )));
+    }
+
+    Ok(HttpResponse::Ok().json(CursorPaginationResult::new(
+        assets,
         schema.page_size,
-        assets
+        total_items,
+        |asset: &Asset| Cursor::new(asset.id, ()).encode()
     )))
 }
 
@@ -109,6 +160,7 @@ pub async fn get_assets(
 )]
 async fn fetch_assets(
     pool: &PgPool,
+    pagination: &Pagination,
     schema: &GetAssetsSchema,
 ) -> Result<Vec<Asset>, sqlx::Error> {
     let mut builder = sqlx::QueryBuilder::new(r#"SELECT
@@ -145,15 +197,38 @@ async fn fetch_assets(
     JOIN asset_categories ac ON am.category = ac.id
     "#);
     
-    apply_filters(&mut builder, schema);
+    let mut has_filters = apply_filters(&mut builder, schema);
+
+    if let Pagination::Cursor(Some(after)) = *pagination {
+        build_where_condition!(@add_where_and builder, has_filters);
+
+        builder.push("a.id ")
+            .push(match schema.sort_order {
+                SortOrder::Asc => "> ",
+                SortOrder::Desc => "< ",
+            })
+            .push_bind(after);
+    }
+
+    _ = has_filters;
 
     builder.push(" ORDER BY a.id ")
-        .push(schema.sort_order.as_str())
-        .push(" LIMIT ")
-        .push_bind(schema.page_size as i64)
-        .push(" OFFSET ")
-        .push_bind(schema.page_size as i64 * (schema.page - 1) as i64)
-        .build_query_as::<Asset>()
+        .push(schema.sort_order.as_str());
+
+    match pagination {
+        Pagination::Page => {
+            builder.push(" LIMIT ")
+                .push_bind(schema.page_size as i64)
+                .push(" OFFSET ")
+                .push_bind(schema.page_size as i64 * (schema.page - 1) as i64);
+        },
+        Pagination::Cursor(_) => {
+            builder.push(" LIMIT ")
+                .push_bind(schema.page_size as i64 + 1);
+        },
+    }
+
+    builder.build_query_as::<Asset>()
         .fetch_all(pool)
         .await
 }
@@ -179,10 +254,11 @@ async fn get_assets_count(
         .map(|count: i64| count as u64)
 }
 
+/// Returns whether a `WHERE` clause was started.
 fn apply_filters<'a>(
     builder: &mut QueryBuilder<'a, Postgres>,
     schema: &'a GetAssetsSchema,
-) {
+) -> bool {
     let mut has_filters = false;
 
     build_where_condition!(builder, has_filters, schema.model_id, "model_id", "=");
@@ -198,5 +274,5 @@ fn apply_filters<'a>(
         filter.apply_to_query(builder, &mut has_filters);
     }
 
-    _ = has_filters;
+    has_filters
 }
\ No newline at end of file
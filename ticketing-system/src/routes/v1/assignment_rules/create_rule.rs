@@ -0,0 +1,110 @@
+use actix_web::{http::StatusCode, web, HttpResponse, ResponseError};
+use anyhow::Context;
+use garde_actix_web::web::Json;
+use sqlx::{PgPool, Postgres, Transaction};
+
+use crate::{routes::v1::assignment_rules::get_rules::get_rules_from_db, schema::{assignment_rule::{AssignmentRuleId, CreateAssignmentRuleSchema}, common::UserId}, utils::error_chain_fmt};
+
+#[derive(thiserror::Error)]
+pub enum CreateRuleError {
+    #[error("Department, building or assignee does not exist")]
+    InvalidReference,
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for CreateRuleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for CreateRuleError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            CreateRuleError::InvalidReference => StatusCode::BAD_REQUEST,
+            CreateRuleError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+pub async fn create_rule(
+    Json(schema): Json<CreateAssignmentRuleSchema>,
+    pool: web::Data<PgPool>,
+) -> Result<HttpResponse, CreateRuleError> {
+    let res = insert(&pool, &schema).await;
+
+    if let Err(e) = &res
+        && let Some(db_err) = e.as_database_error()
+            && db_err.is_foreign_key_violation() {
+                return Err(CreateRuleError::InvalidReference);
+            }
+
+    let id = res.context("Failed to insert assignment rule")?;
+
+    let rule = get_rules_from_db(&pool, Some(id))
+        .await
+        .context("Failed to get assignment rule from database")?
+        .pop()
+        .context("Inserted assignment rule is missing")?;
+
+    Ok(HttpResponse::Created().json(rule))
+}
+
+async fn insert(pool: &PgPool, schema: &CreateAssignmentRuleSchema) -> Result<AssignmentRuleId, sqlx::Error> {
+    let mut transaction = pool.begin().await?;
+
+    let id = insert_rule(&mut transaction, schema).await?;
+
+    insert_rule_users(&mut transaction, id, &schema.assignees).await?;
+
+    transaction.commit().await?;
+
+    Ok(id)
+}
+
+#[tracing::instrument(
+    name = "Insert assignment rule into database",
+    skip(transaction)
+)]
+async fn insert_rule(
+    transaction: &mut Transaction<'_, Postgres>,
+    schema: &CreateAssignmentRuleSchema,
+) -> Result<AssignmentRuleId, sqlx::Error> {
+    sqlx::query_scalar!(
+        "INSERT INTO assignment_rules (name, position, department_id, building_id, priority, keywords, strategy)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING id",
+        schema.name,
+        schema.position,
+        schema.department_id,
+        schema.building_id,
+        schema.priority.map(|priority| priority as i16),
+        &schema.keywords,
+        schema.strategy as i16
+    )
+    .fetch_one(&mut **transaction)
+    .await
+}
+
+#[tracing::instrument(
+    name = "Insert assignment rule users into database",
+    skip(transaction)
+)]
+pub async fn insert_rule_users(
+    transaction: &mut Transaction<'_, Postgres>,
+    rule_id: AssignmentRuleId,
+    user_ids: &[UserId],
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "INSERT INTO assignment_rule_users (rule_id, user_id)
+        SELECT $1, user_id FROM UNNEST($2::int[]) AS user_id
+        ON CONFLICT DO NOTHING",
+        rule_id,
+        user_ids
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(())
+}
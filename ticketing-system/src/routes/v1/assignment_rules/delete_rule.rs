@@ -0,0 +1,58 @@
+use actix_web::{http::StatusCode, web, HttpResponse, ResponseError};
+use anyhow::Context;
+use sqlx::PgPool;
+
+use crate::{schema::assignment_rule::AssignmentRuleId, utils::error_chain_fmt};
+
+#[derive(thiserror::Error)]
+pub enum DeleteRuleError {
+    #[error("Assignment rule not found")]
+    RuleNotFound,
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for DeleteRuleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for DeleteRuleError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            DeleteRuleError::RuleNotFound => StatusCode::NOT_FOUND,
+            DeleteRuleError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+pub async fn delete_rule(
+    rule_id: web::Path<AssignmentRuleId>,
+    pool: web::Data<PgPool>,
+) -> Result<HttpResponse, DeleteRuleError> {
+    let deleted = delete(&pool, rule_id.into_inner())
+        .await
+        .context("Failed to delete assignment rule")?;
+
+    if !deleted {
+        return Err(DeleteRuleError::RuleNotFound);
+    }
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[tracing::instrument(
+    name = "Delete assignment rule from database",
+    skip(pool)
+)]
+async fn delete(pool: &PgPool, rule_id: AssignmentRuleId) -> Result<bool, sqlx::Error> {
+    let res = sqlx::query!(
+        "DELETE FROM assignment_rules WHERE id = $1",
+        rule_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(res.rows_affected() == 1)
+}
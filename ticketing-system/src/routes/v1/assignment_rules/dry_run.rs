@@ -0,0 +1,67 @@
+use actix_web::{web, HttpResponse, ResponseError};
+use anyhow::Context;
+use garde_actix_web::web::Json;
+use sqlx::PgPool;
+
+use crate::{schema::{assignment_rule::{DryRunResult, DryRunSchema}, tickets::TicketPriority}, services::assignment::{evaluate_rules, TicketFacts}, utils::error_chain_fmt};
+
+#[derive(thiserror::Error)]
+pub enum DryRunError {
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for DryRunError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for DryRunError {}
+
+/// Shows which rule would fire for such a ticket and who would get it,
+/// without assigning anything.
+pub async fn dry_run(
+    Json(schema): Json<DryRunSchema>,
+    pool: web::Data<PgPool>,
+) -> Result<HttpResponse, DryRunError> {
+    let facts = TicketFacts {
+        department_id: schema.department_id,
+        building_id: schema.building_id,
+        priority: schema.priority.unwrap_or(TicketPriority::Low) as i16,
+        title: &schema.title,
+        description: &schema.description,
+    };
+
+    let mut conn = pool.acquire()
+        .await
+        .context("Failed to acquire connection")?;
+
+    let rule_match = evaluate_rules(&mut conn, &facts)
+        .await
+        .context("Failed to evaluate assignment rules")?;
+
+    let Some(rule_match) = rule_match else {
+        return Ok(HttpResponse::Ok().json(DryRunResult {
+            rule_id: None,
+            rule_name: None,
+            assignee_id: None,
+            assignee_name: None,
+        }));
+    };
+
+    let assignee_name = sqlx::query_scalar!(
+        "SELECT name FROM users WHERE id = $1",
+        rule_match.assignee_id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .context("Failed to get assignee name")?;
+
+    Ok(HttpResponse::Ok().json(DryRunResult {
+        rule_id: Some(rule_match.rule_id),
+        rule_name: Some(rule_match.rule_name),
+        assignee_id: Some(rule_match.assignee_id),
+        assignee_name,
+    }))
+}
@@ -0,0 +1,79 @@
+use actix_web::{web, HttpResponse, ResponseError};
+use anyhow::Context;
+use sqlx::PgPool;
+
+use crate::{schema::{assignment_rule::{AssignmentRule, AssignmentRuleId, AssignmentStrategy}, tickets::TicketPriority}, utils::error_chain_fmt};
+
+#[derive(thiserror::Error)]
+pub enum GetRulesError {
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for GetRulesError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for GetRulesError {}
+
+pub async fn get_rules(
+    pool: web::Data<PgPool>,
+) -> Result<HttpResponse, GetRulesError> {
+    let rules = get_rules_from_db(&pool, None)
+        .await
+        .context("Failed to get assignment rules from database")?;
+
+    Ok(HttpResponse::Ok().json(rules))
+}
+
+/// Returns all rules in evaluation order, or only the one with `id` when it is set.
+#[tracing::instrument(
+    name = "Get assignment rules from database",
+    skip(pool)
+)]
+pub async fn get_rules_from_db(pool: &PgPool, id: Option<AssignmentRuleId>) -> Result<Vec<AssignmentRule>, sqlx::Error> {
+    let rows = sqlx::query!(
+        r#"
+            SELECT
+                r.id,
+                r.name,
+                r.position,
+                r.is_active,
+                r.department_id,
+                r.building_id,
+                r.priority,
+                r.keywords,
+                r.strategy,
+                r.created_at,
+                ARRAY_REMOVE(ARRAY_AGG(ru.user_id ORDER BY ru.user_id), NULL) AS "assignees!"
+            FROM assignment_rules r
+            LEFT JOIN assignment_rule_users ru ON ru.rule_id = r.id
+            WHERE $1::int IS NULL OR r.id = $1
+            GROUP BY r.id
+            ORDER BY r.position, r.id
+        "#,
+        id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let rules = rows.into_iter()
+        .map(|row| AssignmentRule {
+            id: row.id,
+            name: row.name,
+            position: row.position,
+            is_active: row.is_active,
+            department_id: row.department_id,
+            building_id: row.building_id,
+            priority: row.priority.map(TicketPriority::from),
+            keywords: row.keywords,
+            strategy: AssignmentStrategy::from(row.strategy),
+            assignees: row.assignees,
+            created_at: row.created_at,
+        })
+        .collect();
+
+    Ok(rules)
+}
@@ -0,0 +1,11 @@
+pub mod get_rules;
+pub mod create_rule;
+pub mod update_rule;
+pub mod delete_rule;
+pub mod dry_run;
+
+pub use get_rules::get_rules;
+pub use create_rule::create_rule;
+pub use update_rule::update_rule;
+pub use delete_rule::delete_rule;
+pub use dry_run::dry_run;
\ No newline at end of file
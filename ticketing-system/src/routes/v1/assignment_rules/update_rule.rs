@@ -0,0 +1,122 @@
+use actix_web::{http::StatusCode, web, HttpResponse, ResponseError};
+use anyhow::Context;
+use garde_actix_web::web::Json;
+use sqlx::{PgPool, Postgres, Transaction};
+
+use crate::{routes::v1::assignment_rules::{create_rule::insert_rule_users, get_rules::get_rules_from_db}, schema::assignment_rule::{AssignmentRuleId, UpdateAssignmentRuleSchema}, utils::error_chain_fmt};
+
+#[derive(thiserror::Error)]
+pub enum UpdateRuleError {
+    #[error("Assignment rule not found")]
+    RuleNotFound,
+    #[error("Department, building or assignee does not exist")]
+    InvalidReference,
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for UpdateRuleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for UpdateRuleError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            UpdateRuleError::RuleNotFound => StatusCode::NOT_FOUND,
+            UpdateRuleError::InvalidReference => StatusCode::BAD_REQUEST,
+            UpdateRuleError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+pub async fn update_rule(
+    rule_id: web::Path<AssignmentRuleId>,
+    Json(schema): Json<UpdateAssignmentRuleSchema>,
+    pool: web::Data<PgPool>,
+) -> Result<HttpResponse, UpdateRuleError> {
+    let rule_id = rule_id.into_inner();
+
+    let res = update(&pool, rule_id, &schema).await;
+
+    if let Err(e) = &res
+        && let Some(db_err) = e.as_database_error()
+            && db_err.is_foreign_key_violation() {
+                return Err(UpdateRuleError::InvalidReference);
+            }
+
+    if !res.context("Failed to update assignment rule")? {
+        return Err(UpdateRuleError::RuleNotFound);
+    }
+
+    let rule = get_rules_from_db(&pool, Some(rule_id))
+        .await
+        .context("Failed to get assignment rule from database")?
+        .pop()
+        .ok_or(UpdateRuleError::RuleNotFound)?;
+
+    Ok(HttpResponse::Ok().json(rule))
+}
+
+/// Returns false if the rule does not exist.
+async fn update(
+    pool: &PgPool,
+    rule_id: AssignmentRuleId,
+    schema: &UpdateAssignmentRuleSchema,
+) -> Result<bool, sqlx::Error> {
+    let mut transaction = pool.begin().await?;
+
+    if !update_rule_fields(&mut transaction, rule_id, schema).await? {
+        return Ok(false);
+    }
+
+    sqlx::query!(
+        "DELETE FROM assignment_rule_users WHERE rule_id = $1",
+        rule_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    insert_rule_users(&mut transaction, rule_id, &schema.assignees).await?;
+
+    transaction.commit().await?;
+
+    Ok(true)
+}
+
+#[tracing::instrument(
+    name = "Update assignment rule",
+    skip(transaction)
+)]
+async fn update_rule_fields(
+    transaction: &mut Transaction<'_, Postgres>,
+    rule_id: AssignmentRuleId,
+    schema: &UpdateAssignmentRuleSchema,
+) -> Result<bool, sqlx::Error> {
+    let res = sqlx::query!(
+        "UPDATE assignment_rules
+        SET name = $2,
+            position = $3,
+            is_active = $4,
+            department_id = $5,
+            building_id = $6,
+            priority = $7,
+            keywords = $8,
+            strategy = $9
+        WHERE id = $1",
+        rule_id,
+        schema.name,
+        schema.position,
+        schema.is_active,
+        schema.department_id,
+        schema.building_id,
+        schema.priority.map(|priority| priority as i16),
+        &schema.keywords,
+        schema.strategy as i16
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(res.rows_affected() == 1)
+}
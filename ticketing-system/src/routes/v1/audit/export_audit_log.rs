@@ -0,0 +1,151 @@
+use actix_web::{http::{header::{ContentDisposition, DispositionParam, DispositionType}, StatusCode}, web, HttpResponse, ResponseError};
+use anyhow::Context;
+use chrono::Utc;
+use rust_xlsxwriter::{Color, Format, FormatAlign, FormatBorder, Workbook};
+use serde_qs::actix::QsQuery;
+use sqlx::PgPool;
+
+use crate::{routes::v1::audit::get_audit_log::{build_audit_log_query, is_range_valid}, schema::audit::{AuditLogEntry, GetAuditLogSchema}, utils::error_chain_fmt};
+
+const EXPORT_LIMIT: i64 = 50_000;
+
+#[derive(thiserror::Error)]
+pub enum ExportAuditLogError {
+    #[error("`from_date` must be less than or equal to `to_date`")]
+    InvalidRange,
+    #[error("Export is limited to {} entries, narrow the filters", EXPORT_LIMIT)]
+    TooManyEntries,
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for ExportAuditLogError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for ExportAuditLogError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ExportAuditLogError::InvalidRange | ExportAuditLogError::TooManyEntries => StatusCode::BAD_REQUEST,
+            ExportAuditLogError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+pub async fn export_audit_log(
+    schema: QsQuery<GetAuditLogSchema>,
+    pool: web::Data<PgPool>,
+) -> Result<HttpResponse, ExportAuditLogError> {
+    let schema = schema.into_inner();
+
+    if !is_range_valid(&schema) {
+        return Err(ExportAuditLogError::InvalidRange);
+    }
+
+    let entries = select_entries(&pool, &schema)
+        .await
+        .context("Failed to load audit log for export")?;
+
+    // One extra row is fetched to tell a full export from a truncated one.
+    if entries.len() as i64 > EXPORT_LIMIT {
+        return Err(ExportAuditLogError::TooManyEntries);
+    }
+
+    let xlsx = build_workbook_bytes(&entries)
+        .context("Failed to build XLSX export")?;
+
+    let file_name = format!("audit_log_{}.xlsx", Utc::now().format("%Y-%m-%d"));
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/vnd.openxmlformats-officedocument.spreadsheetml.sheet")
+        .insert_header(ContentDisposition {
+            disposition: DispositionType::Attachment,
+            parameters: vec![DispositionParam::Filename(file_name)],
+        })
+        .body(xlsx))
+}
+
+#[tracing::instrument(name = "Get audit log entries for export from database", skip(pool))]
+async fn select_entries(
+    pool: &PgPool,
+    schema: &GetAuditLogSchema,
+) -> Result<Vec<AuditLogEntry>, sqlx::Error> {
+    let mut builder = build_audit_log_query(schema);
+
+    builder.push(" LIMIT ").push_bind(EXPORT_LIMIT + 1);
+
+    builder.build_query_as::<AuditLogEntry>()
+        .fetch_all(pool)
+        .await
+}
+
+fn build_workbook_bytes(entries: &[AuditLogEntry]) -> Result<Vec<u8>, rust_xlsxwriter::XlsxError> {
+    let mut workbook = Workbook::new();
+    let worksheet = workbook.add_worksheet();
+    worksheet.set_name("Журнал аудита")?;
+
+    let widths = [10.0, 18.0, 24.0, 24.0, 26.0, 16.0, 30.0, 40.0];
+
+    for (col, width) in widths.iter().enumerate() {
+        worksheet.set_column_width(col as u16, *width)?;
+    }
+
+    let base_format = Format::new()
+        .set_border(FormatBorder::Thin)
+        .set_text_wrap()
+        .set_align(FormatAlign::Top);
+
+    let header_format = Format::new()
+        .set_bold()
+        .set_font_name("Times New Roman")
+        .set_background_color(Color::RGB(0xE3F2FD))
+        .set_border(FormatBorder::Thin)
+        .set_text_wrap()
+        .set_align(FormatAlign::Center)
+        .set_align(FormatAlign::VerticalCenter);
+
+    let headers = [
+        "ID",
+        "Дата",
+        "Инициатор",
+        "Пользователь",
+        "Действие",
+        "IP",
+        "User-Agent",
+        "Подробности",
+    ];
+
+    for (col, header) in headers.iter().enumerate() {
+        worksheet.write_string_with_format(0, col as u16, *header, &header_format)?;
+    }
+
+    for (idx, entry) in entries.iter().enumerate() {
+        let row = (idx + 1) as u32;
+
+        worksheet.write_number_with_format(row, 0, entry.id as f64, &base_format)?;
+        worksheet.write_string_with_format(
+            row,
+            1,
+            entry.created_at.format("%d.%m.%Y %H:%M:%S").to_string(),
+            &base_format,
+        )?;
+        worksheet.write_string_with_format(row, 2, user_label(entry.actor_id, entry.actor_name.as_deref()), &base_format)?;
+        worksheet.write_string_with_format(row, 3, user_label(entry.target_id, entry.target_name.as_deref()), &base_format)?;
+        worksheet.write_string_with_format(row, 4, &entry.action, &base_format)?;
+        worksheet.write_string_with_format(row, 5, entry.ip.as_deref().unwrap_or_default(), &base_format)?;
+        worksheet.write_string_with_format(row, 6, entry.user_agent.as_deref().unwrap_or_default(), &base_format)?;
+        worksheet.write_string_with_format(row, 7, entry.details.to_string(), &base_format)?;
+    }
+
+    workbook.save_to_buffer()
+}
+
+fn user_label(id: Option<i32>, name: Option<&str>) -> String {
+    match (id, name) {
+        (Some(id), Some(name)) => format!("{} (#{})", name, id),
+        (Some(id), None) => format!("#{}", id),
+        _ => String::new(),
+    }
+}
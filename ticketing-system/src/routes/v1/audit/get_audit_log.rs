@@ -0,0 +1,136 @@
+use actix_web::{http::StatusCode, web, HttpResponse, ResponseError};
+use anyhow::Context;
+use serde_qs::actix::QsQuery;
+use sqlx::{PgPool, Postgres, QueryBuilder};
+
+use crate::{schema::{audit::{AuditLogEntry, GetAuditLogSchema}, common::PaginationResult}, utils::error_chain_fmt};
+
+#[derive(sqlx::FromRow)]
+struct Row {
+    #[sqlx(flatten)]
+    pub entry: AuditLogEntry,
+    pub total_items: i64,
+}
+
+#[derive(thiserror::Error)]
+pub enum GetAuditLogError {
+    #[error("Page number must be greater than 0")]
+    InvalidPage,
+    #[error("`from_date` must be less than or equal to `to_date`")]
+    InvalidRange,
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for GetAuditLogError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for GetAuditLogError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            GetAuditLogError::InvalidPage => StatusCode::BAD_REQUEST,
+            GetAuditLogError::InvalidRange => StatusCode::BAD_REQUEST,
+            GetAuditLogError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+pub async fn get_audit_log(
+    schema: QsQuery<GetAuditLogSchema>,
+    pool: web::Data<PgPool>,
+) -> Result<HttpResponse, GetAuditLogError> {
+    let schema = schema.into_inner();
+
+    if !is_range_valid(&schema) {
+        return Err(GetAuditLogError::InvalidRange);
+    }
+
+    let page_size = schema.page_size
+        .map(|size| size.clamp(10, 50))
+        .unwrap_or(20);
+
+    let page = schema.page.unwrap_or(1) - 1;
+
+    if page < 0 {
+        return Err(GetAuditLogError::InvalidPage)
+    }
+
+    let rows = get_audit_log_page(&pool, page_size, page, &schema)
+        .await
+        .context("Failed to get audit log from the database")?;
+
+    let total_items = match rows.first() {
+        Some(row) => row.total_items as u64,
+        None => 0,
+    };
+
+    let entries = rows.into_iter()
+        .map(|row| row.entry)
+        .collect();
+
+    Ok(HttpResponse::Ok().json(PaginationResult::new_with_pagination(
+        total_items,
+        page_size,
+        entries
+    )))
+}
+
+pub(super) fn is_range_valid(schema: &GetAuditLogSchema) -> bool {
+    match (schema.from_date, schema.to_date) {
+        (Some(from), Some(to)) => from <= to,
+        _ => true,
+    }
+}
+
+pub(super) fn build_audit_log_query(schema: &GetAuditLogSchema) -> QueryBuilder<'_, Postgres> {
+    let mut builder = QueryBuilder::new(
+        "SELECT a.id, a.actor_id, actor.name AS actor_name, a.target_id, target.name AS target_name,
+            a.action, a.ip, a.user_agent, a.details, a.created_at, COUNT(*) OVER() AS total_items
+        FROM audit_log a
+        LEFT JOIN users actor ON actor.id = a.actor_id
+        LEFT JOIN users target ON target.id = a.target_id
+        WHERE TRUE"
+    );
+
+    if let Some(actor_id) = schema.actor_id {
+        builder.push(" AND a.actor_id = ").push_bind(actor_id);
+    }
+
+    if let Some(target_id) = schema.target_id {
+        builder.push(" AND a.target_id = ").push_bind(target_id);
+    }
+
+    if let Some(action) = schema.action {
+        builder.push(" AND a.action = ").push_bind(action.as_str());
+    }
+
+    if let Some(from_date) = schema.from_date {
+        builder.push(" AND a.created_at >= ").push_bind(from_date);
+    }
+
+    if let Some(to_date) = schema.to_date {
+        builder.push(" AND a.created_at <= ").push_bind(to_date);
+    }
+
+    builder.push(" ORDER BY a.created_at DESC, a.id DESC");
+
+    builder
+}
+
+#[tracing::instrument(
+    name = "Get page of audit log from the database",
+    skip(pool)
+)]
+async fn get_audit_log_page(pool: &PgPool, page_size: i8, page: i32, schema: &GetAuditLogSchema) -> Result<Vec<Row>, sqlx::Error> {
+    let mut builder = build_audit_log_query(schema);
+
+    builder.push(" LIMIT ").push_bind(page_size as i64)
+        .push(" OFFSET ").push_bind(page as i64 * page_size as i64);
+
+    builder.build_query_as::<Row>()
+        .fetch_all(pool)
+        .await
+}
@@ -0,0 +1,5 @@
+pub mod get_audit_log;
+pub mod export_audit_log;
+
+pub use get_audit_log::get_audit_log;
+pub use export_audit_log::export_audit_log;
\ No newline at end of file
@@ -1,14 +1,24 @@
 use actix_web::{http::StatusCode, web, HttpResponse, ResponseError};
 use anyhow::Context;
+use garde::Validate;
+use garde_actix_web::web::Json;
 use serde::Deserialize;
 use sqlx::PgPool;
 
-use crate::{auth::extractor::UserIdExtractor, domain::password::Password, schema::common::UserId, utils::{error_chain_fmt, is_password_valid}};
+use crate::{auth::extractor::{DeviceInfo, UserIdExtractor}, domain::password::Password, schema::{audit::AuditAction, common::UserId}, services::{audit::AuditEntry, password_policy::{PasswordPolicy, PasswordPolicyError}}, utils::{error_chain_fmt, is_password_valid}};
 
+struct User {
+    pub name: String,
+    pub email: String,
+    pub login: String,
+    pub password_hash: String,
+}
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Validate)]
 pub struct ChangePasswordSchema {
+    #[garde(skip)]
     pub current_password: String,
+    #[garde(dive)]
     pub new_password: Password,
 }
 
@@ -17,6 +27,8 @@ pub enum ChangePasswordError {
     #[error("Invalid password")]
     InvalidPassword,
     #[error(transparent)]
+    PolicyViolation(#[from] PasswordPolicyError),
+    #[error(transparent)]
     Unexpected(#[from] anyhow::Error)
 }
 
@@ -30,6 +42,7 @@ impl ResponseError for ChangePasswordError {
     fn status_code(&self) -> StatusCode {
         match self {
             ChangePasswordError::InvalidPassword => StatusCode::BAD_REQUEST,
+            ChangePasswordError::PolicyViolation(_) => StatusCode::BAD_REQUEST,
             ChangePasswordError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
@@ -37,34 +50,63 @@ impl ResponseError for ChangePasswordError {
 
 pub async fn change_password(
     user_id: UserIdExtractor,
-    web::Json(req): web::Json<ChangePasswordSchema>,
+    Json(req): Json<ChangePasswordSchema>,
     pool: web::Data<PgPool>,
+    password_policy: web::Data<PasswordPolicy>,
+    device: DeviceInfo,
 ) -> Result<HttpResponse, ChangePasswordError> {
     let user_id = user_id.0;
     
-    let current_password_hash = get_current_password(user_id, &pool).await
+    let user = get_user(user_id, &pool).await
         .context("Failed to get password from database.")?;
 
     if !is_password_valid(
         &req.current_password,
-        &current_password_hash
+        &user.password_hash
     ).context("Failed to check password")? {
         return Err(ChangePasswordError::InvalidPassword)
     };
 
-    update_password(user_id, req.new_password, &pool).await?;
+    password_policy.validate(&req.new_password, &[user.login.as_str(), user.name.as_str(), user.email.as_str()])?;
+
+    if password_policy.is_reused(&pool, user_id, &req.new_password).await? {
+        return Err(PasswordPolicyError::Reused.into());
+    }
+
+    let password_hash = req.new_password.hash()
+        .context("Failed to hash password")?;
+
+    let mut transaction = pool.begin()
+        .await
+        .context("Failed to begin transaction")?;
+
+    password_policy.update_password(&mut transaction, user_id, &password_hash)
+        .await
+        .context("Failed to update password in the database")?;
+
+    AuditEntry::new(AuditAction::PasswordChanged, &device)
+        .actor(user_id)
+        .target(user_id)
+        .record(&mut *transaction)
+        .await
+        .context("Failed to write audit log")?;
+
+    transaction.commit()
+        .await
+        .context("Failed to commit transaction")?;
 
     Ok(HttpResponse::Ok().finish())
 }
 
 #[tracing::instrument(
-    name = "Get current password hash from database",
+    name = "Get user with password hash from database",
     skip(pool)
 )]
-async fn get_current_password(user_id: UserId, pool: &PgPool) -> Result<String, sqlx::Error> {
-    sqlx::query_scalar!(
+async fn get_user(user_id: UserId, pool: &PgPool) -> Result<User, sqlx::Error> {
+    sqlx::query_as!(
+        User,
         r#"
-            SELECT password_hash
+            SELECT name, email, login, password_hash
             FROM users
             WHERE id = $1
         "#,
@@ -72,25 +114,4 @@ async fn get_current_password(user_id: UserId, pool: &PgPool) -> Result<String,
     )
     .fetch_one(pool)
     .await
-}
-    
-#[tracing::instrument(
-    name = "Update password in the database",
-    skip(pool, password)
-)]
-async fn update_password(user_id: UserId, password: Password, pool: &PgPool) -> Result<(), anyhow::Error> {
-    let password_hash = password.hash()?;
-    
-    sqlx::query!(
-        "UPDATE users
-        SET password_hash = $1
-        WHERE id = $2",
-        password_hash,
-        user_id
-    )
-    .execute(pool)
-    .await
-    .context("Failed to update password in the database")?;
-
-    Ok(())
 }
\ No newline at end of file
@@ -5,10 +5,16 @@ use garde_actix_web::web::Json;
 use serde::Deserialize;
 use sqlx::PgPool;
 
-use crate::{domain::password::Password, schema::action_token::ActionTokenKind, utils::error_chain_fmt};
+use crate::{auth::extractor::DeviceInfo, domain::password::Password, schema::{action_token::ActionTokenKind, audit::AuditAction, common::UserId}, services::{audit::AuditEntry, password_policy::{PasswordPolicy, PasswordPolicyError}}, utils::error_chain_fmt};
 
 use crate::services::action_token::ActionTokenStore;
 
+struct User {
+    pub id: UserId,
+    pub name: String,
+    pub login: String,
+}
+
 #[derive(Deserialize, Validate)]
 pub struct ConfirmAccountRecoverySchema {
     #[garde(skip)]
@@ -24,6 +30,8 @@ pub enum ConfirmAccountRecoveryError {
     #[error("User not found")]
     UserNotFound,
     #[error(transparent)]
+    PolicyViolation(#[from] PasswordPolicyError),
+    #[error(transparent)]
     Unexpected(#[from] anyhow::Error),
 }
 
@@ -38,6 +46,7 @@ impl ResponseError for ConfirmAccountRecoveryError {
         match self {
             ConfirmAccountRecoveryError::TokenNotExist => StatusCode::BAD_REQUEST,
             ConfirmAccountRecoveryError::UserNotFound => StatusCode::NOT_FOUND,
+            ConfirmAccountRecoveryError::PolicyViolation(_) => StatusCode::BAD_REQUEST,
             ConfirmAccountRecoveryError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
@@ -47,32 +56,61 @@ pub async fn confirm_account_recovery(
     Json(data): Json<ConfirmAccountRecoverySchema>,
     token_store: web::Data<ActionTokenStore>,
     pool: web::Data<PgPool>,
+    password_policy: web::Data<PasswordPolicy>,
+    device: DeviceInfo,
 ) -> Result<HttpResponse, ConfirmAccountRecoveryError> {
     let email = token_store
         .get_del_payload(ActionTokenKind::PasswordRecovery, &data.token)
         .await?
         .ok_or(ConfirmAccountRecoveryError::TokenNotExist)?;
 
+    let user = get_user_by_email(&pool, &email)
+        .await
+        .context("Failed to get user by email")?
+        .ok_or(ConfirmAccountRecoveryError::UserNotFound)?;
+
+    password_policy.validate(&data.new_password, &[user.login.as_str(), user.name.as_str(), email.as_str()])?;
+
+    if password_policy.is_reused(&pool, user.id, &data.new_password).await? {
+        return Err(PasswordPolicyError::Reused.into());
+    }
+
     let password_hash = data
         .new_password
         .hash()
         .context("Failed to hash password")?;
 
-    let rows_affected = sqlx::query!(
-        "UPDATE users
-        SET password_hash = $1
-        WHERE email = $2",
-        password_hash,
-        email
-    )
-    .execute(pool.as_ref())
-    .await
-    .context("Failed to update password")?
-    .rows_affected();
+    let mut transaction = pool.begin()
+        .await
+        .context("Failed to begin transaction")?;
 
-    if rows_affected == 0 {
-        return Err(ConfirmAccountRecoveryError::UserNotFound);
-    }
+    password_policy.update_password(&mut transaction, user.id, &password_hash)
+        .await
+        .context("Failed to update password")?;
+
+    AuditEntry::new(AuditAction::AccountRecovered, &device)
+        .actor(user.id)
+        .target(user.id)
+        .record(&mut *transaction)
+        .await
+        .context("Failed to write audit log")?;
+
+    transaction.commit()
+        .await
+        .context("Failed to commit transaction")?;
 
     Ok(HttpResponse::Ok().finish())
 }
+
+#[tracing::instrument(name = "Get user by email", skip(pool))]
+async fn get_user_by_email(pool: &PgPool, email: &str) -> Result<Option<User>, sqlx::Error> {
+    sqlx::query_as!(
+        User,
+        "SELECT id, name, login
+        FROM users
+        WHERE email = $1",
+        email
+    )
+    .fetch_optional(pool)
+    .await
+}
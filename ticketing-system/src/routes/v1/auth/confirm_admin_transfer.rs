@@ -4,9 +4,9 @@ use serde::Deserialize;
 use sqlx::PgPool;
 
 use crate::{
-    auth::types::UserRole,
-    schema::action_token::ActionTokenKind,
-    services::action_token::ActionTokenStore,
+    auth::{extractor::DeviceInfo, types::UserRole},
+    schema::{action_token::ActionTokenKind, audit::AuditAction},
+    services::{action_token::ActionTokenStore, audit::AuditEntry},
     utils::error_chain_fmt,
 };
 
@@ -48,6 +48,7 @@ pub async fn confirm_admin_transfer(
     web::Json(schema): web::Json<ConfirmAdminTransferSchema>,
     token_store: web::Data<ActionTokenStore>,
     pool: web::Data<PgPool>,
+    device: DeviceInfo,
 ) -> Result<HttpResponse, ConfirmAdminTransferError> {
     let payload = token_store
         .get_del_payload(ActionTokenKind::AdminTransfer, &schema.token)
@@ -95,6 +96,13 @@ pub async fn confirm_admin_transfer(
         return Err(ConfirmAdminTransferError::UserNotFound);
     }
 
+    AuditEntry::new(AuditAction::AdminTransferred, &device)
+        .actor(from_admin_id)
+        .target(to_admin_id)
+        .record(&mut *transaction)
+        .await
+        .context("Failed to write audit log")?;
+
     transaction
         .commit()
         .await
@@ -1,9 +1,17 @@
-use actix_web::{http::StatusCode, web, HttpResponse, ResponseError};
+use std::sync::LazyLock;
+
+use actix_web::{http::{header::{ContentType, RETRY_AFTER}, StatusCode}, web, HttpResponse, ResponseError};
 use anyhow::Context;
+use argon2::{password_hash::{rand_core::OsRng, SaltString}, Argon2, PasswordHasher as _};
+use rand::{RngExt as _, distr::Alphanumeric, rng};
 use serde::Deserialize;
 use sqlx::PgPool;
 
-use crate::{auth::{jwt::JwtService, token_store::TokenStore, types::{RefreshToken, UserRole}}, schema::auth::TokenResponse, utils::{error_chain_fmt, is_password_valid}};
+use crate::{auth::{extractor::DeviceInfo, jwt::JwtService, token_store::TokenStore, types::{RefreshToken, UserRole}}, directory::{DirectoryError, DirectoryProvider, DirectoryUser}, schema::{auth::{LoginResponse, TokenResponse, TwoFactorChallengeResponse}, common::UserId}, services::{action_token::ActionTokenStore, rate_limiter::{LockoutSubject, RateLimitedRoute, RateLimiter}, two_factor::TwoFactorService}, utils::{error_chain_fmt, is_password_valid}};
+
+static DUMMY_PASSWORD_HASH: LazyLock<String> = LazyLock::new(|| {
+    generate_unusable_password_hash().expect("Failed to generate dummy password hash")
+});
 
 #[derive(Deserialize)]
 pub struct LoginRequest {
@@ -17,17 +25,20 @@ struct User {
     pub id: i32,
     pub password_hash: String,
     pub role: UserRole,
+    pub role_id: i16,
     pub is_active: bool,
+    pub totp_enabled: bool,
 }
 
 #[derive(thiserror::Error)]
 pub enum LoginError {
     #[error("User cannot be authorized")]
     UserCannotBeAuthorized,
-    #[error("Invalid password")]
-    PasswordNotValid,
-    #[error("User does not exist")]
-    UserNotExist,
+    // Unknown logins and wrong passwords are indistinguishable for the client.
+    #[error("Invalid login or password")]
+    InvalidCredentials,
+    #[error("Too many attempts, try again later")]
+    TooManyAttempts(u64),
     #[error(transparent)]
     Unexpected(#[from] anyhow::Error),
 }
@@ -41,40 +52,173 @@ impl std::fmt::Debug for LoginError {
 impl ResponseError for LoginError {
     fn status_code(&self) -> StatusCode {
         match self {
-            LoginError::PasswordNotValid | LoginError::UserNotExist => StatusCode::UNAUTHORIZED,
+            LoginError::InvalidCredentials => StatusCode::UNAUTHORIZED,
             LoginError::UserCannotBeAuthorized => StatusCode::FORBIDDEN,
+            LoginError::TooManyAttempts(_) => StatusCode::TOO_MANY_REQUESTS,
             LoginError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
+
+    fn error_response(&self) -> HttpResponse {
+        let mut resp = HttpResponse::build(self.status_code());
+
+        if let LoginError::TooManyAttempts(retry_after) = self {
+            resp.insert_header((RETRY_AFTER, retry_after.to_string()));
+        }
+
+        resp.insert_header(ContentType::plaintext())
+            .body(self.to_string())
+    }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn login(
     web::Json(req): web::Json<LoginRequest>,
     pool: web::Data<PgPool>,
     jwt_service: web::Data<JwtService>,
-    token_store: web::Data<TokenStore>
+    token_store: web::Data<TokenStore>,
+    action_token_store: web::Data<ActionTokenStore>,
+    two_factor: web::Data<TwoFactorService>,
+    directory: Option<web::Data<dyn DirectoryProvider>>,
+    rate_limiter: web::Data<RateLimiter>,
+    device: DeviceInfo,
 ) -> Result<HttpResponse, LoginError> {
-    let user = get_user(&pool, &req.login)
+    if let Some(retry_after) = rate_limiter
+        .check(RateLimitedRoute::Login, &req.login, device.ip.as_deref())
+        .await? {
+        return Err(LoginError::TooManyAttempts(retry_after));
+    }
+
+    let account_id = get_account_id(&pool, &req.login)
         .await
-        .context("Failed to get user from db")?
-        .ok_or(LoginError::UserNotExist)?;
+        .context("Failed to resolve login")?;
+
+    // Failures are counted per account, whether the login or the email is used.
+    let lockout = match account_id {
+        Some(id) => LockoutSubject::User(id),
+        None => LockoutSubject::Login(&req.login),
+    };
 
-    if !is_password_valid(&req.password, &user.password_hash)
-        .context("Failed to verify password")? {
-        return Err(LoginError::PasswordNotValid);
+    if let Some(retry_after) = rate_limiter.get_lockout(lockout).await? {
+        return Err(LoginError::TooManyAttempts(retry_after));
     }
 
+    let directory_user = match directory {
+        Some(directory) => authenticate_with_directory(&pool, directory.as_ref(), &req).await?,
+        None => None,
+    };
+
+    // Local accounts, like the built-in admin, are checked when
+    // the directory does not know the user or is unavailable.
+    let user = match directory_user {
+        Some(user) => Some(user),
+        None => authenticate_locally(&pool, &req).await?,
+    };
+
+    let user = match user {
+        Some(user) => user,
+        None => {
+            rate_limiter.register_login_failure(lockout).await?;
+            return Err(LoginError::InvalidCredentials);
+        }
+    };
+
+    rate_limiter.reset_login_failures(lockout).await?;
+
     if !user.is_active {
         return Err(LoginError::UserCannotBeAuthorized)
     }
 
-    let resp = get_token_response(&jwt_service, &token_store, user, req.fingerprint)
-        .await?;
+    let resp = get_login_response(
+        &jwt_service,
+        &token_store,
+        &action_token_store,
+        &two_factor,
+        LoginSubject {
+            id: user.id,
+            role: user.role,
+            role_id: user.role_id,
+            totp_enabled: user.totp_enabled,
+        },
+        req.fingerprint,
+        device
+    )
+    .await?;
     
     Ok(HttpResponse::Ok().json(resp))
     
 }
 
+pub struct LoginSubject {
+    pub id: UserId,
+    pub role: UserRole,
+    pub role_id: i16,
+    pub totp_enabled: bool,
+}
+
+/// Issues tokens for an authenticated user, or a 2FA challenge when
+/// the user has TOTP enabled or has to enroll by policy.
+pub async fn get_login_response(
+    jwt_service: &JwtService,
+    token_store: &TokenStore,
+    action_token_store: &ActionTokenStore,
+    two_factor: &TwoFactorService,
+    subject: LoginSubject,
+    fingerprint: String,
+    device: DeviceInfo
+) -> Result<LoginResponse, anyhow::Error> {
+    let setup_required = !subject.totp_enabled && two_factor.is_required(subject.role);
+
+    if subject.totp_enabled || setup_required {
+        let challenge_token = two_factor
+            .create_challenge(action_token_store, subject.id, fingerprint)
+            .await?;
+
+        return Ok(LoginResponse::TwoFactorChallenge(TwoFactorChallengeResponse {
+            challenge_token,
+            setup_required,
+        }));
+    }
+
+    let resp = get_token_response(jwt_service, token_store, subject.id, subject.role, subject.role_id, fingerprint, device)
+        .await?;
+
+    Ok(LoginResponse::Tokens(resp))
+}
+
+async fn authenticate_locally(pool: &PgPool, req: &LoginRequest) -> Result<Option<User>, anyhow::Error> {
+    let user = get_user(pool, &req.login)
+        .await
+        .context("Failed to get user from db")?;
+
+    // Unknown logins still pay for a hash check, so response time
+    // does not reveal whether the account exists.
+    let password_hash = user.as_ref()
+        .map(|user| user.password_hash.as_str())
+        .unwrap_or(DUMMY_PASSWORD_HASH.as_str());
+
+    let is_valid = is_password_valid(&req.password, password_hash)
+        .context("Failed to verify password")?;
+
+    Ok(user.filter(|_| is_valid))
+}
+
+#[tracing::instrument(
+    name = "Get account id by login or email",
+    skip(pool)
+)]
+async fn get_account_id(pool: &PgPool, login: &str) -> Result<Option<UserId>, sqlx::Error> {
+    sqlx::query_scalar!(
+        "SELECT id FROM users
+        WHERE LOWER(login) = LOWER($1) OR LOWER(email) = LOWER($1)
+        ORDER BY id
+        LIMIT 1",
+        login.trim()
+    )
+    .fetch_optional(pool)
+    .await
+}
+
 #[tracing::instrument(
     name = "Get user from database",
     skip(pool)
@@ -82,7 +226,11 @@ pub async fn login(
 async fn get_user(pool: &PgPool, login: &str) -> Result<Option<User>, sqlx::Error> {
     let row = sqlx::query_as!(
         User,
-        r#"SELECT id, password_hash, role, is_active FROM users WHERE email = $1 OR login = $1"#,
+        r#"
+            SELECT id, password_hash, role, role_id, is_active, totp_enabled
+            FROM users
+            WHERE (email = $1 OR login = $1) AND auth_provider = 'local'
+        "#,
         login
     )
     .fetch_optional(pool)
@@ -91,19 +239,173 @@ async fn get_user(pool: &PgPool, login: &str) -> Result<Option<User>, sqlx::Erro
     Ok(row)
 }
 
+async fn authenticate_with_directory(
+    pool: &PgPool,
+    directory: &dyn DirectoryProvider,
+    req: &LoginRequest,
+) -> Result<Option<User>, LoginError> {
+    match directory.authenticate(&req.login, &req.password).await {
+        Ok(Some(directory_user)) => {
+            let user = provision_directory_user(pool, directory_user)
+                .await
+                .context("Failed to provision directory user")?;
+
+            Ok(user)
+        },
+        Ok(None) => Ok(None),
+        Err(DirectoryError::AccessDenied) => Err(LoginError::UserCannotBeAuthorized),
+        Err(e) => {
+            tracing::error!("Directory authentication failed: {:?}", e);
+            Ok(None)
+        }
+    }
+}
+
+/// Creates the user on first sign in or refreshes the profile, role and
+/// departments from the directory. Returns `None` when the login or email
+/// belongs to an account of another provider, which is never taken over,
+/// or when they match two different directory accounts.
+#[tracing::instrument(
+    name = "Provision directory user",
+    skip(pool)
+)]
+async fn provision_directory_user(pool: &PgPool, directory_user: DirectoryUser) -> Result<Option<User>, anyhow::Error> {
+    let mut transaction = pool.begin()
+        .await
+        .context("Failed to begin transaction")?;
+
+    let existing = sqlx::query!(
+        r#"SELECT id, auth_provider = 'ldap' AS "is_directory!"
+        FROM users
+        WHERE login = $1 OR email = $2
+        FOR UPDATE"#,
+        directory_user.login,
+        directory_user.email
+    )
+    .fetch_all(&mut *transaction)
+    .await
+    .context("Failed to find existing user")?;
+
+    if existing.iter().any(|user| !user.is_directory) {
+        tracing::warn!(
+            "Directory user {} collides with an account of another provider",
+            directory_user.login
+        );
+
+        return Ok(None);
+    }
+
+    if existing.len() > 1 {
+        tracing::warn!(
+            "Directory user {} matches more than one account by login and email",
+            directory_user.login
+        );
+
+        return Ok(None);
+    }
+
+    let user = match existing.first() {
+        Some(existing) => sqlx::query_as!(
+            User,
+            r#"
+                UPDATE users
+                SET name = $2, email = $3, login = $4, role = $5
+                WHERE id = $1
+                RETURNING id, password_hash, role, role_id, is_active, totp_enabled
+            "#,
+            existing.id,
+            directory_user.name,
+            directory_user.email,
+            directory_user.login,
+            directory_user.role as i16
+        )
+        .fetch_one(&mut *transaction)
+        .await
+        .context("Failed to update directory user")?,
+        None => {
+            // Directory users never sign in with a local password.
+            let password_hash = generate_unusable_password_hash()?;
+
+            sqlx::query_as!(
+                User,
+                r#"
+                    INSERT INTO users (name, email, login, password_hash, role, auth_provider)
+                    VALUES ($1, $2, $3, $4, $5, 'ldap')
+                    RETURNING id, password_hash, role, role_id, is_active, totp_enabled
+                "#,
+                directory_user.name,
+                directory_user.email,
+                directory_user.login,
+                password_hash,
+                directory_user.role as i16
+            )
+            .fetch_one(&mut *transaction)
+            .await
+            .context("Failed to insert directory user")?
+        }
+    };
+
+    sqlx::query!(
+        "DELETE FROM user_departments
+        WHERE user_id = $1 AND department_id <> ALL($2::smallint[])",
+        user.id,
+        &directory_user.department_ids
+    )
+    .execute(&mut *transaction)
+    .await
+    .context("Failed to delete user departments")?;
+
+    // Mapped departments that do not exist are skipped rather than failing the sign in.
+    sqlx::query!(
+        "INSERT INTO user_departments (user_id, department_id)
+        SELECT $1, id FROM departments WHERE id = ANY($2::smallint[])
+        ON CONFLICT (user_id, department_id) DO NOTHING",
+        user.id,
+        &directory_user.department_ids
+    )
+    .execute(&mut *transaction)
+    .await
+    .context("Failed to insert user departments")?;
+
+    transaction.commit()
+        .await
+        .context("Failed to commit transaction")?;
+
+    Ok(Some(user))
+}
+
+fn generate_unusable_password_hash() -> Result<String, anyhow::Error> {
+    let password: String = std::iter::repeat_with(|| rng().sample(Alphanumeric))
+        .map(char::from)
+        .take(64)
+        .collect();
+
+    let salt = SaltString::generate(&mut OsRng);
+
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .context("Failed to hash password")
+}
+
 #[tracing::instrument(
     name = "Get token response",
     skip_all,
-    fields(id = %user.id, role = %user.role)
+    fields(id = %user_id, role = %role)
 )]
-async fn get_token_response(jwt_service: &JwtService, token_store: &TokenStore, user: User, fingerprint: String) -> Result<TokenResponse, anyhow::Error> {
-    let access_token = jwt_service.create_access_token(user.id, user.role)
-        .context("Failed to create access token.")?;
+pub async fn get_token_response(
+    jwt_service: &JwtService,
+    token_store: &TokenStore,
+    user_id: UserId,
+    role: UserRole,
+    role_id: i16,
+    fingerprint: String,
+    device: DeviceInfo
+) -> Result<TokenResponse, anyhow::Error> {
+    let refresh_token_data = RefreshToken::new(user_id, fingerprint, device);
 
-    let refresh_token_data = RefreshToken {
-        user_id: user.id,
-        fingerprint,
-    };
+    let access_token = jwt_service.create_access_token(user_id, role, role_id, Some(&refresh_token_data.session_id))
+        .context("Failed to create access token.")?;
 
     let refresh_token = token_store.generate_refresh_token(&refresh_token_data).await
         .context("Failed to create refresh token")?;
@@ -0,0 +1,129 @@
+use actix_web::{http::StatusCode, web, HttpResponse, ResponseError};
+use anyhow::Context;
+use serde::Deserialize;
+use sqlx::PgPool;
+
+use crate::{
+    auth::{extractor::DeviceInfo, jwt::JwtService, token_store::TokenStore, types::UserRole},
+    config::MagicLinkSettings,
+    routes::v1::auth::login::{get_login_response, LoginSubject},
+    schema::{action_token::ActionTokenKind, auth::MagicLinkPayload, common::UserId},
+    services::{action_token::ActionTokenStore, two_factor::TwoFactorService},
+    utils::error_chain_fmt,
+};
+
+#[derive(Deserialize)]
+pub struct ConfirmMagicLinkSchema {
+    pub token: String,
+    pub fingerprint: String,
+}
+
+struct User {
+    role: UserRole,
+    role_id: i16,
+    is_active: bool,
+    totp_enabled: bool,
+}
+
+#[derive(thiserror::Error)]
+pub enum ConfirmMagicLinkError {
+    #[error("Magic link sign in is not enabled")]
+    NotEnabled,
+    #[error("Token does not exist")]
+    TokenNotExist,
+    #[error("Fingerprint mismatch")]
+    FingerprintMismatch,
+    #[error("User cannot be authorized")]
+    UserCannotBeAuthorized,
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for ConfirmMagicLinkError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for ConfirmMagicLinkError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ConfirmMagicLinkError::NotEnabled => StatusCode::NOT_FOUND,
+            ConfirmMagicLinkError::TokenNotExist => StatusCode::BAD_REQUEST,
+            ConfirmMagicLinkError::FingerprintMismatch => StatusCode::FORBIDDEN,
+            ConfirmMagicLinkError::UserCannotBeAuthorized => StatusCode::FORBIDDEN,
+            ConfirmMagicLinkError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn confirm_magic_link(
+    web::Json(req): web::Json<ConfirmMagicLinkSchema>,
+    pool: web::Data<PgPool>,
+    jwt_service: web::Data<JwtService>,
+    token_store: web::Data<TokenStore>,
+    action_token_store: web::Data<ActionTokenStore>,
+    two_factor: web::Data<TwoFactorService>,
+    settings: web::Data<MagicLinkSettings>,
+    device: DeviceInfo,
+) -> Result<HttpResponse, ConfirmMagicLinkError> {
+    // Links issued before the flow was turned off stop working too.
+    if !settings.enabled {
+        return Err(ConfirmMagicLinkError::NotEnabled);
+    }
+
+    // The link is used up even if it was opened on another device.
+    let payload = action_token_store
+        .get_del_payload(ActionTokenKind::MagicLink, &req.token)
+        .await?
+        .ok_or(ConfirmMagicLinkError::TokenNotExist)?;
+
+    let payload: MagicLinkPayload = serde_json::from_str(&payload)
+        .context("Failed to parse magic link payload")?;
+
+    if payload.fingerprint != req.fingerprint {
+        tracing::warn!("Magic link for user {} opened with another fingerprint", payload.user_id);
+        return Err(ConfirmMagicLinkError::FingerprintMismatch);
+    }
+
+    let user = get_user(&pool, payload.user_id)
+        .await
+        .context("Failed to get user from db")?
+        .ok_or(ConfirmMagicLinkError::UserCannotBeAuthorized)?;
+
+    if !user.is_active {
+        return Err(ConfirmMagicLinkError::UserCannotBeAuthorized);
+    }
+
+    let resp = get_login_response(
+        &jwt_service,
+        &token_store,
+        &action_token_store,
+        &two_factor,
+        LoginSubject {
+            id: payload.user_id,
+            role: user.role,
+            role_id: user.role_id,
+            totp_enabled: user.totp_enabled,
+        },
+        req.fingerprint,
+        device
+    )
+    .await?;
+
+    Ok(HttpResponse::Ok().json(resp))
+}
+
+#[tracing::instrument(name = "Get user from database", skip(pool))]
+async fn get_user(pool: &PgPool, user_id: UserId) -> Result<Option<User>, sqlx::Error> {
+    sqlx::query_as!(
+        User,
+        "SELECT role, role_id, is_active, totp_enabled
+        FROM users
+        WHERE id = $1",
+        user_id
+    )
+    .fetch_optional(pool)
+    .await
+}
@@ -0,0 +1,5 @@
+pub mod request;
+pub mod confirm;
+
+pub use request::request_magic_link;
+pub use confirm::confirm_magic_link;
\ No newline at end of file
@@ -0,0 +1,197 @@
+use actix_web::{http::{header::{ContentType, RETRY_AFTER}, StatusCode}, web, HttpResponse, ResponseError};
+use anyhow::Context;
+use garde::Validate;
+use garde_actix_web::web::Json;
+use rand::{RngExt as _, distr::Alphanumeric, rng};
+use sailfish::Template;
+use serde::Deserialize;
+use sqlx::PgPool;
+
+use crate::{
+    auth::extractor::DeviceInfo,
+    config::MagicLinkSettings,
+    domain::email::Email,
+    email_client::EmailClient,
+    schema::{action_token::ActionTokenKind, auth::MagicLinkPayload, common::UserId},
+    services::{action_token::ActionTokenStore, rate_limiter::{RateLimitedRoute, RateLimiter}},
+    startup::ApplicationBaseUrl,
+    templates::MagicLinkTemplate,
+    utils::error_chain_fmt,
+};
+
+const MAGIC_LINK_TTL: u64 = 15 * 60;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct RequestMagicLinkSchema {
+    #[garde(dive)]
+    pub email: Email,
+    #[garde(length(min = 1, max = 256))]
+    pub fingerprint: String,
+}
+
+#[derive(thiserror::Error)]
+pub enum RequestMagicLinkError {
+    #[error("Magic link sign in is not enabled")]
+    NotEnabled,
+    #[error("Too many requests, try again later")]
+    TooManyRequests(u64),
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for RequestMagicLinkError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for RequestMagicLinkError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            RequestMagicLinkError::NotEnabled => StatusCode::NOT_FOUND,
+            RequestMagicLinkError::TooManyRequests(_) => StatusCode::TOO_MANY_REQUESTS,
+            RequestMagicLinkError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let mut resp = HttpResponse::build(self.status_code());
+
+        if let RequestMagicLinkError::TooManyRequests(retry_after) = self {
+            resp.insert_header((RETRY_AFTER, retry_after.to_string()));
+        }
+
+        resp.insert_header(ContentType::plaintext())
+            .body(self.to_string())
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn request_magic_link(
+    Json(schema): Json<RequestMagicLinkSchema>,
+    token_store: web::Data<ActionTokenStore>,
+    base_url: web::Data<ApplicationBaseUrl>,
+    email_client: web::Data<dyn EmailClient>,
+    pool: web::Data<PgPool>,
+    rate_limiter: web::Data<RateLimiter>,
+    settings: web::Data<MagicLinkSettings>,
+    device: DeviceInfo,
+) -> Result<HttpResponse, RequestMagicLinkError> {
+    if !settings.enabled {
+        return Err(RequestMagicLinkError::NotEnabled);
+    }
+
+    // Limited per email whether or not the account exists.
+    if let Some(retry_after) = rate_limiter
+        .check(RateLimitedRoute::MagicLink, schema.email.as_ref(), device.ip.as_deref())
+        .await? {
+        return Err(RequestMagicLinkError::TooManyRequests(retry_after));
+    }
+
+    let user_id = get_user_by_email(&pool, &schema.email)
+        .await
+        .context("Failed to find user by email")?;
+
+    // Known and unknown emails answer the same way and just as fast, the
+    // link is issued and sent after the response.
+    if let Some(user_id) = user_id {
+        tokio::spawn(async move {
+            if let Err(e) = issue_magic_link(
+                &token_store,
+                email_client.as_ref(),
+                base_url.0.as_str(),
+                user_id,
+                &schema.email,
+                schema.fingerprint,
+            )
+            .await {
+                tracing::error!("Failed to send magic link: {:?}", e);
+            }
+        });
+    }
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[tracing::instrument(name = "Issue magic link", skip(token_store, email_client, base_url, fingerprint))]
+async fn issue_magic_link(
+    token_store: &ActionTokenStore,
+    email_client: &dyn EmailClient,
+    base_url: &str,
+    user_id: UserId,
+    email: &Email,
+    fingerprint: String,
+) -> Result<(), anyhow::Error> {
+    let payload = serde_json::to_string(&MagicLinkPayload {
+        user_id,
+        fingerprint,
+    })
+    .context("Failed to serialize magic link payload")?;
+
+    // Every request gets a new link, as it is bound to the requesting device.
+    let token = generate_token();
+
+    token_store.save_token_only(
+        ActionTokenKind::MagicLink,
+        &token,
+        &payload,
+        Some(MAGIC_LINK_TTL),
+    )
+    .await?;
+
+    send_magic_link_email(email_client, email, base_url, &token).await
+}
+
+#[tracing::instrument(name = "Get active user by email", skip(pool))]
+async fn get_user_by_email(pool: &PgPool, email: &Email) -> Result<Option<UserId>, sqlx::Error> {
+    sqlx::query_scalar!(
+        "SELECT id
+        FROM users
+        WHERE email = $1 AND is_active",
+        email.as_ref()
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+#[tracing::instrument(name = "Send magic link email", skip(email_client, base_url, token))]
+async fn send_magic_link_email(
+    email_client: &dyn EmailClient,
+    email: &Email,
+    base_url: &str,
+    token: &str,
+) -> Result<(), anyhow::Error> {
+    let link = format!("{}/magic-login?token={}", base_url, token);
+
+    let template = MagicLinkTemplate {
+        base_url,
+        link: link.clone(),
+        ttl_minutes: MAGIC_LINK_TTL / 60,
+    };
+
+    let html = template
+        .render()
+        .context("Failed to render magic link template")?;
+
+    email_client
+        .send_email(
+            email,
+            "Вход в систему",
+            &html,
+            &format!(
+                "Для входа в систему перейдите по ссылке: {}",
+                link
+            ),
+        )
+        .await
+        .context("Failed to send magic link email")
+}
+
+fn generate_token() -> String {
+    let mut rng = rng();
+
+    std::iter::repeat_with(|| rng.sample(Alphanumeric))
+        .map(char::from)
+        .take(48)
+        .collect()
+}
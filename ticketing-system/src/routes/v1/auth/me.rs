@@ -1,9 +1,10 @@
 use actix_web::{http::StatusCode, web, HttpResponse, ResponseError};
 use anyhow::Context;
+use chrono::{DateTime, Utc};
 use serde::Serialize;
 use sqlx::PgPool;
 
-use crate::{auth::{extractor::UserIdExtractor, types::{UserRole, UserStatus}}, schema::common::UserId, utils::error_chain_fmt};
+use crate::{auth::{extractor::{ActorIdExtractor, PermissionsExtractor, UserIdExtractor}, permission::Permission, types::{UserRole, UserStatus}}, schema::common::UserId, services::password_policy::PasswordPolicy, utils::error_chain_fmt};
 
 #[derive(thiserror::Error)]
 pub enum MeError {
@@ -35,20 +36,46 @@ struct User {
     pub email: String,
     pub login: String,
     pub role: UserRole,
+    pub role_id: i16,
     pub status: UserStatus,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub avatar_key: Option<String>,
+    #[serde(skip)]
+    pub password_changed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+struct MeResponse {
+    #[serde(flatten)]
+    user: User,
+    permissions: Vec<Permission>,
+    must_change_password: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    impersonated_by: Option<UserId>,
 }
 
 pub async fn me(
     id: UserIdExtractor,
+    actor_id: ActorIdExtractor,
+    permissions: PermissionsExtractor,
     pool: web::Data<PgPool>,
+    password_policy: web::Data<PasswordPolicy>,
 ) -> Result<HttpResponse, MeError> {
     let user =  get_user_info(&pool, id.0).await
         .context("Failed to get user info")?
         .ok_or(MeError::UserNotExist)?;
     
-    Ok(HttpResponse::Ok().json(user))
+    let mut permissions = permissions.0.iter().copied().collect::<Vec<_>>();
+    permissions.sort_by_key(|permission| permission.as_str());
+
+    let must_change_password = password_policy.must_change_password(user.role, user.password_changed_at);
+
+    Ok(HttpResponse::Ok().json(MeResponse {
+        user,
+        permissions,
+        must_change_password,
+        impersonated_by: actor_id.0,
+    }))
 }
 
 #[tracing::instrument(
@@ -59,7 +86,7 @@ async fn get_user_info(pool: &PgPool, user_id: UserId) -> Result<Option<User>, s
     sqlx::query_as!(
         User,
         r#"
-        SELECT id, name, login, email, role, status, avatar_key
+        SELECT id, name, login, email, role, role_id, status, avatar_key, password_changed_at
         FROM users
         WHERE id = $1
         "#,
@@ -9,6 +9,11 @@ pub mod request_account_recovery;
 pub mod validate_admin_transfer_token;
 pub mod validate_recovery_token;
 pub mod validate_token;
+pub mod two_factor;
+pub mod oidc;
+pub mod sessions;
+pub mod magic;
+pub mod signup;
 
 pub use change_password::change_password;
 pub use confirm_account_recovery::confirm_account_recovery;
@@ -20,4 +25,5 @@ pub use login::login;
 pub use request_account_recovery::request_account_recovery;
 pub use validate_admin_transfer_token::validate_admin_transfer_token;
 pub use validate_recovery_token::validate_recovery_token;
-pub use validate_token::validate_register_token;
\ No newline at end of file
+pub use validate_token::validate_register_token;
+pub use signup::{get_signup_status, signup};
\ No newline at end of file
@@ -0,0 +1,248 @@
+use actix_web::{cookie::Cookie, http::StatusCode, web, HttpRequest, HttpResponse, ResponseError};
+use anyhow::Context;
+use serde::Deserialize;
+use sqlx::PgPool;
+
+use crate::{auth::{extractor::DeviceInfo, jwt::JwtService, token_store::TokenStore, types::UserRole}, routes::v1::auth::{login::{get_login_response, LoginSubject}, oidc::login::STATE_COOKIE}, schema::{action_token::ActionTokenKind, common::UserId}, services::{action_token::ActionTokenStore, oidc::{state_binding, OidcClient, OidcLoginState}, two_factor::TwoFactorService}, utils::error_chain_fmt};
+
+#[derive(Deserialize)]
+pub struct OidcCallbackQuery {
+    pub state: String,
+    pub code: Option<String>,
+    pub error: Option<String>,
+}
+
+struct User {
+    id: i32,
+    role: UserRole,
+    role_id: i16,
+    is_active: bool,
+    totp_enabled: bool,
+}
+
+#[derive(thiserror::Error)]
+pub enum OidcCallbackError {
+    #[error("OIDC is not configured")]
+    NotConfigured,
+    #[error("State not found or expired")]
+    InvalidState,
+    #[error("Provider returned an error: {0}")]
+    ProviderError(String),
+    #[error("Authentication failed")]
+    AuthenticationFailed(#[source] anyhow::Error),
+    #[error("No user is linked to this identity")]
+    UserNotLinked,
+    #[error("Identity is linked to another user")]
+    IdentityLinkedToAnotherUser,
+    #[error("User cannot be authorized")]
+    UserCannotBeAuthorized,
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for OidcCallbackError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for OidcCallbackError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            OidcCallbackError::NotConfigured => StatusCode::NOT_FOUND,
+            OidcCallbackError::InvalidState => StatusCode::BAD_REQUEST,
+            OidcCallbackError::ProviderError(_) | OidcCallbackError::AuthenticationFailed(_) => StatusCode::UNAUTHORIZED,
+            OidcCallbackError::UserNotLinked | OidcCallbackError::UserCannotBeAuthorized => StatusCode::FORBIDDEN,
+            OidcCallbackError::IdentityLinkedToAnotherUser => StatusCode::CONFLICT,
+            OidcCallbackError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+/// Called by the frontend with the query string the provider redirected to.
+/// Responds like the password login, including the 2FA challenge. When the
+/// login was started from `/oidc/link`, the identity is linked first, a new
+/// identity is otherwise linked by its verified email.
+#[tracing::instrument(
+    name = "Finish OIDC login",
+    skip_all
+)]
+#[allow(clippy::too_many_arguments)]
+pub async fn oidc_callback(
+    req: HttpRequest,
+    web::Query(query): web::Query<OidcCallbackQuery>,
+    oidc: Option<web::Data<OidcClient>>,
+    pool: web::Data<PgPool>,
+    jwt_service: web::Data<JwtService>,
+    token_store: web::Data<TokenStore>,
+    action_token_store: web::Data<ActionTokenStore>,
+    two_factor: web::Data<TwoFactorService>,
+    device: DeviceInfo,
+) -> Result<HttpResponse, OidcCallbackError> {
+    let oidc = oidc.ok_or(OidcCallbackError::NotConfigured)?;
+
+    // The state is consumed even on failure, so it cannot be replayed.
+    let login_state: OidcLoginState = action_token_store
+        .get_del_payload(ActionTokenKind::OidcState, &query.state)
+        .await?
+        .map(|payload| serde_json::from_str(&payload).context("Failed to parse OIDC login state"))
+        .transpose()?
+        .ok_or(OidcCallbackError::InvalidState)?;
+
+    // A state started in another browser must not log this one in.
+    let is_bound = req.cookie(STATE_COOKIE)
+        .is_some_and(|cookie| cookie.value() == state_binding(&query.state));
+
+    if !is_bound {
+        return Err(OidcCallbackError::InvalidState);
+    }
+
+    if let Some(error) = query.error {
+        return Err(OidcCallbackError::ProviderError(error));
+    }
+
+    let code = query.code.ok_or(OidcCallbackError::InvalidState)?;
+
+    let id_token = oidc.exchange_code(&code, &login_state.code_verifier)
+        .await
+        .map_err(OidcCallbackError::AuthenticationFailed)?;
+
+    let claims = oidc.validate_id_token(&id_token, &login_state.nonce)
+        .await
+        .map_err(OidcCallbackError::AuthenticationFailed)?;
+
+    if let Some(user_id) = login_state.link_user_id {
+        let linked_user_id = link_identity(&pool, &oidc.provider_name, &claims.sub, user_id).await?;
+
+        if linked_user_id != user_id {
+            return Err(OidcCallbackError::IdentityLinkedToAnotherUser);
+        }
+    }
+
+    let mut user = get_user_by_identity(&pool, &oidc.provider_name, &claims.sub).await?;
+
+    if user.is_none()
+        && let Some(email) = claims.email.as_deref().filter(|_| claims.email_verified)
+        && link_identity_by_email(&pool, &oidc.provider_name, &claims.sub, email).await?
+    {
+        user = get_user_by_identity(&pool, &oidc.provider_name, &claims.sub).await?;
+    }
+
+    let user = user.ok_or(OidcCallbackError::UserNotLinked)?;
+
+    if !user.is_active {
+        return Err(OidcCallbackError::UserCannotBeAuthorized);
+    }
+
+    let resp = get_login_response(
+        &jwt_service,
+        &token_store,
+        &action_token_store,
+        &two_factor,
+        LoginSubject {
+            id: user.id,
+            role: user.role,
+            role_id: user.role_id,
+            totp_enabled: user.totp_enabled,
+        },
+        login_state.fingerprint,
+        device
+    )
+    .await?;
+
+    let mut removal = Cookie::new(STATE_COOKIE, "");
+    removal.make_removal();
+
+    Ok(
+        HttpResponse::Ok()
+            .cookie(removal)
+            .json(resp)
+    )
+}
+
+#[tracing::instrument(
+    name = "Get user by OIDC identity",
+    skip(pool)
+)]
+async fn get_user_by_identity(
+    pool: &PgPool,
+    provider: &str,
+    subject: &str,
+) -> Result<Option<User>, anyhow::Error> {
+    sqlx::query_as!(
+        User,
+        r#"
+            SELECT u.id, u.role, u.role_id, u.is_active, u.totp_enabled
+            FROM user_identities i
+            JOIN users u ON u.id = i.user_id
+            WHERE i.provider = $1 AND i.subject = $2
+        "#,
+        provider,
+        subject
+    )
+    .fetch_optional(pool)
+    .await
+    .context("Failed to get user by identity")
+}
+
+/// Links the identity from a signed in session. Returns the user the
+/// identity ends up linked to.
+#[tracing::instrument(
+    name = "Link OIDC identity",
+    skip(pool)
+)]
+async fn link_identity(
+    pool: &PgPool,
+    provider: &str,
+    subject: &str,
+    user_id: UserId,
+) -> Result<UserId, anyhow::Error> {
+    sqlx::query_scalar!(
+        "INSERT INTO user_identities (provider, subject, user_id)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (provider, subject) DO UPDATE SET user_id = user_identities.user_id
+        RETURNING user_id",
+        provider,
+        subject,
+        user_id
+    )
+    .fetch_one(pool)
+    .await
+    .context("Failed to link identity")
+}
+
+/// Links a new identity to the only user with the email the provider has
+/// verified. Users that already have an identity from the provider are left
+/// alone, so a second provider account can not take one over.
+/// Returns whether the identity was linked.
+#[tracing::instrument(
+    name = "Link OIDC identity by email",
+    skip(pool)
+)]
+async fn link_identity_by_email(
+    pool: &PgPool,
+    provider: &str,
+    subject: &str,
+    email: &str,
+) -> Result<bool, anyhow::Error> {
+    let res = sqlx::query!(
+        "INSERT INTO user_identities (provider, subject, user_id)
+        SELECT $1::varchar, $2::varchar, u.id
+        FROM users u
+        WHERE LOWER(u.email) = LOWER($3)
+            AND (SELECT COUNT(*) FROM users WHERE LOWER(email) = LOWER($3)) = 1
+            AND NOT EXISTS (
+                SELECT 1 FROM user_identities i
+                WHERE i.user_id = u.id AND i.provider = $1
+            )
+        ON CONFLICT (provider, subject) DO NOTHING",
+        provider,
+        subject,
+        email
+    )
+    .execute(pool)
+    .await
+    .context("Failed to link identity by email")?;
+
+    Ok(res.rows_affected() == 1)
+}
@@ -0,0 +1,63 @@
+use actix_web::{http::StatusCode, web, HttpResponse, ResponseError};
+use serde::{Deserialize, Serialize};
+
+use crate::{auth::extractor::UserIdExtractor, routes::v1::auth::oidc::login::begin_login, services::{action_token::ActionTokenStore, oidc::OidcClient}, utils::error_chain_fmt};
+
+#[derive(Deserialize)]
+pub struct OidcLinkRequest {
+    pub fingerprint: String,
+}
+
+#[derive(Serialize)]
+pub struct OidcLinkResponse {
+    pub url: String,
+}
+
+#[derive(thiserror::Error)]
+pub enum OidcLinkError {
+    #[error("OIDC is not configured")]
+    NotConfigured,
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for OidcLinkError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for OidcLinkError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            OidcLinkError::NotConfigured => StatusCode::NOT_FOUND,
+            OidcLinkError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+/// Starts a login that links the provider identity to the signed in user.
+/// The frontend navigates to the returned URL, and the callback finishes
+/// like a regular OIDC login.
+#[tracing::instrument(
+    name = "Start OIDC identity link",
+    skip_all,
+    fields(user_id = %user_id.0)
+)]
+pub async fn oidc_link(
+    user_id: UserIdExtractor,
+    web::Json(req): web::Json<OidcLinkRequest>,
+    oidc: Option<web::Data<OidcClient>>,
+    action_token_store: web::Data<ActionTokenStore>,
+) -> Result<HttpResponse, OidcLinkError> {
+    let oidc = oidc.ok_or(OidcLinkError::NotConfigured)?;
+
+    let login_state = oidc.new_login_state(req.fingerprint, Some(user_id.0));
+    let (url, cookie) = begin_login(&oidc, &action_token_store, &login_state).await?;
+
+    Ok(
+        HttpResponse::Ok()
+            .cookie(cookie)
+            .json(OidcLinkResponse { url })
+    )
+}
@@ -0,0 +1,96 @@
+use actix_web::{cookie::{time::Duration, Cookie, SameSite}, http::{header::LOCATION, StatusCode}, web, HttpResponse, ResponseError};
+use anyhow::Context;
+use serde::Deserialize;
+
+use crate::{schema::action_token::ActionTokenKind, services::{action_token::ActionTokenStore, oidc::{generate_random_string, state_binding, OidcClient, OidcLoginState}}, utils::error_chain_fmt};
+
+const STATE_TTL: u64 = 10 * 60;
+
+pub const STATE_COOKIE: &str = "oidc_state";
+
+#[derive(Deserialize)]
+pub struct OidcLoginQuery {
+    pub fingerprint: String,
+}
+
+#[derive(thiserror::Error)]
+pub enum OidcLoginError {
+    #[error("OIDC is not configured")]
+    NotConfigured,
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for OidcLoginError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for OidcLoginError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            OidcLoginError::NotConfigured => StatusCode::NOT_FOUND,
+            OidcLoginError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+/// Redirects the browser to the provider. State, nonce and PKCE verifier
+/// are kept in Redis until the callback, and a hash of the state is set
+/// as a cookie so only this browser can finish the login.
+#[tracing::instrument(
+    name = "Start OIDC login",
+    skip_all
+)]
+pub async fn oidc_login(
+    web::Query(query): web::Query<OidcLoginQuery>,
+    oidc: Option<web::Data<OidcClient>>,
+    action_token_store: web::Data<ActionTokenStore>,
+) -> Result<HttpResponse, OidcLoginError> {
+    let oidc = oidc.ok_or(OidcLoginError::NotConfigured)?;
+
+    let login_state = oidc.new_login_state(query.fingerprint, None);
+    let (url, cookie) = begin_login(&oidc, &action_token_store, &login_state).await?;
+
+    Ok(
+        HttpResponse::Found()
+            .insert_header((LOCATION, url))
+            .cookie(cookie)
+            .finish()
+    )
+}
+
+/// Saves the login state under a new `state` and returns the provider URL
+/// together with the cookie binding the state to the browser.
+pub(super) async fn begin_login(
+    oidc: &OidcClient,
+    action_token_store: &ActionTokenStore,
+    login_state: &OidcLoginState,
+) -> Result<(String, Cookie<'static>), anyhow::Error> {
+    let state = generate_random_string(32);
+
+    let payload = serde_json::to_string(login_state)
+        .context("Failed to serialize OIDC login state")?;
+
+    action_token_store.save_token_only(
+        ActionTokenKind::OidcState,
+        &state,
+        &payload,
+        Some(STATE_TTL)
+    )
+    .await?;
+
+    let url = oidc.authorization_url(&state, login_state).await?;
+
+    // Without a path the cookie is scoped to the directory of this route,
+    // which is where the callback lives.
+    let cookie = Cookie::build(STATE_COOKIE, state_binding(&state))
+        .http_only(true)
+        .secure(true)
+        .same_site(SameSite::Lax)
+        .max_age(Duration::seconds(STATE_TTL as i64))
+        .finish();
+
+    Ok((url, cookie))
+}
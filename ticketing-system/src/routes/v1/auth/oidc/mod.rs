@@ -0,0 +1,7 @@
+pub mod login;
+pub mod link;
+pub mod callback;
+
+pub use login::oidc_login;
+pub use link::oidc_link;
+pub use callback::oidc_callback;
@@ -3,7 +3,7 @@ use anyhow::Context;
 use serde::Deserialize;
 use sqlx::PgPool;
 
-use crate::{auth::{jwt::JwtService, token_store::{TokenStore, TokenStoreError}, types::UserRole}, schema::{auth::TokenResponse, common::UserId}, utils::error_chain_fmt};
+use crate::{auth::{extractor::DeviceInfo, jwt::JwtService, token_store::{TokenStore, TokenStoreError}, types::UserRole}, schema::{audit::AuditAction, auth::TokenResponse, common::UserId}, services::audit::AuditEntry, utils::error_chain_fmt};
 
 #[derive(Debug, Deserialize)]
 pub struct RefreshTokenRequest {
@@ -42,10 +42,22 @@ pub async fn refresh_token(
     token_store: web::Data<TokenStore>,
     pool: web::Data<PgPool>,
     jwt_service: web::Data<JwtService>,
+    device: DeviceInfo,
 ) -> Result<HttpResponse, RefreshTokenError> {
-    let token_data = token_store.get_del_refresh_token(&req.refresh_token, &req.fingerprint).await?;
+    let token_data = match token_store.get_del_refresh_token(&req.refresh_token, &req.fingerprint).await {
+        Err(TokenStoreError::FingerprintMismatch(user_id)) => {
+            AuditEntry::new(AuditAction::FingerprintMismatch, &device)
+                .target(user_id)
+                .record(pool.get_ref())
+                .await
+                .context("Failed to write audit log")?;
 
-    let (role, is_active) = get_user_role_and_is_active(&pool, token_data.user_id)
+            return Err(TokenStoreError::FingerprintMismatch(user_id).into());
+        },
+        res => res?,
+    };
+
+    let (role, role_id, is_active) = get_user_role_and_is_active(&pool, token_data.user_id)
         .await
         .context("Failed to get user role and status from database.")?;
 
@@ -53,7 +65,10 @@ pub async fn refresh_token(
         return Err(RefreshTokenError::UserAccountDeactivated)
     }
 
-    let access_token = jwt_service.create_access_token(token_data.user_id, role)
+    // The rotated token stays in the same session.
+    let token_data = token_data.rotate(device);
+
+    let access_token = jwt_service.create_access_token(token_data.user_id, role, role_id, Some(&token_data.session_id))
         .context("Failed to create access token.")?;
 
     let refresh_token = token_store.generate_refresh_token(&token_data)
@@ -72,14 +87,14 @@ pub async fn refresh_token(
     name = "Get user role and status from database.",
     skip(pool)
 )]
-async fn get_user_role_and_is_active(pool: &PgPool, user_id: UserId) -> Result<(UserRole, bool), sqlx::Error> {
+async fn get_user_role_and_is_active(pool: &PgPool, user_id: UserId) -> Result<(UserRole, i16, bool), sqlx::Error> {
     let rec = sqlx::query!(
-        "SELECT role, is_active FROM users
+        "SELECT role, role_id, is_active FROM users
         WHERE id = $1",
         user_id
     )
     .fetch_one(pool)
     .await?;
 
-    Ok((UserRole::from(rec.role), rec.is_active))
+    Ok((UserRole::from(rec.role), rec.role_id, rec.is_active))
 }
\ No newline at end of file
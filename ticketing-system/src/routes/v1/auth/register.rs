@@ -5,7 +5,7 @@ use garde_actix_web::web::Json;
 use serde::Deserialize;
 use sqlx::PgPool;
 
-use crate::{domain::{login::Login, name::Name, password::Password}, services::registration_token::RegistrationTokenStore, utils::error_chain_fmt};
+use crate::{domain::{login::Login, name::Name, password::Password}, services::{password_policy::{PasswordPolicy, PasswordPolicyError}, registration_token::RegistrationTokenStore}, utils::error_chain_fmt};
 
 #[derive(thiserror::Error)]
 pub enum RegisterError {
@@ -16,6 +16,8 @@ pub enum RegisterError {
     #[error("Login already in use")]
     LoginAlreadyExists,
     #[error(transparent)]
+    PolicyViolation(#[from] PasswordPolicyError),
+    #[error(transparent)]
     Unexpected(#[from] anyhow::Error)
 }
 
@@ -30,6 +32,7 @@ impl ResponseError for RegisterError {
         match self {
             RegisterError::TokenNotExists => StatusCode::UNAUTHORIZED,
             RegisterError::EmailAlreadyExists | RegisterError::LoginAlreadyExists => StatusCode::BAD_REQUEST,
+            RegisterError::PolicyViolation(_) => StatusCode::BAD_REQUEST,
             RegisterError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
@@ -56,8 +59,15 @@ pub async fn register(
     Json(data): Json<RegisterForm>,
     pool: web::Data<PgPool>,
     store: web::Data<RegistrationTokenStore>,
+    password_policy: web::Data<PasswordPolicy>,
 ) -> Result<HttpResponse, RegisterError> {
-    let email = store.get_del_email(&data.token).await?
+    let email = store.get_email(&data.token).await?
+        .ok_or(RegisterError::TokenNotExists)?;
+
+    // Checked before the token is consumed, so the user can pick another password.
+    password_policy.validate(&data.password, &[data.login.as_ref(), data.name.as_ref(), email.as_str()])?;
+
+    store.get_del_email(&data.token).await?
         .ok_or(RegisterError::TokenNotExists)?;
 
     let password_hash = data.password.hash()
@@ -1,4 +1,4 @@
-use actix_web::{web, HttpResponse, ResponseError};
+use actix_web::{http::{header::{ContentType, RETRY_AFTER}, StatusCode}, web, HttpResponse, ResponseError};
 use anyhow::Context;
 use garde::Validate;
 use garde_actix_web::web::Json;
@@ -8,10 +8,11 @@ use serde::Deserialize;
 use sqlx::PgPool;
 
 use crate::{
+    auth::extractor::DeviceInfo,
     domain::email::Email,
     email_client::EmailClient,
-    schema::action_token::ActionTokenKind,
-    services::action_token::ActionTokenStore,
+    schema::{action_token::ActionTokenKind, audit::AuditAction},
+    services::{action_token::ActionTokenStore, audit::AuditEntry, rate_limiter::{RateLimitedRoute, RateLimiter}},
     startup::ApplicationBaseUrl,
     templates::ResetPasswordTemplate,
     utils::error_chain_fmt,
@@ -27,6 +28,8 @@ pub struct RequestAccountRecoverySchema {
 
 #[derive(thiserror::Error)]
 pub enum RequestAccountRecoveryError {
+    #[error("Too many requests, try again later")]
+    TooManyRequests(u64),
     #[error(transparent)]
     Unexpected(#[from] anyhow::Error),
 }
@@ -37,7 +40,25 @@ impl std::fmt::Debug for RequestAccountRecoveryError {
     }
 }
 
-impl ResponseError for RequestAccountRecoveryError {}
+impl ResponseError for RequestAccountRecoveryError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            RequestAccountRecoveryError::TooManyRequests(_) => StatusCode::TOO_MANY_REQUESTS,
+            RequestAccountRecoveryError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let mut resp = HttpResponse::build(self.status_code());
+
+        if let RequestAccountRecoveryError::TooManyRequests(retry_after) = self {
+            resp.insert_header((RETRY_AFTER, retry_after.to_string()));
+        }
+
+        resp.insert_header(ContentType::plaintext())
+            .body(self.to_string())
+    }
+}
 
 pub async fn request_account_recovery(
     Json(schema): Json<RequestAccountRecoverySchema>,
@@ -45,14 +66,23 @@ pub async fn request_account_recovery(
     base_url: web::Data<ApplicationBaseUrl>,
     email_client: web::Data<dyn EmailClient>,
     pool: web::Data<PgPool>,
+    rate_limiter: web::Data<RateLimiter>,
+    device: DeviceInfo,
 ) -> Result<HttpResponse, RequestAccountRecoveryError> {
+    // Limited per email whether or not the account exists.
+    if let Some(retry_after) = rate_limiter
+        .check(RateLimitedRoute::AccountRecovery, schema.email.as_ref(), device.ip.as_deref())
+        .await? {
+        return Err(RequestAccountRecoveryError::TooManyRequests(retry_after));
+    }
+
     let user = get_user_by_email(&pool, &schema.email)
         .await
         .context("Failed to find user by email")?;
 
-    if user.is_none() {
+    let Some(user_id) = user else {
         return Ok(HttpResponse::Ok().finish());
-    }
+    };
 
     let email_ref = schema.email.as_ref();
 
@@ -80,6 +110,12 @@ pub async fn request_account_recovery(
     )
     .await?;
 
+    AuditEntry::new(AuditAction::AccountRecoveryRequested, &device)
+        .target(user_id)
+        .record(pool.get_ref())
+        .await
+        .context("Failed to write audit log")?;
+
     Ok(HttpResponse::Ok().finish())
 }
 
@@ -0,0 +1,20 @@
+use actix_web::{web, HttpResponse};
+
+use crate::{auth::{extractor::{SessionIdExtractor, UserIdExtractor}, token_store::{TokenStore, TokenStoreError}}, schema::auth::SessionResponse};
+
+pub async fn get_sessions(
+    user_id: UserIdExtractor,
+    session_id: SessionIdExtractor,
+    token_store: web::Data<TokenStore>,
+) -> Result<HttpResponse, TokenStoreError> {
+    let mut sessions = token_store.get_user_sessions(user_id.0).await?;
+
+    sessions.sort_by_key(|session| std::cmp::Reverse(session.last_used_at));
+
+    let resp: Vec<_> = sessions
+        .into_iter()
+        .map(|session| SessionResponse::new(session, session_id.0.as_deref()))
+        .collect();
+
+    Ok(HttpResponse::Ok().json(resp))
+}
@@ -0,0 +1,7 @@
+pub mod get_sessions;
+pub mod revoke_session;
+pub mod revoke_other_sessions;
+
+pub use get_sessions::get_sessions;
+pub use revoke_session::revoke_session;
+pub use revoke_other_sessions::revoke_other_sessions;
\ No newline at end of file
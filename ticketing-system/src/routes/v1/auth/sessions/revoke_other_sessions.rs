@@ -0,0 +1,14 @@
+use actix_web::{web, HttpResponse};
+
+use crate::auth::{extractor::{SessionIdExtractor, UserIdExtractor}, token_store::{TokenStore, TokenStoreError}};
+
+/// Logs out everywhere except the session of the current access token.
+pub async fn revoke_other_sessions(
+    user_id: UserIdExtractor,
+    session_id: SessionIdExtractor,
+    token_store: web::Data<TokenStore>,
+) -> Result<HttpResponse, TokenStoreError> {
+    token_store.revoke_other_sessions(user_id.0, session_id.0.as_deref()).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
@@ -0,0 +1,38 @@
+use actix_web::{http::StatusCode, web, HttpResponse, ResponseError};
+
+use crate::{auth::{extractor::UserIdExtractor, token_store::{TokenStore, TokenStoreError}}, utils::error_chain_fmt};
+
+#[derive(thiserror::Error)]
+pub enum RevokeSessionError {
+    #[error("Session not found")]
+    SessionNotFound,
+    #[error(transparent)]
+    TokenStore(#[from] TokenStoreError),
+}
+
+impl std::fmt::Debug for RevokeSessionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for RevokeSessionError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            RevokeSessionError::SessionNotFound => StatusCode::NOT_FOUND,
+            RevokeSessionError::TokenStore(e) => e.status_code(),
+        }
+    }
+}
+
+pub async fn revoke_session(
+    user_id: UserIdExtractor,
+    session_id: web::Path<String>,
+    token_store: web::Data<TokenStore>,
+) -> Result<HttpResponse, RevokeSessionError> {
+    if !token_store.revoke_session(user_id.0, &session_id).await? {
+        return Err(RevokeSessionError::SessionNotFound);
+    }
+
+    Ok(HttpResponse::NoContent().finish())
+}
@@ -0,0 +1,174 @@
+use actix_web::{http::{header::{ContentType, RETRY_AFTER}, StatusCode}, web, HttpResponse, ResponseError};
+use anyhow::Context;
+use garde::Validate;
+use garde_actix_web::web::Json;
+use rand::{RngExt as _, distr::Alphanumeric, rng};
+use serde::Deserialize;
+use sqlx::PgPool;
+
+use crate::{
+    auth::extractor::DeviceInfo,
+    domain::email::Email,
+    email_client::EmailClient,
+    routes::v1::user::invite::send_confirmation_email,
+    schema::auth::SignupStatusResponse,
+    services::{rate_limiter::{RateLimitedRoute, RateLimiter}, registration_token::RegistrationTokenStore, signup::SignupService},
+    startup::ApplicationBaseUrl,
+    utils::error_chain_fmt,
+};
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct SignupSchema {
+    #[garde(dive)]
+    pub email: Email,
+}
+
+#[derive(thiserror::Error)]
+pub enum SignupError {
+    #[error("Signup is disabled")]
+    Disabled,
+    #[error("Signup is not allowed for this email domain")]
+    DomainNotAllowed,
+    #[error("Too many requests, try again later")]
+    TooManyRequests(u64),
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for SignupError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for SignupError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            SignupError::Disabled => StatusCode::FORBIDDEN,
+            SignupError::DomainNotAllowed => StatusCode::BAD_REQUEST,
+            SignupError::TooManyRequests(_) => StatusCode::TOO_MANY_REQUESTS,
+            SignupError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let mut resp = HttpResponse::build(self.status_code());
+
+        if let SignupError::TooManyRequests(retry_after) = self {
+            resp.insert_header((RETRY_AFTER, retry_after.to_string()));
+        }
+
+        resp.insert_header(ContentType::plaintext())
+            .body(self.to_string())
+    }
+}
+
+/// Sends the same confirmation link as an invite, so the account is
+/// created by `register` with the default client role.
+#[tracing::instrument(
+    name = "Sign up a new user",
+    skip(signup_service, reg_store, base_url, email_client, pool, rate_limiter, device)
+)]
+#[allow(clippy::too_many_arguments)]
+pub async fn signup(
+    Json(schema): Json<SignupSchema>,
+    signup_service: web::Data<SignupService>,
+    reg_store: web::Data<RegistrationTokenStore>,
+    base_url: web::Data<ApplicationBaseUrl>,
+    email_client: web::Data<dyn EmailClient>,
+    pool: web::Data<PgPool>,
+    rate_limiter: web::Data<RateLimiter>,
+    device: DeviceInfo,
+) -> Result<HttpResponse, SignupError> {
+    if !signup_service.is_enabled(&pool).await? {
+        return Err(SignupError::Disabled);
+    }
+
+    if !signup_service.is_domain_allowed(schema.email.domain()) {
+        return Err(SignupError::DomainNotAllowed);
+    }
+
+    if let Some(retry_after) = rate_limiter
+        .check(RateLimitedRoute::Signup, schema.email.as_ref(), device.ip.as_deref())
+        .await? {
+        return Err(SignupError::TooManyRequests(retry_after));
+    }
+
+    // Same response for registered emails, so signup can't be used to enumerate users.
+    if is_email_exists(&pool, &schema.email)
+        .await
+        .context("Failed to check email existence")? {
+        return Ok(HttpResponse::Ok().finish());
+    }
+
+    // Sent after the response, so timing doesn't tell registered emails apart either.
+    tokio::spawn(async move {
+        if let Err(e) = issue_confirmation(
+            &reg_store,
+            email_client.as_ref(),
+            base_url.0.as_str(),
+            &schema.email,
+        )
+        .await {
+            tracing::error!("Failed to send signup confirmation: {:?}", e);
+        }
+    });
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[tracing::instrument(name = "Issue signup confirmation", skip(reg_store, email_client, base_url))]
+async fn issue_confirmation(
+    reg_store: &RegistrationTokenStore,
+    email_client: &dyn EmailClient,
+    base_url: &str,
+    email: &Email,
+) -> Result<(), anyhow::Error> {
+    let token = if let Some(token) = reg_store.get_token(email).await? {
+        token
+    } else {
+        generate_token()
+    };
+
+    reg_store.save_token(&token, email).await?;
+
+    send_confirmation_email(email_client, email, base_url, &token).await
+}
+
+pub async fn get_signup_status(
+    signup_service: web::Data<SignupService>,
+    pool: web::Data<PgPool>,
+) -> Result<HttpResponse, SignupError> {
+    let enabled = signup_service.is_enabled(&pool).await?;
+
+    Ok(HttpResponse::Ok().json(SignupStatusResponse {
+        enabled,
+        allowed_domains: signup_service.allowed_domains().to_vec(),
+    }))
+}
+
+#[tracing::instrument(
+    name = "Check if email already exists in database",
+    skip(pool)
+)]
+async fn is_email_exists(
+    pool: &PgPool,
+    email: &Email,
+) -> Result<bool, sqlx::Error> {
+    let exists = sqlx::query_scalar!(
+        "SELECT EXISTS(SELECT 1 FROM users WHERE email = $1)",
+        email.as_ref()
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(exists.unwrap_or(false))
+}
+
+fn generate_token() -> String {
+    let mut rng = rng();
+    std::iter::repeat_with(|| rng.sample(Alphanumeric))
+        .map(char::from)
+        .take(32)
+        .collect()
+}
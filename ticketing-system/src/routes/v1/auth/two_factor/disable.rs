@@ -0,0 +1,89 @@
+use actix_web::{http::StatusCode, web, HttpResponse, ResponseError};
+use anyhow::Context;
+use serde::Deserialize;
+use sqlx::PgPool;
+
+use crate::{auth::{extractor::UserIdExtractor, types::UserRole}, schema::common::UserId, services::two_factor::TwoFactorService, utils::{error_chain_fmt, is_password_valid}};
+
+#[derive(Deserialize)]
+pub struct DisableTwoFactorSchema {
+    pub password: String,
+    pub code: String,
+}
+
+struct User {
+    password_hash: String,
+    role: UserRole,
+}
+
+#[derive(thiserror::Error)]
+pub enum DisableTwoFactorError {
+    #[error("Invalid password")]
+    InvalidPassword,
+    #[error("Invalid code")]
+    InvalidCode,
+    #[error("Two-factor authentication is required for this role")]
+    Required,
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for DisableTwoFactorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for DisableTwoFactorError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            DisableTwoFactorError::InvalidPassword | DisableTwoFactorError::InvalidCode => StatusCode::BAD_REQUEST,
+            DisableTwoFactorError::Required => StatusCode::FORBIDDEN,
+            DisableTwoFactorError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+pub async fn disable_two_factor(
+    user_id: UserIdExtractor,
+    web::Json(req): web::Json<DisableTwoFactorSchema>,
+    pool: web::Data<PgPool>,
+    two_factor: web::Data<TwoFactorService>,
+) -> Result<HttpResponse, DisableTwoFactorError> {
+    let user_id = user_id.0;
+
+    let user = get_user(&pool, user_id)
+        .await
+        .context("Failed to get user from db")?;
+
+    if two_factor.is_required(user.role) {
+        return Err(DisableTwoFactorError::Required);
+    }
+
+    if !is_password_valid(&req.password, &user.password_hash)
+        .context("Failed to verify password")? {
+        return Err(DisableTwoFactorError::InvalidPassword);
+    }
+
+    if !two_factor.verify_second_factor(&pool, user_id, &req.code).await? {
+        return Err(DisableTwoFactorError::InvalidCode);
+    }
+
+    two_factor.disable(&pool, user_id).await?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[tracing::instrument(
+    name = "Get user password hash and role",
+    skip(pool)
+)]
+async fn get_user(pool: &PgPool, user_id: UserId) -> Result<User, sqlx::Error> {
+    sqlx::query_as!(
+        User,
+        "SELECT password_hash, role FROM users WHERE id = $1",
+        user_id
+    )
+    .fetch_one(pool)
+    .await
+}
@@ -0,0 +1,47 @@
+use actix_web::{http::StatusCode, web, HttpResponse, ResponseError};
+use serde::Deserialize;
+use sqlx::PgPool;
+
+use crate::{auth::extractor::UserIdExtractor, schema::auth::RecoveryCodesResponse, services::two_factor::TwoFactorService, utils::error_chain_fmt};
+
+#[derive(Deserialize)]
+pub struct EnableTwoFactorSchema {
+    pub code: String,
+}
+
+#[derive(thiserror::Error)]
+pub enum EnableTwoFactorError {
+    #[error("Invalid code")]
+    InvalidCode,
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for EnableTwoFactorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for EnableTwoFactorError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            EnableTwoFactorError::InvalidCode => StatusCode::BAD_REQUEST,
+            EnableTwoFactorError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+pub async fn enable_two_factor(
+    user_id: UserIdExtractor,
+    web::Json(req): web::Json<EnableTwoFactorSchema>,
+    pool: web::Data<PgPool>,
+    two_factor: web::Data<TwoFactorService>,
+) -> Result<HttpResponse, EnableTwoFactorError> {
+    let recovery_codes = two_factor
+        .complete_enrollment(&pool, user_id.0, &req.code)
+        .await?
+        .ok_or(EnableTwoFactorError::InvalidCode)?;
+
+    Ok(HttpResponse::Ok().json(RecoveryCodesResponse { recovery_codes }))
+}
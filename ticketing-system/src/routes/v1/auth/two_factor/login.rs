@@ -0,0 +1,124 @@
+use actix_web::{http::StatusCode, web, HttpResponse, ResponseError};
+use anyhow::Context;
+use serde::Deserialize;
+use sqlx::PgPool;
+
+use crate::{auth::{extractor::DeviceInfo, jwt::JwtService, token_store::TokenStore, types::UserRole}, routes::v1::auth::login::get_token_response, schema::{auth::TwoFactorLoginResponse, common::UserId}, services::{action_token::ActionTokenStore, two_factor::TwoFactorService}, utils::error_chain_fmt};
+
+#[derive(Deserialize)]
+pub struct TwoFactorLoginSchema {
+    pub challenge_token: String,
+    pub code: String,
+}
+
+struct User {
+    role: UserRole,
+    role_id: i16,
+    is_active: bool,
+    totp_enabled: bool,
+}
+
+#[derive(thiserror::Error)]
+pub enum TwoFactorLoginError {
+    #[error("Challenge not found or expired")]
+    ChallengeNotFound,
+    #[error("Invalid code")]
+    InvalidCode,
+    #[error("User cannot be authorized")]
+    UserCannotBeAuthorized,
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for TwoFactorLoginError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for TwoFactorLoginError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            TwoFactorLoginError::ChallengeNotFound | TwoFactorLoginError::InvalidCode => StatusCode::UNAUTHORIZED,
+            TwoFactorLoginError::UserCannotBeAuthorized => StatusCode::FORBIDDEN,
+            TwoFactorLoginError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+pub async fn login_two_factor(
+    web::Json(req): web::Json<TwoFactorLoginSchema>,
+    pool: web::Data<PgPool>,
+    jwt_service: web::Data<JwtService>,
+    token_store: web::Data<TokenStore>,
+    action_token_store: web::Data<ActionTokenStore>,
+    two_factor: web::Data<TwoFactorService>,
+    device: DeviceInfo,
+) -> Result<HttpResponse, TwoFactorLoginError> {
+    let challenge = two_factor
+        .take_challenge(&action_token_store, &req.challenge_token)
+        .await?
+        .ok_or(TwoFactorLoginError::ChallengeNotFound)?;
+
+    let user = get_user(&pool, challenge.user_id)
+        .await
+        .context("Failed to get user from db")?
+        .ok_or(TwoFactorLoginError::ChallengeNotFound)?;
+
+    if !user.is_active {
+        return Err(TwoFactorLoginError::UserCannotBeAuthorized);
+    }
+
+    // Users who enroll during sign in confirm the pending secret here.
+    let (is_valid, recovery_codes) = if user.totp_enabled {
+        let is_valid = two_factor
+            .verify_second_factor(&pool, challenge.user_id, &req.code)
+            .await?;
+
+        (is_valid, None)
+    } else {
+        let codes = two_factor
+            .complete_enrollment(&pool, challenge.user_id, &req.code)
+            .await?;
+
+        (codes.is_some(), codes)
+    };
+
+    if !is_valid {
+        two_factor
+            .retry_challenge(&action_token_store, &req.challenge_token, challenge)
+            .await?;
+
+        return Err(TwoFactorLoginError::InvalidCode);
+    }
+
+    let tokens = get_token_response(
+        &jwt_service,
+        &token_store,
+        challenge.user_id,
+        user.role,
+        user.role_id,
+        challenge.fingerprint,
+        device
+    )
+    .await?;
+
+    Ok(HttpResponse::Ok().json(TwoFactorLoginResponse {
+        tokens,
+        recovery_codes,
+    }))
+}
+
+#[tracing::instrument(
+    name = "Get user from database",
+    skip(pool)
+)]
+async fn get_user(pool: &PgPool, user_id: UserId) -> Result<Option<User>, sqlx::Error> {
+    sqlx::query_as!(
+        User,
+        "SELECT role, role_id, is_active, totp_enabled FROM users WHERE id = $1",
+        user_id
+    )
+    .fetch_optional(pool)
+    .await
+}
@@ -0,0 +1,13 @@
+pub mod login;
+pub mod setup_login;
+pub mod setup;
+pub mod enable;
+pub mod disable;
+pub mod recovery_codes;
+
+pub use login::login_two_factor;
+pub use setup_login::setup_login_two_factor;
+pub use setup::setup_two_factor;
+pub use enable::enable_two_factor;
+pub use disable::disable_two_factor;
+pub use recovery_codes::regenerate_recovery_codes;
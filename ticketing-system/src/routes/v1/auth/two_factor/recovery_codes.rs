@@ -0,0 +1,57 @@
+use actix_web::{http::StatusCode, web, HttpResponse, ResponseError};
+use serde::Deserialize;
+use sqlx::PgPool;
+
+use crate::{auth::extractor::UserIdExtractor, schema::auth::RecoveryCodesResponse, services::two_factor::TwoFactorService, utils::error_chain_fmt};
+
+#[derive(Deserialize)]
+pub struct RegenerateRecoveryCodesSchema {
+    pub code: String,
+}
+
+#[derive(thiserror::Error)]
+pub enum RegenerateRecoveryCodesError {
+    #[error("Invalid code")]
+    InvalidCode,
+    #[error("Two-factor authentication is not enabled")]
+    NotEnabled,
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for RegenerateRecoveryCodesError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for RegenerateRecoveryCodesError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            RegenerateRecoveryCodesError::InvalidCode => StatusCode::BAD_REQUEST,
+            RegenerateRecoveryCodesError::NotEnabled => StatusCode::BAD_REQUEST,
+            RegenerateRecoveryCodesError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+pub async fn regenerate_recovery_codes(
+    user_id: UserIdExtractor,
+    web::Json(req): web::Json<RegenerateRecoveryCodesSchema>,
+    pool: web::Data<PgPool>,
+    two_factor: web::Data<TwoFactorService>,
+) -> Result<HttpResponse, RegenerateRecoveryCodesError> {
+    let user_id = user_id.0;
+
+    // Only a TOTP code is accepted, a recovery code cannot be used to get new ones.
+    if !two_factor.verify_totp(&pool, user_id, &req.code).await? {
+        return Err(RegenerateRecoveryCodesError::InvalidCode);
+    }
+
+    let recovery_codes = two_factor
+        .regenerate_recovery_codes(&pool, user_id)
+        .await?
+        .ok_or(RegenerateRecoveryCodesError::NotEnabled)?;
+
+    Ok(HttpResponse::Ok().json(RecoveryCodesResponse { recovery_codes }))
+}
@@ -0,0 +1,54 @@
+use actix_web::{http::StatusCode, web, HttpResponse, ResponseError};
+use anyhow::Context;
+use sqlx::PgPool;
+
+use crate::{auth::extractor::UserIdExtractor, routes::v1::auth::two_factor::setup_login::get_user, services::two_factor::TwoFactorService, utils::error_chain_fmt};
+
+#[derive(thiserror::Error)]
+pub enum SetupTwoFactorError {
+    #[error("User not found")]
+    UserNotFound,
+    #[error("Two-factor authentication is already enabled")]
+    AlreadyEnabled,
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for SetupTwoFactorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for SetupTwoFactorError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            SetupTwoFactorError::UserNotFound => StatusCode::NOT_FOUND,
+            SetupTwoFactorError::AlreadyEnabled => StatusCode::CONFLICT,
+            SetupTwoFactorError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+pub async fn setup_two_factor(
+    user_id: UserIdExtractor,
+    pool: web::Data<PgPool>,
+    two_factor: web::Data<TwoFactorService>,
+) -> Result<HttpResponse, SetupTwoFactorError> {
+    let user_id = user_id.0;
+
+    let (login, totp_enabled) = get_user(&pool, user_id)
+        .await
+        .context("Failed to get user from db")?
+        .ok_or(SetupTwoFactorError::UserNotFound)?;
+
+    if totp_enabled {
+        return Err(SetupTwoFactorError::AlreadyEnabled);
+    }
+
+    let resp = two_factor
+        .start_enrollment(&pool, user_id, &login)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(resp))
+}
@@ -0,0 +1,79 @@
+use actix_web::{http::StatusCode, web, HttpResponse, ResponseError};
+use anyhow::Context;
+use serde::Deserialize;
+use sqlx::PgPool;
+
+use crate::{schema::common::UserId, services::{action_token::ActionTokenStore, two_factor::TwoFactorService}, utils::error_chain_fmt};
+
+#[derive(Deserialize)]
+pub struct SetupLoginTwoFactorSchema {
+    pub challenge_token: String,
+}
+
+#[derive(thiserror::Error)]
+pub enum SetupLoginTwoFactorError {
+    #[error("Challenge not found or expired")]
+    ChallengeNotFound,
+    #[error("Two-factor authentication is already enabled")]
+    AlreadyEnabled,
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for SetupLoginTwoFactorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for SetupLoginTwoFactorError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            SetupLoginTwoFactorError::ChallengeNotFound => StatusCode::UNAUTHORIZED,
+            SetupLoginTwoFactorError::AlreadyEnabled => StatusCode::CONFLICT,
+            SetupLoginTwoFactorError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+pub async fn setup_login_two_factor(
+    web::Json(req): web::Json<SetupLoginTwoFactorSchema>,
+    pool: web::Data<PgPool>,
+    action_token_store: web::Data<ActionTokenStore>,
+    two_factor: web::Data<TwoFactorService>,
+) -> Result<HttpResponse, SetupLoginTwoFactorError> {
+    let challenge = two_factor
+        .get_challenge(&action_token_store, &req.challenge_token)
+        .await?
+        .ok_or(SetupLoginTwoFactorError::ChallengeNotFound)?;
+
+    let (login, totp_enabled) = get_user(&pool, challenge.user_id)
+        .await
+        .context("Failed to get user from db")?
+        .ok_or(SetupLoginTwoFactorError::ChallengeNotFound)?;
+
+    if totp_enabled {
+        return Err(SetupLoginTwoFactorError::AlreadyEnabled);
+    }
+
+    let resp = two_factor
+        .start_enrollment(&pool, challenge.user_id, &login)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(resp))
+}
+
+#[tracing::instrument(
+    name = "Get user login and 2FA state",
+    skip(pool)
+)]
+pub(super) async fn get_user(pool: &PgPool, user_id: UserId) -> Result<Option<(String, bool)>, sqlx::Error> {
+    let row = sqlx::query!(
+        "SELECT login, totp_enabled FROM users WHERE id = $1",
+        user_id
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|row| (row.login, row.totp_enabled)))
+}
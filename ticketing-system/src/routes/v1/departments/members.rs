@@ -0,0 +1,158 @@
+use actix_web::{http::StatusCode, web, HttpResponse, ResponseError};
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+
+use crate::{auth::{department_scope::DepartmentId, types::DepartmentRole}, schema::common::UserId, utils::error_chain_fmt};
+
+#[derive(Deserialize)]
+pub struct SetMemberSchema {
+    #[serde(default = "default_role")]
+    pub role: DepartmentRole,
+}
+
+fn default_role() -> DepartmentRole {
+    DepartmentRole::Member
+}
+
+#[derive(Serialize)]
+struct Member {
+    id: UserId,
+    name: String,
+    role: DepartmentRole,
+}
+
+#[derive(thiserror::Error)]
+pub enum DepartmentMembersError {
+    #[error("Department or user not found")]
+    NotFound,
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for DepartmentMembersError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for DepartmentMembersError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            DepartmentMembersError::NotFound => StatusCode::NOT_FOUND,
+            DepartmentMembersError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+pub async fn get_department_members(
+    pool: web::Data<PgPool>,
+    id: web::Path<DepartmentId>,
+) -> Result<HttpResponse, DepartmentMembersError> {
+    let members = get_members(&pool, *id)
+        .await
+        .context("Failed to get department members")?;
+
+    Ok(HttpResponse::Ok().json(members))
+}
+
+pub async fn set_department_member(
+    pool: web::Data<PgPool>,
+    path: web::Path<(DepartmentId, UserId)>,
+    web::Json(schema): web::Json<SetMemberSchema>,
+) -> Result<HttpResponse, DepartmentMembersError> {
+    let (department_id, user_id) = path.into_inner();
+
+    let res = upsert_member(&pool, department_id, user_id, schema.role).await;
+
+    if let Err(e) = &res
+        && let Some(db_err) = e.as_database_error()
+            && db_err.is_foreign_key_violation() {
+                return Err(DepartmentMembersError::NotFound);
+            }
+
+    res.context("Failed to set department member")?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+pub async fn remove_department_member(
+    pool: web::Data<PgPool>,
+    path: web::Path<(DepartmentId, UserId)>,
+) -> Result<HttpResponse, DepartmentMembersError> {
+    let (department_id, user_id) = path.into_inner();
+
+    let removed = remove_member(&pool, department_id, user_id)
+        .await
+        .context("Failed to remove department member")?;
+
+    if !removed {
+        return Err(DepartmentMembersError::NotFound);
+    }
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[tracing::instrument(
+    name = "Get department members from database",
+    skip(pool)
+)]
+async fn get_members(pool: &PgPool, department_id: DepartmentId) -> Result<Vec<Member>, sqlx::Error> {
+    sqlx::query_as!(
+        Member,
+        r#"
+            SELECT u.id, u.name, ud.role
+            FROM user_departments ud
+            JOIN users u ON u.id = ud.user_id
+            WHERE ud.department_id = $1
+            ORDER BY u.name
+        "#,
+        department_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+#[tracing::instrument(
+    name = "Upsert department member in database",
+    skip(pool)
+)]
+async fn upsert_member(
+    pool: &PgPool,
+    department_id: DepartmentId,
+    user_id: UserId,
+    role: DepartmentRole,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "INSERT INTO user_departments (user_id, department_id, role)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (user_id, department_id) DO UPDATE SET role = EXCLUDED.role",
+        user_id,
+        department_id,
+        role as i16
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+#[tracing::instrument(
+    name = "Remove department member from database",
+    skip(pool)
+)]
+async fn remove_member(
+    pool: &PgPool,
+    department_id: DepartmentId,
+    user_id: UserId,
+) -> Result<bool, sqlx::Error> {
+    let res = sqlx::query!(
+        "DELETE FROM user_departments WHERE user_id = $1 AND department_id = $2",
+        user_id,
+        department_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(res.rows_affected() == 1)
+}
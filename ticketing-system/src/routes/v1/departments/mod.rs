@@ -1,7 +1,9 @@
 mod create_department;
 mod toggle_department_active;
 mod update_department;
+mod members;
 
 pub use create_department::create_department;
 pub use toggle_department_active::toggle_department_active;
-pub use update_department::update_department;
\ No newline at end of file
+pub use update_department::update_department;
+pub use members::{get_department_members, remove_department_member, set_department_member};
\ No newline at end of file
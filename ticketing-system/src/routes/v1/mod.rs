@@ -1,6 +1,6 @@
 use actix_web::web;
 
-use crate::{auth::{middleware::JwtMiddleware, types::UserRole}, routes::v1::{assets::{categories::{create_category::create_category, delete_category::delete_category, get_categories::get_categories, update_category::update_category}, create_asset::create_asset, delete_asset::delete_asset, get_assets::get_assets, models::{create_model::create_model, delete_model::delete_model, get_models::get_models, update_model::update_model}, statuses::{create_status::create_status, delete_status::delete_status, get_statuses::get_statuses, update_status::update_status}, update_asset::update_asset}, attachments::get_attachment, auth::{change_password, confirm_account_recovery, confirm_admin_transfer, login, me, refresh_token, register, request_account_recovery, validate_admin_transfer_token, validate_recovery_token, validate_register_token}, buildings::{create_building, set_building_active, update_building}, departments::{create_department, toggle_department_active, update_department}, notifications::{delete_notifications::delete_notifications, get_notifications::get_notifications, get_notifications_count::get_notifications_count, read_notifications::read_notifications, system::{create_system_notification::create_system_notification, delete_system_notification::delete_system_notification, get_system_notifications::get_system_notifications, update_system_notification::update_system_notification}}, pages::{create_page, delete_page, get_page, get_pages, update_page}, tags::{create_tag, delete_tag, get_tags, update_tag}, tickets::{assets::{attach_asset::attach_asset, delete_ticket_asset::delete_ticket_asset as delete_ticket_asset, get_ticket_assets::get_ticket_assets}, assign_ticket_to_self, assign_ticket_to_user, create_message::create_message, create_ticket, delete_message::delete_message, delete_ticket, get_consts, get_messages::get_messages, get_ticket, get_tickets, metrics::get_metrics, unassign_ticket_from_self, unassign_ticket_from_user, update_ticket}, user::{activate_account, change_user_role, change_user_status, deactivate_account, get_users, invite_user, request_admin_transfer, update_avatar, update_user_profile}}};
+use crate::{auth::{middleware::{JwtConfig, JwtMiddleware}, permission::Permission}, routes::v1::{assets::{categories::{create_category::create_category, delete_category::delete_category, get_categories::get_categories, update_category::update_category}, create_asset::create_asset, delete_asset::delete_asset, get_assets::get_assets, models::{create_model::create_model, delete_model::delete_model, get_models::get_models, update_model::update_model}, statuses::{create_status::create_status, delete_status::delete_status, get_statuses::get_statuses, update_status::update_status}, update_asset::update_asset}, assignment_rules::{create_rule, delete_rule, dry_run, get_rules, update_rule}, attachments::get_attachment, audit::{export_audit_log, get_audit_log}, auth::{change_password, confirm_account_recovery, confirm_admin_transfer, login, magic::{confirm_magic_link, request_magic_link}, me, oidc::{oidc_callback, oidc_link, oidc_login}, refresh_token, register, request_account_recovery, sessions::{get_sessions, revoke_other_sessions, revoke_session}, signup, get_signup_status, two_factor::{disable_two_factor, enable_two_factor, login_two_factor, regenerate_recovery_codes, setup_login_two_factor, setup_two_factor}, validate_admin_transfer_token, validate_recovery_token, validate_register_token}, buildings::{create_building, set_building_active, update_building}, departments::{create_department, get_department_members, remove_department_member, set_department_member, toggle_department_active, update_department}, notifications::{delete_notifications::delete_notifications, get_notifications::get_notifications, get_notifications_count::get_notifications_count, read_notifications::read_notifications, system::{create_system_notification::create_system_notification, delete_system_notification::delete_system_notification, get_system_notifications::get_system_notifications, update_system_notification::update_system_notification}}, pages::{create_page, delete_page, get_page, get_pages, get_suggested_pages, update_page}, roles::{create_role, delete_role, get_permissions, get_roles, update_role}, tags::{create_tag, delete_tag, get_tags, update_tag}, tickets::{assets::{attach_asset::attach_asset, delete_ticket_asset::delete_ticket_asset as delete_ticket_asset, get_ticket_assets::get_ticket_assets}, assign_ticket_to_self, bulk_update_tickets, assign_ticket_to_user, create_message::create_message, create_ticket, delete_message::delete_message, delete_ticket, export_tickets, get_consts, get_messages::get_messages, get_similar_tickets, get_ticket, get_tickets, links::{create_link, delete_link, get_links}, merge_tickets, metrics::get_metrics, pages::{attach_page, detach_page}, unassign_ticket_from_self, unassign_ticket_from_user, update_ticket, views::{create_view, delete_view, get_view_badges, get_view_tickets, get_views, update_view}}, user::{activate_account, cancel_absence, create_absence, get_absences, change_user_role, change_user_status, deactivate_account, get_user_sessions, get_users, impersonate_user, invite_user, request_admin_transfer, revoke_user_session, revoke_user_sessions, set_signup_enabled, tokens::{create_token, get_tokens, revoke_token}, unlock_account, update_avatar, update_user_profile}}};
 
 pub mod auth;
 pub mod tickets;
@@ -13,6 +13,9 @@ pub mod buildings;
 pub mod notifications;
 pub mod assets;
 pub mod reports;
+pub mod roles;
+pub mod audit;
+pub mod assignment_rules;
 
 pub fn config(cfg: &mut web::ServiceConfig) {
     cfg.service(
@@ -20,60 +23,124 @@ pub fn config(cfg: &mut web::ServiceConfig) {
             .service(
                 web::scope("/auth")
                     .route("/login", web::post().to(login))
+                    .route("/login/2fa", web::post().to(login_two_factor))
+                    .route("/login/2fa/setup", web::post().to(setup_login_two_factor))
+                    .route("/oidc/login", web::get().to(oidc_login))
+                    .route("/oidc/link", web::post().to(oidc_link)
+                        .wrap(JwtMiddleware::jwt_only()))
+                    .route("/oidc/callback", web::get().to(oidc_callback))
+                    .route("/magic/request", web::post().to(request_magic_link))
+                    .route("/magic/confirm", web::post().to(confirm_magic_link))
                     .route("/me", web::get().to(me)
                         .wrap(JwtMiddleware::default()))
                     .route("/token", web::post().to(refresh_token))
                     .route("/register", web::post().to(register))
+                    .route("/signup", web::post().to(signup))
+                    .route("/signup", web::get().to(get_signup_status))
                     .route("/validate", web::post().to(validate_register_token))
                     .route("/recovery/request", web::post().to(request_account_recovery))
                     .route("/recovery/validate", web::post().to(validate_recovery_token))
                     .route("/recovery/confirm", web::post().to(confirm_account_recovery))
                     .route("/admin_transfer/validate", web::post().to(validate_admin_transfer_token))
                     .route("/admin_transfer/confirm", web::post().to(confirm_admin_transfer))
+                    .service(
+                        web::scope("/2fa")
+                            .wrap(JwtMiddleware::jwt_only())
+                            .route("/setup", web::post().to(setup_two_factor))
+                            .route("/enable", web::post().to(enable_two_factor))
+                            .route("/disable", web::post().to(disable_two_factor))
+                            .route("/recovery_codes", web::post().to(regenerate_recovery_codes))
+                    )
+                    .service(
+                        web::scope("/sessions")
+                            .wrap(JwtMiddleware::jwt_only())
+                            .route("", web::get().to(get_sessions))
+                            .route("", web::delete().to(revoke_other_sessions))
+                            .route("/{id}", web::delete().to(revoke_session))
+                    )
             )
             .service(
                 web::scope("/tickets")
                     .route("/consts", web::get().to(get_consts))
                     .route("/stats", web::get().to(tickets::get_stats))
+                    .route("/similar", web::get().to(get_similar_tickets)
+                        .wrap(JwtMiddleware::default()))
                     .route("/metrics", web::get().to(get_metrics)
-                        .wrap(JwtMiddleware::min_role(UserRole::Employee)))
+                        .wrap(JwtMiddleware::permission(Permission::TicketsMetrics)))
+                    .route("/export", web::get().to(export_tickets)
+                        .wrap(JwtMiddleware::permission(Permission::TicketsRead)))
+                    .route("/bulk", web::post().to(bulk_update_tickets)
+                        .wrap(JwtMiddleware::permission(Permission::TicketsUpdate)))
                     .route("/", web::post().to(create_ticket)
                         .wrap(JwtMiddleware::default()))
                     .route("/", web::get().to(get_tickets)
-                        .wrap(JwtMiddleware::min_role(UserRole::Client)))
+                        .wrap(JwtMiddleware::permission(Permission::TicketsRead)))
+                    .service(
+                        web::scope("/views")
+                            .route("", web::get().to(get_views)
+                                .wrap(JwtMiddleware::permission(Permission::TicketsRead)))
+                            .route("", web::post().to(create_view)
+                                .wrap(JwtMiddleware::permission(Permission::TicketsRead)))
+                            .route("/badges", web::get().to(get_view_badges)
+                                .wrap(JwtMiddleware::permission(Permission::TicketsRead)))
+                            .route("/{id}", web::get().to(get_view_tickets)
+                                .wrap(JwtMiddleware::permission(Permission::TicketsRead)))
+                            .route("/{id}", web::put().to(update_view)
+                                .wrap(JwtMiddleware::permission(Permission::TicketsRead)))
+                            .route("/{id}", web::delete().to(delete_view)
+                                .wrap(JwtMiddleware::permission(Permission::TicketsRead)))
+                    )
                     .service(
                         web::scope("/{id}")
                             .route("", web::put().to(update_ticket)
-                                .wrap(JwtMiddleware::min_role(UserRole::Employee)))
+                                .wrap(JwtMiddleware::permission(Permission::TicketsUpdate)))
                             .route("/assign", web::patch().to(assign_ticket_to_self)
-                                .wrap(JwtMiddleware::min_role(UserRole::Employee)))
+                                .wrap(JwtMiddleware::permission(Permission::TicketsAssign)))
                             .route("/unassign", web::patch().to(unassign_ticket_from_self)
-                                .wrap(JwtMiddleware::min_role(UserRole::Employee)))
+                                .wrap(JwtMiddleware::permission(Permission::TicketsAssign)))
                             .route("/assign/{user_id}", web::post().to(assign_ticket_to_user)
-                                .wrap(JwtMiddleware::min_role(UserRole::Moderator)))
+                                .wrap(JwtMiddleware::permission(Permission::TicketsAssign)))
                             .route("/unassign/{user_id}", web::post().to(unassign_ticket_from_user)
-                                .wrap(JwtMiddleware::min_role(UserRole::Moderator)))
+                                .wrap(JwtMiddleware::permission(Permission::TicketsAssign)))
                             .route("", web::get().to(get_ticket)
-                                .wrap(JwtMiddleware::min_role(UserRole::Client)))
+                                .wrap(JwtMiddleware::permission(Permission::TicketsRead)))
                             .route("", web::delete().to(delete_ticket)
-                                .wrap(JwtMiddleware::min_role(UserRole::Admin)))
+                                .wrap(JwtMiddleware::permission(Permission::TicketsDelete)))
+                            .route("/merge", web::post().to(merge_tickets)
+                                .wrap(JwtMiddleware::permission(Permission::TicketsUpdate)))
+                            .service(
+                                web::scope("/links")
+                                    .route("", web::get().to(get_links)
+                                        .wrap(JwtMiddleware::permission(Permission::TicketsRead)))
+                                    .route("", web::post().to(create_link)
+                                        .wrap(JwtMiddleware::permission(Permission::TicketsUpdate)))
+                                    .route("/{linked_id}", web::delete().to(delete_link)
+                                        .wrap(JwtMiddleware::permission(Permission::TicketsUpdate)))
+                            )
+                            .service(
+                                web::scope("/pages")
+                                    .route("", web::post().to(attach_page)
+                                        .wrap(JwtMiddleware::permission(Permission::TicketsUpdate)))
+                                    .route("/{page_id}", web::delete().to(detach_page)
+                                        .wrap(JwtMiddleware::permission(Permission::TicketsUpdate)))
+                            )
                             .service(
                                 web::scope("/messages")
                                     .route("", web::get().to(get_messages)
-                                        .wrap(JwtMiddleware::min_role(UserRole::Client)))
+                                        .wrap(JwtMiddleware::permission(Permission::TicketsRead)))
                                     .route("", web::post().to(create_message)
-                                        .wrap(JwtMiddleware::min_role(UserRole::Client)))
+                                        .wrap(JwtMiddleware::permission(Permission::MessagesWrite)))
                                     .route("/{id}", web::delete().to(delete_message)
-                                        .wrap(JwtMiddleware::min_role(UserRole::Employee)))
+                                        .wrap(JwtMiddleware::permission(Permission::MessagesDelete)))
                             )
                             .service(
                                 web::scope("/assets")
                                     .route("", web::get().to(get_ticket_assets)
-                                        .wrap(JwtMiddleware::min_role(UserRole::Employee)))
+                                        .wrap(JwtMiddleware::permission(Permission::TicketAssetsManage)))
                                     .route("", web::post().to(attach_asset)
-                                        .wrap(JwtMiddleware::min_role(UserRole::Employee)))
+                                        .wrap(JwtMiddleware::permission(Permission::TicketAssetsManage)))
                                     .route("/{asset_id}", web::delete().to(delete_ticket_asset)
-                                        .wrap(JwtMiddleware::min_role(UserRole::Employee)))
+                                        .wrap(JwtMiddleware::permission(Permission::TicketAssetsManage)))
                             )
                     )
             )
@@ -83,66 +150,122 @@ pub fn config(cfg: &mut web::ServiceConfig) {
             )
             .service(
                 web::scope("/user/admin")
-                .wrap(JwtMiddleware::min_role(UserRole::Admin))
-                    .route("/invite", web::post().to(invite_user))
-                    .route("/role", web::patch().to(change_user_role))
-                    .route("/transfer", web::post().to(request_admin_transfer))
+                    .route("/invite", web::post().to(invite_user)
+                        .wrap(JwtMiddleware::permission(Permission::UsersInvite)))
+                    .route("/role", web::patch().to(change_user_role)
+                        .wrap(JwtMiddleware::permission(Permission::UsersChangeRole)))
+                    .route("/transfer", web::post().to(request_admin_transfer)
+                        .wrap(JwtMiddleware::permission(Permission::UsersTransferAdmin)))
+                    .route("/signup", web::put().to(set_signup_enabled)
+                        .wrap(JwtMiddleware::permission(Permission::UsersInvite)))
+                    .route("/impersonate/{id}", web::post().to(impersonate_user)
+                        .wrap(JwtMiddleware::new(JwtConfig::new().permission(Permission::UsersImpersonate).jwt_only())))
             )
             .service(
                 web::scope("/user")
                     .route("/stats", web::get().to(user::get_stats)
-                        .wrap(JwtMiddleware::min_role(UserRole::Employee)))
+                        .wrap(JwtMiddleware::permission(Permission::UsersStats)))
                     .route("/profile", web::put().to(update_user_profile)
-                        .wrap(JwtMiddleware::min_role(UserRole::Client)))
+                        .wrap(JwtMiddleware::permission(Permission::ProfileUpdate)))
                     .route("/password", web::put().to(change_password)
-                        .wrap(JwtMiddleware::min_role(UserRole::Client)))
+                        .wrap(JwtMiddleware::new(JwtConfig::new().permission(Permission::ProfileUpdate).jwt_only())))
                     .route("/list", web::get().to(get_users)
-                        .wrap(JwtMiddleware::min_role(UserRole::Employee)))
+                        .wrap(JwtMiddleware::permission(Permission::UsersRead)))
                     .route("/status", web::patch().to(change_user_status)
-                        .wrap(JwtMiddleware::min_role(UserRole::Employee)))
+                        .wrap(JwtMiddleware::permission(Permission::UsersChangeStatus)))
                     .route("/avatar", web::put().to(update_avatar)
-                        .wrap(JwtMiddleware::min_role(UserRole::Client)))
+                        .wrap(JwtMiddleware::permission(Permission::ProfileUpdate)))
+                    .service(
+                        web::scope("/tokens")
+                            .wrap(JwtMiddleware::new(JwtConfig::new().permission(Permission::TokensManage).jwt_only()))
+                            .route("", web::get().to(get_tokens))
+                            .route("", web::post().to(create_token))
+                            .route("/{token_id}", web::delete().to(revoke_token))
+                    )
                     .service(
                         web::scope("/{id}")
                         .route("/activate", web::post().to(activate_account)
-                            .wrap(JwtMiddleware::min_role(UserRole::Admin)))
+                            .wrap(JwtMiddleware::permission(Permission::UsersManage)))
                         .route("/deactivate", web::post().to(deactivate_account)
-                            .wrap(JwtMiddleware::min_role(UserRole::Admin)))
+                            .wrap(JwtMiddleware::permission(Permission::UsersManage)))
+                        .route("/unlock", web::post().to(unlock_account)
+                            .wrap(JwtMiddleware::permission(Permission::UsersManage)))
+                        .route("/sessions", web::get().to(get_user_sessions)
+                            .wrap(JwtMiddleware::permission(Permission::UsersManage)))
+                        .route("/sessions", web::delete().to(revoke_user_sessions)
+                            .wrap(JwtMiddleware::permission(Permission::UsersManage)))
+                        .route("/sessions/{session_id}", web::delete().to(revoke_user_session)
+                            .wrap(JwtMiddleware::permission(Permission::UsersManage)))
+                        .route("/absences", web::get().to(get_absences)
+                            .wrap(JwtMiddleware::permission(Permission::UsersChangeStatus)))
+                        .route("/absences", web::post().to(create_absence)
+                            .wrap(JwtMiddleware::permission(Permission::UsersChangeStatus)))
+                        .route("/absences/{absence_id}", web::delete().to(cancel_absence)
+                            .wrap(JwtMiddleware::permission(Permission::UsersChangeStatus)))
                     )
             )
+            .service(
+                web::scope("/roles")
+                    .wrap(JwtMiddleware::permission(Permission::RolesManage))
+                    .route("", web::get().to(get_roles))
+                    .route("", web::post().to(create_role))
+                    .route("/permissions", web::get().to(get_permissions))
+                    .route("/{id}", web::put().to(update_role))
+                    .route("/{id}", web::delete().to(delete_role))
+            )
+            .service(
+                web::scope("/assignment_rules")
+                    .wrap(JwtMiddleware::permission(Permission::AssignmentRulesManage))
+                    .route("", web::get().to(get_rules))
+                    .route("", web::post().to(create_rule))
+                    .route("/dry_run", web::post().to(dry_run))
+                    .route("/{id}", web::put().to(update_rule))
+                    .route("/{id}", web::delete().to(delete_rule))
+            )
+            .service(
+                web::scope("/audit")
+                    .wrap(JwtMiddleware::permission(Permission::AuditRead))
+                    .route("", web::get().to(get_audit_log))
+                    .route("/export", web::get().to(export_audit_log))
+            )
             .service(
                 web::scope("/pages")
                     .route("/", web::post().to(create_page)
-                        .wrap(JwtMiddleware::min_role(UserRole::Employee)))
+                        .wrap(JwtMiddleware::permission(Permission::PagesWrite)))
                     .route("/", web::get().to(get_pages)
                         .wrap(JwtMiddleware::optional()))
+                    .route("/suggestions", web::get().to(get_suggested_pages)
+                        .wrap(JwtMiddleware::default()))
                     .route("/{id}", web::get().to(get_page)
                         .wrap(JwtMiddleware::optional()))
                     .route("/{id}", web::delete().to(delete_page)
-                        .wrap(JwtMiddleware::min_role(UserRole::Employee)))
+                        .wrap(JwtMiddleware::permission(Permission::PagesWrite)))
                     .route("/{id}", web::put().to(update_page)
-                        .wrap(JwtMiddleware::min_role(UserRole::Employee)))
+                        .wrap(JwtMiddleware::permission(Permission::PagesWrite)))
             )
             .service(
                 web::scope("/tags")
                     .route("/", web::post().to(create_tag)
-                        .wrap(JwtMiddleware::min_role(UserRole::Moderator)))
+                        .wrap(JwtMiddleware::permission(Permission::TagsWrite)))
                     .route("/", web::get().to(get_tags))
                     .route("/{id}", web::put().to(update_tag)
-                        .wrap(JwtMiddleware::min_role(UserRole::Moderator)))
+                        .wrap(JwtMiddleware::permission(Permission::TagsWrite)))
                     .route("/{id}", web::delete().to(delete_tag)
-                        .wrap(JwtMiddleware::min_role(UserRole::Moderator)))
+                        .wrap(JwtMiddleware::permission(Permission::TagsWrite)))
             )
             .service(
                 web::scope("/departments")
-                    .wrap(JwtMiddleware::min_role(UserRole::Admin))
+                    .wrap(JwtMiddleware::permission(Permission::DepartmentsWrite))
                     .route("/", web::post().to(create_department))
                     .route("/{id}", web::put().to(update_department))
                     .route("/{id}/toggle_active", web::post().to(toggle_department_active))
+                    .route("/{id}/members", web::get().to(get_department_members))
+                    .route("/{id}/members/{user_id}", web::put().to(set_department_member))
+                    .route("/{id}/members/{user_id}", web::delete().to(remove_department_member))
             )
             .service(
                 web::scope("/buildings")
-                    .wrap(JwtMiddleware::min_role(UserRole::Admin))
+                    .wrap(JwtMiddleware::permission(Permission::BuildingsWrite))
                     .route("/", web::post().to(create_building))
                     .route("/{id}", web::put().to(update_building))
                     .route("/{id}/set_active", web::post().to(set_building_active))
@@ -152,15 +275,15 @@ pub fn config(cfg: &mut web::ServiceConfig) {
                     .route("", web::get().to(get_system_notifications)
                         .wrap(JwtMiddleware::optional()))
                     .route("", web::post().to(create_system_notification)
-                        .wrap(JwtMiddleware::min_role(UserRole::Admin)))
+                        .wrap(JwtMiddleware::permission(Permission::SystemNotificationsWrite)))
                     .route("/{id}", web::delete().to(delete_system_notification)
-                        .wrap(JwtMiddleware::min_role(UserRole::Admin)))
+                        .wrap(JwtMiddleware::permission(Permission::SystemNotificationsWrite)))
                     .route("/{id}", web::put().to(update_system_notification)
-                        .wrap(JwtMiddleware::min_role(UserRole::Admin)))
+                        .wrap(JwtMiddleware::permission(Permission::SystemNotificationsWrite)))
             )
             .service(
                 web::scope("/notifications")
-                    .wrap(JwtMiddleware::min_role(UserRole::Client))
+                    .wrap(JwtMiddleware::permission(Permission::NotificationsRead))
                     .route("", web::get().to(get_notifications))
                     .route("/count", web::get().to(get_notifications_count))
                     .route("/read", web::post().to(read_notifications))
@@ -170,35 +293,47 @@ pub fn config(cfg: &mut web::ServiceConfig) {
                 web::scope("/assets")
                     .service(
                         web::scope("/categories")
-                            .wrap(JwtMiddleware::min_role(UserRole::Employee))
-                            .route("", web::get().to(get_categories))
-                            .route("", web::post().to(create_category))
-                            .route("/{id}", web::put().to(update_category))
-                            .route("/{id}", web::delete().to(delete_category))
+                            .route("", web::get().to(get_categories)
+                                .wrap(JwtMiddleware::permission(Permission::AssetsRead)))
+                            .route("", web::post().to(create_category)
+                                .wrap(JwtMiddleware::permission(Permission::AssetsWrite)))
+                            .route("/{id}", web::put().to(update_category)
+                                .wrap(JwtMiddleware::permission(Permission::AssetsWrite)))
+                            .route("/{id}", web::delete().to(delete_category)
+                                .wrap(JwtMiddleware::permission(Permission::AssetsWrite)))
                     )
                     .service(
                         web::scope("/models")
-                            .wrap(JwtMiddleware::min_role(UserRole::Employee))
-                            .route("", web::get().to(get_models))
-                            .route("", web::post().to(create_model))
-                            .route("/{id}", web::put().to(update_model))
-                            .route("/{id}", web::delete().to(delete_model))
+                            .route("", web::get().to(get_models)
+                                .wrap(JwtMiddleware::permission(Permission::AssetsRead)))
+                            .route("", web::post().to(create_model)
+                                .wrap(JwtMiddleware::permission(Permission::AssetsWrite)))
+                            .route("/{id}", web::put().to(update_model)
+                                .wrap(JwtMiddleware::permission(Permission::AssetsWrite)))
+                            .route("/{id}", web::delete().to(delete_model)
+                                .wrap(JwtMiddleware::permission(Permission::AssetsWrite)))
                     )
                     .service(
                         web::scope("/statuses")
-                            .wrap(JwtMiddleware::min_role(UserRole::Employee))
-                            .route("", web::get().to(get_statuses))
-                            .route("", web::post().to(create_status))
-                            .route("/{id}", web::put().to(update_status))
-                            .route("/{id}", web::delete().to(delete_status))
+                            .route("", web::get().to(get_statuses)
+                                .wrap(JwtMiddleware::permission(Permission::AssetsRead)))
+                            .route("", web::post().to(create_status)
+                                .wrap(JwtMiddleware::permission(Permission::AssetsWrite)))
+                            .route("/{id}", web::put().to(update_status)
+                                .wrap(JwtMiddleware::permission(Permission::AssetsWrite)))
+                            .route("/{id}", web::delete().to(delete_status)
+                                .wrap(JwtMiddleware::permission(Permission::AssetsWrite)))
                     )
                     .service(
                         web::scope("")
-                            .wrap(JwtMiddleware::min_role(UserRole::Employee))
-                            .route("", web::get().to(get_assets))
-                            .route("", web::post().to(create_asset))
-                            .route("/{id}", web::put().to(update_asset))
-                            .route("/{id}", web::delete().to(delete_asset))
+                            .route("", web::get().to(get_assets)
+                                .wrap(JwtMiddleware::permission(Permission::AssetsRead)))
+                            .route("", web::post().to(create_asset)
+                                .wrap(JwtMiddleware::permission(Permission::AssetsWrite)))
+                            .route("/{id}", web::put().to(update_asset)
+                                .wrap(JwtMiddleware::permission(Permission::AssetsWrite)))
+                            .route("/{id}", web::delete().to(delete_asset)
+                                .wrap(JwtMiddleware::permission(Permission::AssetsWrite)))
                     )
             )
             .service(
@@ -207,7 +342,7 @@ pub fn config(cfg: &mut web::ServiceConfig) {
                         "/tickets/statistics",
                         web::post()
                             .to(reports::generate_tickets_statistics_report)
-                            .wrap(JwtMiddleware::min_role(UserRole::Employee))
+                            .wrap(JwtMiddleware::permission(Permission::ReportsGenerate))
                     )
             )
     );
@@ -5,7 +5,7 @@ use serde::{Deserialize, Serialize};
 use serde_qs::web::QsQuery;
 use sqlx::PgPool;
 
-use crate::{auth::{extractor::{user_role::OptionalUserRoleExtractor}, types::UserRole}, schema::notification::{SystemNotificationId, SystemNotificationCategory}, utils::error_chain_fmt};
+use crate::{auth::{extractor::permissions::OptionalPermissionsExtractor, permission::Permission}, schema::notification::{SystemNotificationId, SystemNotificationCategory}, utils::error_chain_fmt};
 
 #[derive(thiserror::Error)]
 pub enum GetNotificationsError {
@@ -37,11 +37,11 @@ struct SystemNotification {
 
 pub async fn get_system_notifications(
     pool: web::Data<PgPool>,
-    role: OptionalUserRoleExtractor,
+    permissions: OptionalPermissionsExtractor,
     QsQuery(schema): QsQuery<GetNotificationsSchema>,
 ) -> Result<HttpResponse, GetNotificationsError> {
     let all_notifications = if schema.all {
-        role.0.is_some_and(|role| role.has_access(UserRole::Admin))
+        permissions.0.is_some_and(|permissions| permissions.has(Permission::SystemNotificationsWrite))
     } else {
         false
     };
@@ -1,9 +1,9 @@
-use actix_web::{web, HttpResponse, ResponseError};
+use actix_web::{http::StatusCode, web, HttpResponse, ResponseError};
 use anyhow::Context;
 use serde::Deserialize;
 use sqlx::{PgPool, Postgres, Transaction};
 
-use crate::{auth::extractor::UserIdExtractor, domain::title::Title, schema::{common::UserId, page::{PageId, TagId}}, utils::error_chain_fmt};
+use crate::{auth::{extractor::{PermissionsExtractor, UserIdExtractor}, permission::Permission}, domain::title::Title, schema::{common::UserId, page::{PageId, TagId}}, utils::error_chain_fmt};
 
 #[derive(Deserialize, Debug)]
 pub struct InsertPageSchema {
@@ -16,6 +16,8 @@ pub struct InsertPageSchema {
 
 #[derive(thiserror::Error)]
 pub enum InsertPageError {
+    #[error("Insufficient permissions to publish page")]
+    InsufficientPermissions,
     #[error(transparent)]
     Unexpected(#[from] anyhow::Error)
 }
@@ -26,13 +28,25 @@ impl std::fmt::Debug for InsertPageError {
     }
 }
 
-impl ResponseError for InsertPageError {}
+impl ResponseError for InsertPageError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            InsertPageError::InsufficientPermissions => StatusCode::FORBIDDEN,
+            InsertPageError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
 
 pub async fn create_page(
     web::Json(schema): web::Json<InsertPageSchema>,
     pool: web::Data<PgPool>,
     user_id: UserIdExtractor,
+    permissions: PermissionsExtractor,
 ) -> Result<HttpResponse, InsertPageError> {
+    if schema.is_public && !permissions.0.has(Permission::PagesPublish) {
+        return Err(InsertPageError::InsufficientPermissions)
+    }
+
     let mut transaction = pool.begin().await
         .context("Failed to begin transaction")?;
 
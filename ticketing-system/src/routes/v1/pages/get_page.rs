@@ -3,7 +3,7 @@ use anyhow::Context;
 use serde::Serialize;
 use sqlx::PgPool;
 
-use crate::{auth::{extractor::user_role::OptionalUserRoleExtractor, types::UserRole}, schema::page::{Page, PageId, Tag}, utils::error_chain_fmt};
+use crate::{auth::{extractor::permissions::OptionalPermissionsExtractor, permission::Permission}, schema::page::{Page, PageId, Tag}, utils::error_chain_fmt};
 
 #[derive(Serialize)]
 struct PageSchema {
@@ -40,16 +40,14 @@ impl ResponseError for GetPageError {
 pub async fn get_page(
     pool: web::Data<PgPool>,
     id: web::Path<PageId>,
-    role: OptionalUserRoleExtractor,
+    permissions: OptionalPermissionsExtractor,
 ) -> Result<HttpResponse, GetPageError> {
     let page = fetch_page(&pool, *id).await
         .context("Failed to fetch page")?
         .ok_or(GetPageError::NotFound)?;
 
-    let only_is_public = match role.0 {
-        Some(role) => !role.has_access(UserRole::Employee),
-        None => true,
-    };
+    let only_is_public = !permissions.0
+        .is_some_and(|permissions| permissions.has(Permission::PagesReadPrivate));
 
     if only_is_public && !page.is_public {
         return Err(GetPageError::NotFound);
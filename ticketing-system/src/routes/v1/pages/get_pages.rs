@@ -1,11 +1,11 @@
-use actix_web::{web, HttpResponse, ResponseError};
+use actix_web::{http::StatusCode, web, HttpResponse, ResponseError};
 use anyhow::Context;
 use garde::Validate;
 use garde_actix_web::web::QsQuery;
 use serde::{Deserialize, Serialize};
-use sqlx::{prelude::FromRow, PgPool};
+use sqlx::{prelude::FromRow, PgPool, Postgres, QueryBuilder};
 
-use crate::{auth::extractor::user_role::OptionalUserRoleExtractor, build_where_condition, schema::{common::{PaginationResult, SortOrder, UserId}, page::{PageId, Tag, TagId}}, utils::error_chain_fmt};
+use crate::{auth::{extractor::permissions::OptionalPermissionsExtractor, permission::Permission}, build_where_condition, schema::{common::{Cursor, CursorPaginationResult, PaginationResult, SortOrder, UserId}, page::{PageId, Tag, TagId}}, utils::error_chain_fmt};
 
 fn default_page_size() -> i8 { 10 }
 
@@ -24,6 +24,11 @@ pub struct GetPagesSchema {
     pub author: Option<UserId>,
     pub sort_order: Option<SortOrder>,
     pub search: Option<String>,
+    /// Switches to cursor pagination, empty for the first page.
+    pub cursor: Option<String>,
+    /// Counts the total in cursor pagination.
+    #[serde(default)]
+    pub with_total: bool,
 }
 
 #[derive(Serialize)]
@@ -40,11 +45,21 @@ struct PageWithMeta {
     pub is_public: bool,
     pub title: String,
     pub tags: sqlx::types::Json<Vec<Tag>>,
-    pub total_items: i64,
+    /// Only counted in page number pagination.
+    pub total_items: Option<i64>,
+}
+
+#[derive(Debug)]
+enum Pagination {
+    Page,
+    /// Id of the last page of the previous batch, `None` for the first one.
+    Cursor(Option<PageId>),
 }
 
 #[derive(thiserror::Error)]
 pub enum GetPagesError {
+    #[error("Invalid cursor")]
+    InvalidCursor,
     #[error(transparent)]
     Unexpected(#[from] anyhow::Error)
 }
@@ -55,31 +70,47 @@ impl std::fmt::Debug for GetPagesError {
     }
 }
 
-impl ResponseError for GetPagesError {}
+impl ResponseError for GetPagesError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            GetPagesError::InvalidCursor => StatusCode::BAD_REQUEST,
+            GetPagesError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
 
 pub async fn get_pages(
     pool: web::Data<PgPool>,
     schema: QsQuery<GetPagesSchema>,
-    role: OptionalUserRoleExtractor,
+    permissions: OptionalPermissionsExtractor,
 ) -> Result<HttpResponse, GetPagesError> {
     let schema = schema.into_inner();
 
-    let only_public = if let Some(role) = role.0 {
-        !role.has_access(crate::auth::types::UserRole::Employee)
-    } else {
-        true
+    let only_public = !permissions.0
+        .is_some_and(|permissions| permissions.has(Permission::PagesReadPrivate));
+
+    let pagination = match schema.cursor.as_deref() {
+        Some("") => Pagination::Cursor(None),
+        Some(cursor) => {
+            let cursor = Cursor::<PageId>::decode(cursor)
+                .ok_or(GetPagesError::InvalidCursor)?;
+
+            Pagination::Cursor(Some(cursor.id))
+        },
+        None => Pagination::Page,
     };
 
     let pages = fetch_pages(
         &pool,
         only_public,
+        &pagination,
         &schema
     )
     .await
     .context("Failed to fetch pages")?;
 
     let total_items = pages.first()
-        .map(|meta| meta.total_items)
+        .and_then(|meta| meta.total_items)
         .unwrap_or(0);
 
     let pages = pages.into_iter().map(|page| PageSchema {
@@ -90,10 +121,29 @@ pub async fn get_pages(
     })
     .collect();
 
-    Ok(HttpResponse::Ok().json(PaginationResult::new_with_pagination(
-        total_items as u64,
+    if let Pagination::Page = pagination {
+        return Ok(HttpResponse::Ok().json(PaginationResult::new_with_pagination(
+            total_items as u64,
+            schema.page_size,
+            pages
+        )));
+    }
+
+    let total_items = if schema.with_total {
+        let count = get_pages_count(&pool, only_public, &schema)
+            .await
+            .context("Failed to count pages")?;
+
+        Some(count)
+    } else {
+        None
+    };
+
+    Ok(HttpResponse::Ok().json(CursorPaginationResult::new(
+        pages,
         schema.page_size,
-        pages
+        total_items,
+        |page: &PageSchema| Cursor::new(page.id, ()).encode()
     )))
 }
 
@@ -104,6 +154,7 @@ pub async fn get_pages(
 async fn fetch_pages(
     pool: &PgPool,
     only_public: bool,
+    pagination: &Pagination,
     schema: &GetPagesSchema
 ) -> Result<Vec<PageWithMeta>, sqlx::Error> {
     let mut builder = sqlx::QueryBuilder::new("
@@ -120,11 +171,89 @@ async fn fetch_pages(
                 ) FILTER (WHERE t.id IS NOT NULL), 
                 '[]'::json
             ) as tags,
-            COUNT(*) OVER() as total_items
+    ");
+
+    match pagination {
+        Pagination::Page => builder.push("COUNT(*) OVER() as total_items"),
+        Pagination::Cursor(_) => builder.push("NULL::bigint as total_items"),
+    };
+
+    builder.push("
+        FROM pages p
+        LEFT JOIN pages_tags pt ON p.id = pt.page_id
+        LEFT JOIN tags t ON pt.tag_id = t.id
+    ");
+
+    let mut has_filters = apply_filters(&mut builder, only_public, schema);
+
+    let sort_order = schema.sort_order.unwrap_or_default();
+
+    if let Pagination::Cursor(Some(after)) = *pagination {
+        build_where_condition!(@add_where_and builder, has_filters);
+
+        builder.push("p.id ")
+            .push(match sort_order {
+                SortOrder::Asc => "> ",
+                SortOrder::Desc => "< ",
+            })
+            .push_bind(after);
+    }
+
+    if has_filters {
+        builder.push(" ");
+    }
+
+    builder.push("GROUP BY p.id ORDER BY p.id ")
+        .push(sort_order.as_str());
+
+    match pagination {
+        Pagination::Page => {
+            builder.push(" LIMIT ")
+                .push_bind(schema.page_size as i64)
+                .push(" OFFSET ")
+                .push_bind(schema.page_size as i64 * (schema.page - 1) as i64);
+        },
+        Pagination::Cursor(_) => {
+            builder.push(" LIMIT ")
+                .push_bind(schema.page_size as i64 + 1);
+        },
+    }
+
+    builder.build_query_as::<PageWithMeta>()
+        .fetch_all(pool)
+        .await
+}
+
+#[tracing::instrument(
+    name = "Get pages count from database",
+    skip(pool),
+)]
+async fn get_pages_count(
+    pool: &PgPool,
+    only_public: bool,
+    schema: &GetPagesSchema
+) -> Result<u64, sqlx::Error> {
+    let mut builder = sqlx::QueryBuilder::new("
+        SELECT COUNT(DISTINCT p.id)
         FROM pages p
         LEFT JOIN pages_tags pt ON p.id = pt.page_id
         LEFT JOIN tags t ON pt.tag_id = t.id
     ");
+
+    apply_filters(&mut builder, only_public, schema);
+
+    builder.build_query_scalar()
+        .fetch_one(pool)
+        .await
+        .map(|count: i64| count as u64)
+}
+
+/// Returns whether a `WHERE` clause was started.
+fn apply_filters<'a>(
+    builder: &mut QueryBuilder<'a, Postgres>,
+    only_public: bool,
+    schema: &'a GetPagesSchema,
+) -> bool {
     let mut has_filters = false;
 
     build_where_condition!(builder, has_filters, schema.author, "author", "=");
@@ -142,17 +271,5 @@ async fn fetch_pages(
         builder.push("title ILIKE ").push_bind(s);
     }
 
-    if has_filters {
-        builder.push(" ");
-    }
-
-    builder.push("GROUP BY p.id ORDER BY p.id ")
-        .push(schema.sort_order.unwrap_or_default().as_str())
-        .push(" LIMIT ")
-        .push_bind(schema.page_size as i64)
-        .push(" OFFSET ")
-        .push_bind(schema.page_size as i64 * (schema.page - 1) as i64)
-        .build_query_as::<PageWithMeta>()
-        .fetch_all(pool)
-        .await
+    has_filters
 }
\ No newline at end of file
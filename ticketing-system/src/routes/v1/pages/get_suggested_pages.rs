@@ -0,0 +1,159 @@
+use actix_web::{http::StatusCode, web, HttpResponse, ResponseError};
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+
+use crate::{auth::{department_scope::{can_access_ticket, DepartmentScope}, extractor::{PermissionsExtractor, UserIdExtractor}}, schema::{page::PageId, tickets::TicketId}, utils::error_chain_fmt};
+
+const MIN_SCORE: f32 = 0.1;
+const LIMIT: i64 = 5;
+
+/// Either an existing ticket or a draft of a new one.
+#[derive(Deserialize, Debug)]
+pub struct GetSuggestedPagesSchema {
+    pub ticket_id: Option<TicketId>,
+    pub title: Option<String>,
+    pub description: Option<String>,
+}
+
+#[derive(Serialize)]
+struct SuggestedPage {
+    pub id: PageId,
+    pub title: String,
+    pub score: f32,
+}
+
+#[derive(thiserror::Error)]
+pub enum GetSuggestedPagesError {
+    #[error("Ticket not found")]
+    TicketNotFound,
+    #[error("Insufficient permissions to get this ticket")]
+    InsufficientPermissions,
+    #[error("Either `ticket_id` or `title` is required")]
+    EmptyQuery,
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for GetSuggestedPagesError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for GetSuggestedPagesError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            GetSuggestedPagesError::TicketNotFound => StatusCode::NOT_FOUND,
+            GetSuggestedPagesError::InsufficientPermissions => StatusCode::FORBIDDEN,
+            GetSuggestedPagesError::EmptyQuery => StatusCode::BAD_REQUEST,
+            GetSuggestedPagesError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+pub async fn get_suggested_pages(
+    schema: web::Query<GetSuggestedPagesSchema>,
+    pool: web::Data<PgPool>,
+    user_id: UserIdExtractor,
+    permissions: PermissionsExtractor,
+) -> Result<HttpResponse, GetSuggestedPagesError> {
+    let schema = schema.into_inner();
+
+    let (title, description) = match schema.ticket_id {
+        Some(ticket_id) => {
+            let scope = DepartmentScope::resolve(&pool, user_id.0, &permissions.0)
+                .await
+                .context("Failed to resolve department scope")?;
+
+            let can_access = can_access_ticket(&pool, &scope, user_id.0, ticket_id)
+                .await
+                .context("Failed to check ticket access")?
+                .ok_or(GetSuggestedPagesError::TicketNotFound)?;
+
+            if !can_access {
+                return Err(GetSuggestedPagesError::InsufficientPermissions);
+            }
+
+            get_ticket_text(&pool, ticket_id)
+                .await
+                .context("Failed to get ticket")?
+                .ok_or(GetSuggestedPagesError::TicketNotFound)?
+        },
+        None => (
+            schema.title.ok_or(GetSuggestedPagesError::EmptyQuery)?,
+            schema.description.unwrap_or_default(),
+        ),
+    };
+
+    let pages = find_pages(&pool, &title, &description)
+        .await
+        .context("Failed to find suggested pages")?;
+
+    Ok(HttpResponse::Ok().json(pages))
+}
+
+#[tracing::instrument(
+    name = "Get ticket title and description from database",
+    skip(pool)
+)]
+async fn get_ticket_text(pool: &PgPool, ticket_id: TicketId) -> Result<Option<(String, String)>, sqlx::Error> {
+    let row = sqlx::query!(
+        "SELECT title, description FROM tickets WHERE id = $1",
+        ticket_id
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|row| (row.title, row.description)))
+}
+
+/// Ranks public pages by the sum of three scores, each between 0 and 1:
+/// trigram similarity of the title, the best tag or synonym found in the text
+/// of the ticket, and full-text rank of the content with any of the ticket words.
+#[tracing::instrument(
+    name = "Find suggested pages in database",
+    skip(pool)
+)]
+async fn find_pages(pool: &PgPool, title: &str, description: &str) -> Result<Vec<SuggestedPage>, sqlx::Error> {
+    let text = format!("{} {}", title, description);
+
+    sqlx::query_as!(
+        SuggestedPage,
+        r#"
+            WITH query AS (
+                SELECT REPLACE(plainto_tsquery('russian', $2)::text, ' & ', ' | ')::tsquery AS words
+            )
+            SELECT id, title, score AS "score!"
+            FROM (
+                SELECT
+                    p.id,
+                    p.title,
+                    similarity(p.title, $1)
+                    + COALESCE((
+                        SELECT MAX(word_similarity(names.name, $2))
+                        FROM pages_tags pt
+                        CROSS JOIN LATERAL (
+                            SELECT t.name FROM tags t WHERE t.id = pt.tag_id
+                            UNION ALL
+                            SELECT s.name FROM tags_synonyms s WHERE s.tag_id = pt.tag_id
+                        ) names
+                        WHERE pt.page_id = p.id
+                        HAVING MAX(word_similarity(names.name, $2)) >= 0.6
+                    ), 0)
+                    + ts_rank_cd(to_tsvector('russian', p.text), query.words, 32) AS score
+                FROM pages p, query
+                WHERE p.is_public
+            ) ranked
+            WHERE score >= $3
+            ORDER BY score DESC, id
+            LIMIT $4
+        "#,
+        title,
+        text,
+        MIN_SCORE,
+        LIMIT
+    )
+    .fetch_all(pool)
+    .await
+}
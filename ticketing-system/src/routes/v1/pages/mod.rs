@@ -3,9 +3,11 @@ pub mod get_pages;
 pub mod delete_page;
 pub mod get_page;
 pub mod update_page;
+pub mod get_suggested_pages;
 
 pub use create_page::create_page;
 pub use get_pages::get_pages;
 pub use delete_page::delete_page;
 pub use get_page::get_page;
-pub use update_page::update_page;
\ No newline at end of file
+pub use update_page::update_page;
+pub use get_suggested_pages::get_suggested_pages;
\ No newline at end of file
@@ -3,7 +3,7 @@ use anyhow::Context;
 use serde::Deserialize;
 use sqlx::{PgPool, Postgres, Transaction};
 
-use crate::{build_update_query, domain::title::Title, routes::v1::pages::create_page::{insert_related_pages, insert_tags}, schema::page::{PageId, TagId}, utils::error_chain_fmt};
+use crate::{auth::{extractor::PermissionsExtractor, permission::Permission}, build_update_query, domain::title::Title, routes::v1::pages::create_page::{insert_related_pages, insert_tags}, schema::page::{PageId, TagId}, utils::error_chain_fmt};
 
 #[derive(Deserialize, Debug)]
 pub struct UpdatePageSchema {
@@ -18,6 +18,8 @@ pub struct UpdatePageSchema {
 
 #[derive(thiserror::Error)]
 pub enum UpdatePageError {
+    #[error("Insufficient permissions to publish page")]
+    InsufficientPermissions,
     #[error(transparent)]
     Unexpected(#[from] anyhow::Error)
 }
@@ -31,6 +33,7 @@ impl std::fmt::Debug for UpdatePageError {
 impl ResponseError for UpdatePageError {
     fn status_code(&self) -> StatusCode {
         match self {
+            UpdatePageError::InsufficientPermissions => StatusCode::FORBIDDEN,
             UpdatePageError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
@@ -40,7 +43,13 @@ pub async fn update_page(
     web::Json(schema): web::Json<UpdatePageSchema>,
     pool: web::Data<PgPool>,
     path: web::Path<PageId>,
+    permissions: PermissionsExtractor,
 ) -> Result<HttpResponse, UpdatePageError> {
+    // Hiding a page is fine, making it public needs the publish permission.
+    if schema.is_public == Some(true) && !permissions.0.has(Permission::PagesPublish) {
+        return Err(UpdatePageError::InsufficientPermissions)
+    }
+
     let page_id = path.into_inner();
 
     let mut transaction = pool.begin().await
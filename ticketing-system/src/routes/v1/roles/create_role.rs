@@ -0,0 +1,116 @@
+use actix_web::{http::StatusCode, web, HttpResponse, ResponseError};
+use anyhow::Context;
+use garde_actix_web::web::Json;
+use sqlx::{PgPool, Postgres, Transaction};
+
+use crate::{auth::{extractor::UserRoleExtractor, permission::Permission}, schema::role::{CreateRoleSchema, Role, RoleId}, utils::error_chain_fmt};
+
+#[derive(thiserror::Error)]
+pub enum CreateRoleError {
+    #[error("Role level cannot be higher than your own")]
+    LevelTooHigh,
+    #[error("Role with this name already exists")]
+    RoleNameExists,
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for CreateRoleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for CreateRoleError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            CreateRoleError::LevelTooHigh => StatusCode::FORBIDDEN,
+            CreateRoleError::RoleNameExists => StatusCode::CONFLICT,
+            CreateRoleError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+pub async fn create_role(
+    user_role: UserRoleExtractor,
+    Json(mut schema): Json<CreateRoleSchema>,
+    pool: web::Data<PgPool>,
+) -> Result<HttpResponse, CreateRoleError> {
+    if !user_role.0.has_access(schema.level) {
+        return Err(CreateRoleError::LevelTooHigh);
+    }
+
+    let mut transaction = pool.begin()
+        .await
+        .context("Failed to begin transaction")?;
+
+    let id = insert_role(&mut transaction, &schema)
+        .await
+        .context("Failed to insert role")?
+        .ok_or(CreateRoleError::RoleNameExists)?;
+
+    schema.permissions.sort_by_key(|permission| permission.as_str());
+    schema.permissions.dedup();
+
+    insert_role_permissions(&mut transaction, id, &schema.permissions)
+        .await
+        .context("Failed to insert role permissions")?;
+
+    transaction.commit()
+        .await
+        .context("Failed to commit transaction")?;
+
+    Ok(HttpResponse::Created().json(Role {
+        id,
+        name: schema.name,
+        level: schema.level,
+        is_system: false,
+        permissions: schema.permissions,
+    }))
+}
+
+#[tracing::instrument(
+    name = "Insert role into database",
+    skip(transaction, schema)
+)]
+async fn insert_role(
+    transaction: &mut Transaction<'_, Postgres>,
+    schema: &CreateRoleSchema,
+) -> Result<Option<RoleId>, sqlx::Error> {
+    sqlx::query_scalar!(
+        "INSERT INTO roles (name, level)
+        VALUES ($1, $2)
+        ON CONFLICT (name) DO NOTHING
+        RETURNING id",
+        schema.name,
+        schema.level as i16
+    )
+    .fetch_optional(&mut **transaction)
+    .await
+}
+
+#[tracing::instrument(
+    name = "Insert role permissions into database",
+    skip(transaction)
+)]
+pub async fn insert_role_permissions(
+    transaction: &mut Transaction<'_, Postgres>,
+    role_id: RoleId,
+    permissions: &[Permission],
+) -> Result<(), sqlx::Error> {
+    let permissions = permissions.iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+
+    sqlx::query!(
+        "INSERT INTO role_permissions (role_id, permission)
+        SELECT $1, permission FROM UNNEST($2::text[]) AS permission
+        ON CONFLICT DO NOTHING",
+        role_id,
+        &permissions
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(())
+}
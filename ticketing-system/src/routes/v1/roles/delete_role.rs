@@ -0,0 +1,85 @@
+use actix_web::{http::StatusCode, web, HttpResponse, ResponseError};
+use anyhow::Context;
+use sqlx::PgPool;
+
+use crate::{schema::role::RoleId, services::permission::PermissionService, utils::error_chain_fmt};
+
+#[derive(thiserror::Error)]
+pub enum DeleteRoleError {
+    #[error("Role not found")]
+    RoleNotFound,
+    #[error("Built-in roles cannot be deleted")]
+    SystemRole,
+    #[error("Role is assigned to users")]
+    RoleInUse,
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for DeleteRoleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for DeleteRoleError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            DeleteRoleError::RoleNotFound => StatusCode::NOT_FOUND,
+            DeleteRoleError::SystemRole | DeleteRoleError::RoleInUse => StatusCode::CONFLICT,
+            DeleteRoleError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+pub async fn delete_role(
+    role_id: web::Path<RoleId>,
+    pool: web::Data<PgPool>,
+    permission_service: web::Data<PermissionService>,
+) -> Result<HttpResponse, DeleteRoleError> {
+    let role_id = role_id.into_inner();
+
+    let res = delete(&pool, role_id).await;
+
+    if let Err(e) = &res
+        && let Some(db_err) = e.as_database_error()
+            && db_err.is_foreign_key_violation() {
+                return Err(DeleteRoleError::RoleInUse);
+            }
+
+    match res.context("Failed to delete role")? {
+        None => return Err(DeleteRoleError::RoleNotFound),
+        Some(true) => return Err(DeleteRoleError::SystemRole),
+        Some(false) => (),
+    }
+
+    permission_service.invalidate(role_id).await;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Returns whether the role is a built-in one, which is kept,
+/// or None if there is no such role.
+#[tracing::instrument(
+    name = "Delete role from database",
+    skip(pool)
+)]
+async fn delete(pool: &PgPool, role_id: RoleId) -> Result<Option<bool>, sqlx::Error> {
+    let is_system = sqlx::query_scalar!(
+        "SELECT is_system FROM roles WHERE id = $1",
+        role_id
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    if is_system == Some(false) {
+        sqlx::query!(
+            "DELETE FROM roles WHERE id = $1 AND NOT is_system",
+            role_id
+        )
+        .execute(pool)
+        .await?;
+    }
+
+    Ok(is_system)
+}
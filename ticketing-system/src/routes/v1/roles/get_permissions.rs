@@ -0,0 +1,8 @@
+use actix_web::HttpResponse;
+use strum::IntoEnumIterator as _;
+
+use crate::auth::permission::Permission;
+
+pub async fn get_permissions() -> HttpResponse {
+    HttpResponse::Ok().json(Permission::iter().collect::<Vec<_>>())
+}
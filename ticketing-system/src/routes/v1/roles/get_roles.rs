@@ -0,0 +1,72 @@
+use std::str::FromStr as _;
+
+use actix_web::{web, HttpResponse, ResponseError};
+use anyhow::Context;
+use sqlx::PgPool;
+
+use crate::{auth::{permission::Permission, types::UserRole}, schema::role::{Role, RoleId}, utils::error_chain_fmt};
+
+#[derive(thiserror::Error)]
+pub enum GetRolesError {
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for GetRolesError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for GetRolesError {}
+
+pub async fn get_roles(
+    pool: web::Data<PgPool>,
+) -> Result<HttpResponse, GetRolesError> {
+    let roles = get_roles_from_db(&pool, None)
+        .await
+        .context("Failed to get roles from database")?;
+
+    Ok(HttpResponse::Ok().json(roles))
+}
+
+/// Returns all roles, or only the one with `id` when it is set.
+#[tracing::instrument(
+    name = "Get roles from database",
+    skip(pool)
+)]
+pub async fn get_roles_from_db(pool: &PgPool, id: Option<RoleId>) -> Result<Vec<Role>, sqlx::Error> {
+    let rows = sqlx::query!(
+        r#"
+            SELECT
+                r.id,
+                r.name,
+                r.level,
+                r.is_system,
+                ARRAY_REMOVE(ARRAY_AGG(p.permission ORDER BY p.permission), NULL) AS "permissions!"
+            FROM roles r
+            LEFT JOIN role_permissions p ON p.role_id = r.id
+            WHERE $1::smallint IS NULL OR r.id = $1
+            GROUP BY r.id
+            ORDER BY r.id
+        "#,
+        id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let roles = rows.into_iter()
+        .map(|row| Role {
+            id: row.id,
+            name: row.name,
+            level: UserRole::from(row.level),
+            is_system: row.is_system,
+            // Permissions removed from the code are ignored until cleaned up.
+            permissions: row.permissions.iter()
+                .filter_map(|name| Permission::from_str(name).ok())
+                .collect(),
+        })
+        .collect();
+
+    Ok(roles)
+}
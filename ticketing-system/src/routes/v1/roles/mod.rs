@@ -0,0 +1,11 @@
+pub mod get_roles;
+pub mod get_permissions;
+pub mod create_role;
+pub mod update_role;
+pub mod delete_role;
+
+pub use get_roles::get_roles;
+pub use get_permissions::get_permissions;
+pub use create_role::create_role;
+pub use update_role::update_role;
+pub use delete_role::delete_role;
\ No newline at end of file
@@ -0,0 +1,146 @@
+use actix_web::{http::StatusCode, web, HttpResponse, ResponseError};
+use anyhow::Context;
+use garde_actix_web::web::Json;
+use sqlx::{PgPool, Postgres, Transaction};
+
+use crate::{auth::{extractor::UserRoleExtractor, types::UserRole}, routes::v1::roles::{create_role::insert_role_permissions, get_roles::get_roles_from_db}, schema::role::{RoleId, UpdateRoleSchema}, services::permission::PermissionService, utils::error_chain_fmt};
+
+#[derive(thiserror::Error)]
+pub enum UpdateRoleError {
+    #[error("Role not found")]
+    RoleNotFound,
+    #[error("Role level cannot be higher than your own")]
+    LevelTooHigh,
+    #[error("Permissions of built-in roles cannot be changed")]
+    SystemRole,
+    #[error("Role with this name already exists")]
+    RoleNameExists,
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for UpdateRoleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for UpdateRoleError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            UpdateRoleError::RoleNotFound => StatusCode::NOT_FOUND,
+            UpdateRoleError::LevelTooHigh => StatusCode::FORBIDDEN,
+            UpdateRoleError::SystemRole => StatusCode::CONFLICT,
+            UpdateRoleError::RoleNameExists => StatusCode::CONFLICT,
+            UpdateRoleError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+pub async fn update_role(
+    user_role: UserRoleExtractor,
+    role_id: web::Path<RoleId>,
+    Json(schema): Json<UpdateRoleSchema>,
+    pool: web::Data<PgPool>,
+    permission_service: web::Data<PermissionService>,
+) -> Result<HttpResponse, UpdateRoleError> {
+    let role_id = role_id.into_inner();
+
+    let mut transaction = pool.begin()
+        .await
+        .context("Failed to begin transaction")?;
+
+    let (level, is_system) = lock_role(&mut transaction, role_id)
+        .await
+        .context("Failed to get role")?
+        .ok_or(UpdateRoleError::RoleNotFound)?;
+
+    if !user_role.0.has_access(level) {
+        return Err(UpdateRoleError::LevelTooHigh);
+    }
+
+    // Otherwise the admin role could lose `roles.manage` and nobody could fix it.
+    if is_system && schema.permissions.is_some() {
+        return Err(UpdateRoleError::SystemRole);
+    }
+
+    if let Some(name) = &schema.name {
+        let res = update_name(&mut transaction, role_id, name).await;
+
+        if let Err(e) = &res
+            && let Some(db_err) = e.as_database_error()
+                && db_err.is_unique_violation() {
+                    return Err(UpdateRoleError::RoleNameExists);
+                }
+
+        res.context("Failed to update role")?;
+    }
+
+    if let Some(permissions) = &schema.permissions {
+        sqlx::query!(
+            "DELETE FROM role_permissions WHERE role_id = $1",
+            role_id
+        )
+        .execute(&mut *transaction)
+        .await
+        .context("Failed to delete role permissions")?;
+
+        insert_role_permissions(&mut transaction, role_id, permissions)
+            .await
+            .context("Failed to insert role permissions")?;
+    }
+
+    transaction.commit()
+        .await
+        .context("Failed to commit transaction")?;
+
+    permission_service.invalidate(role_id).await;
+
+    let role = get_roles_from_db(&pool, Some(role_id))
+        .await
+        .context("Failed to get role from database")?
+        .pop()
+        .ok_or(UpdateRoleError::RoleNotFound)?;
+
+    Ok(HttpResponse::Ok().json(role))
+}
+
+/// Returns the level of the role and whether it is a built-in one. The row
+/// is locked, so permissions are replaced consistently.
+#[tracing::instrument(
+    name = "Lock role",
+    skip(transaction)
+)]
+async fn lock_role(
+    transaction: &mut Transaction<'_, Postgres>,
+    role_id: RoleId,
+) -> Result<Option<(UserRole, bool)>, sqlx::Error> {
+    let row = sqlx::query!(
+        "SELECT level, is_system FROM roles WHERE id = $1 FOR UPDATE",
+        role_id
+    )
+    .fetch_optional(&mut **transaction)
+    .await?;
+
+    Ok(row.map(|row| (UserRole::from(row.level), row.is_system)))
+}
+
+#[tracing::instrument(
+    name = "Update role name",
+    skip(transaction)
+)]
+async fn update_name(
+    transaction: &mut Transaction<'_, Postgres>,
+    role_id: RoleId,
+    name: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "UPDATE roles SET name = $2 WHERE id = $1",
+        role_id,
+        name
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(())
+}
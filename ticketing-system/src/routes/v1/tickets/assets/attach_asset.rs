@@ -1,11 +1,11 @@
-use actix_web::{HttpResponse, ResponseError, web::{self, Path}};
+use actix_web::{HttpResponse, ResponseError, http::StatusCode, web::{self, Path}};
 use anyhow::Context;
 use garde::Validate;
 use garde_actix_web::web::Json;
 use serde::Deserialize;
 use sqlx::PgPool;
 
-use crate::{schema::assets::AssetId, utils::error_chain_fmt};
+use crate::{auth::{department_scope::{can_access_ticket, DepartmentScope}, extractor::{PermissionsExtractor, UserIdExtractor}}, schema::{assets::AssetId, tickets::TicketId}, utils::error_chain_fmt};
 
 #[derive(Debug, Deserialize, Validate)]
 pub struct AttachAssetSchema {
@@ -17,6 +17,10 @@ pub struct AttachAssetSchema {
 
 #[derive(thiserror::Error)]
 pub enum AttachAssetError {
+    #[error("Ticket not found")]
+    NotFound,
+    #[error("Insufficient permissions to access this ticket")]
+    InsufficientPermissions,
     #[error(transparent)]
     Unexpected(#[from] anyhow::Error),
 }
@@ -27,15 +31,40 @@ impl std::fmt::Debug for AttachAssetError {
     }
 }
 
-impl ResponseError for AttachAssetError {}
+impl ResponseError for AttachAssetError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            AttachAssetError::NotFound => StatusCode::NOT_FOUND,
+            AttachAssetError::InsufficientPermissions => StatusCode::FORBIDDEN,
+            AttachAssetError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
 
 pub async fn attach_asset(
-    ticket_id: Path<i64>,
+    ticket_id: Path<TicketId>,
     Json(schema): Json<AttachAssetSchema>,
     pool: web::Data<PgPool>,
+    user_id: UserIdExtractor,
+    permissions: PermissionsExtractor,
 ) -> Result<HttpResponse, AttachAssetError> {
+    let ticket_id = ticket_id.into_inner();
+
+    let scope = DepartmentScope::resolve(&pool, user_id.0, &permissions.0)
+        .await
+        .context("Failed to resolve department scope")?;
+
+    let can_access = can_access_ticket(&pool, &scope, user_id.0, ticket_id)
+        .await
+        .context("Failed to check ticket access")?
+        .ok_or(AttachAssetError::NotFound)?;
+
+    if !can_access {
+        return Err(AttachAssetError::InsufficientPermissions);
+    }
+
     attach(
-        ticket_id.into_inner(),
+        ticket_id,
         schema,
         &pool
     )
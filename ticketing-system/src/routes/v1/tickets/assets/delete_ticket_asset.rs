@@ -1,11 +1,15 @@
-use actix_web::{HttpResponse, ResponseError, web::{self, Path}};
+use actix_web::{HttpResponse, ResponseError, http::StatusCode, web::{self, Path}};
 use anyhow::Context;
 use sqlx::PgPool;
 
-use crate::{schema::{assets::AssetId, tickets::TicketId}, utils::error_chain_fmt};
+use crate::{auth::{department_scope::{can_access_ticket, DepartmentScope}, extractor::{PermissionsExtractor, UserIdExtractor}}, schema::{assets::AssetId, tickets::TicketId}, utils::error_chain_fmt};
 
 #[derive(thiserror::Error)]
 pub enum DeleteTicketAssetError {
+	#[error("Ticket not found")]
+	NotFound,
+	#[error("Insufficient permissions to access this ticket")]
+	InsufficientPermissions,
 	#[error(transparent)]
 	Unexpected(#[from] anyhow::Error),
 }
@@ -16,14 +20,37 @@ impl std::fmt::Debug for DeleteTicketAssetError {
 	}
 }
 
-impl ResponseError for DeleteTicketAssetError {}
+impl ResponseError for DeleteTicketAssetError {
+	fn status_code(&self) -> StatusCode {
+		match self {
+			DeleteTicketAssetError::NotFound => StatusCode::NOT_FOUND,
+			DeleteTicketAssetError::InsufficientPermissions => StatusCode::FORBIDDEN,
+			DeleteTicketAssetError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
+		}
+	}
+}
 
 pub async fn delete_ticket_asset(
 	path: Path<(TicketId, AssetId)>,
 	pool: web::Data<PgPool>,
+	user_id: UserIdExtractor,
+	permissions: PermissionsExtractor,
 ) -> Result<HttpResponse, DeleteTicketAssetError> {
 	let (ticket_id, asset_id) = path.into_inner();
 
+	let scope = DepartmentScope::resolve(&pool, user_id.0, &permissions.0)
+		.await
+		.context("Failed to resolve department scope")?;
+
+	let can_access = can_access_ticket(&pool, &scope, user_id.0, ticket_id)
+		.await
+		.context("Failed to check ticket access")?
+		.ok_or(DeleteTicketAssetError::NotFound)?;
+
+	if !can_access {
+		return Err(DeleteTicketAssetError::InsufficientPermissions);
+	}
+
 	delete(
 		ticket_id,
 		asset_id,
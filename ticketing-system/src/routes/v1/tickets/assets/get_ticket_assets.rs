@@ -1,13 +1,13 @@
 use std::net::IpAddr;
 
-use actix_web::{HttpResponse, ResponseError, web::{self, Path}};
+use actix_web::{HttpResponse, ResponseError, http::StatusCode, web::{self, Path}};
 use anyhow::Context;
 use chrono::{DateTime, Utc};
 use mac_address::MacAddress;
 use serde::{Deserialize, Serialize};
 use sqlx::{PgPool, prelude::FromRow, query_as, types::Json};
 
-use crate::{schema::{assets::{AssetId, Model, Status}, tickets::TicketId}, utils::error_chain_fmt};
+use crate::{auth::{department_scope::{can_access_ticket, DepartmentScope}, extractor::{PermissionsExtractor, UserIdExtractor}}, schema::{assets::{AssetId, Model, Status}, tickets::TicketId}, utils::error_chain_fmt};
 
 #[derive(Deserialize, Serialize, FromRow)]
 struct Asset {
@@ -30,6 +30,10 @@ struct Asset {
 
 #[derive(thiserror::Error)]
 pub enum GetTicketAssetsError {
+	#[error("Ticket not found")]
+	NotFound,
+	#[error("Insufficient permissions to access this ticket")]
+	InsufficientPermissions,
 	#[error(transparent)]
 	Unexpected(#[from] anyhow::Error),
 }
@@ -40,15 +44,40 @@ impl std::fmt::Debug for GetTicketAssetsError {
 	}
 }
 
-impl ResponseError for GetTicketAssetsError {}
+impl ResponseError for GetTicketAssetsError {
+	fn status_code(&self) -> StatusCode {
+		match self {
+			GetTicketAssetsError::NotFound => StatusCode::NOT_FOUND,
+			GetTicketAssetsError::InsufficientPermissions => StatusCode::FORBIDDEN,
+			GetTicketAssetsError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
+		}
+	}
+}
 
 pub async fn get_ticket_assets(
 	ticket_id: Path<TicketId>,
 	pool: web::Data<PgPool>,
+	user_id: UserIdExtractor,
+	permissions: PermissionsExtractor,
 ) -> Result<HttpResponse, GetTicketAssetsError> {
+	let ticket_id = ticket_id.into_inner();
+
+	let scope = DepartmentScope::resolve(&pool, user_id.0, &permissions.0)
+		.await
+		.context("Failed to resolve department scope")?;
+
+	let can_access = can_access_ticket(&pool, &scope, user_id.0, ticket_id)
+		.await
+		.context("Failed to check ticket access")?
+		.ok_or(GetTicketAssetsError::NotFound)?;
+
+	if !can_access {
+		return Err(GetTicketAssetsError::InsufficientPermissions);
+	}
+
 	let assets = select_assets(
 		&pool,
-		ticket_id.into_inner()
+		ticket_id
 	)
 	.await
 	.context("Failed to get ticket assets")?;
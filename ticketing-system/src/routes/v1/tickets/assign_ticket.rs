@@ -1,11 +1,17 @@
-use actix_web::{web, HttpResponse, ResponseError};
+use actix_web::{http::StatusCode, web, HttpResponse, ResponseError};
 use anyhow::Context;
 use sqlx::{PgPool, Postgres, Transaction};
 
-use crate::{auth::extractor::UserIdExtractor, schema::{common::UserId, tickets::{TicketId, TicketStatus}}, utils::error_chain_fmt};
+use crate::{auth::{department_scope::{get_department_role, get_ticket_department, DepartmentScope}, extractor::{PermissionsExtractor, UserIdExtractor}, permission::{Permission, Permissions}, types::DepartmentRole}, schema::{common::UserId, tickets::{TicketId, TicketStatus}}, utils::error_chain_fmt};
 
 #[derive(thiserror::Error)]
 pub enum AssignTicketError {
+    #[error("Insufficient permissions to assign this ticket")]
+    InsufficientPermissions,
+    #[error("Ticket not found")]
+    TicketNotFound,
+    #[error("User is not a member of the ticket's department")]
+    NotDepartmentMember,
     #[error(transparent)]
     Unexpected(#[from] anyhow::Error),
 }
@@ -16,15 +22,37 @@ impl std::fmt::Debug for AssignTicketError {
     }
 }
 
-impl ResponseError for AssignTicketError {}
+impl ResponseError for AssignTicketError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            AssignTicketError::InsufficientPermissions => StatusCode::FORBIDDEN,
+            AssignTicketError::TicketNotFound => StatusCode::NOT_FOUND,
+            AssignTicketError::NotDepartmentMember => StatusCode::BAD_REQUEST,
+            AssignTicketError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
 
 pub async fn assign_ticket_to_self(
     id: web::Path<TicketId>,
     user_id: UserIdExtractor,
+    permissions: PermissionsExtractor,
     pool: web::Data<PgPool>,
 ) -> Result<HttpResponse, AssignTicketError> {
     let ticket_id = id.into_inner();
 
+    if !permissions.0.has(Permission::TicketsAllDepartments) {
+        let department_id = get_ticket_department(&pool, ticket_id)
+            .await
+            .context("Failed to get ticket department")?
+            .ok_or(AssignTicketError::TicketNotFound)?;
+
+        get_department_role(&pool, user_id.0, department_id)
+            .await
+            .context("Failed to get department role")?
+            .ok_or(AssignTicketError::InsufficientPermissions)?;
+    }
+
     assign_ticket(&pool, ticket_id, user_id.0).await?;
 
     Ok(HttpResponse::Ok().finish())
@@ -32,15 +60,58 @@ pub async fn assign_ticket_to_self(
 
 pub async fn assign_ticket_to_user(
     path: web::Path<(TicketId, UserId)>,
+    caller_id: UserIdExtractor,
+    permissions: PermissionsExtractor,
     pool: web::Data<PgPool>,
 ) -> Result<HttpResponse, AssignTicketError> {
     let (ticket_id, user_id) = path.into_inner();
 
+    let department_id = get_ticket_department(&pool, ticket_id)
+        .await
+        .context("Failed to get ticket department")?
+        .ok_or(AssignTicketError::TicketNotFound)?;
+
+    if !can_assign_others(&pool, caller_id.0, &permissions.0, department_id).await? {
+        return Err(AssignTicketError::InsufficientPermissions);
+    }
+
+    get_department_role(&pool, user_id, department_id)
+        .await
+        .context("Failed to get department role")?
+        .ok_or(AssignTicketError::NotDepartmentMember)?;
+
     assign_ticket(&pool, ticket_id, user_id).await?;
 
     Ok(HttpResponse::Ok().finish())
 }
 
+/// Users with cross-department access assign anywhere. Others have to be members
+/// of the department, and either hold the permission or moderate the department.
+pub async fn can_assign_others(
+    pool: &PgPool,
+    user_id: UserId,
+    permissions: &Permissions,
+    department_id: i16,
+) -> Result<bool, anyhow::Error> {
+    let scope = DepartmentScope::resolve(pool, user_id, permissions)
+        .await
+        .context("Failed to resolve department scope")?;
+
+    if scope == DepartmentScope::All {
+        return Ok(permissions.has(Permission::TicketsAssignOthers));
+    }
+
+    let role = get_department_role(pool, user_id, department_id)
+        .await
+        .context("Failed to get department role")?;
+
+    Ok(match role {
+        Some(DepartmentRole::Moderator) => true,
+        Some(DepartmentRole::Member) => permissions.has(Permission::TicketsAssignOthers),
+        None => false,
+    })
+}
+
 async fn assign_ticket(
     pool: &PgPool,
     ticket_id: TicketId,
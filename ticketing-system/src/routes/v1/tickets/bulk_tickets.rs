@@ -0,0 +1,483 @@
+use std::collections::HashMap;
+
+use actix_web::{http::StatusCode, web, HttpResponse, ResponseError};
+use anyhow::Context;
+use garde_actix_web::web::Json;
+use sqlx::{PgPool, Postgres, QueryBuilder, Transaction};
+
+use crate::{auth::{department_scope::{get_department_role, DepartmentId}, extractor::{DeviceInfo, PermissionsExtractor, UserIdExtractor, UserRoleExtractor}, permission::{Permission, Permissions}}, routes::v1::tickets::{assign_ticket::can_assign_others, get_tickets::{resolve_visibility, GetTicketsSchema, TicketFilters}}, schema::{audit::AuditAction, common::UserId, notification::Notification, tickets::{BulkOperation, BulkTicketOutcome, BulkTicketResult, BulkTicketsSchema, TicketId, TicketStatus, MAX_BULK_TICKETS}}, services::{audit::AuditEntry, notification::NotificationService}, utils::error_chain_fmt};
+
+struct LockedTicket {
+    pub id: TicketId,
+    pub department_id: DepartmentId,
+    pub is_involved: bool,
+}
+
+#[derive(thiserror::Error)]
+pub enum BulkTicketsError {
+    #[error("Either `ticket_ids` or `filters` must be given")]
+    InvalidTarget,
+    #[error("Invalid filters: {0}")]
+    InvalidFilters(serde_json::Error),
+    #[error("More than {} tickets match the filters", MAX_BULK_TICKETS)]
+    TooManyTickets,
+    #[error("Insufficient permissions for this operation")]
+    InsufficientPermissions,
+    #[error("Department not found")]
+    DepartmentNotFound,
+    #[error("Building not found")]
+    BuildingNotFound,
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for BulkTicketsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for BulkTicketsError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            BulkTicketsError::InvalidTarget => StatusCode::BAD_REQUEST,
+            BulkTicketsError::InvalidFilters(_) => StatusCode::BAD_REQUEST,
+            BulkTicketsError::TooManyTickets => StatusCode::BAD_REQUEST,
+            BulkTicketsError::InsufficientPermissions => StatusCode::FORBIDDEN,
+            BulkTicketsError::DepartmentNotFound => StatusCode::BAD_REQUEST,
+            BulkTicketsError::BuildingNotFound => StatusCode::BAD_REQUEST,
+            BulkTicketsError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+/// Applies one operation to the given tickets and reports the outcome for each.
+/// Tickets the user cannot change are skipped, or fail the whole request if it is atomic.
+pub async fn bulk_update_tickets(
+    Json(schema): Json<BulkTicketsSchema>,
+    pool: web::Data<PgPool>,
+    notification_service: web::Data<NotificationService>,
+    user_id: UserIdExtractor,
+    user_role: UserRoleExtractor,
+    permissions: PermissionsExtractor,
+    device: DeviceInfo,
+) -> Result<HttpResponse, BulkTicketsError> {
+    let user_id = user_id.0;
+    let permissions = permissions.0;
+
+    let required = match schema.operation {
+        BulkOperation::Assign { .. } | BulkOperation::Unassign { .. } => Some(Permission::TicketsAssign),
+        BulkOperation::AddInternalMessage { .. } => Some(Permission::MessagesWrite),
+        _ => None,
+    };
+
+    if required.is_some_and(|permission| !permissions.has(permission)) {
+        return Err(BulkTicketsError::InsufficientPermissions);
+    }
+
+    let (client_id, scope) = resolve_visibility(&pool, user_id, user_role.0, &permissions)
+        .await
+        .context("Failed to resolve department scope")?;
+
+    // Clients change their own tickets only, whatever the scope says.
+    let in_scope = |department_id| client_id.is_none() && scope.contains(department_id);
+
+    match schema.operation {
+        BulkOperation::SetDepartment { department_id } => {
+            if !in_scope(department_id) {
+                return Err(BulkTicketsError::InsufficientPermissions);
+            }
+
+            if !department_exists(&pool, department_id).await.context("Failed to check department")? {
+                return Err(BulkTicketsError::DepartmentNotFound);
+            }
+        },
+        BulkOperation::SetBuilding { building_id }
+            if !building_exists(&pool, building_id).await.context("Failed to check building")? =>
+        {
+            return Err(BulkTicketsError::BuildingNotFound);
+        },
+        _ => {},
+    }
+
+    let ticket_ids = match (schema.ticket_ids, schema.filters) {
+        (Some(mut ticket_ids), None) => {
+            ticket_ids.sort_unstable();
+            ticket_ids.dedup();
+            ticket_ids
+        },
+        (None, Some(filters)) => {
+            let filters: GetTicketsSchema = serde_json::from_value(filters.into())
+                .map_err(BulkTicketsError::InvalidFilters)?;
+
+            let read_internal = permissions.has(Permission::MessagesReadInternal);
+            let filters = TicketFilters::new(&filters, None, &client_id, &scope, read_internal, user_id);
+
+            let ticket_ids = select_ticket_ids(&pool, &filters)
+                .await
+                .context("Failed to select tickets by filters")?;
+
+            if ticket_ids.len() > MAX_BULK_TICKETS {
+                return Err(BulkTicketsError::TooManyTickets);
+            }
+
+            ticket_ids
+        },
+        _ => return Err(BulkTicketsError::InvalidTarget),
+    };
+
+    let mut transaction = pool.begin()
+        .await
+        .context("Failed to begin transaction")?;
+
+    let tickets = lock_tickets(&mut transaction, &ticket_ids, user_id)
+        .await
+        .context("Failed to lock tickets")?;
+
+    // Assignment rights depend on the department only.
+    let mut department_outcomes: HashMap<DepartmentId, Option<BulkTicketOutcome>> = HashMap::new();
+    let mut results = Vec::with_capacity(ticket_ids.len());
+
+    for &ticket_id in &ticket_ids {
+        let outcome = match tickets.get(&ticket_id) {
+            None => Some(BulkTicketOutcome::NotFound),
+            Some(ticket) if !ticket.is_involved && !in_scope(ticket.department_id) => {
+                Some(BulkTicketOutcome::Forbidden)
+            },
+            Some(ticket) => match department_outcomes.get(&ticket.department_id) {
+                Some(outcome) => *outcome,
+                None => {
+                    let outcome = check_assignment(&pool, &schema.operation, user_id, &permissions, ticket.department_id)
+                        .await
+                        .context("Failed to check assignment rights")?;
+
+                    department_outcomes.insert(ticket.department_id, outcome);
+                    outcome
+                },
+            },
+        };
+
+        results.push(BulkTicketResult {
+            ticket_id,
+            outcome: outcome.unwrap_or(BulkTicketOutcome::Updated),
+        });
+    }
+
+    let accepted = results.iter()
+        .filter(|result| result.outcome == BulkTicketOutcome::Updated)
+        .map(|result| result.ticket_id)
+        .collect::<Vec<_>>();
+
+    if schema.atomic && accepted.len() != results.len() {
+        for result in results.iter_mut().filter(|result| result.outcome == BulkTicketOutcome::Updated) {
+            result.outcome = BulkTicketOutcome::Skipped;
+        }
+
+        return Ok(HttpResponse::Ok().json(results));
+    }
+
+    let updated = apply_operation(
+        &mut transaction,
+        &notification_service,
+        &schema.operation,
+        &accepted,
+        user_id
+    )
+    .await
+    .context("Failed to apply bulk operation")?;
+
+    for result in results.iter_mut() {
+        if result.outcome == BulkTicketOutcome::Updated && !updated.contains(&result.ticket_id) {
+            result.outcome = BulkTicketOutcome::Unchanged;
+        }
+    }
+
+    if !updated.is_empty() {
+        AuditEntry::new(AuditAction::TicketsBulkUpdated, &device)
+            .actor(user_id)
+            .details(serde_json::json!({
+                "operation": schema.operation,
+                "ticket_ids": updated,
+            }))
+            .record(&mut *transaction)
+            .await
+            .context("Failed to record audit entry")?;
+    }
+
+    transaction.commit()
+        .await
+        .context("Failed to commit transaction")?;
+
+    Ok(HttpResponse::Ok().json(results))
+}
+
+/// Mirrors the single ticket endpoints: anyone in the department assigns themselves,
+/// assigning or unassigning others takes the right to do so.
+async fn check_assignment(
+    pool: &PgPool,
+    operation: &BulkOperation,
+    caller_id: UserId,
+    permissions: &Permissions,
+    department_id: DepartmentId,
+) -> Result<Option<BulkTicketOutcome>, anyhow::Error> {
+    let (user_id, is_assign) = match *operation {
+        BulkOperation::Assign { user_id } => (user_id, true),
+        BulkOperation::Unassign { user_id } => (user_id, false),
+        _ => return Ok(None),
+    };
+
+    if user_id != caller_id && !can_assign_others(pool, caller_id, permissions, department_id).await? {
+        return Ok(Some(BulkTicketOutcome::Forbidden));
+    }
+
+    if !is_assign || (user_id == caller_id && permissions.has(Permission::TicketsAllDepartments)) {
+        return Ok(None);
+    }
+
+    let role = get_department_role(pool, user_id, department_id)
+        .await
+        .context("Failed to get department role")?;
+
+    Ok(match role {
+        Some(_) => None,
+        None if user_id == caller_id => Some(BulkTicketOutcome::Forbidden),
+        None => Some(BulkTicketOutcome::NotDepartmentMember),
+    })
+}
+
+#[tracing::instrument(name = "Select ticket ids for bulk operation", skip_all)]
+async fn select_ticket_ids(
+    pool: &PgPool,
+    filters: &TicketFilters<'_>,
+) -> Result<Vec<TicketId>, sqlx::Error> {
+    let mut builder = QueryBuilder::<Postgres>::new("SELECT t.id FROM tickets t\n");
+
+    filters.push_search_source(&mut builder);
+    filters.push_conditions(&mut builder);
+
+    // One more than allowed tells that the filters match too many.
+    builder.push("\nORDER BY t.id LIMIT ").push_bind(MAX_BULK_TICKETS as i64 + 1);
+
+    builder.build_query_scalar()
+        .fetch_all(pool)
+        .await
+}
+
+#[tracing::instrument(name = "Lock tickets for bulk operation", skip(transaction))]
+async fn lock_tickets(
+    transaction: &mut Transaction<'_, Postgres>,
+    ticket_ids: &[TicketId],
+    user_id: UserId,
+) -> Result<HashMap<TicketId, LockedTicket>, sqlx::Error> {
+    let tickets = sqlx::query_as!(
+        LockedTicket,
+        r#"SELECT
+            id,
+            department_id,
+            author_id IS NOT DISTINCT FROM $2
+                OR EXISTS (
+                    SELECT 1 FROM tickets_users
+                    WHERE ticket_id = t.id AND assigned_to = $2
+                ) AS "is_involved!"
+        FROM tickets t
+        WHERE id = ANY($1)
+        ORDER BY id
+        FOR UPDATE"#,
+        ticket_ids,
+        user_id
+    )
+    .fetch_all(transaction.as_mut())
+    .await?;
+
+    Ok(tickets.into_iter().map(|ticket| (ticket.id, ticket)).collect())
+}
+
+async fn department_exists(pool: &PgPool, department_id: DepartmentId) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM departments WHERE id = $1) AS "exists!""#,
+        department_id
+    )
+    .fetch_one(pool)
+    .await
+}
+
+async fn building_exists(pool: &PgPool, building_id: i16) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM buildings WHERE id = $1) AS "exists!""#,
+        building_id
+    )
+    .fetch_one(pool)
+    .await
+}
+
+/// Returns the tickets that actually changed.
+#[tracing::instrument(
+    name = "Apply bulk operation to tickets",
+    skip(transaction, notification_service)
+)]
+async fn apply_operation(
+    transaction: &mut Transaction<'_, Postgres>,
+    notification_service: &NotificationService,
+    operation: &BulkOperation,
+    ticket_ids: &[TicketId],
+    user_id: UserId,
+) -> Result<Vec<TicketId>, sqlx::Error> {
+    if ticket_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    match *operation {
+        BulkOperation::SetStatus { status } => {
+            let tickets = sqlx::query!(
+                "UPDATE tickets
+                SET status = $2,
+                    closed_at = CASE WHEN $2 = $3 THEN COALESCE(closed_at, NOW()) ELSE closed_at END
+                WHERE id = ANY($1) AND status <> $2
+                RETURNING id, author_id",
+                ticket_ids,
+                status as i16,
+                TicketStatus::Closed as i16
+            )
+            .fetch_all(&mut **transaction)
+            .await?;
+
+            for ticket in &tickets {
+                let Some(author_id) = ticket.author_id.filter(|&id| id != user_id) else {
+                    continue;
+                };
+
+                notification_service.notify(
+                    &mut **transaction,
+                    ticket.id,
+                    &[author_id],
+                    Notification::StatusChanged { new_status: status }
+                )
+                .await?;
+            }
+
+            Ok(tickets.into_iter().map(|ticket| ticket.id).collect())
+        },
+        BulkOperation::SetPriority { priority } => {
+            sqlx::query_scalar!(
+                "UPDATE tickets SET priority = $2 WHERE id = ANY($1) AND priority <> $2 RETURNING id",
+                ticket_ids,
+                priority as i16
+            )
+            .fetch_all(&mut **transaction)
+            .await
+        },
+        BulkOperation::SetDepartment { department_id } => {
+            sqlx::query_scalar!(
+                "UPDATE tickets SET department_id = $2 WHERE id = ANY($1) AND department_id <> $2 RETURNING id",
+                ticket_ids,
+                department_id
+            )
+            .fetch_all(&mut **transaction)
+            .await
+        },
+        BulkOperation::SetBuilding { building_id } => {
+            sqlx::query_scalar!(
+                "UPDATE tickets SET building_id = $2 WHERE id = ANY($1) AND building_id <> $2 RETURNING id",
+                ticket_ids,
+                building_id
+            )
+            .fetch_all(&mut **transaction)
+            .await
+        },
+        BulkOperation::Assign { user_id: assignee_id } => {
+            let assigned = sqlx::query_scalar!(
+                "INSERT INTO tickets_users (assigned_to, ticket_id)
+                SELECT $1, UNNEST($2::bigint[])
+                ON CONFLICT DO NOTHING
+                RETURNING ticket_id",
+                assignee_id,
+                ticket_ids
+            )
+            .fetch_all(&mut **transaction)
+            .await?;
+
+            sqlx::query!(
+                "UPDATE tickets
+                SET status = $2,
+                    first_response_at = COALESCE(first_response_at, NOW())
+                WHERE id = ANY($1) AND status = $3",
+                &assigned,
+                TicketStatus::InProgress as i16,
+                TicketStatus::Open as i16
+            )
+            .execute(&mut **transaction)
+            .await?;
+
+            Ok(assigned)
+        },
+        BulkOperation::Unassign { user_id: assignee_id } => {
+            let unassigned = sqlx::query_scalar!(
+                "DELETE FROM tickets_users
+                WHERE assigned_to = $1 AND ticket_id = ANY($2)
+                RETURNING ticket_id",
+                assignee_id,
+                ticket_ids
+            )
+            .fetch_all(&mut **transaction)
+            .await?;
+
+            sqlx::query!(
+                "UPDATE tickets
+                SET status = $2
+                WHERE id = ANY($1) AND status = $3 AND NOT EXISTS (
+                    SELECT 1
+                    FROM tickets_users tu
+                    WHERE tu.ticket_id = tickets.id
+                )",
+                &unassigned,
+                TicketStatus::Open as i16,
+                TicketStatus::InProgress as i16
+            )
+            .execute(&mut **transaction)
+            .await?;
+
+            Ok(unassigned)
+        },
+        BulkOperation::AddInternalMessage { ref message } => {
+            let ticket_ids = sqlx::query_scalar!(
+                "INSERT INTO ticket_messages (ticket_id, user_id, message_text, is_internal)
+                SELECT UNNEST($1::bigint[]), $2, $3, TRUE
+                RETURNING ticket_id",
+                ticket_ids,
+                user_id,
+                message
+            )
+            .fetch_all(&mut **transaction)
+            .await?;
+
+            // Internal messages are for the staff working on the ticket, not its author.
+            let assignees = sqlx::query!(
+                "SELECT ticket_id, assigned_to
+                FROM tickets_users
+                WHERE ticket_id = ANY($1) AND assigned_to <> $2",
+                &ticket_ids,
+                user_id
+            )
+            .fetch_all(&mut **transaction)
+            .await?;
+
+            let mut recipients: HashMap<TicketId, Vec<UserId>> = HashMap::new();
+
+            for assignee in assignees {
+                recipients.entry(assignee.ticket_id).or_default().push(assignee.assigned_to);
+            }
+
+            for (ticket_id, user_ids) in recipients {
+                notification_service.notify(
+                    &mut **transaction,
+                    ticket_id,
+                    &user_ids,
+                    Notification::NewMessages { count: 1 }
+                )
+                .await?;
+            }
+
+            Ok(ticket_ids)
+        },
+    }
+}
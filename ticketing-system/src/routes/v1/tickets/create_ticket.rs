@@ -1,14 +1,14 @@
 use std::{ops::Deref, sync::Arc};
 
 use actix_multipart::form::{bytes::Bytes, json::Json, MultipartForm};
-use actix_web::{http::StatusCode, web, HttpResponse, ResponseError};
+use actix_web::{http::{header::ContentType, StatusCode}, web, HttpResponse, ResponseError};
 use anyhow::Context;
 use chrono::{DateTime, Utc};
 use futures_util::{stream, StreamExt as _};
 use serde::{Deserialize, Serialize};
 use sqlx::{PgPool, Postgres, Transaction};
 
-use crate::{auth::extractor::UserIdExtractor, domain::description::Description, events::{event_publisher::EventPublisher, Event}, schema::{common::UserId, tickets::TicketId}, services::attachment::{Attachment, AttachmentService, AttachmentServiceError, AttachmentType}, startup::ApplicationBaseUrl, utils::{cleanup_images, error_chain_fmt}};
+use crate::{auth::extractor::{PermissionsExtractor, UserIdExtractor, UserRoleExtractor}, domain::description::Description, events::{event_publisher::EventPublisher, Event}, routes::v1::tickets::{get_tickets::resolve_visibility, similar::{find_similar_tickets, GetSimilarTicketsSchema, SimilarTicket, SimilarTicketsViewer}}, schema::{common::UserId, tickets::{TicketId, TicketPriority}}, services::{assignment::{apply_rules, TicketFacts}, attachment::{Attachment, AttachmentService, AttachmentServiceError, AttachmentType}}, startup::ApplicationBaseUrl, utils::{cleanup_images, error_chain_fmt}};
 
 #[derive(Deserialize, Debug)]
 pub struct CreateTicketSchema {
@@ -20,6 +20,10 @@ pub struct CreateTicketSchema {
     pub cabinet: Option<String>,
     pub building_id: i16,
     pub department_id: i16,
+    /// Refuse to create the ticket when similar open ones exist,
+    /// so the client can confirm it is not a duplicate.
+    #[serde(default)]
+    pub check_similar: bool,
 }
 
 #[derive(MultipartForm)]
@@ -39,6 +43,8 @@ pub enum CreateTicketError {
     AttachmentServiceError(#[from] AttachmentServiceError),
     #[error("A lot of attachments")]
     ALotOfAttachments,
+    #[error("Similar tickets already exist")]
+    SimilarTicketsFound(Vec<SimilarTicket>),
     #[error(transparent)]
     Unexpected(#[from] anyhow::Error)
 }
@@ -54,11 +60,22 @@ impl ResponseError for CreateTicketError {
         match self {
             CreateTicketError::AttachmentServiceError(e) => e.status_code(),
             CreateTicketError::ALotOfAttachments => StatusCode::BAD_REQUEST,
+            CreateTicketError::SimilarTicketsFound(_) => StatusCode::CONFLICT,
             CreateTicketError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
+
+    fn error_response(&self) -> HttpResponse {
+        match self {
+            CreateTicketError::SimilarTicketsFound(tickets) => HttpResponse::Conflict().json(tickets),
+            _ => HttpResponse::build(self.status_code())
+                .insert_header(ContentType::plaintext())
+                .body(self.to_string()),
+        }
+    }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn create_ticket(
     MultipartForm(ticket): MultipartForm<CreateTicketForm>,
     pool: web::Data<PgPool>,
@@ -66,6 +83,8 @@ pub async fn create_ticket(
     base_url: web::Data<ApplicationBaseUrl>,
     event_publisher: web::Data<EventPublisher>,
     user_id: UserIdExtractor,
+    user_role: UserRoleExtractor,
+    permissions: PermissionsExtractor,
 ) -> Result<HttpResponse, CreateTicketError> {
     if ticket.attachments.len() > 5 {
         return Err(CreateTicketError::ALotOfAttachments)
@@ -73,6 +92,33 @@ pub async fn create_ticket(
 
     let fields = ticket.fields;
 
+    if fields.check_similar {
+        let query = GetSimilarTicketsSchema {
+            title: fields.title.clone(),
+            description: Some(fields.description.as_ref().to_string()),
+            building_id: fields.building_id,
+            cabinet: fields.cabinet.clone(),
+            days: None,
+        };
+
+        let (client_id, department_scope) = resolve_visibility(&pool, user_id.0, user_role.0, &permissions.0)
+            .await
+            .context("Failed to resolve department scope")?;
+
+        let viewer = SimilarTicketsViewer {
+            user_id: user_id.0,
+            client_id,
+            department_scope: &department_scope,
+        };
+
+        let similar = find_similar_tickets(&pool, &query, &viewer).await
+            .context("Failed to find similar tickets")?;
+
+        if !similar.is_empty() {
+            return Err(CreateTicketError::SimilarTicketsFound(similar));
+        }
+    }
+
     let mut transaction = pool.begin().await
         .context("Failed to begin transaction")?;
 
@@ -101,6 +147,18 @@ pub async fn create_ticket(
             }
     }
 
+    let facts = TicketFacts {
+        department_id: fields.department_id,
+        building_id: fields.building_id,
+        priority: TicketPriority::Low as i16,
+        title: &fields.title,
+        description: fields.description.as_ref(),
+    };
+
+    // Last before the commit, so the rule stays locked only briefly.
+    apply_rules(&mut transaction, ticket_id, &facts).await
+        .context("Failed to apply assignment rules")?;
+
     transaction.commit().await
         .context("Failed to commit transaction")?;
 
@@ -0,0 +1,375 @@
+use actix_web::{http::{header::{ContentDisposition, DispositionParam, DispositionType}, StatusCode}, web, HttpResponse, ResponseError};
+use anyhow::Context;
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use futures_util::{stream, StreamExt};
+use rust_xlsxwriter::{Color, Format, FormatAlign, FormatBorder, Workbook};
+use serde::Deserialize;
+use serde_qs::actix::QsQuery;
+use sqlx::{FromRow, PgPool, Postgres, QueryBuilder};
+
+use crate::{auth::{department_scope::DepartmentScope, extractor::{PermissionsExtractor, UserIdExtractor, UserRoleExtractor}, permission::Permission}, routes::v1::tickets::get_tickets::{push_keyset_condition, push_order_by, resolve_visibility, GetTicketsSchema, TicketCursor, TicketFilters, TicketSortKey}, schema::{common::{SortOrder, UserId}, tickets::{OrderBy, TicketId, TicketPriority, TicketSource, TicketStatus}}, utils::error_chain_fmt};
+
+const BATCH_SIZE: usize = 500;
+
+const HEADERS: [&str; 15] = [
+    "ID",
+    "Заголовок",
+    "Описание",
+    "Автор",
+    "Контакты",
+    "Статус",
+    "Приоритет",
+    "Отдел",
+    "Здание",
+    "Кабинет",
+    "Источник",
+    "Исполнители",
+    "Дата создания",
+    "Плановая дата",
+    "Дата закрытия",
+];
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Xlsx,
+    Csv,
+}
+
+/// The format goes along with the `get_tickets` filters in the query.
+#[derive(Deserialize, Debug)]
+pub struct ExportFormatSchema {
+    pub format: ExportFormat,
+}
+
+#[derive(FromRow)]
+struct ExportedTicket {
+    pub id: TicketId,
+    pub title: String,
+    pub description: String,
+    pub author: String,
+    pub author_contacts: String,
+    pub status: TicketStatus,
+    pub priority: TicketPriority,
+    pub department_name: String,
+    pub building_name: String,
+    pub cabinet: Option<String>,
+    pub source: TicketSource,
+    pub assignees: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub planned_at: Option<DateTime<Utc>>,
+    pub closed_at: Option<DateTime<Utc>>,
+}
+
+impl ExportedTicket {
+    fn values(&self) -> [String; 15] {
+        let format_date = |date: Option<DateTime<Utc>>| date
+            .map(|date| date.format("%d.%m.%Y %H:%M").to_string())
+            .unwrap_or_default();
+
+        [
+            self.id.to_string(),
+            self.title.clone(),
+            self.description.clone(),
+            self.author.clone(),
+            self.author_contacts.clone(),
+            self.status.as_str().to_string(),
+            self.priority.as_str().to_string(),
+            self.department_name.clone(),
+            self.building_name.clone(),
+            self.cabinet.clone().unwrap_or_default(),
+            self.source.as_str().to_string(),
+            self.assignees.clone().unwrap_or_default(),
+            format_date(Some(self.created_at)),
+            format_date(self.planned_at),
+            format_date(self.closed_at),
+        ]
+    }
+}
+
+/// What it takes to fetch the next batch, owned so the CSV stream can outlive the handler.
+struct ExportState {
+    pool: web::Data<PgPool>,
+    schema: GetTicketsSchema,
+    client_id: Option<UserId>,
+    department_scope: DepartmentScope,
+    read_internal: bool,
+    user_id: UserId,
+    cursor: Option<TicketCursor>,
+    is_done: bool,
+}
+
+impl ExportState {
+    async fn next_batch(&mut self) -> Result<Vec<ExportedTicket>, sqlx::Error> {
+        if self.is_done {
+            return Ok(Vec::new());
+        }
+
+        let filters = TicketFilters::new(
+            &self.schema,
+            None,
+            &self.client_id,
+            &self.department_scope,
+            self.read_internal,
+            self.user_id
+        );
+
+        let order_by = self.schema.order_by.unwrap_or_default();
+        let sort_order = self.schema.sort_order.unwrap_or_default();
+
+        let tickets = select_batch(&self.pool, &filters, order_by, sort_order, self.cursor.as_ref()).await?;
+
+        self.is_done = tickets.len() < BATCH_SIZE;
+        self.cursor = tickets.last().map(|ticket| TicketCursor::new(
+            ticket.id,
+            TicketSortKey::new(order_by, ticket.planned_at, ticket.priority)
+        ));
+
+        Ok(tickets)
+    }
+}
+
+#[derive(thiserror::Error)]
+pub enum ExportTicketsError {
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for ExportTicketsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for ExportTicketsError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ExportTicketsError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+/// Exports every ticket matching the filters, fetched in batches.
+/// Pagination parameters are ignored, search results keep the requested order.
+pub async fn export_tickets(
+    format: web::Query<ExportFormatSchema>,
+    schema: QsQuery<GetTicketsSchema>,
+    pool: web::Data<PgPool>,
+    user_id: UserIdExtractor,
+    user_role: UserRoleExtractor,
+    permissions: PermissionsExtractor,
+) -> Result<HttpResponse, ExportTicketsError> {
+    let (client_id, department_scope) = resolve_visibility(&pool, user_id.0, user_role.0, &permissions.0)
+        .await
+        .context("Failed to resolve department scope")?;
+
+    let mut state = ExportState {
+        pool,
+        schema: schema.into_inner(),
+        client_id,
+        department_scope,
+        read_internal: permissions.0.has(Permission::MessagesReadInternal),
+        user_id: user_id.0,
+        cursor: None,
+        is_done: false,
+    };
+
+    let date = Utc::now().format("%Y-%m-%d");
+
+    match format.format {
+        ExportFormat::Csv => {
+            let header = Bytes::from(format!("\u{feff}{}", csv_line(HEADERS)));
+
+            let rows = stream::try_unfold(state, |mut state| async move {
+                if state.is_done {
+                    return Ok::<_, sqlx::Error>(None);
+                }
+
+                let tickets = state.next_batch().await?;
+
+                let chunk = tickets.iter()
+                    .map(|ticket| csv_line(ticket.values()))
+                    .collect::<String>();
+
+                Ok(Some((Bytes::from(chunk), state)))
+            });
+
+            let body = stream::once(async { Ok(header) }).chain(rows);
+
+            Ok(HttpResponse::Ok()
+                .content_type("text/csv; charset=utf-8")
+                .insert_header(attachment(format!("tickets_{}.csv", date)))
+                .streaming(body))
+        },
+        ExportFormat::Xlsx => {
+            let xlsx = build_workbook_bytes(&mut state)
+                .await
+                .context("Failed to build XLSX export")?;
+
+            Ok(HttpResponse::Ok()
+                .content_type("application/vnd.openxmlformats-officedocument.spreadsheetml.sheet")
+                .insert_header(attachment(format!("tickets_{}.xlsx", date)))
+                .body(xlsx))
+        },
+    }
+}
+
+fn attachment(file_name: String) -> ContentDisposition {
+    ContentDisposition {
+        disposition: DispositionType::Attachment,
+        parameters: vec![DispositionParam::Filename(file_name)],
+    }
+}
+
+#[tracing::instrument(name = "Get batch of tickets for export from database", skip_all)]
+async fn select_batch(
+    pool: &PgPool,
+    filters: &TicketFilters<'_>,
+    order_by: OrderBy,
+    sort_order: SortOrder,
+    cursor: Option<&TicketCursor>,
+) -> Result<Vec<ExportedTicket>, sqlx::Error> {
+    let mut builder = QueryBuilder::<Postgres>::new(
+        r#"SELECT
+            t.id,
+            t.title,
+            t.description,
+            t.author,
+            t.author_contacts,
+            t.status,
+            t.priority,
+            d.name AS department_name,
+            b.name AS building_name,
+            t.cabinet,
+            t.source,
+            (
+                SELECT STRING_AGG(u.name, ', ' ORDER BY u.name)
+                FROM tickets_users tu
+                JOIN users u ON u.id = tu.assigned_to
+                WHERE tu.ticket_id = t.id
+            ) AS assignees,
+            t.created_at,
+            t.planned_at,
+            t.closed_at
+        FROM tickets t
+        JOIN buildings b ON b.id = t.building_id
+        JOIN departments d ON d.id = t.department_id
+        "#
+    );
+
+    filters.push_search_source(&mut builder);
+
+    let has_filters = filters.push_conditions(&mut builder);
+
+    if let Some(cursor) = cursor {
+        builder.push(if has_filters { " AND " } else { "WHERE " });
+        push_keyset_condition(&mut builder, cursor, sort_order);
+    }
+
+    builder.push("\n");
+
+    push_order_by(&mut builder, order_by, sort_order);
+
+    builder.push(" LIMIT ").push_bind(BATCH_SIZE as i64);
+
+    builder.build_query_as::<ExportedTicket>()
+        .fetch_all(pool)
+        .await
+}
+
+async fn build_workbook_bytes(state: &mut ExportState) -> Result<Vec<u8>, anyhow::Error> {
+    let mut workbook = Workbook::new();
+
+    // Rows are flushed to a temporary file as they are written.
+    let worksheet = workbook.add_worksheet_with_constant_memory();
+    worksheet.set_name("Заявки")?;
+
+    let widths = [8.0, 30.0, 50.0, 24.0, 16.0, 12.0, 12.0, 20.0, 24.0, 10.0, 14.0, 30.0, 17.0, 17.0, 17.0];
+
+    for (col, width) in widths.iter().enumerate() {
+        worksheet.set_column_width(col as u16, *width)?;
+    }
+
+    let base_format = Format::new()
+        .set_border(FormatBorder::Thin)
+        .set_text_wrap()
+        .set_align(FormatAlign::Top);
+
+    let header_format = Format::new()
+        .set_bold()
+        .set_font_name("Times New Roman")
+        .set_background_color(Color::RGB(0xE3F2FD))
+        .set_border(FormatBorder::Thin)
+        .set_text_wrap()
+        .set_align(FormatAlign::Center)
+        .set_align(FormatAlign::VerticalCenter);
+
+    for (col, header) in HEADERS.iter().enumerate() {
+        worksheet.write_string_with_format(0, col as u16, *header, &header_format)?;
+    }
+
+    let mut row = 0;
+
+    while !state.is_done {
+        let tickets = state.next_batch()
+            .await
+            .context("Failed to load tickets for export")?;
+
+        for ticket in tickets {
+            row += 1;
+
+            worksheet.write_number_with_format(row, 0, ticket.id as f64, &base_format)?;
+
+            for (col, value) in ticket.values().iter().enumerate().skip(1) {
+                worksheet.write_string_with_format(row, col as u16, value, &base_format)?;
+            }
+        }
+    }
+
+    Ok(workbook.save_to_buffer()?)
+}
+
+/// A CSV record with the fields quoted when needed. Fields that spreadsheets
+/// would evaluate as formulas are prefixed with a quote.
+fn csv_line<S: AsRef<str>>(values: impl IntoIterator<Item = S>) -> String {
+    let mut line = String::new();
+
+    for (idx, value) in values.into_iter().enumerate() {
+        let value = value.as_ref();
+
+        if idx > 0 {
+            line.push(',');
+        }
+
+        let value = if value.starts_with(['=', '+', '-', '@']) {
+            format!("'{}", value)
+        } else {
+            value.to_string()
+        };
+
+        if value.contains([',', '"', '\n', '\r']) {
+            line.push('"');
+            line.push_str(&value.replace('"', "\"\""));
+            line.push('"');
+        } else {
+            line.push_str(&value);
+        }
+    }
+
+    line.push_str("\r\n");
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_line_quotes_and_neutralizes_fields() {
+        assert_eq!(csv_line(["1", "plain"]), "1,plain\r\n");
+        assert_eq!(csv_line(["a,b", "say \"hi\""]), "\"a,b\",\"say \"\"hi\"\"\"\r\n");
+        assert_eq!(csv_line(["line\nbreak"]), "\"line\nbreak\"\r\n");
+        assert_eq!(csv_line(["=1+2", "-"]), "'=1+2,'-\r\n");
+    }
+}
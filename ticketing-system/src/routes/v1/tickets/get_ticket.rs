@@ -4,7 +4,7 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::{PgPool, types::Json};
 
-use crate::{auth::{extractor::{UserIdExtractor, UserRoleExtractor}, types::UserRole}, schema::{common::UserId, tickets::{Building, Department, TicketId, TicketPriority, TicketSource, TicketStatus}}, utils::error_chain_fmt};
+use crate::{auth::{department_scope::DepartmentScope, extractor::{PermissionsExtractor, UserIdExtractor, UserRoleExtractor}, permission::Permission, types::UserRole}, schema::{common::UserId, page::Page, tickets::{Building, Department, TicketId, TicketPriority, TicketSource, TicketStatus}}, utils::error_chain_fmt};
 
 #[derive(thiserror::Error)]
 pub enum GetTicketError {
@@ -69,6 +69,7 @@ struct TicketSchemaWithAttachments {
     pub cabinet: Option<String>,
     pub department: Department,
     pub source: TicketSource,
+    pub solution_pages: Vec<Page>,
 }
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -96,6 +97,7 @@ impl From<TicketQueryResult> for TicketSchemaWithAttachments {
             cabinet: ticket.cabinet,
             department: ticket.department.0,
             source: ticket.source,
+            solution_pages: Vec::new(),
         }
     }
 }
@@ -104,6 +106,7 @@ pub async fn get_ticket(
     id: web::Path<TicketId>,
     user_id: UserIdExtractor,
     user_role: UserRoleExtractor,
+    permissions: PermissionsExtractor,
     pool: web::Data<PgPool>,
 ) -> Result<HttpResponse, GetTicketError> {
     let id = id.into_inner();
@@ -118,7 +121,51 @@ pub async fn get_ticket(
         return Err(GetTicketError::InsufficientPermissions);
     }
 
-    Ok(HttpResponse::Ok().json(TicketSchemaWithAttachments::from(ticket)))
+    if user_role.0 != UserRole::Client {
+        let scope = DepartmentScope::resolve(&pool, user_id.0, &permissions.0)
+            .await
+            .context("Failed to resolve department scope")?;
+
+        let is_involved = ticket.author_id == Some(user_id.0)
+            || ticket.assigned_to.0.iter().any(|user| user.id == user_id.0);
+
+        if !is_involved && !scope.contains(ticket.department.0.id) {
+            return Err(GetTicketError::InsufficientPermissions);
+        }
+    }
+
+    let mut ticket = TicketSchemaWithAttachments::from(ticket);
+
+    ticket.solution_pages = select_solution_pages(&pool, id, !permissions.0.has(Permission::PagesReadPrivate))
+        .await
+        .context("Failed to get solution pages")?;
+
+    Ok(HttpResponse::Ok().json(ticket))
+}
+
+/// Pages attached to the ticket as solutions. Without `pages.read_private`
+/// only the public ones are returned.
+#[tracing::instrument(
+    name = "Get ticket solution pages from database",
+    skip(pool)
+)]
+async fn select_solution_pages(
+    pool: &PgPool,
+    ticket_id: TicketId,
+    public_only: bool,
+) -> Result<Vec<Page>, sqlx::Error> {
+    sqlx::query_as!(
+        Page,
+        "SELECT p.id, p.title
+        FROM ticket_pages tp
+        JOIN pages p ON p.id = tp.page_id
+        WHERE tp.ticket_id = $1 AND (p.is_public OR NOT $2)
+        ORDER BY tp.created_at, p.id",
+        ticket_id,
+        public_only
+    )
+    .fetch_all(pool)
+    .await
 }
 
 #[tracing::instrument(
@@ -3,9 +3,9 @@ use anyhow::Context;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_qs::actix::QsQuery;
-use sqlx::{FromRow, PgPool, QueryBuilder, Row};
+use sqlx::{FromRow, PgPool, Postgres, QueryBuilder, Row};
 
-use crate::{auth::{extractor::{UserIdExtractor, UserRoleExtractor}, types::UserRole}, build_where_condition, schema::{common::{PaginationResult, SortOrder, UserId}, tickets::{Building, OrderBy, TicketId, TicketPriority, TicketStatus}}, utils::error_chain_fmt};
+use crate::{auth::{department_scope::DepartmentScope, extractor::{PermissionsExtractor, UserIdExtractor, UserRoleExtractor}, permission::{Permission, Permissions}, types::UserRole}, build_where_condition, schema::{common::{Cursor, CursorPaginationResult, PaginationResult, SortOrder, UserId}, tickets::{Building, OrderBy, TicketId, TicketPriority, TicketStatus}}, utils::error_chain_fmt};
 
 #[derive(Deserialize)]
 pub struct GetTicketsSchema {
@@ -21,6 +21,11 @@ pub struct GetTicketsSchema {
     pub assigned_to: Option<UserId>,
     pub search: Option<String>,
     pub departments: Option<Vec<i16>>,
+    /// Switches to cursor pagination, empty for the first page.
+    pub cursor: Option<String>,
+    /// Counts the total in cursor pagination.
+    #[serde(default)]
+    pub with_total: bool,
 }
 
 pub struct TicketWithMeta {
@@ -33,9 +38,11 @@ pub struct TicketWithMeta {
     pub priority: TicketPriority,
     pub planned_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
-    pub total_items: i64,
+    /// Only counted in page number pagination.
+    pub total_items: Option<i64>,
     pub building: Building,
     pub cabinet: Option<String>,
+    pub search_snippet: Option<String>,
 }
 
 impl FromRow<'_, sqlx::postgres::PgRow> for TicketWithMeta {
@@ -56,13 +63,14 @@ impl FromRow<'_, sqlx::postgres::PgRow> for TicketWithMeta {
                 code: row.try_get("building_code")?,
                 name: row.try_get("building_name")?
             },
-            cabinet: row.try_get("cabinet")?
+            cabinet: row.try_get("cabinet")?,
+            search_snippet: row.try_get("search_snippet")?,
         })
     }
 }
 
 #[derive(Serialize)]
-struct TicketSchema {
+pub struct TicketSchema {
     pub id: TicketId,
     pub title: String,
     pub description: String,
@@ -74,12 +82,56 @@ struct TicketSchema {
     pub created_at: DateTime<Utc>,
     pub building: Building,
     pub cabinet: Option<String>,
+    /// Description fragments with the matched words wrapped in `<b>`, only when searching.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub search_snippet: Option<String>,
+}
+
+/// Value of the sort column in a cursor. Cursors of another order are rejected.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum TicketSortKey {
+    Id,
+    PlannedAt(Option<DateTime<Utc>>),
+    Priority(i16),
+}
+
+impl TicketSortKey {
+    pub fn new(order_by: OrderBy, planned_at: Option<DateTime<Utc>>, priority: TicketPriority) -> Self {
+        match order_by {
+            OrderBy::Id => TicketSortKey::Id,
+            OrderBy::PlannedAt => TicketSortKey::PlannedAt(planned_at),
+            OrderBy::Priority => TicketSortKey::Priority(priority as i16),
+        }
+    }
+
+    fn is_for(&self, order_by: OrderBy) -> bool {
+        matches!(
+            (self, order_by),
+            (TicketSortKey::Id, OrderBy::Id)
+                | (TicketSortKey::PlannedAt(_), OrderBy::PlannedAt)
+                | (TicketSortKey::Priority(_), OrderBy::Priority)
+        )
+    }
+}
+
+pub type TicketCursor = Cursor<TicketId, TicketSortKey>;
+
+enum Pagination {
+    /// Zero based page number.
+    Page(TicketId),
+    /// `None` for the first page.
+    Cursor(Option<TicketCursor>),
 }
 
 #[derive(thiserror::Error)]
 pub enum GetTicketsError {
     #[error("Page number must be greater than 0")]
     InvalidPage,
+    #[error("Invalid cursor")]
+    InvalidCursor,
+    #[error("Search results ordered by relevance cannot be paginated with a cursor")]
+    CursorWithRelevance,
     #[error(transparent)]
     Unexpected(#[from] anyhow::Error)
 }
@@ -94,44 +146,99 @@ impl ResponseError for GetTicketsError {
     fn status_code(&self) -> StatusCode {
         match self {
             GetTicketsError::InvalidPage => StatusCode::BAD_REQUEST,
+            GetTicketsError::InvalidCursor => StatusCode::BAD_REQUEST,
+            GetTicketsError::CursorWithRelevance => StatusCode::BAD_REQUEST,
             GetTicketsError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
 }
 
+/// One of the two pagination formats, chosen by the `cursor` parameter.
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum TicketListing {
+    Pages(PaginationResult<TicketSchema>),
+    Cursor(CursorPaginationResult<TicketSchema>),
+}
+
 pub async fn get_tickets(
     schema: QsQuery<GetTicketsSchema>,
     pool: web::Data<PgPool>,
     user_id: UserIdExtractor,
     user_role: UserRoleExtractor,
+    permissions: PermissionsExtractor,
 ) -> Result<HttpResponse, GetTicketsError> {
-    let schema = schema.into_inner();
-    
+    let listing = list_tickets(
+        &pool,
+        &schema.into_inner(),
+        None,
+        user_id.0,
+        user_role.0,
+        &permissions.0
+    )
+    .await?;
+
+    Ok(HttpResponse::Ok().json(listing))
+}
+
+/// Lists the tickets visible to the user, optionally only the ones created after a moment.
+pub async fn list_tickets(
+    pool: &PgPool,
+    schema: &GetTicketsSchema,
+    created_after: Option<DateTime<Utc>>,
+    user_id: UserId,
+    user_role: UserRole,
+    permissions: &Permissions,
+) -> Result<TicketListing, GetTicketsError> {
     let page_size = schema.page_size
         .map(|size| size.clamp(10, 50))
         .unwrap_or(10);
 
-    let page = schema.page.unwrap_or(1) - 1;
+    let order_by = schema.order_by.unwrap_or_default();
 
-    if page < 0 {
-        return Err(GetTicketsError::InvalidPage)
-    }
+    let pagination = match schema.cursor.as_deref() {
+        Some("") => Pagination::Cursor(None),
+        Some(cursor) => {
+            let cursor = TicketCursor::decode(cursor)
+                .filter(|cursor| cursor.key.is_for(order_by))
+                .ok_or(GetTicketsError::InvalidCursor)?;
 
-    let client_id = if user_role.0 == UserRole::Client {
-        Some(user_id.0)
-    } else {
-        None
+            Pagination::Cursor(Some(cursor))
+        },
+        None => {
+            let page = schema.page.unwrap_or(1) - 1;
+
+            if page < 0 {
+                return Err(GetTicketsError::InvalidPage)
+            }
+
+            Pagination::Page(page)
+        },
     };
 
-    let mut builder = get_builder(&schema, page, page_size, &client_id);
+    if matches!(pagination, Pagination::Cursor(_))
+        && schema.order_by.is_none()
+        && search_query(schema).is_some()
+    {
+        return Err(GetTicketsError::CursorWithRelevance);
+    }
+
+    let (client_id, department_scope) = resolve_visibility(pool, user_id, user_role, permissions)
+        .await
+        .context("Failed to resolve department scope")?;
+
+    let read_internal = permissions.has(Permission::MessagesReadInternal);
+    let filters = TicketFilters::new(schema, created_after, &client_id, &department_scope, read_internal, user_id);
+
+    let mut builder = get_builder(&filters, &pagination, page_size);
 
     let query = builder.build_query_as::<TicketWithMeta>();
 
-    let tickets = query.fetch_all(pool.as_ref()).await
+    let tickets = query.fetch_all(pool).await
         .context("Failed to fetch tickets from database.")?;
 
     let total_items = match tickets.first() {
-        Some(ticket) => ticket.total_items as u64,
+        Some(ticket) => ticket.total_items.unwrap_or_default() as u64,
         None => 0,
     };
 
@@ -146,26 +253,206 @@ pub async fn get_tickets(
         planned_at: ticket.planned_at,
         created_at: ticket.created_at,
         building: ticket.building,
-        cabinet: ticket.cabinet
+        cabinet: ticket.cabinet,
+        search_snippet: ticket.search_snippet,
     }).collect::<Vec<_>>();
 
-    let res = PaginationResult::new_with_pagination(
-        total_items,
-        page_size,
-        tickets
-    );
+    if let Pagination::Page(_) = pagination {
+        return Ok(TicketListing::Pages(PaginationResult::new_with_pagination(
+            total_items,
+            page_size,
+            tickets
+        )));
+    }
+
+    let total_items = if schema.with_total {
+        let count = count_tickets(pool, &filters)
+            .await
+            .context("Failed to count tickets.")?;
 
-    Ok(HttpResponse::Ok().json(res))
+        Some(count)
+    } else {
+        None
+    };
+
+    Ok(TicketListing::Cursor(CursorPaginationResult::new(tickets, page_size, total_items, |ticket| {
+        TicketCursor::new(ticket.id, TicketSortKey::new(order_by, ticket.planned_at, ticket.priority)).encode()
+    })))
+}
+
+/// Counts the tickets visible to the user created after a moment, for every
+/// pair of filters and moment in one query. Counts come in the order of the pairs.
+pub async fn count_new_tickets(
+    pool: &PgPool,
+    queries: &[(GetTicketsSchema, DateTime<Utc>)],
+    user_id: UserId,
+    user_role: UserRole,
+    permissions: &Permissions,
+) -> Result<Vec<u64>, anyhow::Error> {
+    if queries.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let (client_id, department_scope) = resolve_visibility(pool, user_id, user_role, permissions)
+        .await
+        .context("Failed to resolve department scope")?;
+
+    let read_internal = permissions.has(Permission::MessagesReadInternal);
+    let filters: Vec<_> = queries.iter()
+        .map(|(schema, created_after)| {
+            TicketFilters::new(schema, Some(*created_after), &client_id, &department_scope, read_internal, user_id)
+        })
+        .collect();
+
+    let mut builder = sqlx::QueryBuilder::<Postgres>::new("");
+
+    for (idx, filters) in filters.iter().enumerate() {
+        if idx > 0 {
+            builder.push("\nUNION ALL\n");
+        }
+
+        builder.push("(SELECT ").push_bind(idx as i32).push(" AS idx, COUNT(*) FROM tickets t\n");
+        filters.push_search_source(&mut builder);
+        filters.push_conditions(&mut builder);
+        builder.push(")");
+    }
+
+    let rows: Vec<(i32, i64)> = builder.build_query_as()
+        .fetch_all(pool)
+        .await
+        .context("Failed to count tickets.")?;
+
+    let mut counts = vec![0; queries.len()];
+
+    for (idx, count) in rows {
+        counts[idx as usize] = count as u64;
+    }
+
+    Ok(counts)
+}
+
+/// Clients only see their own tickets, staff the ones of their department scope.
+pub async fn resolve_visibility(
+    pool: &PgPool,
+    user_id: UserId,
+    user_role: UserRole,
+    permissions: &Permissions,
+) -> Result<(Option<UserId>, DepartmentScope), sqlx::Error> {
+    if user_role == UserRole::Client {
+        return Ok((Some(user_id), DepartmentScope::All));
+    }
+
+    let scope = DepartmentScope::resolve(pool, user_id, permissions).await?;
+
+    Ok((None, scope))
+}
+
+fn search_query(schema: &GetTicketsSchema) -> Option<&str> {
+    schema.search.as_deref()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+}
+
+/// Conditions shared by the page and the count queries.
+pub struct TicketFilters<'a> {
+    schema: &'a GetTicketsSchema,
+    created_after: Option<DateTime<Utc>>,
+    search: Option<&'a str>,
+    ticket_number: Option<TicketId>,
+    client_id: &'a Option<UserId>,
+    department_scope: &'a DepartmentScope,
+    read_internal: bool,
+    user_id: UserId,
+}
+
+impl<'a> TicketFilters<'a> {
+    pub fn new(
+        schema: &'a GetTicketsSchema,
+        created_after: Option<DateTime<Utc>>,
+        client_id: &'a Option<UserId>,
+        department_scope: &'a DepartmentScope,
+        read_internal: bool,
+        user_id: UserId,
+    ) -> Self {
+        let search = search_query(schema);
+
+        // Ticket numbers and short tokens are not words for the russian dictionary,
+        // so they fall back to the id and to trigram matching of the title.
+        let ticket_number = search.and_then(|s| s.trim_start_matches('#').parse::<TicketId>().ok());
+
+        Self { schema, created_after, search, ticket_number, client_id, department_scope, read_internal, user_id }
+    }
+
+    pub fn push_search_source(&self, builder: &mut QueryBuilder<'a, Postgres>) {
+        if let Some(s) = self.search {
+            builder.push("CROSS JOIN websearch_to_tsquery('russian', ").push_bind(s).push(") q\n");
+        }
+    }
+
+    /// Returns whether a `WHERE` clause was started.
+    pub fn push_conditions(&self, builder: &mut QueryBuilder<'a, Postgres>) -> bool {
+        let schema = self.schema;
+        let mut has_filters = false;
+
+        build_where_condition!(builder, has_filters, schema.statuses, "t.status", in);
+        build_where_condition!(builder, has_filters, schema.priorities, "priority", in);
+        build_where_condition!(builder, has_filters, schema.planned_from, "planned_at", ">=");
+        build_where_condition!(builder, has_filters, schema.planned_to, "planned_at", "<=");
+        build_where_condition!(builder, has_filters, schema.buildings, "building_id", in);
+        build_where_condition!(builder, has_filters, schema.departments, "department_id", in);
+        build_where_condition!(builder, has_filters, self.client_id, "author_id", "=");
+
+        // Bound by value, the filters themselves may not outlive the builder.
+        if let Some(created_after) = self.created_after {
+            build_where_condition!(@add_where_and builder, has_filters);
+
+            builder.push("t.created_at > ").push_bind(created_after);
+        }
+
+        if let Some(assigned_to) = schema.assigned_to {
+            build_where_condition!(@add_where_and builder, has_filters);
+
+            builder.push("EXISTS (SELECT 1 FROM tickets_users tu WHERE tu.ticket_id = t.id AND tu.assigned_to = ")
+                .push_bind(assigned_to)
+                .push(")");
+        }
+
+        // Staff see tickets of their departments and the ones they are involved in.
+        if let DepartmentScope::Departments(departments) = self.department_scope {
+            build_where_condition!(@add_where_and builder, has_filters);
+
+            builder.push("(t.department_id = ANY(").push_bind(departments)
+                .push(") OR author_id = ").push_bind(self.user_id)
+                .push(" OR EXISTS (SELECT 1 FROM tickets_users m WHERE m.ticket_id = t.id AND m.assigned_to = ")
+                .push_bind(self.user_id)
+                .push("))");
+        }
+
+        if let Some(s) = self.search {
+            build_where_condition!(@add_where_and builder, has_filters);
+
+            // Internal messages match only for those who may read them.
+            builder.push("(t.search_vector @@ q")
+                .push(" OR EXISTS (SELECT 1 FROM ticket_messages tm WHERE tm.ticket_id = t.id AND tm.search_vector @@ q")
+                .push(" AND (NOT tm.is_internal OR ").push_bind(self.read_internal).push("))")
+                .push(" OR ").push_bind(s).push(" <% title")
+                .push(" OR t.id = ").push_bind(self.ticket_number)
+                .push(")");
+        }
+
+        has_filters
+    }
 }
 
 #[inline]
 fn get_builder<'a>(
-    schema: &'a GetTicketsSchema,
-    page: TicketId,
+    filters: &TicketFilters<'a>,
+    pagination: &Pagination,
     page_size: i8,
-    client_id: &'a Option<UserId>,
-) -> QueryBuilder<'a, sqlx::Postgres> {
-    let mut builder = sqlx::QueryBuilder::<sqlx::Postgres>::new(
+) -> QueryBuilder<'a, Postgres> {
+    let schema = filters.schema;
+
+    let mut builder = sqlx::QueryBuilder::<Postgres>::new(
         r#"SELECT 
             t.id,
             title,
@@ -180,55 +467,144 @@ fn get_builder<'a>(
             b.code as "building_code",
             b.name as "building_name",
             cabinet,
-            COUNT(*) OVER() as total_items
+        "#
+    );
+
+    match pagination {
+        Pagination::Page(_) => builder.push("COUNT(*) OVER() as total_items, "),
+        Pagination::Cursor(_) => builder.push("NULL::bigint as total_items, "),
+    };
+
+    if let Some(s) = filters.search {
+        builder.push("ts_rank_cd(t.search_vector, q) + word_similarity(").push_bind(s)
+            .push(", title) + CASE WHEN t.id = ").push_bind(filters.ticket_number)
+            .push(" THEN 1 ELSE 0 END AS rank,")
+            .push(" ts_headline('russian', description, q, 'MaxFragments=2, MaxWords=20, MinWords=5') AS search_snippet");
+    } else {
+        builder.push("NULL::text AS search_snippet");
+    }
+
+    builder.push(
+        r#"
         FROM tickets t
-        LEFT JOIN tickets_users tu ON tu.ticket_id = t.id
         JOIN buildings b ON b.id = t.building_id
         JOIN departments d ON d.id = t.department_id
         "#
     );
 
-    let mut has_filters = false;
+    filters.push_search_source(&mut builder);
 
-    build_where_condition!(builder, has_filters, schema.statuses, "t.status", in);
-    build_where_condition!(builder, has_filters, schema.priorities, "priority", in);
-    build_where_condition!(builder, has_filters, schema.planned_from, "planned_at", ">=");
-    build_where_condition!(builder, has_filters, schema.planned_to, "planned_at", "<=");
-    build_where_condition!(builder, has_filters, schema.buildings, "building_id", in);
-    build_where_condition!(builder, has_filters, schema.assigned_to, "tu.assigned_to", "=");
-    build_where_condition!(builder, has_filters, schema.departments, "department_id", in);
-    build_where_condition!(builder, has_filters, client_id, "author_id", "=");
+    let mut has_filters = filters.push_conditions(&mut builder);
 
-    if let Some(s) = &schema.search {
-        build_where_condition!(@add_where_and builder, has_filters);
-        let s = format!("%{}%", s);
+    let sort_order = schema.sort_order.unwrap_or_default();
 
-        builder.push("(title ILIKE ").push_bind(s.clone())
-            .push(" OR description ILIKE ").push_bind(s)
-            .push(")");
+    if let Pagination::Cursor(Some(cursor)) = pagination {
+        build_where_condition!(@add_where_and builder, has_filters);
+        push_keyset_condition(&mut builder, cursor, sort_order);
     }
 
     if has_filters {
         builder.push("\n");
     }
 
-    let order_by = schema.order_by.unwrap_or_default();
+    // Search results are ordered by relevance unless an order is requested.
+    match (schema.order_by, filters.search) {
+        (None, Some(_)) => {
+            builder.push("ORDER BY rank DESC, t.id DESC");
+        },
+        (order_by, _) => {
+            push_order_by(&mut builder, order_by.unwrap_or_default(), sort_order);
+        },
+    }
+
+    match pagination {
+        Pagination::Page(page) => {
+            builder
+                .push(" LIMIT ")
+                .push_bind(page_size as i64)
+                .push(" OFFSET ")
+                .push_bind(page_size as i64 * page);
+        },
+        Pagination::Cursor(_) => {
+            builder
+                .push(" LIMIT ")
+                .push_bind(page_size as i64 + 1);
+        },
+    }
+
+    builder
+}
 
+/// Orders by the column with the id as a tiebreaker, as the cursors expect.
+pub fn push_order_by(builder: &mut QueryBuilder<'_, Postgres>, order_by: OrderBy, sort_order: SortOrder) {
     let order_by_column = match order_by {
-        crate::schema::tickets::OrderBy::Id => "t.id",
-        crate::schema::tickets::OrderBy::PlannedAt => "planned_at",
-        crate::schema::tickets::OrderBy::Priority => "priority",
+        OrderBy::Id => "",
+        OrderBy::PlannedAt => "planned_at",
+        OrderBy::Priority => "priority",
     };
 
-    builder
-        .push("GROUP BY t.id, b.id ORDER BY ")
-        .push(order_by_column)
-        .push(" ")
-        .push(schema.sort_order.clone().unwrap_or_default().as_str())
-        .push(" LIMIT ")
-        .push_bind(page_size as i64)
-        .push(" OFFSET ")
-        .push_bind(page_size as i64 * page);
+    builder.push("ORDER BY ");
+
+    if !order_by_column.is_empty() {
+        builder
+            .push(order_by_column)
+            .push(" ")
+            .push(sort_order.as_str())
+            .push(", ");
+    }
 
     builder
+        .push("t.id ")
+        .push(sort_order.as_str());
+}
+
+async fn count_tickets(pool: &PgPool, filters: &TicketFilters<'_>) -> Result<u64, sqlx::Error> {
+    let mut builder = sqlx::QueryBuilder::<Postgres>::new("SELECT COUNT(*) FROM tickets t\n");
+
+    filters.push_search_source(&mut builder);
+    filters.push_conditions(&mut builder);
+
+    builder.build_query_scalar()
+        .fetch_one(pool)
+        .await
+        .map(|count: i64| count as u64)
+}
+
+/// Rows after the cursor in the query order. `NULL` planned dates come last
+/// in ascending order and first in descending, as Postgres sorts them by default.
+pub fn push_keyset_condition(
+    builder: &mut QueryBuilder<'_, Postgres>,
+    cursor: &TicketCursor,
+    sort_order: SortOrder,
+) {
+    let (cmp, ascending) = match sort_order {
+        SortOrder::Asc => (" > ", true),
+        SortOrder::Desc => (" < ", false),
+    };
+
+    match cursor.key {
+        TicketSortKey::Id => {
+            builder.push("t.id").push(cmp).push_bind(cursor.id);
+        },
+        TicketSortKey::Priority(priority) => {
+            builder.push("(priority, t.id)").push(cmp)
+                .push("(").push_bind(priority).push(", ").push_bind(cursor.id).push(")");
+        },
+        TicketSortKey::PlannedAt(Some(planned_at)) => {
+            builder.push("((planned_at, t.id)").push(cmp)
+                .push("(").push_bind(planned_at).push(", ").push_bind(cursor.id).push(")");
+
+            if ascending {
+                builder.push(" OR planned_at IS NULL");
+            }
+
+            builder.push(")");
+        },
+        TicketSortKey::PlannedAt(None) if ascending => {
+            builder.push("(planned_at IS NULL AND t.id > ").push_bind(cursor.id).push(")");
+        },
+        TicketSortKey::PlannedAt(None) => {
+            builder.push("(planned_at IS NOT NULL OR t.id < ").push_bind(cursor.id).push(")");
+        },
+    }
 }
\ No newline at end of file
@@ -0,0 +1,103 @@
+use actix_web::{http::StatusCode, web, HttpResponse, ResponseError};
+use anyhow::Context;
+use garde_actix_web::web::Json;
+use sqlx::PgPool;
+
+use crate::{auth::{department_scope::{can_access_ticket, DepartmentScope}, extractor::{PermissionsExtractor, UserIdExtractor}}, schema::{common::UserId, tickets::{CreateTicketLinkSchema, TicketId}}, utils::error_chain_fmt};
+
+#[derive(thiserror::Error)]
+pub enum CreateLinkError {
+    #[error("Ticket not found")]
+    NotFound,
+    #[error("Insufficient permissions to update this ticket")]
+    InsufficientPermissions,
+    #[error("Ticket cannot be linked to itself")]
+    SelfLink,
+    #[error("Tickets are already linked")]
+    AlreadyLinked,
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for CreateLinkError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for CreateLinkError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            CreateLinkError::NotFound => StatusCode::NOT_FOUND,
+            CreateLinkError::InsufficientPermissions => StatusCode::FORBIDDEN,
+            CreateLinkError::SelfLink => StatusCode::BAD_REQUEST,
+            CreateLinkError::AlreadyLinked => StatusCode::CONFLICT,
+            CreateLinkError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+pub async fn create_link(
+    ticket_id: web::Path<TicketId>,
+    Json(schema): Json<CreateTicketLinkSchema>,
+    pool: web::Data<PgPool>,
+    user_id: UserIdExtractor,
+    permissions: PermissionsExtractor,
+) -> Result<HttpResponse, CreateLinkError> {
+    let ticket_id = ticket_id.into_inner();
+
+    if ticket_id == schema.ticket_id {
+        return Err(CreateLinkError::SelfLink);
+    }
+
+    let scope = DepartmentScope::resolve(&pool, user_id.0, &permissions.0)
+        .await
+        .context("Failed to resolve department scope")?;
+
+    for id in [ticket_id, schema.ticket_id] {
+        let can_access = can_access_ticket(&pool, &scope, user_id.0, id)
+            .await
+            .context("Failed to check ticket access")?
+            .ok_or(CreateLinkError::NotFound)?;
+
+        if !can_access {
+            return Err(CreateLinkError::InsufficientPermissions);
+        }
+    }
+
+    let res = insert_link(&pool, ticket_id, &schema, user_id.0).await;
+
+    if let Err(e) = &res
+        && let Some(db_err) = e.as_database_error()
+            && db_err.is_unique_violation() {
+                return Err(CreateLinkError::AlreadyLinked);
+            }
+
+    res.context("Failed to insert ticket link")?;
+
+    Ok(HttpResponse::Created().finish())
+}
+
+#[tracing::instrument(
+    name = "Insert ticket link into database",
+    skip(pool)
+)]
+async fn insert_link(
+    pool: &PgPool,
+    ticket_id: TicketId,
+    schema: &CreateTicketLinkSchema,
+    user_id: UserId,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "INSERT INTO ticket_links (ticket_id, linked_ticket_id, kind, created_by)
+        VALUES ($1, $2, $3, $4)",
+        ticket_id,
+        schema.ticket_id,
+        schema.kind as i16,
+        user_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,82 @@
+use actix_web::{http::StatusCode, web, HttpResponse, ResponseError};
+use anyhow::Context;
+use sqlx::PgPool;
+
+use crate::{auth::{department_scope::{can_access_ticket, DepartmentScope}, extractor::{PermissionsExtractor, UserIdExtractor}}, schema::tickets::TicketId, utils::error_chain_fmt};
+
+#[derive(thiserror::Error)]
+pub enum DeleteLinkError {
+    #[error("Link not found")]
+    NotFound,
+    #[error("Insufficient permissions to update this ticket")]
+    InsufficientPermissions,
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for DeleteLinkError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for DeleteLinkError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            DeleteLinkError::NotFound => StatusCode::NOT_FOUND,
+            DeleteLinkError::InsufficientPermissions => StatusCode::FORBIDDEN,
+            DeleteLinkError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+/// Removes the link between the tickets, whichever of them is its source.
+pub async fn delete_link(
+    path: web::Path<(TicketId, TicketId)>,
+    pool: web::Data<PgPool>,
+    user_id: UserIdExtractor,
+    permissions: PermissionsExtractor,
+) -> Result<HttpResponse, DeleteLinkError> {
+    let (ticket_id, linked_ticket_id) = path.into_inner();
+
+    let scope = DepartmentScope::resolve(&pool, user_id.0, &permissions.0)
+        .await
+        .context("Failed to resolve department scope")?;
+
+    let can_access = can_access_ticket(&pool, &scope, user_id.0, ticket_id)
+        .await
+        .context("Failed to check ticket access")?
+        .ok_or(DeleteLinkError::NotFound)?;
+
+    if !can_access {
+        return Err(DeleteLinkError::InsufficientPermissions);
+    }
+
+    let deleted = delete(&pool, ticket_id, linked_ticket_id)
+        .await
+        .context("Failed to delete ticket link")?;
+
+    if !deleted {
+        return Err(DeleteLinkError::NotFound);
+    }
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[tracing::instrument(
+    name = "Delete ticket link from database",
+    skip(pool)
+)]
+async fn delete(pool: &PgPool, ticket_id: TicketId, linked_ticket_id: TicketId) -> Result<bool, sqlx::Error> {
+    let res = sqlx::query!(
+        "DELETE FROM ticket_links
+        WHERE (ticket_id = $1 AND linked_ticket_id = $2)
+            OR (ticket_id = $2 AND linked_ticket_id = $1)",
+        ticket_id,
+        linked_ticket_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(res.rows_affected() == 1)
+}
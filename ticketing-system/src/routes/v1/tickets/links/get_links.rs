@@ -0,0 +1,97 @@
+use actix_web::{http::StatusCode, web, HttpResponse, ResponseError};
+use anyhow::Context;
+use sqlx::PgPool;
+
+use crate::{auth::{department_scope::{can_access_ticket, DepartmentScope}, extractor::{PermissionsExtractor, UserIdExtractor}}, schema::tickets::{TicketId, TicketLink, TicketLinkKind, TicketStatus}, utils::error_chain_fmt};
+
+#[derive(thiserror::Error)]
+pub enum GetLinksError {
+    #[error("Ticket not found")]
+    NotFound,
+    #[error("Insufficient permissions to view this ticket")]
+    InsufficientPermissions,
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for GetLinksError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for GetLinksError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            GetLinksError::NotFound => StatusCode::NOT_FOUND,
+            GetLinksError::InsufficientPermissions => StatusCode::FORBIDDEN,
+            GetLinksError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+pub async fn get_links(
+    ticket_id: web::Path<TicketId>,
+    pool: web::Data<PgPool>,
+    user_id: UserIdExtractor,
+    permissions: PermissionsExtractor,
+) -> Result<HttpResponse, GetLinksError> {
+    let ticket_id = ticket_id.into_inner();
+
+    let scope = DepartmentScope::resolve(&pool, user_id.0, &permissions.0)
+        .await
+        .context("Failed to resolve department scope")?;
+
+    let can_access = can_access_ticket(&pool, &scope, user_id.0, ticket_id)
+        .await
+        .context("Failed to check ticket access")?
+        .ok_or(GetLinksError::NotFound)?;
+
+    if !can_access {
+        return Err(GetLinksError::InsufficientPermissions);
+    }
+
+    let links = select_links(&pool, ticket_id)
+        .await
+        .context("Failed to get ticket links")?;
+
+    Ok(HttpResponse::Ok().json(links))
+}
+
+#[tracing::instrument(
+    name = "Get ticket links from database",
+    skip(pool)
+)]
+async fn select_links(pool: &PgPool, ticket_id: TicketId) -> Result<Vec<TicketLink>, sqlx::Error> {
+    let rows = sqlx::query!(
+        r#"
+            SELECT
+                t.id,
+                t.title,
+                t.status,
+                l.kind,
+                l.ticket_id = $1 AS "outgoing!",
+                l.created_at
+            FROM ticket_links l
+            JOIN tickets t ON t.id = CASE WHEN l.ticket_id = $1 THEN l.linked_ticket_id ELSE l.ticket_id END
+            WHERE l.ticket_id = $1 OR l.linked_ticket_id = $1
+            ORDER BY l.created_at, t.id
+        "#,
+        ticket_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let links = rows.into_iter()
+        .map(|row| TicketLink {
+            ticket_id: row.id,
+            title: row.title,
+            status: TicketStatus::from(row.status),
+            kind: TicketLinkKind::from(row.kind),
+            outgoing: row.outgoing,
+            created_at: row.created_at,
+        })
+        .collect();
+
+    Ok(links)
+}
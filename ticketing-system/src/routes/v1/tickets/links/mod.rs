@@ -0,0 +1,7 @@
+pub mod get_links;
+pub mod create_link;
+pub mod delete_link;
+
+pub use get_links::get_links;
+pub use create_link::create_link;
+pub use delete_link::delete_link;
\ No newline at end of file
@@ -0,0 +1,247 @@
+use actix_web::{http::StatusCode, web, HttpResponse, ResponseError};
+use anyhow::Context;
+use garde_actix_web::web::Json;
+use sqlx::{PgPool, Postgres, Transaction};
+
+use crate::{auth::{department_scope::{can_access_ticket, DepartmentScope}, extractor::{PermissionsExtractor, UserIdExtractor}}, schema::{common::UserId, notification::Notification, tickets::{MergeTicketsSchema, TicketId, TicketLinkKind, TicketStatus}}, services::notification::NotificationService, utils::error_chain_fmt};
+
+struct MergedTicket {
+    pub id: TicketId,
+    pub status: i16,
+    pub author_id: Option<UserId>,
+}
+
+#[derive(thiserror::Error)]
+pub enum MergeTicketsError {
+    #[error("Ticket not found")]
+    NotFound,
+    #[error("Insufficient permissions to merge these tickets")]
+    InsufficientPermissions,
+    #[error("Ticket cannot be merged into itself")]
+    SelfMerge,
+    #[error("Closed or cancelled tickets cannot be merged")]
+    TicketClosed,
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for MergeTicketsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for MergeTicketsError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            MergeTicketsError::NotFound => StatusCode::NOT_FOUND,
+            MergeTicketsError::InsufficientPermissions => StatusCode::FORBIDDEN,
+            MergeTicketsError::SelfMerge => StatusCode::BAD_REQUEST,
+            MergeTicketsError::TicketClosed => StatusCode::CONFLICT,
+            MergeTicketsError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+/// Moves messages, attachments and assets of the given tickets into the one
+/// from the path, then cancels them as its duplicates.
+pub async fn merge_tickets(
+    primary_id: web::Path<TicketId>,
+    Json(mut schema): Json<MergeTicketsSchema>,
+    pool: web::Data<PgPool>,
+    notification_service: web::Data<NotificationService>,
+    user_id: UserIdExtractor,
+    permissions: PermissionsExtractor,
+) -> Result<HttpResponse, MergeTicketsError> {
+    let primary_id = primary_id.into_inner();
+
+    schema.ticket_ids.sort_unstable();
+    schema.ticket_ids.dedup();
+
+    if schema.ticket_ids.contains(&primary_id) {
+        return Err(MergeTicketsError::SelfMerge);
+    }
+
+    let scope = DepartmentScope::resolve(&pool, user_id.0, &permissions.0)
+        .await
+        .context("Failed to resolve department scope")?;
+
+    for &id in std::iter::once(&primary_id).chain(&schema.ticket_ids) {
+        let can_access = can_access_ticket(&pool, &scope, user_id.0, id)
+            .await
+            .context("Failed to check ticket access")?
+            .ok_or(MergeTicketsError::NotFound)?;
+
+        if !can_access {
+            return Err(MergeTicketsError::InsufficientPermissions);
+        }
+    }
+
+    let mut transaction = pool.begin()
+        .await
+        .context("Failed to begin transaction")?;
+
+    let tickets = lock_tickets(&mut transaction, primary_id, &schema.ticket_ids)
+        .await
+        .context("Failed to lock tickets")?;
+
+    if tickets.len() != schema.ticket_ids.len() + 1 {
+        return Err(MergeTicketsError::NotFound);
+    }
+
+    let is_closed = |status: i16| {
+        status == TicketStatus::Closed as i16 || status == TicketStatus::Cancelled as i16
+    };
+
+    if tickets.iter().any(|ticket| is_closed(ticket.status)) {
+        return Err(MergeTicketsError::TicketClosed);
+    }
+
+    move_contents(&mut transaction, primary_id, &schema.ticket_ids)
+        .await
+        .context("Failed to move ticket contents")?;
+
+    close_duplicates(&mut transaction, primary_id, &schema.ticket_ids, user_id.0)
+        .await
+        .context("Failed to close duplicate tickets")?;
+
+    // Authors learn where their request went, except the one who merged it.
+    for ticket in tickets.iter().filter(|ticket| ticket.id != primary_id) {
+        let Some(author_id) = ticket.author_id.filter(|&id| id != user_id.0) else {
+            continue;
+        };
+
+        notification_service.notify(
+            &mut *transaction,
+            ticket.id,
+            &[author_id],
+            Notification::Merged { into: primary_id }
+        )
+        .await
+        .context("Failed to create notifications")?;
+    }
+
+    transaction.commit()
+        .await
+        .context("Failed to commit transaction")?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[tracing::instrument(
+    name = "Lock tickets for merging",
+    skip(transaction)
+)]
+async fn lock_tickets(
+    transaction: &mut Transaction<'_, Postgres>,
+    primary_id: TicketId,
+    ticket_ids: &[TicketId],
+) -> Result<Vec<MergedTicket>, sqlx::Error> {
+    sqlx::query_as!(
+        MergedTicket,
+        "SELECT id, status, author_id
+        FROM tickets
+        WHERE id = $1 OR id = ANY($2)
+        ORDER BY id
+        FOR UPDATE",
+        primary_id,
+        ticket_ids
+    )
+    .fetch_all(transaction.as_mut())
+    .await
+}
+
+#[tracing::instrument(
+    name = "Move messages, attachments and assets to primary ticket",
+    skip(transaction)
+)]
+async fn move_contents(
+    transaction: &mut Transaction<'_, Postgres>,
+    primary_id: TicketId,
+    ticket_ids: &[TicketId],
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "UPDATE ticket_messages SET ticket_id = $1 WHERE ticket_id = ANY($2)",
+        primary_id,
+        ticket_ids
+    )
+    .execute(transaction.as_mut())
+    .await?;
+
+    // Keys are unique per upload, so they never collide with the primary ones.
+    sqlx::query!(
+        "UPDATE ticket_attachments SET ticket_id = $1 WHERE ticket_id = ANY($2)",
+        primary_id,
+        ticket_ids
+    )
+    .execute(transaction.as_mut())
+    .await?;
+
+    sqlx::query!(
+        "INSERT INTO ticket_assets (ticket_id, asset_id, comment)
+        SELECT DISTINCT ON (asset_id) $1, asset_id, comment
+        FROM ticket_assets
+        WHERE ticket_id = ANY($2) AND asset_id NOT IN (
+            SELECT asset_id FROM ticket_assets WHERE ticket_id = $1
+        )
+        ORDER BY asset_id",
+        primary_id,
+        ticket_ids
+    )
+    .execute(transaction.as_mut())
+    .await?;
+
+    sqlx::query!(
+        "DELETE FROM ticket_assets WHERE ticket_id = ANY($1)",
+        ticket_ids
+    )
+    .execute(transaction.as_mut())
+    .await?;
+
+    Ok(())
+}
+
+#[tracing::instrument(
+    name = "Close duplicate tickets",
+    skip(transaction)
+)]
+async fn close_duplicates(
+    transaction: &mut Transaction<'_, Postgres>,
+    primary_id: TicketId,
+    ticket_ids: &[TicketId],
+    user_id: UserId,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "UPDATE tickets
+        SET status = $2
+        WHERE id = ANY($1)",
+        ticket_ids,
+        TicketStatus::Cancelled as i16
+    )
+    .execute(transaction.as_mut())
+    .await?;
+
+    // A pair has one link at most, the duplicate one replaces it.
+    sqlx::query!(
+        "DELETE FROM ticket_links
+        WHERE (ticket_id = ANY($1) AND linked_ticket_id = $2)
+            OR (ticket_id = $2 AND linked_ticket_id = ANY($1))",
+        ticket_ids,
+        primary_id
+    )
+    .execute(transaction.as_mut())
+    .await?;
+
+    sqlx::query!(
+        "INSERT INTO ticket_links (ticket_id, linked_ticket_id, kind, created_by)
+        SELECT ticket_id, $2, $3, $4 FROM UNNEST($1::bigint[]) AS ticket_id",
+        ticket_ids,
+        primary_id,
+        TicketLinkKind::Duplicates as i16,
+        user_id
+    )
+    .execute(transaction.as_mut())
+    .await?;
+
+    Ok(())
+}
@@ -1,9 +1,9 @@
-use actix_web::{HttpResponse, ResponseError, web};
+use actix_web::{HttpResponse, ResponseError, http::StatusCode, web};
 use anyhow::Context;
 use serde::Deserialize;
 use sqlx::PgPool;
 
-use crate::{auth::{extractor::{UserIdExtractor, UserRoleExtractor}, types::UserRole}, schema::{common::UserId, notification::Notification, tickets::TicketId}, services::notification::NotificationService, utils::error_chain_fmt};
+use crate::{auth::{department_scope::{can_access_ticket, DepartmentScope}, extractor::{PermissionsExtractor, UserIdExtractor}, permission::Permission}, schema::{common::UserId, notification::Notification, tickets::TicketId}, services::notification::NotificationService, utils::error_chain_fmt};
 
 #[derive(Deserialize, Debug)]
 pub struct CreateMessageSchema {
@@ -14,6 +14,10 @@ pub struct CreateMessageSchema {
 
 #[derive(thiserror::Error)]
 pub enum CreateMessageError {
+    #[error("Ticket not found")]
+    NotFound,
+    #[error("Insufficient permissions to access this ticket")]
+    InsufficientPermissions,
     #[error(transparent)]
     Unexpected(#[from] anyhow::Error)
 }
@@ -24,7 +28,15 @@ impl std::fmt::Debug for CreateMessageError {
     }
 }
 
-impl ResponseError for CreateMessageError {}
+impl ResponseError for CreateMessageError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            CreateMessageError::NotFound => StatusCode::NOT_FOUND,
+            CreateMessageError::InsufficientPermissions => StatusCode::FORBIDDEN,
+            CreateMessageError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
 
 pub async fn create_message(
     pool: web::Data<PgPool>,
@@ -32,14 +44,27 @@ pub async fn create_message(
     ticket_id: web::Path<TicketId>,
     web::Json(mut schema): web::Json<CreateMessageSchema>,
     user_id: UserIdExtractor,
-    role: UserRoleExtractor,
+    permissions: PermissionsExtractor,
 ) -> Result<HttpResponse, CreateMessageError> {
-    if role.0 == UserRole::Client {
+    if !permissions.0.has(Permission::MessagesReadInternal) {
         schema.is_internal = false;
     }
 
     let ticket_id = ticket_id.into_inner();
 
+    let scope = DepartmentScope::resolve(&pool, user_id.0, &permissions.0)
+        .await
+        .context("Failed to resolve department scope")?;
+
+    let can_access = can_access_ticket(&pool, &scope, user_id.0, ticket_id)
+        .await
+        .context("Failed to check ticket access")?
+        .ok_or(CreateMessageError::NotFound)?;
+
+    if !can_access {
+        return Err(CreateMessageError::InsufficientPermissions);
+    }
+
     insert_message(
         &pool,
         ticket_id,
@@ -48,6 +73,12 @@ pub async fn create_message(
     ).await
     .context("Failed to insert message")?;
 
+    if !schema.is_internal {
+        record_first_response(&pool, ticket_id, user_id.0)
+            .await
+            .context("Failed to record first response")?;
+    }
+
     tokio::spawn(async move {
         if let Ok(user_ids) = get_user_ids(
             &pool,
@@ -101,6 +132,35 @@ async fn insert_message(
     Ok(())
 }
 
+/// The first public reply of an assignee answers the ticket. Tickets assigned
+/// by rules get their first response here rather than at assignment.
+#[tracing::instrument(
+    name = "Record first response",
+    skip(pool)
+)]
+async fn record_first_response(
+    pool: &PgPool,
+    ticket_id: TicketId,
+    user_id: UserId,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "UPDATE tickets
+        SET first_response_at = NOW()
+        WHERE id = $1
+            AND first_response_at IS NULL
+            AND EXISTS (
+                SELECT 1 FROM tickets_users
+                WHERE ticket_id = $1 AND assigned_to = $2
+            )",
+        ticket_id,
+        user_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
 #[tracing::instrument(
     name = "Get user ids for notification",
     skip(pool)
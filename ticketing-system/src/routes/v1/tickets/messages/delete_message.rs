@@ -2,12 +2,14 @@ use actix_web::{HttpResponse, ResponseError, http::StatusCode, web};
 use anyhow::Context;
 use sqlx::{PgPool, postgres::PgQueryResult};
 
-use crate::{auth::{extractor::{UserIdExtractor, UserRoleExtractor}, types::UserRole}, schema::{common::UserId, tickets::{MessageId, TicketId}}, utils::error_chain_fmt};
+use crate::{auth::{department_scope::{can_access_ticket, DepartmentScope}, extractor::{PermissionsExtractor, UserIdExtractor}, permission::Permission}, schema::{common::UserId, tickets::{MessageId, TicketId}}, utils::error_chain_fmt};
 
 #[derive(thiserror::Error)]
 pub enum DeleteMessageError {
     #[error("Message not found")]
     NotFound,
+    #[error("Insufficient permissions to access this ticket")]
+    InsufficientPermissions,
     #[error(transparent)]
     Unexpected(#[from] anyhow::Error)
 }
@@ -22,6 +24,7 @@ impl ResponseError for DeleteMessageError {
     fn status_code(&self) -> StatusCode {
         match self {
             DeleteMessageError::NotFound => StatusCode::NOT_FOUND,
+            DeleteMessageError::InsufficientPermissions => StatusCode::FORBIDDEN,
             DeleteMessageError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
@@ -31,16 +34,29 @@ pub async fn delete_message(
     pool: web::Data<PgPool>,
     path: web::Path<(TicketId, MessageId)>,
     user_id: UserIdExtractor,
-    user_role: UserRoleExtractor,
+    permissions: PermissionsExtractor,
 ) -> Result<HttpResponse, DeleteMessageError> {
     let (ticket_id, message_id) = path.into_inner();
 
+    let scope = DepartmentScope::resolve(&pool, user_id.0, &permissions.0)
+        .await
+        .context("Failed to resolve department scope")?;
+
+    let can_access = can_access_ticket(&pool, &scope, user_id.0, ticket_id)
+        .await
+        .context("Failed to check ticket access")?
+        .ok_or(DeleteMessageError::NotFound)?;
+
+    if !can_access {
+        return Err(DeleteMessageError::InsufficientPermissions);
+    }
+
     let res = delete(
         &pool,
         ticket_id,
         message_id,
         user_id.0,
-        user_role.0
+        permissions.0.has(Permission::MessagesDeleteOthers)
     ).await
     .context("Failed to delete message")?;
 
@@ -56,9 +72,9 @@ async fn delete(
     ticket_id: TicketId,
     message_id: MessageId,
     user_id: UserId,
-    user_role: UserRole,
+    can_delete_others: bool,
 ) -> Result<PgQueryResult, sqlx::Error> {
-    if !user_role.has_access(UserRole::Employee) {
+    if !can_delete_others {
         sqlx::query!(
             "
                 DELETE FROM ticket_messages
@@ -1,11 +1,11 @@
-use actix_web::{HttpResponse, ResponseError, web};
+use actix_web::{HttpResponse, ResponseError, http::StatusCode, web};
 use anyhow::Context;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_qs::actix::QsQuery;
 use sqlx::{PgPool, prelude::FromRow, types::Json};
 
-use crate::{auth::{extractor::UserRoleExtractor, types::UserRole}, schema::{common::UserId, tickets::{MessageId, TicketId}}, utils::error_chain_fmt};
+use crate::{auth::{department_scope::{can_access_ticket, DepartmentScope}, extractor::{PermissionsExtractor, UserIdExtractor}, permission::Permission}, schema::{common::UserId, tickets::{MessageId, TicketId}}, utils::error_chain_fmt};
 
 fn default_limit() -> i8 { 50 }
 
@@ -34,6 +34,10 @@ struct Message {
 
 #[derive(thiserror::Error)]
 pub enum GetMessagesError {
+    #[error("Ticket not found")]
+    NotFound,
+    #[error("Insufficient permissions to access this ticket")]
+    InsufficientPermissions,
     #[error(transparent)]
     Unexpected(#[from] anyhow::Error),
 }
@@ -44,22 +48,45 @@ impl std::fmt::Debug for GetMessagesError {
     }
 }
 
-impl ResponseError for GetMessagesError {}
+impl ResponseError for GetMessagesError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            GetMessagesError::NotFound => StatusCode::NOT_FOUND,
+            GetMessagesError::InsufficientPermissions => StatusCode::FORBIDDEN,
+            GetMessagesError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
 
 pub async fn get_messages(
     pool: web::Data<PgPool>,
     ticket_id: web::Path<TicketId>,
     schema: QsQuery<GetMessagesSchema>,
-    user_role: UserRoleExtractor,
+    user_id: UserIdExtractor,
+    permissions: PermissionsExtractor,
 ) -> Result<HttpResponse, GetMessagesError> {
     let schema = schema.into_inner();
+    let ticket_id = ticket_id.into_inner();
+
+    let scope = DepartmentScope::resolve(&pool, user_id.0, &permissions.0)
+        .await
+        .context("Failed to resolve department scope")?;
+
+    let can_access = can_access_ticket(&pool, &scope, user_id.0, ticket_id)
+        .await
+        .context("Failed to check ticket access")?
+        .ok_or(GetMessagesError::NotFound)?;
+
+    if !can_access {
+        return Err(GetMessagesError::InsufficientPermissions);
+    }
 
-    let only_not_internal = !user_role.0.has_access(UserRole::Employee);
+    let only_not_internal = !permissions.0.has(Permission::MessagesReadInternal);
 
     let messages = select_messages(
         &pool,
         &schema,
-        ticket_id.into_inner(),
+        ticket_id,
         only_not_internal
     ).await
     .context("Failed to get messages")?;
@@ -10,6 +10,13 @@ pub mod stats;
 pub mod messages;
 pub mod assets;
 pub mod metrics;
+pub mod links;
+pub mod merge_tickets;
+pub mod similar;
+pub mod pages;
+pub mod views;
+pub mod export_tickets;
+pub mod bulk_tickets;
 
 pub use get_tickets::get_tickets;
 pub use unassign_ticket::{unassign_ticket_from_self, unassign_ticket_from_user};
@@ -20,5 +27,9 @@ pub use create_ticket::create_ticket;
 pub use get_ticket::get_ticket;
 pub use delete_ticket::delete_ticket;
 pub use stats::get_stats;
+pub use merge_tickets::merge_tickets;
+pub use similar::get_similar_tickets;
+pub use export_tickets::export_tickets;
+pub use bulk_tickets::bulk_update_tickets;
 
 pub use messages::*;
\ No newline at end of file
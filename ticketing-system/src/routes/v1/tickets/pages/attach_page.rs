@@ -0,0 +1,104 @@
+use actix_web::{http::StatusCode, web, HttpResponse, ResponseError};
+use anyhow::Context;
+use serde::Deserialize;
+use sqlx::PgPool;
+
+use crate::{auth::{department_scope::{can_access_ticket, DepartmentScope}, extractor::{PermissionsExtractor, UserIdExtractor}}, schema::{common::UserId, page::PageId, tickets::TicketId}, utils::error_chain_fmt};
+
+#[derive(Deserialize, Debug)]
+pub struct AttachPageSchema {
+    pub page_id: PageId,
+}
+
+#[derive(thiserror::Error)]
+pub enum AttachPageError {
+    #[error("Ticket or page not found")]
+    NotFound,
+    #[error("Insufficient permissions to update this ticket")]
+    InsufficientPermissions,
+    #[error("Page is already attached to the ticket")]
+    AlreadyAttached,
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for AttachPageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for AttachPageError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            AttachPageError::NotFound => StatusCode::NOT_FOUND,
+            AttachPageError::InsufficientPermissions => StatusCode::FORBIDDEN,
+            AttachPageError::AlreadyAttached => StatusCode::CONFLICT,
+            AttachPageError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+/// Marks a knowledge base page as a solution of the ticket.
+pub async fn attach_page(
+    ticket_id: web::Path<TicketId>,
+    schema: web::Json<AttachPageSchema>,
+    pool: web::Data<PgPool>,
+    user_id: UserIdExtractor,
+    permissions: PermissionsExtractor,
+) -> Result<HttpResponse, AttachPageError> {
+    let ticket_id = ticket_id.into_inner();
+
+    let scope = DepartmentScope::resolve(&pool, user_id.0, &permissions.0)
+        .await
+        .context("Failed to resolve department scope")?;
+
+    let can_access = can_access_ticket(&pool, &scope, user_id.0, ticket_id)
+        .await
+        .context("Failed to check ticket access")?
+        .ok_or(AttachPageError::NotFound)?;
+
+    if !can_access {
+        return Err(AttachPageError::InsufficientPermissions);
+    }
+
+    let res = insert_ticket_page(&pool, ticket_id, schema.page_id, user_id.0).await;
+
+    if let Err(e) = &res
+        && let Some(db_err) = e.as_database_error() {
+            if db_err.is_unique_violation() {
+                return Err(AttachPageError::AlreadyAttached);
+            }
+
+            if db_err.is_foreign_key_violation() {
+                return Err(AttachPageError::NotFound);
+            }
+        }
+
+    res.context("Failed to attach page to ticket")?;
+
+    Ok(HttpResponse::Created().finish())
+}
+
+#[tracing::instrument(
+    name = "Insert ticket page into database",
+    skip(pool)
+)]
+async fn insert_ticket_page(
+    pool: &PgPool,
+    ticket_id: TicketId,
+    page_id: PageId,
+    user_id: UserId,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "INSERT INTO ticket_pages (ticket_id, page_id, added_by)
+        VALUES ($1, $2, $3)",
+        ticket_id,
+        page_id,
+        user_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use actix_web::{http::StatusCode, web, HttpResponse, ResponseError};
+use anyhow::Context;
+use sqlx::PgPool;
+
+use crate::{auth::{department_scope::{can_access_ticket, DepartmentScope}, extractor::{PermissionsExtractor, UserIdExtractor}}, schema::{page::PageId, tickets::TicketId}, utils::error_chain_fmt};
+
+#[derive(thiserror::Error)]
+pub enum DetachPageError {
+    #[error("Page is not attached to the ticket")]
+    NotFound,
+    #[error("Insufficient permissions to update this ticket")]
+    InsufficientPermissions,
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for DetachPageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for DetachPageError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            DetachPageError::NotFound => StatusCode::NOT_FOUND,
+            DetachPageError::InsufficientPermissions => StatusCode::FORBIDDEN,
+            DetachPageError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+pub async fn detach_page(
+    path: web::Path<(TicketId, PageId)>,
+    pool: web::Data<PgPool>,
+    user_id: UserIdExtractor,
+    permissions: PermissionsExtractor,
+) -> Result<HttpResponse, DetachPageError> {
+    let (ticket_id, page_id) = path.into_inner();
+
+    let scope = DepartmentScope::resolve(&pool, user_id.0, &permissions.0)
+        .await
+        .context("Failed to resolve department scope")?;
+
+    let can_access = can_access_ticket(&pool, &scope, user_id.0, ticket_id)
+        .await
+        .context("Failed to check ticket access")?
+        .ok_or(DetachPageError::NotFound)?;
+
+    if !can_access {
+        return Err(DetachPageError::InsufficientPermissions);
+    }
+
+    let deleted = delete_ticket_page(&pool, ticket_id, page_id)
+        .await
+        .context("Failed to detach page from ticket")?;
+
+    if !deleted {
+        return Err(DetachPageError::NotFound);
+    }
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[tracing::instrument(
+    name = "Delete ticket page from database",
+    skip(pool)
+)]
+async fn delete_ticket_page(pool: &PgPool, ticket_id: TicketId, page_id: PageId) -> Result<bool, sqlx::Error> {
+    let res = sqlx::query!(
+        "DELETE FROM ticket_pages WHERE ticket_id = $1 AND page_id = $2",
+        ticket_id,
+        page_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(res.rows_affected() == 1)
+}
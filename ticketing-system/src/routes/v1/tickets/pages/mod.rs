@@ -0,0 +1,5 @@
+pub mod attach_page;
+pub mod detach_page;
+
+pub use attach_page::attach_page;
+pub use detach_page::detach_page;
\ No newline at end of file
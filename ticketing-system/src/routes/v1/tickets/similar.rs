@@ -0,0 +1,177 @@
+use actix_web::{web, HttpResponse, ResponseError};
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+
+use crate::{auth::{department_scope::DepartmentScope, extractor::{PermissionsExtractor, UserIdExtractor, UserRoleExtractor}}, routes::v1::tickets::get_tickets::resolve_visibility, schema::{common::UserId, tickets::{TicketId, TicketStatus}}, utils::error_chain_fmt};
+
+const DEFAULT_LOOKBACK_DAYS: i32 = 14;
+const MAX_LOOKBACK_DAYS: i32 = 90;
+const MIN_SIMILARITY: f32 = 0.3;
+const LIMIT: i64 = 5;
+
+#[derive(Deserialize, Debug)]
+pub struct GetSimilarTicketsSchema {
+    pub title: String,
+    pub description: Option<String>,
+    pub building_id: i16,
+    pub cabinet: Option<String>,
+    pub days: Option<i32>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SimilarTicket {
+    /// Left out for tickets the caller can't see.
+    #[serde(flatten)]
+    pub ticket: Option<SimilarTicketDetails>,
+    pub similarity: f32,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SimilarTicketDetails {
+    pub id: TicketId,
+    pub title: String,
+    pub status: TicketStatus,
+    pub cabinet: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Who is asking, as resolved by `resolve_visibility`.
+pub struct SimilarTicketsViewer<'a> {
+    pub user_id: UserId,
+    pub client_id: Option<UserId>,
+    pub department_scope: &'a DepartmentScope,
+}
+
+#[derive(thiserror::Error)]
+pub enum GetSimilarTicketsError {
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for GetSimilarTicketsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for GetSimilarTicketsError {}
+
+/// Lets clients see that the problem might already be reported before submitting a ticket.
+pub async fn get_similar_tickets(
+    schema: web::Query<GetSimilarTicketsSchema>,
+    pool: web::Data<PgPool>,
+    user_id: UserIdExtractor,
+    user_role: UserRoleExtractor,
+    permissions: PermissionsExtractor,
+) -> Result<HttpResponse, GetSimilarTicketsError> {
+    let (client_id, department_scope) = resolve_visibility(&pool, user_id.0, user_role.0, &permissions.0)
+        .await
+        .context("Failed to resolve department scope")?;
+
+    let viewer = SimilarTicketsViewer {
+        user_id: user_id.0,
+        client_id,
+        department_scope: &department_scope,
+    };
+
+    let tickets = find_similar_tickets(&pool, &schema, &viewer)
+        .await
+        .context("Failed to find similar tickets")?;
+
+    Ok(HttpResponse::Ok().json(tickets))
+}
+
+/// Ranks open and in progress tickets of the same building, and the same cabinet
+/// when it is known, by trigram similarity of the title or description.
+/// Tickets the viewer can't see are reduced to their similarity.
+#[tracing::instrument(
+    name = "Find similar tickets in database",
+    skip(pool, viewer)
+)]
+pub async fn find_similar_tickets(
+    pool: &PgPool,
+    schema: &GetSimilarTicketsSchema,
+    viewer: &SimilarTicketsViewer<'_>,
+) -> Result<Vec<SimilarTicket>, sqlx::Error> {
+    let title = schema.title.trim();
+
+    // Trigrams of a couple of letters match almost anything.
+    if title.chars().count() < 3 {
+        return Ok(Vec::new());
+    }
+
+    let days = schema.days
+        .map(|days| days.clamp(1, MAX_LOOKBACK_DAYS))
+        .unwrap_or(DEFAULT_LOOKBACK_DAYS);
+
+    let cabinet = schema.cabinet.as_deref()
+        .map(str::trim)
+        .filter(|cabinet| !cabinet.is_empty());
+
+    let rows = sqlx::query!(
+        r#"
+            SELECT id, title, status, cabinet, created_at, department_id, is_involved AS "is_involved!", score AS "score!"
+            FROM (
+                SELECT
+                    t.id,
+                    t.title,
+                    t.status,
+                    t.cabinet,
+                    t.created_at,
+                    t.department_id,
+                    t.author_id IS NOT DISTINCT FROM $9
+                        OR EXISTS (
+                            SELECT 1 FROM tickets_users tu
+                            WHERE tu.ticket_id = t.id AND tu.assigned_to = $9
+                        ) AS is_involved,
+                    GREATEST(
+                        similarity(t.title, $1),
+                        COALESCE(similarity(t.description, $2), 0)
+                    ) AS score
+                FROM tickets t
+                WHERE t.status = ANY($3)
+                    AND t.building_id = $4
+                    AND ($5::text IS NULL OR t.cabinet IS NULL OR LOWER(TRIM(t.cabinet)) = LOWER($5))
+                    AND t.created_at >= NOW() - make_interval(days => $6)
+            ) ranked
+            WHERE score >= $7
+            ORDER BY score DESC, created_at DESC
+            LIMIT $8
+        "#,
+        title,
+        schema.description.as_deref(),
+        &[TicketStatus::Open as i16, TicketStatus::InProgress as i16],
+        schema.building_id,
+        cabinet,
+        days,
+        MIN_SIMILARITY,
+        LIMIT,
+        viewer.user_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    // Same rule as the ticket list: clients see their own tickets, staff
+    // the ones of their departments and the ones they are involved in.
+    let tickets = rows.into_iter()
+        .map(|row| {
+            let is_visible = row.is_involved
+                || (viewer.client_id.is_none() && viewer.department_scope.contains(row.department_id));
+
+            SimilarTicket {
+                ticket: is_visible.then(|| SimilarTicketDetails {
+                    id: row.id,
+                    title: row.title,
+                    status: TicketStatus::from(row.status),
+                    cabinet: row.cabinet,
+                    created_at: row.created_at,
+                }),
+                similarity: row.score,
+            }
+        })
+        .collect();
+
+    Ok(tickets)
+}
@@ -1,11 +1,15 @@
-use actix_web::{web, HttpResponse, ResponseError};
+use actix_web::{http::StatusCode, web, HttpResponse, ResponseError};
 use anyhow::Context;
 use sqlx::{PgPool, Postgres, Transaction};
 
-use crate::{auth::extractor::UserIdExtractor, schema::{common::UserId, tickets::{TicketId, TicketStatus}}, utils::error_chain_fmt};
+use crate::{auth::{department_scope::get_ticket_department, extractor::{PermissionsExtractor, UserIdExtractor}}, routes::v1::tickets::assign_ticket::can_assign_others, schema::{common::UserId, tickets::{TicketId, TicketStatus}}, utils::error_chain_fmt};
 
 #[derive(thiserror::Error)]
 pub enum UnassignTicketError {
+    #[error("Insufficient permissions to unassign this ticket")]
+    InsufficientPermissions,
+    #[error("Ticket not found")]
+    TicketNotFound,
     #[error(transparent)]
     Unexpected(#[from] anyhow::Error)
 }
@@ -16,7 +20,15 @@ impl std::fmt::Debug for UnassignTicketError {
     }
 }
 
-impl ResponseError for UnassignTicketError {}
+impl ResponseError for UnassignTicketError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            UnassignTicketError::InsufficientPermissions => StatusCode::FORBIDDEN,
+            UnassignTicketError::TicketNotFound => StatusCode::NOT_FOUND,
+            UnassignTicketError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
 
 pub async fn unassign_ticket_from_self(
     id: web::Path<TicketId>,
@@ -32,10 +44,21 @@ pub async fn unassign_ticket_from_self(
 
 pub async fn unassign_ticket_from_user(
     id: web::Path<(TicketId, UserId)>,
+    caller_id: UserIdExtractor,
+    permissions: PermissionsExtractor,
     pool: web::Data<PgPool>,
 ) -> Result<HttpResponse, UnassignTicketError> {
     let (ticket_id, user_id) = id.into_inner();
 
+    let department_id = get_ticket_department(&pool, ticket_id)
+        .await
+        .context("Failed to get ticket department")?
+        .ok_or(UnassignTicketError::TicketNotFound)?;
+
+    if !can_assign_others(&pool, caller_id.0, &permissions.0, department_id).await? {
+        return Err(UnassignTicketError::InsufficientPermissions);
+    }
+
     unassign_ticket(&pool, ticket_id, user_id).await?;
 
     Ok(HttpResponse::Ok().finish())
@@ -6,7 +6,7 @@ use chrono::{DateTime, Utc};
 use serde::Deserialize;
 use sqlx::{Execute as _, PgPool, Postgres, Transaction};
 
-use crate::{build_update_query, domain::description::Description, routes::v1::tickets::create_ticket::{insert_attachments, upload_attachments}, schema::tickets::{TicketId, TicketPriority, TicketSource, TicketStatus}, services::attachment::{AttachmentService, AttachmentServiceError, AttachmentType}, utils::{cleanup_images, error_chain_fmt}};
+use crate::{auth::{department_scope::{can_access_ticket, DepartmentScope}, extractor::{PermissionsExtractor, UserIdExtractor}}, build_update_query, domain::description::Description, routes::v1::tickets::create_ticket::{insert_attachments, upload_attachments}, schema::tickets::{TicketId, TicketPriority, TicketSource, TicketStatus}, services::attachment::{AttachmentService, AttachmentServiceError, AttachmentType}, utils::{cleanup_images, error_chain_fmt}};
 
 #[derive(Deserialize, Debug)]
 pub struct UpdateTicketSchema {
@@ -55,6 +55,10 @@ pub enum UpdateTicketError {
     AttachmentServiceError(#[from] AttachmentServiceError),
     #[error("All fields are empty")]
     AllFieldsEmpty,
+    #[error("Insufficient permissions to update this ticket")]
+    InsufficientPermissions,
+    #[error("Ticket not found")]
+    NotFound,
     #[error(transparent)]
     Unexpected(#[from] anyhow::Error),
 }
@@ -70,6 +74,8 @@ impl ResponseError for UpdateTicketError {
         match self {
             UpdateTicketError::AttachmentServiceError(e) => e.status_code(),
             UpdateTicketError::AllFieldsEmpty => StatusCode::BAD_REQUEST,
+            UpdateTicketError::InsufficientPermissions => StatusCode::FORBIDDEN,
+            UpdateTicketError::NotFound => StatusCode::NOT_FOUND,
             UpdateTicketError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
@@ -80,10 +86,26 @@ pub async fn update_ticket(
     MultipartForm(form): MultipartForm<UpdateTicketForm>,
     pool: web::Data<PgPool>,
     service: web::Data<AttachmentService>,
+    user_id: UserIdExtractor,
+    permissions: PermissionsExtractor,
 ) -> Result<HttpResponse, UpdateTicketError> {
     let schema = form.fields.0;
     let ticket_id = ticket_id.into_inner();
 
+    let scope = DepartmentScope::resolve(&pool, user_id.0, &permissions.0)
+        .await
+        .context("Failed to resolve department scope")?;
+
+    let can_access = can_access_ticket(&pool, &scope, user_id.0, ticket_id)
+        .await
+        .context("Failed to check ticket access")?
+        .ok_or(UpdateTicketError::NotFound)?;
+
+    // Tickets can be moved only to the user's own departments.
+    if !can_access || schema.department_id.is_some_and(|id| !scope.contains(id)) {
+        return Err(UpdateTicketError::InsufficientPermissions);
+    }
+
     let all_fields_none = schema.all_fields_none();
 
     if all_fields_none
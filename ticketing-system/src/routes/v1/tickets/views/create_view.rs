@@ -0,0 +1,106 @@
+use actix_web::{http::StatusCode, web, HttpResponse, ResponseError};
+use anyhow::Context;
+use garde_actix_web::web::Json;
+use serde_json::{Map, Value};
+use sqlx::PgPool;
+
+use crate::{auth::{department_scope::DepartmentScope, extractor::{PermissionsExtractor, UserIdExtractor}}, routes::v1::tickets::get_tickets::GetTicketsSchema, schema::{common::UserId, tickets::{TicketViewId, TicketViewSchema}}, utils::error_chain_fmt};
+
+#[derive(thiserror::Error)]
+pub enum CreateViewError {
+    #[error("Invalid filters: {0}")]
+    InvalidFilters(serde_json::Error),
+    #[error("Views can only be shared with your own departments")]
+    InsufficientPermissions,
+    #[error("Department does not exist")]
+    InvalidDepartment,
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for CreateViewError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for CreateViewError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            CreateViewError::InvalidFilters(_) => StatusCode::BAD_REQUEST,
+            CreateViewError::InsufficientPermissions => StatusCode::FORBIDDEN,
+            CreateViewError::InvalidDepartment => StatusCode::BAD_REQUEST,
+            CreateViewError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+pub async fn create_view(
+    Json(schema): Json<TicketViewSchema>,
+    pool: web::Data<PgPool>,
+    user_id: UserIdExtractor,
+    permissions: PermissionsExtractor,
+) -> Result<HttpResponse, CreateViewError> {
+    let filters = normalize_filters(schema.filters)
+        .map_err(CreateViewError::InvalidFilters)?;
+
+    if let Some(department_id) = schema.department_id {
+        let scope = DepartmentScope::resolve(&pool, user_id.0, &permissions.0)
+            .await
+            .context("Failed to resolve department scope")?;
+
+        if !scope.contains(department_id) {
+            return Err(CreateViewError::InsufficientPermissions);
+        }
+    }
+
+    let res = insert_view(&pool, user_id.0, &schema.name, &filters, schema.department_id).await;
+
+    if let Err(e) = &res
+        && let Some(db_err) = e.as_database_error()
+            && db_err.is_foreign_key_violation() {
+                return Err(CreateViewError::InvalidDepartment);
+            }
+
+    let id = res.context("Failed to insert ticket view")?;
+
+    Ok(HttpResponse::Created().json(serde_json::json!({ "id": id })))
+}
+
+/// Drops the pagination parameters and checks that the rest is what
+/// `GET /v1/tickets/` accepts.
+pub fn normalize_filters(mut filters: Map<String, Value>) -> Result<Value, serde_json::Error> {
+    for key in ["page", "cursor", "with_total"] {
+        filters.remove(key);
+    }
+
+    let filters = Value::Object(filters);
+
+    serde_json::from_value::<GetTicketsSchema>(filters.clone())?;
+
+    Ok(filters)
+}
+
+#[tracing::instrument(
+    name = "Insert ticket view into database",
+    skip(pool)
+)]
+async fn insert_view(
+    pool: &PgPool,
+    owner_id: UserId,
+    name: &str,
+    filters: &Value,
+    department_id: Option<i16>,
+) -> Result<TicketViewId, sqlx::Error> {
+    sqlx::query_scalar!(
+        "INSERT INTO ticket_views (owner_id, name, filters, department_id)
+        VALUES ($1, $2, $3, $4)
+        RETURNING id",
+        owner_id,
+        name,
+        filters,
+        department_id
+    )
+    .fetch_one(pool)
+    .await
+}
@@ -0,0 +1,61 @@
+use actix_web::{http::StatusCode, web, HttpResponse, ResponseError};
+use anyhow::Context;
+use sqlx::PgPool;
+
+use crate::{auth::extractor::UserIdExtractor, schema::{common::UserId, tickets::TicketViewId}, utils::error_chain_fmt};
+
+#[derive(thiserror::Error)]
+pub enum DeleteViewError {
+    #[error("View not found")]
+    NotFound,
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for DeleteViewError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for DeleteViewError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            DeleteViewError::NotFound => StatusCode::NOT_FOUND,
+            DeleteViewError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+/// Only the owner can delete a view, even a shared one.
+pub async fn delete_view(
+    id: web::Path<TicketViewId>,
+    pool: web::Data<PgPool>,
+    user_id: UserIdExtractor,
+) -> Result<HttpResponse, DeleteViewError> {
+    let deleted = delete(&pool, id.into_inner(), user_id.0)
+        .await
+        .context("Failed to delete ticket view")?;
+
+    if !deleted {
+        return Err(DeleteViewError::NotFound);
+    }
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[tracing::instrument(
+    name = "Delete ticket view from database",
+    skip(pool)
+)]
+async fn delete(pool: &PgPool, id: TicketViewId, owner_id: UserId) -> Result<bool, sqlx::Error> {
+    let res = sqlx::query!(
+        "DELETE FROM ticket_views WHERE id = $1 AND owner_id = $2",
+        id,
+        owner_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(res.rows_affected() == 1)
+}
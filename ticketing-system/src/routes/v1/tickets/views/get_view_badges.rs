@@ -0,0 +1,67 @@
+use actix_web::{web, HttpResponse, ResponseError};
+use anyhow::Context;
+use sqlx::PgPool;
+
+use crate::{auth::{department_scope::DepartmentScope, extractor::{PermissionsExtractor, UserIdExtractor, UserRoleExtractor}}, routes::v1::tickets::{get_tickets::{count_new_tickets, GetTicketsSchema}, views::{get_view_tickets::get_last_visits, get_views::get_visible_views}}, schema::tickets::TicketViewBadge, utils::error_chain_fmt};
+
+#[derive(thiserror::Error)]
+pub enum GetViewBadgesError {
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for GetViewBadgesError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for GetViewBadgesError {}
+
+/// Counts of tickets created since the last visit of every visible view,
+/// or since the view was created for the ones never opened.
+pub async fn get_view_badges(
+    pool: web::Data<PgPool>,
+    user_id: UserIdExtractor,
+    user_role: UserRoleExtractor,
+    permissions: PermissionsExtractor,
+) -> Result<HttpResponse, GetViewBadgesError> {
+    let scope = DepartmentScope::resolve(&pool, user_id.0, &permissions.0)
+        .await
+        .context("Failed to resolve department scope")?;
+
+    let views = get_visible_views(&pool, None, user_id.0, &scope)
+        .await
+        .context("Failed to get ticket views")?;
+
+    let view_ids: Vec<_> = views.iter().map(|view| view.id).collect();
+
+    let visits = get_last_visits(&pool, &view_ids, user_id.0)
+        .await
+        .context("Failed to get last visits of ticket views")?;
+
+    let mut ids = Vec::with_capacity(views.len());
+    let mut queries = Vec::with_capacity(views.len());
+
+    for view in views {
+        // Views saved before a filter changed shape have no badge rather than failing the sidebar.
+        let Ok(schema) = serde_json::from_value::<GetTicketsSchema>(view.filters) else {
+            tracing::warn!("Ticket view {} has invalid filters", view.id);
+            continue;
+        };
+
+        let visited_at = visits.get(&view.id).copied().unwrap_or(view.created_at);
+
+        ids.push(view.id);
+        queries.push((schema, visited_at));
+    }
+
+    let counts = count_new_tickets(&pool, &queries, user_id.0, user_role.0, &permissions.0).await?;
+
+    let badges: Vec<_> = ids.into_iter()
+        .zip(counts)
+        .map(|(id, new_tickets)| TicketViewBadge { id, new_tickets })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(badges))
+}
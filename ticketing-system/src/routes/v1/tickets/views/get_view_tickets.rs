@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+
+use actix_web::{http::StatusCode, web, HttpResponse, ResponseError};
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use sqlx::PgPool;
+
+use crate::{auth::{department_scope::DepartmentScope, extractor::{PermissionsExtractor, UserIdExtractor, UserRoleExtractor}}, routes::v1::tickets::{get_tickets::{list_tickets, GetTicketsError, GetTicketsSchema}, views::get_views::get_visible_views}, schema::{common::UserId, tickets::{TicketId, TicketViewId}}, utils::error_chain_fmt};
+
+/// Pagination is not part of a view and comes with every request.
+#[derive(Deserialize, Debug)]
+pub struct GetViewTicketsSchema {
+    pub page: Option<TicketId>,
+    pub page_size: Option<i8>,
+    pub cursor: Option<String>,
+    #[serde(default)]
+    pub with_total: bool,
+    /// Only tickets created since the previous visit.
+    #[serde(default)]
+    pub only_new: bool,
+}
+
+#[derive(thiserror::Error)]
+pub enum GetViewTicketsError {
+    #[error("View not found")]
+    NotFound,
+    #[error(transparent)]
+    Listing(#[from] GetTicketsError),
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for GetViewTicketsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for GetViewTicketsError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            GetViewTicketsError::NotFound => StatusCode::NOT_FOUND,
+            GetViewTicketsError::Listing(e) => e.status_code(),
+            GetViewTicketsError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+/// Executes the view as `GET /v1/tickets/` and marks it as visited.
+pub async fn get_view_tickets(
+    id: web::Path<TicketViewId>,
+    query: web::Query<GetViewTicketsSchema>,
+    pool: web::Data<PgPool>,
+    user_id: UserIdExtractor,
+    user_role: UserRoleExtractor,
+    permissions: PermissionsExtractor,
+) -> Result<HttpResponse, GetViewTicketsError> {
+    let id = id.into_inner();
+    let query = query.into_inner();
+
+    let scope = DepartmentScope::resolve(&pool, user_id.0, &permissions.0)
+        .await
+        .context("Failed to resolve department scope")?;
+
+    let view = get_visible_views(&pool, Some(id), user_id.0, &scope)
+        .await
+        .context("Failed to get ticket view")?
+        .pop()
+        .ok_or(GetViewTicketsError::NotFound)?;
+
+    let mut schema: GetTicketsSchema = serde_json::from_value(view.filters)
+        .context("Stored view filters are no longer valid")?;
+
+    schema.page = query.page;
+    schema.page_size = query.page_size.or(schema.page_size);
+    schema.cursor = query.cursor;
+    schema.with_total = query.with_total;
+
+    let created_after = if query.only_new {
+        let visited_at = get_last_visit(&pool, id, user_id.0)
+            .await
+            .context("Failed to get last visit of ticket view")?;
+
+        Some(visited_at.unwrap_or(view.created_at))
+    } else {
+        None
+    };
+
+    let listing = list_tickets(
+        &pool,
+        &schema,
+        created_after,
+        user_id.0,
+        user_role.0,
+        &permissions.0
+    )
+    .await?;
+
+    // Further pages of the same listing keep the badge as it was.
+    if schema.page.unwrap_or(1) == 1 && schema.cursor.as_deref().is_none_or(str::is_empty) {
+        mark_visited(&pool, id, user_id.0)
+            .await
+            .context("Failed to mark ticket view as visited")?;
+    }
+
+    Ok(HttpResponse::Ok().json(listing))
+}
+
+#[tracing::instrument(
+    name = "Get last visit of ticket view from database",
+    skip(pool)
+)]
+pub async fn get_last_visit(
+    pool: &PgPool,
+    view_id: TicketViewId,
+    user_id: UserId,
+) -> Result<Option<DateTime<Utc>>, sqlx::Error> {
+    sqlx::query_scalar!(
+        "SELECT visited_at FROM ticket_view_visits WHERE view_id = $1 AND user_id = $2",
+        view_id,
+        user_id
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+#[tracing::instrument(
+    name = "Get last visits of ticket views from database",
+    skip(pool)
+)]
+pub async fn get_last_visits(
+    pool: &PgPool,
+    view_ids: &[TicketViewId],
+    user_id: UserId,
+) -> Result<HashMap<TicketViewId, DateTime<Utc>>, sqlx::Error> {
+    let visits = sqlx::query!(
+        "SELECT view_id, visited_at FROM ticket_view_visits WHERE view_id = ANY($1) AND user_id = $2",
+        view_ids,
+        user_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(visits.into_iter().map(|v| (v.view_id, v.visited_at)).collect())
+}
+
+#[tracing::instrument(
+    name = "Mark ticket view as visited",
+    skip(pool)
+)]
+async fn mark_visited(pool: &PgPool, view_id: TicketViewId, user_id: UserId) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "INSERT INTO ticket_view_visits (view_id, user_id)
+        VALUES ($1, $2)
+        ON CONFLICT (view_id, user_id) DO UPDATE SET visited_at = NOW()",
+        view_id,
+        user_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
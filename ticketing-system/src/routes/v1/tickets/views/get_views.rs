@@ -0,0 +1,71 @@
+use actix_web::{web, HttpResponse, ResponseError};
+use anyhow::Context;
+use sqlx::PgPool;
+
+use crate::{auth::{department_scope::DepartmentScope, extractor::{PermissionsExtractor, UserIdExtractor}}, schema::{common::UserId, tickets::{TicketView, TicketViewId}}, utils::error_chain_fmt};
+
+#[derive(thiserror::Error)]
+pub enum GetViewsError {
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for GetViewsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for GetViewsError {}
+
+/// Own views and the ones shared with the departments of the user.
+pub async fn get_views(
+    pool: web::Data<PgPool>,
+    user_id: UserIdExtractor,
+    permissions: PermissionsExtractor,
+) -> Result<HttpResponse, GetViewsError> {
+    let scope = DepartmentScope::resolve(&pool, user_id.0, &permissions.0)
+        .await
+        .context("Failed to resolve department scope")?;
+
+    let views = get_visible_views(&pool, None, user_id.0, &scope)
+        .await
+        .context("Failed to get ticket views")?;
+
+    Ok(HttpResponse::Ok().json(views))
+}
+
+#[tracing::instrument(
+    name = "Get visible ticket views from database",
+    skip(pool)
+)]
+pub async fn get_visible_views(
+    pool: &PgPool,
+    id: Option<TicketViewId>,
+    user_id: UserId,
+    scope: &DepartmentScope,
+) -> Result<Vec<TicketView>, sqlx::Error> {
+    let (all_departments, departments) = match scope {
+        DepartmentScope::All => (true, &[][..]),
+        DepartmentScope::Departments(departments) => (false, departments.as_slice()),
+    };
+
+    sqlx::query_as!(
+        TicketView,
+        "SELECT id, owner_id, name, filters, department_id, created_at
+        FROM ticket_views
+        WHERE ($1::int IS NULL OR id = $1)
+            AND (
+                owner_id = $2
+                OR ($3 AND department_id IS NOT NULL)
+                OR department_id = ANY($4)
+            )
+        ORDER BY name, id",
+        id,
+        user_id,
+        all_departments,
+        departments
+    )
+    .fetch_all(pool)
+    .await
+}
@@ -0,0 +1,13 @@
+pub mod get_views;
+pub mod create_view;
+pub mod update_view;
+pub mod delete_view;
+pub mod get_view_tickets;
+pub mod get_view_badges;
+
+pub use get_views::get_views;
+pub use create_view::create_view;
+pub use update_view::update_view;
+pub use delete_view::delete_view;
+pub use get_view_tickets::get_view_tickets;
+pub use get_view_badges::get_view_badges;
\ No newline at end of file
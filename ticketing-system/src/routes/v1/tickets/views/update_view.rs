@@ -0,0 +1,105 @@
+use actix_web::{http::StatusCode, web, HttpResponse, ResponseError};
+use anyhow::Context;
+use garde_actix_web::web::Json;
+use serde_json::Value;
+use sqlx::PgPool;
+
+use crate::{auth::{department_scope::DepartmentScope, extractor::{PermissionsExtractor, UserIdExtractor}}, routes::v1::tickets::views::create_view::normalize_filters, schema::{common::UserId, tickets::{TicketViewId, TicketViewSchema}}, utils::error_chain_fmt};
+
+#[derive(thiserror::Error)]
+pub enum UpdateViewError {
+    #[error("View not found")]
+    NotFound,
+    #[error("Invalid filters: {0}")]
+    InvalidFilters(serde_json::Error),
+    #[error("Views can only be shared with your own departments")]
+    InsufficientPermissions,
+    #[error("Department does not exist")]
+    InvalidDepartment,
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for UpdateViewError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for UpdateViewError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            UpdateViewError::NotFound => StatusCode::NOT_FOUND,
+            UpdateViewError::InvalidFilters(_) => StatusCode::BAD_REQUEST,
+            UpdateViewError::InsufficientPermissions => StatusCode::FORBIDDEN,
+            UpdateViewError::InvalidDepartment => StatusCode::BAD_REQUEST,
+            UpdateViewError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+/// Replaces the view. Only its owner can change it.
+pub async fn update_view(
+    id: web::Path<TicketViewId>,
+    Json(schema): Json<TicketViewSchema>,
+    pool: web::Data<PgPool>,
+    user_id: UserIdExtractor,
+    permissions: PermissionsExtractor,
+) -> Result<HttpResponse, UpdateViewError> {
+    let filters = normalize_filters(schema.filters)
+        .map_err(UpdateViewError::InvalidFilters)?;
+
+    if let Some(department_id) = schema.department_id {
+        let scope = DepartmentScope::resolve(&pool, user_id.0, &permissions.0)
+            .await
+            .context("Failed to resolve department scope")?;
+
+        if !scope.contains(department_id) {
+            return Err(UpdateViewError::InsufficientPermissions);
+        }
+    }
+
+    let res = update(&pool, id.into_inner(), user_id.0, &schema.name, &filters, schema.department_id).await;
+
+    if let Err(e) = &res
+        && let Some(db_err) = e.as_database_error()
+            && db_err.is_foreign_key_violation() {
+                return Err(UpdateViewError::InvalidDepartment);
+            }
+
+    let updated = res.context("Failed to update ticket view")?;
+
+    if !updated {
+        return Err(UpdateViewError::NotFound);
+    }
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[tracing::instrument(
+    name = "Update ticket view in database",
+    skip(pool)
+)]
+async fn update(
+    pool: &PgPool,
+    id: TicketViewId,
+    owner_id: UserId,
+    name: &str,
+    filters: &Value,
+    department_id: Option<i16>,
+) -> Result<bool, sqlx::Error> {
+    let res = sqlx::query!(
+        "UPDATE ticket_views
+        SET name = $3, filters = $4, department_id = $5
+        WHERE id = $1 AND owner_id = $2",
+        id,
+        owner_id,
+        name,
+        filters,
+        department_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(res.rows_affected() == 1)
+}
@@ -0,0 +1,302 @@
+use actix_web::{http::StatusCode, web, HttpResponse, ResponseError};
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+use crate::{
+    auth::{extractor::{PermissionsExtractor, UserIdExtractor}, permission::Permission, types::{UserRole, UserStatus}},
+    schema::{absence::{Absence, AbsenceId, CreateAbsenceSchema}, common::UserId},
+    services::absence::process_absence,
+    utils::error_chain_fmt,
+};
+
+struct AbsenceState {
+    pub started: bool,
+    pub finished: bool,
+}
+
+#[derive(thiserror::Error)]
+pub enum AbsenceError {
+    #[error("Insufficient permissions to manage absences of this user")]
+    InsufficientPermissions,
+    #[error("User not found")]
+    UserNotFound,
+    #[error("Absence not found")]
+    AbsenceNotFound,
+    #[error("Absence kind must not be `available`")]
+    InvalidKind,
+    #[error("Absence must end after it starts and in the future")]
+    InvalidRange,
+    #[error("Deputy must be another active staff member")]
+    InvalidDeputy,
+    #[error("Absence overlaps with another one")]
+    Overlap,
+    #[error("Absence has already ended")]
+    AlreadyFinished,
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for AbsenceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for AbsenceError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            AbsenceError::InsufficientPermissions => StatusCode::FORBIDDEN,
+            AbsenceError::UserNotFound | AbsenceError::AbsenceNotFound => StatusCode::NOT_FOUND,
+            AbsenceError::InvalidKind
+            | AbsenceError::InvalidRange
+            | AbsenceError::InvalidDeputy
+            | AbsenceError::AlreadyFinished => StatusCode::BAD_REQUEST,
+            AbsenceError::Overlap => StatusCode::CONFLICT,
+            AbsenceError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+pub async fn get_absences(
+    user_id: web::Path<UserId>,
+    caller_id: UserIdExtractor,
+    permissions: PermissionsExtractor,
+    pool: web::Data<PgPool>,
+) -> Result<HttpResponse, AbsenceError> {
+    let user_id = user_id.into_inner();
+
+    check_access(&permissions, caller_id.0, user_id)?;
+
+    let absences = select_absences(&pool, user_id)
+        .await
+        .context("Failed to get absences")?;
+
+    Ok(HttpResponse::Ok().json(absences))
+}
+
+pub async fn create_absence(
+    user_id: web::Path<UserId>,
+    caller_id: UserIdExtractor,
+    permissions: PermissionsExtractor,
+    web::Json(schema): web::Json<CreateAbsenceSchema>,
+    pool: web::Data<PgPool>,
+) -> Result<HttpResponse, AbsenceError> {
+    let user_id = user_id.into_inner();
+
+    check_access(&permissions, caller_id.0, user_id)?;
+
+    if schema.kind == UserStatus::Available {
+        return Err(AbsenceError::InvalidKind);
+    }
+
+    if schema.starts_at >= schema.ends_at || schema.ends_at <= Utc::now() {
+        return Err(AbsenceError::InvalidRange);
+    }
+
+    if !is_user_active(&pool, user_id).await.context("Failed to check user")? {
+        return Err(AbsenceError::UserNotFound);
+    }
+
+    if let Some(deputy_id) = schema.deputy_id
+        && (deputy_id == user_id || !is_active_staff(&pool, deputy_id).await.context("Failed to check deputy")?) {
+        return Err(AbsenceError::InvalidDeputy);
+    }
+
+    if is_overlapping(&pool, user_id, schema.starts_at, schema.ends_at)
+        .await
+        .context("Failed to check overlapping absences")? {
+        return Err(AbsenceError::Overlap);
+    }
+
+    // The constraint catches what the check above misses under concurrency.
+    let id = insert_absence(&pool, user_id, caller_id.0, &schema)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(db_err) => match db_err.constraint() {
+                Some("user_absences_no_overlap") => AbsenceError::Overlap,
+                _ => AbsenceError::Unexpected(anyhow::anyhow!(db_err).context("Failed to insert absence")),
+            },
+            e => AbsenceError::Unexpected(anyhow::anyhow!(e).context("Failed to insert absence")),
+        })?;
+
+    // An absence starting right away should not wait for the scheduler.
+    process_absence(&pool, id).await;
+
+    Ok(HttpResponse::Created().json(serde_json::json!({ "id": id })))
+}
+
+/// Deletes a planned absence, or ends an ongoing one now.
+pub async fn cancel_absence(
+    path: web::Path<(UserId, AbsenceId)>,
+    caller_id: UserIdExtractor,
+    permissions: PermissionsExtractor,
+    pool: web::Data<PgPool>,
+) -> Result<HttpResponse, AbsenceError> {
+    let (user_id, absence_id) = path.into_inner();
+
+    check_access(&permissions, caller_id.0, user_id)?;
+
+    let state = get_absence_state(&pool, user_id, absence_id)
+        .await
+        .context("Failed to get absence")?
+        .ok_or(AbsenceError::AbsenceNotFound)?;
+
+    if state.finished {
+        return Err(AbsenceError::AlreadyFinished);
+    }
+
+    if state.started {
+        end_absence_now(&pool, absence_id)
+            .await
+            .context("Failed to end absence")?;
+
+        process_absence(&pool, absence_id).await;
+    } else {
+        delete_absence(&pool, absence_id)
+            .await
+            .context("Failed to delete absence")?;
+    }
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+fn check_access(
+    permissions: &PermissionsExtractor,
+    caller_id: UserId,
+    user_id: UserId,
+) -> Result<(), AbsenceError> {
+    if caller_id != user_id && !permissions.0.has(Permission::UsersManageStatus) {
+        return Err(AbsenceError::InsufficientPermissions);
+    }
+
+    Ok(())
+}
+
+#[tracing::instrument(name = "Get user absences from the database", skip(pool))]
+async fn select_absences(pool: &PgPool, user_id: UserId) -> Result<Vec<Absence>, sqlx::Error> {
+    sqlx::query_as!(
+        Absence,
+        r#"
+        SELECT a.id, a.user_id, a.kind, a.starts_at, a.ends_at, a.deputy_id,
+            d.name AS "deputy_name?", a.reassign_tickets, a.started, a.finished, a.created_at
+        FROM user_absences a
+        LEFT JOIN users d ON d.id = a.deputy_id
+        WHERE a.user_id = $1
+        ORDER BY a.starts_at DESC
+        "#,
+        user_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+#[tracing::instrument(name = "Check if user is active staff", skip(pool))]
+async fn is_active_staff(pool: &PgPool, user_id: UserId) -> Result<bool, sqlx::Error> {
+    let exists = sqlx::query_scalar!(
+        "SELECT EXISTS(SELECT 1 FROM users WHERE id = $1 AND is_active AND role >= $2)",
+        user_id,
+        UserRole::Employee as i16
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(exists.unwrap_or(false))
+}
+
+#[tracing::instrument(name = "Check if user is active", skip(pool))]
+async fn is_user_active(pool: &PgPool, user_id: UserId) -> Result<bool, sqlx::Error> {
+    let exists = sqlx::query_scalar!(
+        "SELECT EXISTS(SELECT 1 FROM users WHERE id = $1 AND is_active)",
+        user_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(exists.unwrap_or(false))
+}
+
+#[tracing::instrument(name = "Check for overlapping absences", skip(pool))]
+async fn is_overlapping(
+    pool: &PgPool,
+    user_id: UserId,
+    starts_at: DateTime<Utc>,
+    ends_at: DateTime<Utc>,
+) -> Result<bool, sqlx::Error> {
+    let exists = sqlx::query_scalar!(
+        "SELECT EXISTS(
+            SELECT 1 FROM user_absences
+            WHERE user_id = $1 AND NOT finished AND starts_at < $3 AND ends_at > $2
+        )",
+        user_id,
+        starts_at,
+        ends_at
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(exists.unwrap_or(false))
+}
+
+#[tracing::instrument(name = "Insert absence into the database", skip(pool))]
+async fn insert_absence(
+    pool: &PgPool,
+    user_id: UserId,
+    created_by: UserId,
+    schema: &CreateAbsenceSchema,
+) -> Result<AbsenceId, sqlx::Error> {
+    sqlx::query_scalar!(
+        "INSERT INTO user_absences (user_id, kind, starts_at, ends_at, deputy_id, reassign_tickets, created_by)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING id",
+        user_id,
+        schema.kind.clone() as i16,
+        schema.starts_at,
+        schema.ends_at,
+        schema.deputy_id,
+        schema.reassign_tickets,
+        created_by
+    )
+    .fetch_one(pool)
+    .await
+}
+
+#[tracing::instrument(name = "Get absence state from the database", skip(pool))]
+async fn get_absence_state(
+    pool: &PgPool,
+    user_id: UserId,
+    absence_id: AbsenceId,
+) -> Result<Option<AbsenceState>, sqlx::Error> {
+    sqlx::query_as!(
+        AbsenceState,
+        "SELECT started, finished FROM user_absences WHERE id = $1 AND user_id = $2",
+        absence_id,
+        user_id
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+#[tracing::instrument(name = "End absence now", skip(pool))]
+async fn end_absence_now(pool: &PgPool, absence_id: AbsenceId) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "UPDATE user_absences SET ends_at = NOW() WHERE id = $1",
+        absence_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+#[tracing::instrument(name = "Delete absence from the database", skip(pool))]
+async fn delete_absence(pool: &PgPool, absence_id: AbsenceId) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "DELETE FROM user_absences WHERE id = $1",
+        absence_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
@@ -1,14 +1,16 @@
 use actix_web::{http::StatusCode, web, HttpResponse, ResponseError};
 use anyhow::Context;
 use serde::Deserialize;
-use sqlx::{postgres::PgQueryResult, PgPool};
+use sqlx::{postgres::PgQueryResult, PgPool, Postgres, Transaction};
 
-use crate::{auth::{extractor::UserRoleExtractor, types::UserRole}, schema::common::UserId, utils::error_chain_fmt};
+use crate::{auth::{extractor::{DeviceInfo, UserIdExtractor, UserRoleExtractor}, types::UserRole}, schema::{audit::AuditAction, common::UserId}, services::audit::AuditEntry, utils::error_chain_fmt};
 
+/// Either a built-in `role` or the `role_id` of any role.
 #[derive(Deserialize)]
 pub struct ChangeRoleSchema {
     pub id: UserId,
-    pub role: UserRole,
+    pub role: Option<UserRole>,
+    pub role_id: Option<i16>,
 }
 
 #[derive(thiserror::Error)]
@@ -17,6 +19,8 @@ pub enum ChangeRoleError {
     InsufficientPermissions,
     #[error("User not found")]
     UserNotFound,
+    #[error("Role not found")]
+    RoleNotFound,
     #[error(transparent)]
     Unexpected(#[from] anyhow::Error)
 }
@@ -32,6 +36,7 @@ impl ResponseError for ChangeRoleError {
         match self {
             ChangeRoleError::InsufficientPermissions => StatusCode::FORBIDDEN,
             ChangeRoleError::UserNotFound => StatusCode::BAD_REQUEST,
+            ChangeRoleError::RoleNotFound => StatusCode::BAD_REQUEST,
             ChangeRoleError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
@@ -40,42 +45,84 @@ impl ResponseError for ChangeRoleError {
 pub async fn change_user_role(
     web::Json(schema): web::Json<ChangeRoleSchema>,
     role: UserRoleExtractor,
+    user_id: UserIdExtractor,
+    device: DeviceInfo,
     pool: web::Data<PgPool>,
 ) -> Result<HttpResponse, ChangeRoleError> {
-    if role.0 <= schema.role {
+    let role_id = schema.role_id
+        .or(schema.role.map(|role| role as i16))
+        .ok_or(ChangeRoleError::RoleNotFound)?;
+
+    let level = get_role_level(&pool, role_id)
+        .await
+        .context("Failed to get role level from the database.")?
+        .ok_or(ChangeRoleError::RoleNotFound)?;
+
+    if role.0 <= level {
         return Err(ChangeRoleError::InsufficientPermissions)
     }
 
-    let res = change_role(schema.id, schema.role, &pool, role.0).await
+    let mut transaction = pool.begin()
+        .await
+        .context("Failed to begin transaction")?;
+
+    let res = change_role(schema.id, role_id, &mut transaction, role.0).await
         .context("Failed to change user role in the database.")?;
 
     if res.rows_affected() == 0 {
         return Err(ChangeRoleError::UserNotFound)
     }
 
+    AuditEntry::new(AuditAction::RoleChanged, &device)
+        .actor(user_id.0)
+        .target(schema.id)
+        .details(serde_json::json!({ "role_id": role_id }))
+        .record(&mut *transaction)
+        .await
+        .context("Failed to write audit log")?;
+
+    transaction.commit()
+        .await
+        .context("Failed to commit transaction")?;
+
     Ok(HttpResponse::Ok().finish())
 }
 
 #[tracing::instrument(
-    name = "Change user role",
+    name = "Get role level",
     skip(pool)
 )]
+async fn get_role_level(pool: &PgPool, role_id: i16) -> Result<Option<UserRole>, sqlx::Error> {
+    let level = sqlx::query_scalar!(
+        "SELECT level FROM roles WHERE id = $1",
+        role_id
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(level.map(UserRole::from))
+}
+
+#[tracing::instrument(
+    name = "Change user role",
+    skip(transaction)
+)]
 async fn change_role(
     id: UserId,
-    new_role: UserRole,
-    pool: &PgPool,
+    role_id: i16,
+    transaction: &mut Transaction<'_, Postgres>,
     user_role: UserRole,
 ) -> Result<PgQueryResult, sqlx::Error> {
     sqlx::query!(
         r#"
             UPDATE users
-            SET role = $1
+            SET role_id = $1
             WHERE id = $2 AND role < $3
         "#,
-        new_role as i16,
+        role_id,
         id,
         user_role as i16,
     )
-    .execute(pool)
+    .execute(&mut **transaction)
     .await
 }
\ No newline at end of file
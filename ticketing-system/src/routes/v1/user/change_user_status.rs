@@ -3,7 +3,7 @@ use anyhow::Context;
 use serde::Deserialize;
 use sqlx::{postgres::PgQueryResult, PgPool};
 
-use crate::{auth::{extractor::{UserIdExtractor, UserRoleExtractor}, types::{UserRole, UserStatus}}, schema::common::UserId, utils::error_chain_fmt};
+use crate::{auth::{extractor::{PermissionsExtractor, UserIdExtractor}, permission::Permission, types::UserStatus}, schema::common::UserId, utils::error_chain_fmt};
 
 #[derive(Deserialize)]
 pub struct ChangeStatusSchema {
@@ -38,12 +38,12 @@ impl ResponseError for ChangeStatusError {
 }
 
 pub async fn change_user_status(
-    role: UserRoleExtractor,
+    permissions: PermissionsExtractor,
     id: UserIdExtractor,
     web::Json(schema): web::Json<ChangeStatusSchema>,
     pool: web::Data<PgPool>,
 ) -> Result<HttpResponse, ChangeStatusError> {
-    if role.0 < UserRole::Moderator && id.0 != schema.id {
+    if !permissions.0.has(Permission::UsersManageStatus) && id.0 != schema.id {
         return Err(ChangeStatusError::InsufficientPermissions)
     }
 
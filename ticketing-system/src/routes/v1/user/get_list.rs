@@ -2,9 +2,9 @@ use actix_web::{http::StatusCode, web, HttpResponse, ResponseError};
 use anyhow::Context;
 use serde::{Deserialize, Serialize};
 use serde_qs::actix::QsQuery;
-use sqlx::PgPool;
+use sqlx::{PgPool, Postgres, QueryBuilder};
 
-use crate::{auth::types::{UserRole, UserStatus}, schema::common::{PaginationResult, UserId}, utils::error_chain_fmt};
+use crate::{auth::types::{UserRole, UserStatus}, schema::common::{Cursor, CursorPaginationResult, PaginationResult, UserId}, utils::error_chain_fmt};
 
 #[derive(Deserialize, Debug)]
 pub struct GetUsersSchema {
@@ -14,6 +14,11 @@ pub struct GetUsersSchema {
     pub minimal_role: Option<UserRole>,
     #[serde(default = "default_is_active")]
     pub is_active: bool,
+    /// Switches to cursor pagination, empty for the first page.
+    pub cursor: Option<String>,
+    /// Counts the total in cursor pagination.
+    #[serde(default)]
+    pub with_total: bool,
 }
 
 fn default_is_active() -> bool {
@@ -41,13 +46,24 @@ struct Row {
     pub role: UserRole,
     pub status: UserStatus,
     pub avatar_key: Option<String>,
-    pub total_items: i64,
+    /// Only counted in page number pagination.
+    pub total_items: Option<i64>,
+}
+
+#[derive(Debug)]
+enum Pagination {
+    /// Zero based page number.
+    Page(i32),
+    /// Id of the last user of the previous page, `None` for the first page.
+    Cursor(Option<UserId>),
 }
 
 #[derive(thiserror::Error)]
 pub enum GetUsersError {
     #[error("Page number must be greater than 0")]
     InvalidPage,
+    #[error("Invalid cursor")]
+    InvalidCursor,
     #[error(transparent)]
     Unexpected(#[from] anyhow::Error)
 }
@@ -63,6 +79,7 @@ impl ResponseError for GetUsersError {
         match self {
             GetUsersError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
             GetUsersError::InvalidPage => StatusCode::BAD_REQUEST,
+            GetUsersError::InvalidCursor => StatusCode::BAD_REQUEST,
         }
     }
 }
@@ -77,18 +94,31 @@ pub async fn get_users(
         .map(|size| size.clamp(10, 50))
         .unwrap_or(10);
 
-    let page = schema.page.unwrap_or(1) - 1;
+    let pagination = match schema.cursor.as_deref() {
+        Some("") => Pagination::Cursor(None),
+        Some(cursor) => {
+            let cursor = Cursor::<UserId>::decode(cursor)
+                .ok_or(GetUsersError::InvalidCursor)?;
 
-    if page < 0 {
-        return Err(GetUsersError::InvalidPage)
-    }
+            Pagination::Cursor(Some(cursor.id))
+        },
+        None => {
+            let page = schema.page.unwrap_or(1) - 1;
+
+            if page < 0 {
+                return Err(GetUsersError::InvalidPage)
+            }
+
+            Pagination::Page(page)
+        },
+    };
 
-    let rows = get_users_page(&pool, page_size, page, schema)
+    let rows = get_users_page(&pool, page_size, &pagination, &schema)
         .await
         .context("Failed to get users from the database")?;
 
     let total_items = match rows.first() {
-        Some(row) => row.total_items as u64,
+        Some(row) => row.total_items.unwrap_or_default() as u64,
         None => 0,
     };
 
@@ -102,10 +132,29 @@ pub async fn get_users(
         avatar_key: r.avatar_key,
     }).collect();
 
-    Ok(HttpResponse::Ok().json(PaginationResult::new_with_pagination(
-        total_items,
+    if let Pagination::Page(_) = pagination {
+        return Ok(HttpResponse::Ok().json(PaginationResult::new_with_pagination(
+            total_items,
+            page_size,
+            users
+        )));
+    }
+
+    let total_items = if schema.with_total {
+        let count = get_users_count(&pool, &schema)
+            .await
+            .context("Failed to count users")?;
+
+        Some(count)
+    } else {
+        None
+    };
+
+    Ok(HttpResponse::Ok().json(CursorPaginationResult::new(
+        users,
         page_size,
-        users
+        total_items,
+        |user: &UserSchema| Cursor::new(user.id, ()).encode()
     )))
 }
 
@@ -113,13 +162,62 @@ pub async fn get_users(
     name = "Get page of users from the database",
     skip(pool)
 )]
-async fn get_users_page(pool: &PgPool, page_size: i8, page: i32, schema: GetUsersSchema) -> Result<Vec<Row>, sqlx::Error> {
+async fn get_users_page(
+    pool: &PgPool,
+    page_size: i8,
+    pagination: &Pagination,
+    schema: &GetUsersSchema,
+) -> Result<Vec<Row>, sqlx::Error> {
     let mut builder = sqlx::QueryBuilder::new(
-        "SELECT id, name, email, login, role, status, avatar_key, COUNT(*) OVER() as total_items
-        FROM users
-        WHERE "
+        "SELECT id, name, email, login, role, status, avatar_key, "
     );
 
+    match pagination {
+        Pagination::Page(_) => builder.push("COUNT(*) OVER() as total_items"),
+        Pagination::Cursor(_) => builder.push("NULL::bigint as total_items"),
+    };
+
+    builder.push(" FROM users ");
+
+    push_filters(&mut builder, schema);
+
+    match *pagination {
+        Pagination::Page(page) => {
+            builder.push(" ORDER BY id LIMIT ").push_bind(page_size as i64)
+                .push(" OFFSET ").push_bind(page as i64 * page_size as i64);
+        },
+        Pagination::Cursor(after) => {
+            if let Some(after) = after {
+                builder.push(" AND id > ").push_bind(after);
+            }
+
+            builder.push(" ORDER BY id LIMIT ").push_bind(page_size as i64 + 1);
+        },
+    }
+
+    let query = builder.build_query_as::<Row>();
+
+    query.fetch_all(pool).await
+}
+
+#[tracing::instrument(
+    name = "Get users count from the database",
+    skip(pool)
+)]
+async fn get_users_count(pool: &PgPool, schema: &GetUsersSchema) -> Result<u64, sqlx::Error> {
+    let mut builder = sqlx::QueryBuilder::new("SELECT COUNT(*) FROM users ");
+
+    push_filters(&mut builder, schema);
+
+    builder.build_query_scalar()
+        .fetch_one(pool)
+        .await
+        .map(|count: i64| count as u64)
+}
+
+fn push_filters<'a>(builder: &mut QueryBuilder<'a, Postgres>, schema: &'a GetUsersSchema) {
+    builder.push("WHERE ");
+
     if !schema.is_active {
         builder.push("NOT ");
     }
@@ -137,11 +235,4 @@ async fn get_users_page(pool: &PgPool, page_size: i8, page: i32, schema: GetUser
     if let Some(minimal_role) = schema.minimal_role {
         builder.push(" AND role >= ").push_bind(minimal_role as i16);
     }
-
-    builder.push(" LIMIT ").push_bind(page_size as i64)
-        .push(" OFFSET ").push_bind(page as i64 * page_size as i64);
-
-    let query = builder.build_query_as::<Row>();
-
-    query.fetch_all(pool).await
 }
\ No newline at end of file
@@ -0,0 +1,108 @@
+use actix_web::{http::StatusCode, web, HttpResponse, ResponseError};
+use anyhow::Context;
+use sqlx::PgPool;
+
+use crate::{
+    auth::{extractor::{DeviceInfo, UserIdExtractor, UserRoleExtractor}, jwt::JwtService, types::UserRole},
+    schema::{audit::AuditAction, auth::ImpersonationTokenResponse, common::UserId},
+    services::audit::AuditEntry,
+    utils::error_chain_fmt,
+};
+
+struct Target {
+    pub role: i16,
+    pub role_id: i16,
+    pub is_active: bool,
+}
+
+#[derive(thiserror::Error)]
+pub enum ImpersonateUserError {
+    #[error("User not found")]
+    UserNotFound,
+    #[error("Cannot impersonate this user")]
+    InvalidTarget,
+    #[error("Insufficient permissions to impersonate this user")]
+    InsufficientPermissions,
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for ImpersonateUserError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for ImpersonateUserError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ImpersonateUserError::UserNotFound => StatusCode::NOT_FOUND,
+            ImpersonateUserError::InvalidTarget => StatusCode::BAD_REQUEST,
+            ImpersonateUserError::InsufficientPermissions => StatusCode::FORBIDDEN,
+            ImpersonateUserError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+pub async fn impersonate_user(
+    target_id: web::Path<UserId>,
+    user_id: UserIdExtractor,
+    role: UserRoleExtractor,
+    device: DeviceInfo,
+    jwt_service: web::Data<JwtService>,
+    pool: web::Data<PgPool>,
+) -> Result<HttpResponse, ImpersonateUserError> {
+    let target_id = target_id.into_inner();
+
+    if target_id == user_id.0 {
+        return Err(ImpersonateUserError::InvalidTarget);
+    }
+
+    let target = get_target(&pool, target_id)
+        .await
+        .context("Failed to get impersonation target")?
+        .ok_or(ImpersonateUserError::UserNotFound)?;
+
+    if !target.is_active {
+        return Err(ImpersonateUserError::InvalidTarget);
+    }
+
+    let target_role = UserRole::from(target.role);
+
+    if target_role >= role.0 {
+        return Err(ImpersonateUserError::InsufficientPermissions);
+    }
+
+    let access_token = jwt_service
+        .create_impersonation_token(target_id, target_role, target.role_id, user_id.0)
+        .context("Failed to create impersonation token")?;
+
+    let expires_in = jwt_service.impersonation_token_lifetime().num_seconds();
+
+    AuditEntry::new(AuditAction::ImpersonationStarted, &device)
+        .actor(user_id.0)
+        .target(target_id)
+        .details(serde_json::json!({ "expires_in": expires_in }))
+        .record(pool.get_ref())
+        .await
+        .context("Failed to write audit log")?;
+
+    Ok(HttpResponse::Ok().json(ImpersonationTokenResponse {
+        access_token,
+        token_type: "Bearer".to_string(),
+        expires_in,
+    }))
+}
+
+#[tracing::instrument(name = "Get impersonation target from database", skip(pool))]
+async fn get_target(pool: &PgPool, user_id: UserId) -> Result<Option<Target>, sqlx::Error> {
+    sqlx::query_as!(
+        Target,
+        "SELECT role, role_id, is_active
+        FROM users
+        WHERE id = $1",
+        user_id
+    )
+    .fetch_optional(pool)
+    .await
+}
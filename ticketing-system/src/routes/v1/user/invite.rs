@@ -7,7 +7,7 @@ use sailfish::Template;
 use serde::Deserialize;
 use sqlx::PgPool;
 
-use crate::{domain::email::Email, email_client::EmailClient, services::registration_token::RegistrationTokenStore, startup::ApplicationBaseUrl, templates::InviteTemplate, utils::error_chain_fmt};
+use crate::{auth::extractor::{DeviceInfo, UserIdExtractor}, domain::email::Email, email_client::EmailClient, schema::audit::AuditAction, services::{audit::AuditEntry, registration_token::RegistrationTokenStore}, startup::ApplicationBaseUrl, templates::InviteTemplate, utils::error_chain_fmt};
 
 #[derive(Debug, Deserialize, Validate)]
 pub struct InviteUserSchema {
@@ -40,7 +40,7 @@ impl ResponseError for InviteUserError {
 
 #[tracing::instrument(
     name = "Invite a new user",
-    skip(email_client, base_url, reg_store)
+    skip(email_client, base_url, reg_store, user_id, device)
 )]
 pub async fn invite_user(
     Json(schema): Json<InviteUserSchema>,
@@ -48,6 +48,8 @@ pub async fn invite_user(
     base_url: web::Data<ApplicationBaseUrl>,
     email_client: web::Data<dyn EmailClient>,
     pool: web::Data<PgPool>,
+    user_id: UserIdExtractor,
+    device: DeviceInfo,
 ) -> Result<HttpResponse, InviteUserError> {
     if is_email_exists(&pool, &schema.email)
         .await
@@ -71,6 +73,13 @@ pub async fn invite_user(
     )
     .await?;
 
+    AuditEntry::new(AuditAction::UserInvited, &device)
+        .actor(user_id.0)
+        .details(serde_json::json!({ "email": schema.email.as_ref() }))
+        .record(pool.get_ref())
+        .await
+        .context("Failed to write audit log")?;
+
     Ok(HttpResponse::Ok().finish())
 }
 
@@ -78,7 +87,7 @@ pub async fn invite_user(
     name = "Send a confirmation email to a new user",
     skip(email_client, base_url)
 )]
-async fn send_confirmation_email(
+pub(crate) async fn send_confirmation_email(
     email_client: &dyn EmailClient,
     email: &Email,
     base_url: &str,
@@ -7,6 +7,12 @@ pub mod update_profile;
 pub mod change_user_role;
 pub mod toggle_user_active;
 pub mod update_avatar;
+pub mod sessions;
+pub mod unlock_account;
+pub mod tokens;
+pub mod impersonate;
+pub mod signup;
+pub mod absences;
 
 pub use invite::invite_user;
 pub use change_user_status::change_user_status;
@@ -16,4 +22,9 @@ pub use request_admin_transfer::request_admin_transfer;
 pub use update_profile::update_user_profile;
 pub use change_user_role::change_user_role;
 pub use toggle_user_active::{activate_account, deactivate_account};
-pub use update_avatar::update_avatar;
\ No newline at end of file
+pub use update_avatar::update_avatar;
+pub use sessions::{get_user_sessions, revoke_user_session, revoke_user_sessions};
+pub use unlock_account::unlock_account;
+pub use impersonate::impersonate_user;
+pub use signup::set_signup_enabled;
+pub use absences::{cancel_absence, create_absence, get_absences};
\ No newline at end of file
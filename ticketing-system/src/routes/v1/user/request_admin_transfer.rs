@@ -7,12 +7,14 @@ use sqlx::PgPool;
 
 use crate::{
     auth::types::UserRole,
-    auth::extractor::UserIdExtractor,
+    auth::extractor::{DeviceInfo, UserIdExtractor},
     domain::email::Email,
     email_client::EmailClient,
+    schema::audit::AuditAction,
     schema::common::UserId,
     schema::action_token::ActionTokenKind,
     services::action_token::ActionTokenStore,
+    services::audit::AuditEntry,
     startup::ApplicationBaseUrl,
     templates::AdminTransferTemplate,
     utils::error_chain_fmt,
@@ -67,6 +69,7 @@ pub async fn request_admin_transfer(
     base_url: web::Data<ApplicationBaseUrl>,
     email_client: web::Data<dyn EmailClient>,
     pool: web::Data<PgPool>,
+    device: DeviceInfo,
 ) -> Result<HttpResponse, RequestAdminTransferError> {
     if schema.user_id == user_id.0 {
         return Err(RequestAdminTransferError::InvalidTarget);
@@ -112,6 +115,13 @@ pub async fn request_admin_transfer(
     )
     .await?;
 
+    AuditEntry::new(AuditAction::AdminTransferRequested, &device)
+        .actor(user_id.0)
+        .target(target.id)
+        .record(pool.get_ref())
+        .await
+        .context("Failed to write audit log")?;
+
     Ok(HttpResponse::Ok().finish())
 }
 
@@ -0,0 +1,64 @@
+use actix_web::{http::StatusCode, web, HttpResponse, ResponseError};
+
+use crate::{auth::token_store::{TokenStore, TokenStoreError}, schema::{auth::SessionResponse, common::UserId}, utils::error_chain_fmt};
+
+#[derive(thiserror::Error)]
+pub enum UserSessionsError {
+    #[error("Session not found")]
+    SessionNotFound,
+    #[error(transparent)]
+    TokenStore(#[from] TokenStoreError),
+}
+
+impl std::fmt::Debug for UserSessionsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for UserSessionsError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            UserSessionsError::SessionNotFound => StatusCode::NOT_FOUND,
+            UserSessionsError::TokenStore(e) => e.status_code(),
+        }
+    }
+}
+
+pub async fn get_user_sessions(
+    user_id: web::Path<UserId>,
+    token_store: web::Data<TokenStore>,
+) -> Result<HttpResponse, UserSessionsError> {
+    let mut sessions = token_store.get_user_sessions(*user_id).await?;
+
+    sessions.sort_by_key(|session| std::cmp::Reverse(session.last_used_at));
+
+    let resp: Vec<_> = sessions
+        .into_iter()
+        .map(|session| SessionResponse::new(session, None))
+        .collect();
+
+    Ok(HttpResponse::Ok().json(resp))
+}
+
+pub async fn revoke_user_session(
+    path: web::Path<(UserId, String)>,
+    token_store: web::Data<TokenStore>,
+) -> Result<HttpResponse, UserSessionsError> {
+    let (user_id, session_id) = path.into_inner();
+
+    if !token_store.revoke_session(user_id, &session_id).await? {
+        return Err(UserSessionsError::SessionNotFound);
+    }
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+pub async fn revoke_user_sessions(
+    user_id: web::Path<UserId>,
+    token_store: web::Data<TokenStore>,
+) -> Result<HttpResponse, UserSessionsError> {
+    token_store.revoke_other_sessions(*user_id, None).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
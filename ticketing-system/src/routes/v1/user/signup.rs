@@ -0,0 +1,63 @@
+use actix_web::{http::StatusCode, web, HttpResponse, ResponseError};
+use anyhow::Context;
+use serde::Deserialize;
+use sqlx::PgPool;
+
+use crate::{
+    auth::extractor::{DeviceInfo, UserIdExtractor},
+    schema::audit::AuditAction,
+    services::{audit::AuditEntry, signup::SignupService},
+    utils::error_chain_fmt,
+};
+
+#[derive(Debug, Deserialize)]
+pub struct SetSignupEnabledSchema {
+    pub enabled: bool,
+}
+
+#[derive(thiserror::Error)]
+pub enum SetSignupEnabledError {
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for SetSignupEnabledError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for SetSignupEnabledError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            SetSignupEnabledError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+pub async fn set_signup_enabled(
+    schema: web::Json<SetSignupEnabledSchema>,
+    signup_service: web::Data<SignupService>,
+    pool: web::Data<PgPool>,
+    user_id: UserIdExtractor,
+    device: DeviceInfo,
+) -> Result<HttpResponse, SetSignupEnabledError> {
+    let mut transaction = pool.begin()
+        .await
+        .context("Failed to begin transaction")?;
+
+    signup_service.set_enabled(&mut transaction, schema.enabled).await?;
+
+    AuditEntry::new(AuditAction::SignupToggled, &device)
+        .actor(user_id.0)
+        .details(serde_json::json!({ "enabled": schema.enabled }))
+        .record(&mut *transaction)
+        .await
+        .context("Failed to write audit log")?;
+
+    transaction.commit()
+        .await
+        .context("Failed to commit transaction")?;
+
+    Ok(HttpResponse::Ok().finish())
+}
@@ -1,8 +1,8 @@
 use actix_web::{http::StatusCode, web, HttpResponse, ResponseError};
 use anyhow::Context;
-use sqlx::{postgres::PgQueryResult, PgPool};
+use sqlx::{postgres::PgQueryResult, PgPool, Postgres, Transaction};
 
-use crate::{schema::common::UserId, utils::error_chain_fmt};
+use crate::{auth::extractor::{DeviceInfo, UserIdExtractor}, schema::{audit::AuditAction, common::UserId}, services::audit::AuditEntry, utils::error_chain_fmt};
 
 
 
@@ -32,37 +32,71 @@ impl ResponseError for ToggleUserActiveError {
 pub async fn activate_account(
     pool: web::Data<PgPool>,
     user_id: web::Path<UserId>,
+    actor_id: UserIdExtractor,
+    device: DeviceInfo,
 ) -> Result<HttpResponse, ToggleUserActiveError> {
-    if toggle_user_active(&pool, true, *user_id)
+    let mut transaction = pool.begin()
+        .await
+        .context("Failed to begin transaction")?;
+
+    if toggle_user_active(&mut transaction, true, *user_id)
         .await
         .context("Failed to activate user account")?
         .rows_affected() == 0 {
             return Err(ToggleUserActiveError::UserNotFound)
         }
 
+    AuditEntry::new(AuditAction::UserActivated, &device)
+        .actor(actor_id.0)
+        .target(*user_id)
+        .record(&mut *transaction)
+        .await
+        .context("Failed to write audit log")?;
+
+    transaction.commit()
+        .await
+        .context("Failed to commit transaction")?;
+
     Ok(HttpResponse::Ok().finish())
 }
 
 pub async fn deactivate_account(
     pool: web::Data<PgPool>,
     user_id: web::Path<UserId>,
+    actor_id: UserIdExtractor,
+    device: DeviceInfo,
 ) -> Result<HttpResponse, ToggleUserActiveError> {
-    if toggle_user_active(&pool, false, *user_id)
+    let mut transaction = pool.begin()
+        .await
+        .context("Failed to begin transaction")?;
+
+    if toggle_user_active(&mut transaction, false, *user_id)
         .await
         .context("Failed to deactivate user account")?
         .rows_affected() == 0 {
             return Err(ToggleUserActiveError::UserNotFound)
         }
 
+    AuditEntry::new(AuditAction::UserDeactivated, &device)
+        .actor(actor_id.0)
+        .target(*user_id)
+        .record(&mut *transaction)
+        .await
+        .context("Failed to write audit log")?;
+
+    transaction.commit()
+        .await
+        .context("Failed to commit transaction")?;
+
     Ok(HttpResponse::Ok().finish())
 }
 
 #[tracing::instrument(
     name = "Toggle user account in database",
-    skip(pool),
+    skip(transaction),
 )]
 async fn toggle_user_active(
-    pool: &PgPool,
+    transaction: &mut Transaction<'_, Postgres>,
     is_active: bool,
     user_id: UserId
 ) -> Result<PgQueryResult, sqlx::Error> {
@@ -75,6 +109,6 @@ async fn toggle_user_active(
         is_active,
         user_id
     )
-    .execute(pool)
+    .execute(&mut **transaction)
     .await
 }
\ No newline at end of file
@@ -0,0 +1,95 @@
+use actix_web::{http::StatusCode, web, HttpResponse, ResponseError};
+use anyhow::Context;
+use garde_actix_web::web::Json;
+use sqlx::PgPool;
+
+use crate::{auth::{extractor::{UserIdExtractor, UserRoleExtractor}, personal_access_token::{generate_token, hash_token}, types::UserRole}, schema::{common::UserId, personal_access_token::{CreatePersonalAccessTokenSchema, CreatedPersonalAccessToken, PersonalAccessToken}}, utils::error_chain_fmt};
+
+#[derive(thiserror::Error)]
+pub enum CreateTokenError {
+    #[error("Token role cannot be higher than the owner's role")]
+    RoleTooHigh,
+    #[error("Expiration time must be in the future")]
+    InvalidExpiration,
+    #[error("Scopes must be API paths starting with /v1/")]
+    InvalidScope,
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for CreateTokenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for CreateTokenError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            CreateTokenError::RoleTooHigh => StatusCode::FORBIDDEN,
+            CreateTokenError::InvalidExpiration | CreateTokenError::InvalidScope => StatusCode::BAD_REQUEST,
+            CreateTokenError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+pub async fn create_token(
+    user_id: UserIdExtractor,
+    user_role: UserRoleExtractor,
+    Json(schema): Json<CreatePersonalAccessTokenSchema>,
+    pool: web::Data<PgPool>,
+) -> Result<HttpResponse, CreateTokenError> {
+    let role = schema.role.unwrap_or(user_role.0);
+
+    if !user_role.0.has_access(role) {
+        return Err(CreateTokenError::RoleTooHigh);
+    }
+
+    if schema.expires_at <= chrono::Utc::now() {
+        return Err(CreateTokenError::InvalidExpiration);
+    }
+
+    if schema.scopes.iter().any(|scope| !scope.starts_with("/v1/")) {
+        return Err(CreateTokenError::InvalidScope);
+    }
+
+    let token = generate_token();
+
+    let info = insert_token(&pool, user_id.0, role, &token, schema)
+        .await
+        .context("Failed to insert personal access token")?;
+
+    Ok(HttpResponse::Created().json(CreatedPersonalAccessToken {
+        info,
+        token,
+    }))
+}
+
+#[tracing::instrument(
+    name = "Insert personal access token",
+    skip(pool, token, schema)
+)]
+async fn insert_token(
+    pool: &PgPool,
+    user_id: UserId,
+    role: UserRole,
+    token: &str,
+    schema: CreatePersonalAccessTokenSchema,
+) -> Result<PersonalAccessToken, sqlx::Error> {
+    sqlx::query_as!(
+        PersonalAccessToken,
+        r#"
+            INSERT INTO personal_access_tokens (user_id, name, token_hash, role, scopes, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING id, name, role, scopes, expires_at, last_used_at, created_at
+        "#,
+        user_id,
+        schema.name,
+        hash_token(token),
+        role as i16,
+        &schema.scopes,
+        schema.expires_at
+    )
+    .fetch_one(pool)
+    .await
+}
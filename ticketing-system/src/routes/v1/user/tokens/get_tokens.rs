@@ -0,0 +1,49 @@
+use actix_web::{web, HttpResponse, ResponseError};
+use anyhow::Context;
+use sqlx::PgPool;
+
+use crate::{auth::extractor::UserIdExtractor, schema::{common::UserId, personal_access_token::PersonalAccessToken}, utils::error_chain_fmt};
+
+#[derive(thiserror::Error)]
+pub enum GetTokensError {
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for GetTokensError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for GetTokensError {}
+
+pub async fn get_tokens(
+    user_id: UserIdExtractor,
+    pool: web::Data<PgPool>,
+) -> Result<HttpResponse, GetTokensError> {
+    let tokens = get_active_tokens(&pool, user_id.0)
+        .await
+        .context("Failed to get personal access tokens")?;
+
+    Ok(HttpResponse::Ok().json(tokens))
+}
+
+#[tracing::instrument(
+    name = "Get active personal access tokens",
+    skip(pool)
+)]
+async fn get_active_tokens(pool: &PgPool, user_id: UserId) -> Result<Vec<PersonalAccessToken>, sqlx::Error> {
+    sqlx::query_as!(
+        PersonalAccessToken,
+        r#"
+            SELECT id, name, role, scopes, expires_at, last_used_at, created_at
+            FROM personal_access_tokens
+            WHERE user_id = $1 AND revoked_at IS NULL AND expires_at > NOW()
+            ORDER BY created_at DESC
+        "#,
+        user_id
+    )
+    .fetch_all(pool)
+    .await
+}
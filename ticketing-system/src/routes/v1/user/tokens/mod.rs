@@ -0,0 +1,7 @@
+pub mod create_token;
+pub mod get_tokens;
+pub mod revoke_token;
+
+pub use create_token::create_token;
+pub use get_tokens::get_tokens;
+pub use revoke_token::revoke_token;
\ No newline at end of file
@@ -0,0 +1,59 @@
+use actix_web::{http::StatusCode, web, HttpResponse, ResponseError};
+use anyhow::Context;
+use sqlx::{postgres::PgQueryResult, PgPool};
+
+use crate::{auth::extractor::UserIdExtractor, schema::common::UserId, utils::error_chain_fmt};
+
+#[derive(thiserror::Error)]
+pub enum RevokeTokenError {
+    #[error("Token not found")]
+    TokenNotFound,
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for RevokeTokenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for RevokeTokenError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            RevokeTokenError::TokenNotFound => StatusCode::NOT_FOUND,
+            RevokeTokenError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+pub async fn revoke_token(
+    user_id: UserIdExtractor,
+    token_id: web::Path<i32>,
+    pool: web::Data<PgPool>,
+) -> Result<HttpResponse, RevokeTokenError> {
+    if revoke(&pool, user_id.0, *token_id)
+        .await
+        .context("Failed to revoke personal access token")?
+        .rows_affected() == 0 {
+        return Err(RevokeTokenError::TokenNotFound);
+    }
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[tracing::instrument(
+    name = "Revoke personal access token",
+    skip(pool)
+)]
+async fn revoke(pool: &PgPool, user_id: UserId, token_id: i32) -> Result<PgQueryResult, sqlx::Error> {
+    sqlx::query!(
+        "UPDATE personal_access_tokens
+        SET revoked_at = NOW()
+        WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL",
+        token_id,
+        user_id
+    )
+    .execute(pool)
+    .await
+}
@@ -0,0 +1,63 @@
+use actix_web::{http::StatusCode, web, HttpResponse, ResponseError};
+use anyhow::Context;
+use sqlx::PgPool;
+
+use crate::{auth::extractor::UserIdExtractor, schema::common::UserId, services::rate_limiter::{LockoutSubject, RateLimiter}, utils::error_chain_fmt};
+
+#[derive(thiserror::Error)]
+pub enum UnlockAccountError {
+    #[error("User not found")]
+    UserNotFound,
+    #[error(transparent)]
+    Unexpected(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for UnlockAccountError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for UnlockAccountError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            UnlockAccountError::UserNotFound => StatusCode::NOT_FOUND,
+            UnlockAccountError::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+/// Clears the login lockout of the user.
+pub async fn unlock_account(
+    admin_id: UserIdExtractor,
+    user_id: web::Path<UserId>,
+    pool: web::Data<PgPool>,
+    rate_limiter: web::Data<RateLimiter>,
+) -> Result<HttpResponse, UnlockAccountError> {
+    let is_found = user_exists(&pool, *user_id)
+        .await
+        .context("Failed to get user from db")?;
+
+    if !is_found {
+        return Err(UnlockAccountError::UserNotFound);
+    }
+
+    rate_limiter.unlock(LockoutSubject::User(*user_id)).await?;
+
+    tracing::info!("User {} unlocked by admin {}", *user_id, admin_id.0);
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[tracing::instrument(
+    name = "Check whether user exists",
+    skip(pool)
+)]
+async fn user_exists(pool: &PgPool, user_id: UserId) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM users WHERE id = $1) AS "exists!""#,
+        user_id
+    )
+    .fetch_one(pool)
+    .await
+}
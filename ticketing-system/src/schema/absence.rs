@@ -0,0 +1,36 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{auth::types::UserStatus, schema::common::UserId};
+
+pub type AbsenceId = i32;
+
+#[derive(Deserialize, Debug)]
+pub struct CreateAbsenceSchema {
+    /// Status the user gets while absent, anything but `available`.
+    pub kind: UserStatus,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub deputy_id: Option<UserId>,
+    /// Hand open tickets over to the deputy, or back to the department
+    /// queue without one, when the absence starts.
+    #[serde(default)]
+    pub reassign_tickets: bool,
+}
+
+// Output
+
+#[derive(Serialize)]
+pub struct Absence {
+    pub id: AbsenceId,
+    pub user_id: UserId,
+    pub kind: UserStatus,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub deputy_id: Option<UserId>,
+    pub deputy_name: Option<String>,
+    pub reassign_tickets: bool,
+    pub started: bool,
+    pub finished: bool,
+    pub created_at: DateTime<Utc>,
+}
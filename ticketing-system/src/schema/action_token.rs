@@ -6,6 +6,9 @@ pub trait ActionTokenName {
 pub enum ActionTokenKind {
     PasswordRecovery,
     AdminTransfer,
+    TwoFactorChallenge,
+    OidcState,
+    MagicLink,
 }
 
 impl ActionTokenName for ActionTokenKind {
@@ -13,6 +16,9 @@ impl ActionTokenName for ActionTokenKind {
         match self {
             ActionTokenKind::PasswordRecovery => "password_recovery",
             ActionTokenKind::AdminTransfer => "admin_transfer",
+            ActionTokenKind::TwoFactorChallenge => "two_factor_challenge",
+            ActionTokenKind::OidcState => "oidc_state",
+            ActionTokenKind::MagicLink => "magic_link",
         }
     }
 }
@@ -0,0 +1,118 @@
+use chrono::{DateTime, Utc};
+use garde::Validate;
+use num_enum::FromPrimitive;
+use serde::{Deserialize, Serialize};
+use sqlx::Type;
+
+use crate::schema::{common::UserId, tickets::TicketPriority};
+
+pub type AssignmentRuleId = i32;
+
+#[derive(Serialize, Deserialize, Type, FromPrimitive, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+#[repr(i16)]
+pub enum AssignmentStrategy {
+    #[default]
+    RoundRobin = 0,
+    /// The candidate with the fewest open and in progress tickets.
+    LeastLoaded = 1,
+}
+
+#[derive(Deserialize, Validate, Debug)]
+pub struct CreateAssignmentRuleSchema {
+    #[garde(length(chars, min = 1, max = 64))]
+    pub name: String,
+    #[garde(skip)]
+    #[serde(default)]
+    pub position: i32,
+    #[garde(skip)]
+    pub department_id: Option<i16>,
+    #[garde(skip)]
+    pub building_id: Option<i16>,
+    #[garde(skip)]
+    pub priority: Option<TicketPriority>,
+    /// Any of them in the title or description is enough, case is ignored.
+    #[garde(length(max = 32), inner(length(chars, min = 1, max = 64)))]
+    #[serde(default)]
+    pub keywords: Vec<String>,
+    #[garde(skip)]
+    pub strategy: AssignmentStrategy,
+    /// Candidates of the rule. Empty means every member of the ticket department.
+    #[garde(length(max = 100))]
+    #[serde(default)]
+    pub assignees: Vec<UserId>,
+}
+
+/// Conditions are replaced as a whole, so a missing one stops being checked.
+#[derive(Deserialize, Validate, Debug)]
+pub struct UpdateAssignmentRuleSchema {
+    #[garde(length(chars, min = 1, max = 64))]
+    pub name: String,
+    #[garde(skip)]
+    #[serde(default)]
+    pub position: i32,
+    #[garde(skip)]
+    #[serde(default = "default_is_active")]
+    pub is_active: bool,
+    #[garde(skip)]
+    pub department_id: Option<i16>,
+    #[garde(skip)]
+    pub building_id: Option<i16>,
+    #[garde(skip)]
+    pub priority: Option<TicketPriority>,
+    #[garde(length(max = 32), inner(length(chars, min = 1, max = 64)))]
+    #[serde(default)]
+    pub keywords: Vec<String>,
+    #[garde(skip)]
+    pub strategy: AssignmentStrategy,
+    #[garde(length(max = 100))]
+    #[serde(default)]
+    pub assignees: Vec<UserId>,
+}
+
+fn default_is_active() -> bool {
+    true
+}
+
+#[derive(Deserialize, Validate, Debug)]
+pub struct DryRunSchema {
+    #[garde(skip)]
+    pub department_id: i16,
+    #[garde(skip)]
+    pub building_id: i16,
+    /// New tickets are created with low priority.
+    #[garde(skip)]
+    pub priority: Option<TicketPriority>,
+    #[garde(length(max = 256))]
+    #[serde(default)]
+    pub title: String,
+    #[garde(length(max = 65536))]
+    #[serde(default)]
+    pub description: String,
+}
+
+// Output
+
+#[derive(Serialize)]
+pub struct AssignmentRule {
+    pub id: AssignmentRuleId,
+    pub name: String,
+    pub position: i32,
+    pub is_active: bool,
+    pub department_id: Option<i16>,
+    pub building_id: Option<i16>,
+    pub priority: Option<TicketPriority>,
+    pub keywords: Vec<String>,
+    pub strategy: AssignmentStrategy,
+    pub assignees: Vec<UserId>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize)]
+pub struct DryRunResult {
+    /// `None` when no rule fires and the ticket stays in the queue.
+    pub rule_id: Option<AssignmentRuleId>,
+    pub rule_name: Option<String>,
+    pub assignee_id: Option<UserId>,
+    pub assignee_name: Option<String>,
+}
@@ -0,0 +1,59 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use strum::{EnumString, IntoStaticStr};
+
+use crate::schema::common::UserId;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, EnumString, IntoStaticStr)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum AuditAction {
+    RoleChanged,
+    UserActivated,
+    UserDeactivated,
+    AdminTransferRequested,
+    AdminTransferred,
+    ImpersonationStarted,
+    SignupToggled,
+    UserInvited,
+    PasswordChanged,
+    AccountRecoveryRequested,
+    AccountRecovered,
+    /// A refresh token was used with another fingerprint and all sessions were revoked.
+    FingerprintMismatch,
+    /// One operation was applied to many tickets at once.
+    TicketsBulkUpdated,
+}
+
+impl AuditAction {
+    pub fn as_str(&self) -> &'static str {
+        self.into()
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct GetAuditLogSchema {
+    pub page: Option<i32>,
+    pub page_size: Option<i8>,
+    pub actor_id: Option<UserId>,
+    pub target_id: Option<UserId>,
+    pub action: Option<AuditAction>,
+    pub from_date: Option<DateTime<Utc>>,
+    pub to_date: Option<DateTime<Utc>>,
+}
+
+// Output
+
+#[derive(Serialize, sqlx::FromRow)]
+pub struct AuditLogEntry {
+    pub id: i64,
+    pub actor_id: Option<UserId>,
+    pub actor_name: Option<String>,
+    pub target_id: Option<UserId>,
+    pub target_name: Option<String>,
+    pub action: String,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub details: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+}
@@ -1,4 +1,21 @@
-use serde::Serialize;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{auth::types::RefreshToken, schema::common::UserId};
+
+#[derive(Serialize, Deserialize)]
+pub struct TwoFactorChallenge {
+    pub user_id: UserId,
+    pub fingerprint: String,
+    pub attempts: u8,
+}
+
+/// A magic link can only be used from the device that requested it.
+#[derive(Serialize, Deserialize)]
+pub struct MagicLinkPayload {
+    pub user_id: UserId,
+    pub fingerprint: String,
+}
 
 // Output
 
@@ -8,4 +25,73 @@ pub struct TokenResponse {
     pub refresh_token: String,
     pub token_type: String,
     pub expires_in: i64,
-}
\ No newline at end of file
+}
+
+/// Impersonation tokens can not be refreshed.
+#[derive(Serialize)]
+pub struct ImpersonationTokenResponse {
+    pub access_token: String,
+    pub token_type: String,
+    pub expires_in: i64,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum LoginResponse {
+    Tokens(TokenResponse),
+    TwoFactorChallenge(TwoFactorChallengeResponse),
+}
+
+#[derive(Serialize)]
+pub struct TwoFactorChallengeResponse {
+    pub challenge_token: String,
+    pub setup_required: bool,
+}
+
+#[derive(Serialize)]
+pub struct TwoFactorLoginResponse {
+    #[serde(flatten)]
+    pub tokens: TokenResponse,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recovery_codes: Option<Vec<String>>,
+}
+
+#[derive(Serialize)]
+pub struct TotpSetupResponse {
+    pub secret: String,
+    pub provisioning_uri: String,
+}
+
+#[derive(Serialize)]
+pub struct RecoveryCodesResponse {
+    pub recovery_codes: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct SessionResponse {
+    pub id: String,
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub current: bool,
+}
+
+impl SessionResponse {
+    pub fn new(token: RefreshToken, current_session_id: Option<&str>) -> Self {
+        Self {
+            current: current_session_id == Some(token.session_id.as_str()),
+            id: token.session_id,
+            user_agent: token.user_agent,
+            ip: token.ip,
+            created_at: DateTime::from_timestamp(token.created_at, 0),
+            last_used_at: DateTime::from_timestamp(token.last_used_at, 0),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct SignupStatusResponse {
+    pub enabled: bool,
+    pub allowed_domains: Vec<String>,
+}
@@ -1,4 +1,5 @@
-use serde::{Deserialize, Serialize};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 pub type UserId = i32;
 
@@ -51,4 +52,89 @@ impl<T> PaginationResult<T> {
             items
         )
     }
+}
+
+/// Position after the last item of a page: the value of the sort column and the id.
+/// Clients get it as an opaque string.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Cursor<Id, Key = ()> {
+    pub key: Key,
+    pub id: Id,
+}
+
+impl<Id, Key> Cursor<Id, Key>
+where
+    Id: Serialize + DeserializeOwned,
+    Key: Serialize + DeserializeOwned,
+{
+    pub fn new(id: Id, key: Key) -> Self {
+        Self { key, id }
+    }
+
+    pub fn encode(&self) -> String {
+        let json = serde_json::to_vec(self).expect("Cursor is always serializable");
+
+        URL_SAFE_NO_PAD.encode(json)
+    }
+
+    pub fn decode(cursor: &str) -> Option<Self> {
+        let json = URL_SAFE_NO_PAD.decode(cursor).ok()?;
+
+        serde_json::from_slice(&json).ok()
+    }
+}
+
+/// Requested with a `cursor` parameter, which is empty for the first page.
+/// The total is only counted on request, as it costs a separate query.
+#[derive(Serialize)]
+pub struct CursorPaginationResult<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_items: Option<u64>,
+}
+
+impl<T> CursorPaginationResult<T> {
+    /// Expects one item more than the page size to tell whether there is a next page.
+    pub fn new(
+        mut items: Vec<T>,
+        page_size: i8,
+        total_items: Option<u64>,
+        cursor: impl FnOnce(&T) -> String,
+    ) -> Self {
+        let has_more = items.len() > page_size as usize;
+        items.truncate(page_size as usize);
+
+        let next_cursor = match items.last() {
+            Some(last) if has_more => Some(cursor(last)),
+            _ => None,
+        };
+
+        Self { items, next_cursor, total_items }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cursor_roundtrips_and_rejects_garbage() {
+        let cursor = Cursor::new(42i64, Some(3i16));
+        let decoded = Cursor::<i64, Option<i16>>::decode(&cursor.encode()).unwrap();
+
+        assert_eq!(decoded.id, 42);
+        assert_eq!(decoded.key, Some(3));
+        assert!(Cursor::<i64>::decode("not a cursor").is_none());
+    }
+
+    #[test]
+    fn next_cursor_is_set_only_when_there_are_more_items() {
+        let res = CursorPaginationResult::new(vec![1, 2, 3], 2, None, |item| item.to_string());
+        assert_eq!(res.items, vec![1, 2]);
+        assert_eq!(res.next_cursor.as_deref(), Some("2"));
+
+        let res = CursorPaginationResult::new(vec![1, 2], 2, None, |item| item.to_string());
+        assert!(res.next_cursor.is_none());
+    }
 }
\ No newline at end of file
@@ -4,4 +4,9 @@ pub mod action_token;
 pub mod common;
 pub mod page;
 pub mod notification;
-pub mod assets;
\ No newline at end of file
+pub mod assets;
+pub mod personal_access_token;
+pub mod role;
+pub mod audit;
+pub mod absence;
+pub mod assignment_rule;
\ No newline at end of file
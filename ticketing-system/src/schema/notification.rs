@@ -2,7 +2,7 @@ use num_enum::FromPrimitive;
 use serde::{Deserialize, Serialize};
 use strum::EnumIter;
 
-use crate::schema::tickets::TicketStatus;
+use crate::schema::tickets::{EscalationTrigger, TicketId, TicketStatus};
 
 pub type SystemNotificationId = i32;
 pub type NotificationId = i64;
@@ -29,4 +29,13 @@ pub enum Notification {
     },
     #[serde(rename = "mention")]
     Mention,
+    #[serde(rename = "escalated")]
+    Escalated {
+        trigger: EscalationTrigger,
+    },
+    /// The ticket was closed as a duplicate of another one.
+    #[serde(rename = "merged")]
+    Merged {
+        into: TicketId,
+    },
 }
\ No newline at end of file
@@ -0,0 +1,40 @@
+use chrono::{DateTime, Utc};
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+
+use crate::auth::types::UserRole;
+
+#[derive(Deserialize, Validate)]
+pub struct CreatePersonalAccessTokenSchema {
+    #[garde(length(chars, min = 1, max = 64))]
+    pub name: String,
+    #[garde(skip)]
+    pub expires_at: DateTime<Utc>,
+    /// Defaults to the owner's role.
+    #[garde(skip)]
+    pub role: Option<UserRole>,
+    #[garde(length(max = 32), inner(length(min = 1, max = 128)))]
+    #[serde(default)]
+    pub scopes: Vec<String>,
+}
+
+// Output
+
+#[derive(Serialize)]
+pub struct PersonalAccessToken {
+    pub id: i32,
+    pub name: String,
+    pub role: UserRole,
+    pub scopes: Vec<String>,
+    pub expires_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// The token itself is returned only once, on creation.
+#[derive(Serialize)]
+pub struct CreatedPersonalAccessToken {
+    #[serde(flatten)]
+    pub info: PersonalAccessToken,
+    pub token: String,
+}
@@ -0,0 +1,39 @@
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+
+use crate::auth::{permission::Permission, types::UserRole};
+
+pub type RoleId = i16;
+
+#[derive(Deserialize, Validate)]
+pub struct CreateRoleSchema {
+    #[garde(length(chars, min = 1, max = 32))]
+    pub name: String,
+    /// Place on the role ladder, e.g. which tickets the role sees
+    /// and whose role its users may change.
+    #[garde(skip)]
+    pub level: UserRole,
+    #[garde(skip)]
+    #[serde(default)]
+    pub permissions: Vec<Permission>,
+}
+
+#[derive(Deserialize, Validate)]
+pub struct UpdateRoleSchema {
+    #[garde(length(chars, min = 1, max = 32))]
+    pub name: Option<String>,
+    /// Replaces all permissions of the role.
+    #[garde(skip)]
+    pub permissions: Option<Vec<Permission>>,
+}
+
+// Output
+
+#[derive(Serialize)]
+pub struct Role {
+    pub id: RoleId,
+    pub name: String,
+    pub level: UserRole,
+    pub is_system: bool,
+    pub permissions: Vec<Permission>,
+}
@@ -0,0 +1,77 @@
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+
+use crate::schema::{common::UserId, tickets::{TicketId, TicketPriority, TicketStatus}};
+
+/// Most tickets a single bulk request may touch.
+pub const MAX_BULK_TICKETS: usize = 100;
+
+/// Tickets are given either by ids or by the parameters of `GET /v1/tickets/`.
+#[derive(Deserialize, Validate, Debug)]
+pub struct BulkTicketsSchema {
+    #[garde(length(min = 1, max = MAX_BULK_TICKETS))]
+    pub ticket_ids: Option<Vec<TicketId>>,
+    #[garde(skip)]
+    pub filters: Option<serde_json::Map<String, serde_json::Value>>,
+    #[garde(dive)]
+    pub operation: BulkOperation,
+    /// Applies the operation to every ticket or to none of them.
+    #[garde(skip)]
+    #[serde(default)]
+    pub atomic: bool,
+}
+
+#[derive(Serialize, Deserialize, Validate, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BulkOperation {
+    SetStatus {
+        #[garde(skip)]
+        status: TicketStatus,
+    },
+    SetPriority {
+        #[garde(skip)]
+        priority: TicketPriority,
+    },
+    Assign {
+        #[garde(skip)]
+        user_id: UserId,
+    },
+    Unassign {
+        #[garde(skip)]
+        user_id: UserId,
+    },
+    SetDepartment {
+        #[garde(range(min = 1))]
+        department_id: i16,
+    },
+    SetBuilding {
+        #[garde(range(min = 1))]
+        building_id: i16,
+    },
+    AddInternalMessage {
+        #[garde(length(min = 1, max = 4096))]
+        message: String,
+    },
+}
+
+// Output
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum BulkTicketOutcome {
+    Updated,
+    /// The ticket already was in the requested state.
+    Unchanged,
+    NotFound,
+    Forbidden,
+    /// The assignee is not a member of the ticket's department.
+    NotDepartmentMember,
+    /// Left as is because another ticket of an atomic request was rejected.
+    Skipped,
+}
+
+#[derive(Serialize, Debug)]
+pub struct BulkTicketResult {
+    pub ticket_id: TicketId,
+    pub outcome: BulkTicketOutcome,
+}
\ No newline at end of file
@@ -0,0 +1,49 @@
+use chrono::{DateTime, Utc};
+use garde::Validate;
+use num_enum::FromPrimitive;
+use serde::{Deserialize, Serialize};
+use sqlx::Type;
+
+use crate::schema::tickets::{TicketId, TicketStatus};
+
+#[derive(Serialize, Deserialize, Type, FromPrimitive, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+#[repr(i16)]
+pub enum TicketLinkKind {
+    #[default]
+    Related = 0,
+    /// The source ticket duplicates the target, set by merging.
+    Duplicates = 1,
+    /// The target cannot be done before the source.
+    Blocks = 2,
+}
+
+#[derive(Deserialize, Validate, Debug)]
+pub struct CreateTicketLinkSchema {
+    #[garde(range(min = 1))]
+    pub ticket_id: TicketId,
+    #[garde(skip)]
+    pub kind: TicketLinkKind,
+}
+
+#[derive(Deserialize, Validate, Debug)]
+pub struct MergeTicketsSchema {
+    /// Tickets merged into the one from the path.
+    #[garde(length(min = 1, max = 20))]
+    pub ticket_ids: Vec<TicketId>,
+}
+
+// Output
+
+#[derive(Serialize)]
+pub struct TicketLink {
+    /// The other ticket of the link.
+    pub ticket_id: TicketId,
+    pub title: String,
+    pub status: TicketStatus,
+    pub kind: TicketLinkKind,
+    /// Whether the requested ticket is the source of the link,
+    /// e.g. it blocks the other one rather than being blocked by it.
+    pub outgoing: bool,
+    pub created_at: DateTime<Utc>,
+}
@@ -4,15 +4,21 @@ use sqlx::Type;
 use strum::EnumIter;
 
 pub mod output;
+pub mod link;
+pub mod view;
+pub mod bulk;
 
 pub use output::*;
+pub use link::*;
+pub use view::*;
+pub use bulk::*;
 
 pub type TicketId = i64;
 pub type MessageId = i64;
 
 // Common
 
-#[derive(Serialize, Deserialize, Type, FromPrimitive, Debug)]
+#[derive(Serialize, Deserialize, Type, FromPrimitive, Clone, Copy, PartialEq, Eq, Debug)]
 #[serde(rename_all = "lowercase")]
 #[repr(i16)]
 pub enum TicketStatus {
@@ -23,7 +29,18 @@ pub enum TicketStatus {
     Cancelled = 3
 }
 
-#[derive(Serialize, Deserialize, Type, FromPrimitive, Debug)]
+impl TicketStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TicketStatus::Open => "Открыт",
+            TicketStatus::InProgress => "В работе",
+            TicketStatus::Closed => "Выполнено",
+            TicketStatus::Cancelled => "Отменено",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Type, FromPrimitive, Clone, Copy, Debug)]
 #[serde(rename_all = "lowercase")]
 #[repr(i16)]
 pub enum TicketPriority {
@@ -34,6 +51,27 @@ pub enum TicketPriority {
     Critical = 3
 }
 
+impl TicketPriority {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TicketPriority::Low => "Низкий",
+            TicketPriority::Medium => "Средний",
+            TicketPriority::High => "Высокий",
+            TicketPriority::Critical => "Критический",
+        }
+    }
+}
+
+/// What made a ticket escalate.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum EscalationTrigger {
+    /// Nobody has responded to the ticket for too long.
+    NoResponse,
+    /// The planned date has passed, but the ticket is not done.
+    PlannedAtPassed,
+}
+
 #[derive(Deserialize, Clone, Copy, EnumIter, Default, FromPrimitive)]
 #[serde(from = "i8")]
 #[repr(i8)]
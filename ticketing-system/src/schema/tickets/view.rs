@@ -0,0 +1,39 @@
+use chrono::{DateTime, Utc};
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+
+use crate::schema::common::UserId;
+
+pub type TicketViewId = i32;
+
+/// Used both to create and to replace a view.
+#[derive(Deserialize, Validate, Debug)]
+pub struct TicketViewSchema {
+    #[garde(length(min = 1, max = 64))]
+    pub name: String,
+    /// Parameters of `GET /v1/tickets/`. Pagination ones are not stored.
+    #[garde(skip)]
+    pub filters: serde_json::Map<String, serde_json::Value>,
+    /// Shares the view with the members of the department.
+    #[garde(range(min = 1))]
+    pub department_id: Option<i16>,
+}
+
+// Output
+
+#[derive(Serialize, Debug)]
+pub struct TicketView {
+    pub id: TicketViewId,
+    pub owner_id: UserId,
+    pub name: String,
+    pub filters: serde_json::Value,
+    pub department_id: Option<i16>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize)]
+pub struct TicketViewBadge {
+    pub id: TicketViewId,
+    /// Tickets created since the user last opened the view.
+    pub new_tickets: u64,
+}
\ No newline at end of file
@@ -0,0 +1,216 @@
+use std::time::Duration;
+
+use anyhow::Context;
+use sqlx::{PgPool, Postgres, Transaction};
+use tokio::time::MissedTickBehavior;
+
+use crate::{
+    auth::types::{UserRole, UserStatus},
+    schema::{absence::AbsenceId, common::UserId, tickets::{TicketId, TicketStatus}},
+};
+
+struct DueAbsence {
+    pub id: AbsenceId,
+    pub user_id: UserId,
+    pub kind: i16,
+    pub deputy_id: Option<UserId>,
+    pub reassign_tickets: bool,
+    pub started: bool,
+}
+
+/// Applies absence boundaries in the background, since nobody calls the API
+/// at the moment a vacation starts or ends.
+pub fn spawn_absence_scheduler(pool: PgPool, interval: Duration) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            ticker.tick().await;
+
+            if let Err(e) = process_due_absences(&pool).await {
+                tracing::error!("Failed to process absences: {:?}", e);
+            }
+        }
+    });
+}
+
+/// Starts and finishes absences whose boundaries have passed. Rows are
+/// locked with `SKIP LOCKED`, so several instances can run this at once.
+#[tracing::instrument(name = "Process due absences", skip(pool))]
+pub async fn process_due_absences(pool: &PgPool) -> Result<(), anyhow::Error> {
+    process_absences(pool, None).await
+}
+
+/// Applies the boundaries of a single absence right after it is created or
+/// cancelled. Failures are left for the scheduler to retry.
+#[tracing::instrument(name = "Process absence", skip(pool))]
+pub async fn process_absence(pool: &PgPool, absence_id: AbsenceId) {
+    if let Err(e) = process_absences(pool, Some(absence_id)).await {
+        tracing::error!("Failed to process absence {}: {:?}", absence_id, e);
+    }
+}
+
+async fn process_absences(pool: &PgPool, absence_id: Option<AbsenceId>) -> Result<(), anyhow::Error> {
+    let mut transaction = pool.begin()
+        .await
+        .context("Failed to begin transaction")?;
+
+    // Finished first, so back-to-back absences end up with the status of the new one.
+    let ending = sqlx::query_as!(
+        DueAbsence,
+        "SELECT id, user_id, kind, deputy_id, reassign_tickets, started
+        FROM user_absences
+        WHERE NOT finished AND ends_at <= NOW() AND ($1::int IS NULL OR id = $1)
+        FOR UPDATE SKIP LOCKED",
+        absence_id
+    )
+    .fetch_all(&mut *transaction)
+    .await
+    .context("Failed to get ending absences")?;
+
+    for absence in &ending {
+        finish_absence(&mut transaction, absence)
+            .await
+            .with_context(|| format!("Failed to finish absence {}", absence.id))?;
+    }
+
+    let starting = sqlx::query_as!(
+        DueAbsence,
+        "SELECT id, user_id, kind, deputy_id, reassign_tickets, started
+        FROM user_absences
+        WHERE NOT started AND starts_at <= NOW() AND ends_at > NOW() AND ($1::int IS NULL OR id = $1)
+        FOR UPDATE SKIP LOCKED",
+        absence_id
+    )
+    .fetch_all(&mut *transaction)
+    .await
+    .context("Failed to get starting absences")?;
+
+    for absence in &starting {
+        start_absence(&mut transaction, absence)
+            .await
+            .with_context(|| format!("Failed to start absence {}", absence.id))?;
+    }
+
+    transaction.commit()
+        .await
+        .context("Failed to commit transaction")?;
+
+    Ok(())
+}
+
+async fn start_absence(
+    transaction: &mut Transaction<'_, Postgres>,
+    absence: &DueAbsence,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "UPDATE users SET status = $2 WHERE id = $1",
+        absence.user_id,
+        absence.kind
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    if absence.reassign_tickets {
+        reassign_open_tickets(transaction, absence.user_id, absence.deputy_id).await?;
+    }
+
+    sqlx::query!(
+        "UPDATE user_absences SET started = TRUE WHERE id = $1",
+        absence.id
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(())
+}
+
+async fn finish_absence(
+    transaction: &mut Transaction<'_, Postgres>,
+    absence: &DueAbsence,
+) -> Result<(), sqlx::Error> {
+    // Leaves the status alone if it was changed by hand during the absence.
+    if absence.started {
+        sqlx::query!(
+            "UPDATE users SET status = $3 WHERE id = $1 AND status = $2",
+            absence.user_id,
+            absence.kind,
+            UserStatus::Available as i16
+        )
+        .execute(&mut **transaction)
+        .await?;
+    }
+
+    sqlx::query!(
+        "UPDATE user_absences SET started = TRUE, finished = TRUE WHERE id = $1",
+        absence.id
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(())
+}
+
+/// Hands open tickets over to the deputy, but only those of departments
+/// the deputy is a member of. The rest go back to the department queue.
+#[tracing::instrument(name = "Reassign open tickets of absent user", skip(transaction))]
+async fn reassign_open_tickets(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: UserId,
+    deputy_id: Option<UserId>,
+) -> Result<(), sqlx::Error> {
+    let deputy_id = match deputy_id {
+        Some(deputy_id) => sqlx::query_scalar!(
+            "SELECT id FROM users WHERE id = $1 AND is_active AND role >= $2",
+            deputy_id,
+            UserRole::Employee as i16
+        )
+        .fetch_optional(&mut **transaction)
+        .await?,
+        None => None,
+    };
+
+    let ticket_ids: Vec<TicketId> = sqlx::query_scalar!(
+        "DELETE FROM tickets_users tu
+        USING tickets t
+        WHERE t.id = tu.ticket_id AND tu.assigned_to = $1 AND t.status = ANY($2)
+        RETURNING tu.ticket_id",
+        user_id,
+        &[TicketStatus::Open as i16, TicketStatus::InProgress as i16]
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+
+    if let Some(deputy_id) = deputy_id {
+        sqlx::query!(
+            "INSERT INTO tickets_users (ticket_id, assigned_to)
+            SELECT t.id, $2
+            FROM tickets t
+            JOIN user_departments ud ON ud.department_id = t.department_id AND ud.user_id = $2
+            WHERE t.id = ANY($1)
+            ON CONFLICT DO NOTHING",
+            &ticket_ids,
+            deputy_id
+        )
+        .execute(&mut **transaction)
+        .await?;
+    }
+
+    sqlx::query!(
+        "UPDATE tickets
+        SET status = $2
+        WHERE id = ANY($1) AND status = $3 AND NOT EXISTS (
+            SELECT 1
+            FROM tickets_users tu
+            WHERE tu.ticket_id = tickets.id
+        )",
+        &ticket_ids,
+        TicketStatus::Open as i16,
+        TicketStatus::InProgress as i16
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(())
+}
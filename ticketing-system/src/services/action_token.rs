@@ -57,6 +57,25 @@ impl ActionTokenStore {
         Ok(())
     }
 
+    /// Saves the token without the payload lookup key, for payloads
+    /// that are never searched by value.
+    pub async fn save_token_only(
+        &self,
+        action: ActionTokenKind,
+        token: &str,
+        payload: &str,
+        ttl: Option<u64>,
+    ) -> Result<(), anyhow::Error> {
+        let mut con = self.get_connection().await?;
+        let ttl = ttl.unwrap_or(self.default_ttl);
+
+        con.set_ex::<_, _, ()>(self.token_key(action, token), payload, ttl)
+            .await
+            .context("Failed to save action token")?;
+
+        Ok(())
+    }
+
     pub async fn get_payload(
         &self,
         action: ActionTokenKind,
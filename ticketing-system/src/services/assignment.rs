@@ -0,0 +1,258 @@
+use sqlx::{PgConnection, Postgres, Transaction};
+
+use crate::{
+    auth::types::UserStatus,
+    schema::{assignment_rule::{AssignmentRuleId, AssignmentStrategy}, common::UserId, tickets::{TicketId, TicketStatus}},
+};
+
+/// What the rules see of a ticket.
+#[derive(Debug)]
+pub struct TicketFacts<'a> {
+    pub department_id: i16,
+    pub building_id: i16,
+    pub priority: i16,
+    pub title: &'a str,
+    pub description: &'a str,
+}
+
+#[derive(Debug)]
+pub struct RuleMatch {
+    pub rule_id: AssignmentRuleId,
+    pub rule_name: String,
+    pub assignee_id: UserId,
+}
+
+struct Rule {
+    pub id: AssignmentRuleId,
+    pub name: String,
+    pub department_id: Option<i16>,
+    pub building_id: Option<i16>,
+    pub priority: Option<i16>,
+    pub keywords: Vec<String>,
+    pub strategy: i16,
+}
+
+impl Rule {
+    fn matches(&self, facts: &TicketFacts<'_>) -> bool {
+        if self.department_id.is_some_and(|id| id != facts.department_id)
+            || self.building_id.is_some_and(|id| id != facts.building_id)
+            || self.priority.is_some_and(|priority| priority != facts.priority)
+        {
+            return false;
+        }
+
+        if self.keywords.is_empty() {
+            return true;
+        }
+
+        let title = facts.title.to_lowercase();
+        let description = facts.description.to_lowercase();
+
+        self.keywords.iter()
+            .map(|keyword| keyword.to_lowercase())
+            .any(|keyword| title.contains(&keyword) || description.contains(&keyword))
+    }
+}
+
+struct Candidate {
+    pub id: UserId,
+    pub load: i64,
+}
+
+/// Finds the first active rule, by position, that matches the ticket and
+/// has someone to assign. Rules without an available candidate are skipped.
+#[tracing::instrument(name = "Evaluate assignment rules", skip(conn))]
+pub async fn evaluate_rules(
+    conn: &mut PgConnection,
+    facts: &TicketFacts<'_>,
+) -> Result<Option<RuleMatch>, sqlx::Error> {
+    let rules = sqlx::query_as!(
+        Rule,
+        "SELECT id, name, department_id, building_id, priority, keywords, strategy
+        FROM assignment_rules
+        WHERE is_active
+        ORDER BY position, id"
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    for rule in rules.into_iter().filter(|rule| rule.matches(facts)) {
+        let candidates = get_candidates(conn, rule.id, facts.department_id).await?;
+
+        let assignee_id = match AssignmentStrategy::from(rule.strategy) {
+            AssignmentStrategy::RoundRobin => {
+                let last_assigned_to = lock_last_assigned_to(conn, rule.id).await?;
+                next_in_turn(&candidates, last_assigned_to)
+            }
+            AssignmentStrategy::LeastLoaded => candidates.iter()
+                .min_by_key(|candidate| candidate.load)
+                .map(|candidate| candidate.id),
+        };
+
+        if let Some(assignee_id) = assignee_id {
+            return Ok(Some(RuleMatch {
+                rule_id: rule.id,
+                rule_name: rule.name,
+                assignee_id,
+            }));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Evaluates the rules for a freshly inserted ticket and assigns it
+/// within the same transaction.
+#[tracing::instrument(name = "Apply assignment rules", skip(transaction))]
+pub async fn apply_rules(
+    transaction: &mut Transaction<'_, Postgres>,
+    ticket_id: TicketId,
+    facts: &TicketFacts<'_>,
+) -> Result<Option<RuleMatch>, sqlx::Error> {
+    let Some(rule_match) = evaluate_rules(transaction, facts).await? else {
+        return Ok(None);
+    };
+
+    sqlx::query!(
+        "INSERT INTO tickets_users (assigned_to, ticket_id)
+        VALUES ($1, $2)
+        ON CONFLICT DO NOTHING",
+        rule_match.assignee_id,
+        ticket_id
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    sqlx::query!(
+        "UPDATE tickets
+        SET status = $1
+        WHERE id = $2 AND status = $3",
+        TicketStatus::InProgress as i16,
+        ticket_id,
+        TicketStatus::Open as i16
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    sqlx::query!(
+        "UPDATE assignment_rules SET last_assigned_to = $2 WHERE id = $1",
+        rule_match.rule_id,
+        rule_match.assignee_id
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(Some(rule_match))
+}
+
+/// Locks the rule until the transaction ends, so tickets created at the same
+/// time take turns instead of going to the same candidate.
+async fn lock_last_assigned_to(
+    conn: &mut PgConnection,
+    rule_id: AssignmentRuleId,
+) -> Result<Option<UserId>, sqlx::Error> {
+    let last_assigned_to = sqlx::query_scalar!(
+        "SELECT last_assigned_to
+        FROM assignment_rules
+        WHERE id = $1
+        FOR UPDATE",
+        rule_id
+    )
+    .fetch_optional(conn)
+    .await?;
+
+    Ok(last_assigned_to.flatten())
+}
+
+/// Active members of the department who are not sick or on vacation,
+/// limited to the rule's own pool when it has one.
+async fn get_candidates(
+    conn: &mut PgConnection,
+    rule_id: AssignmentRuleId,
+    department_id: i16,
+) -> Result<Vec<Candidate>, sqlx::Error> {
+    sqlx::query_as!(
+        Candidate,
+        r#"SELECT u.id, (
+            SELECT COUNT(*)
+            FROM tickets_users tu
+            JOIN tickets t ON t.id = tu.ticket_id
+            WHERE tu.assigned_to = u.id AND t.status = ANY($3)
+        ) AS "load!"
+        FROM users u
+        JOIN user_departments ud ON ud.user_id = u.id AND ud.department_id = $2
+        WHERE u.is_active
+            AND u.status <> ALL($4)
+            AND (
+                NOT EXISTS (SELECT 1 FROM assignment_rule_users WHERE rule_id = $1)
+                OR u.id IN (SELECT user_id FROM assignment_rule_users WHERE rule_id = $1)
+            )
+        ORDER BY u.id"#,
+        rule_id,
+        department_id,
+        &[TicketStatus::Open as i16, TicketStatus::InProgress as i16],
+        &[UserStatus::Sick as i16, UserStatus::Vacation as i16]
+    )
+    .fetch_all(conn)
+    .await
+}
+
+/// The candidate after the previous assignee, wrapping around. Candidates are sorted by id.
+fn next_in_turn(candidates: &[Candidate], last_assigned_to: Option<UserId>) -> Option<UserId> {
+    let last = last_assigned_to.unwrap_or(UserId::MIN);
+
+    candidates.iter()
+        .find(|candidate| candidate.id > last)
+        .or(candidates.first())
+        .map(|candidate| candidate.id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(keywords: &[&str]) -> Rule {
+        Rule {
+            id: 1,
+            name: "rule".to_string(),
+            department_id: Some(1),
+            building_id: None,
+            priority: None,
+            keywords: keywords.iter().map(ToString::to_string).collect(),
+            strategy: AssignmentStrategy::RoundRobin as i16,
+        }
+    }
+
+    fn facts<'a>(department_id: i16, title: &'a str) -> TicketFacts<'a> {
+        TicketFacts {
+            department_id,
+            building_id: 1,
+            priority: 0,
+            title,
+            description: "",
+        }
+    }
+
+    fn candidates(ids: &[UserId]) -> Vec<Candidate> {
+        ids.iter().map(|&id| Candidate { id, load: 0 }).collect()
+    }
+
+    #[test]
+    fn rule_matches_on_conditions_and_keywords() {
+        assert!(rule(&[]).matches(&facts(1, "Anything")));
+        assert!(!rule(&[]).matches(&facts(2, "Anything")));
+        assert!(rule(&["printer"]).matches(&facts(1, "Broken PRINTER in 101")));
+        assert!(!rule(&["printer"]).matches(&facts(1, "No network")));
+    }
+
+    #[test]
+    fn round_robin_wraps_around() {
+        let candidates = candidates(&[3, 5, 8]);
+
+        assert_eq!(next_in_turn(&candidates, None), Some(3));
+        assert_eq!(next_in_turn(&candidates, Some(3)), Some(5));
+        assert_eq!(next_in_turn(&candidates, Some(6)), Some(8));
+        assert_eq!(next_in_turn(&candidates, Some(8)), Some(3));
+        assert_eq!(next_in_turn(&[], Some(8)), None);
+    }
+}
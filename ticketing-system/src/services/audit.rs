@@ -0,0 +1,64 @@
+use crate::{auth::extractor::DeviceInfo, schema::{audit::AuditAction, common::UserId}};
+
+/// A security-relevant event, written to the append-only `audit_log` table.
+#[derive(Debug)]
+pub struct AuditEntry {
+    action: AuditAction,
+    actor_id: Option<UserId>,
+    target_id: Option<UserId>,
+    ip: Option<String>,
+    user_agent: Option<String>,
+    details: serde_json::Value,
+}
+
+impl AuditEntry {
+    pub fn new(action: AuditAction, device: &DeviceInfo) -> Self {
+        Self {
+            action,
+            actor_id: None,
+            target_id: None,
+            ip: device.ip.clone(),
+            user_agent: device.user_agent.clone(),
+            details: serde_json::json!({}),
+        }
+    }
+
+    pub fn actor(mut self, actor_id: UserId) -> Self {
+        self.actor_id = Some(actor_id);
+        self
+    }
+
+    pub fn target(mut self, target_id: UserId) -> Self {
+        self.target_id = Some(target_id);
+        self
+    }
+
+    pub fn details(mut self, details: serde_json::Value) -> Self {
+        self.details = details;
+        self
+    }
+
+    #[tracing::instrument(
+        name = "Insert audit log entry",
+        skip(executor)
+    )]
+    pub async fn record<'a, E>(self, executor: E) -> Result<(), sqlx::Error>
+    where
+        E: sqlx::Executor<'a, Database = sqlx::Postgres>,
+    {
+        sqlx::query!(
+            "INSERT INTO audit_log (actor_id, target_id, action, ip, user_agent, details)
+            VALUES ($1, $2, $3, $4, $5, $6)",
+            self.actor_id,
+            self.target_id,
+            self.action.as_str(),
+            self.ip,
+            self.user_agent,
+            self.details
+        )
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+}
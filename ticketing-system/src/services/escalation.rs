@@ -0,0 +1,287 @@
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Postgres, Transaction};
+use tokio::time::MissedTickBehavior;
+
+use crate::{
+    auth::types::{DepartmentRole, UserRole},
+    config::{EscalationPolicy, EscalationSettings},
+    events::{event_publisher::EventPublisher, Event},
+    schema::{common::UserId, notification::Notification, tickets::{EscalationTrigger, TicketId, TicketPriority, TicketStatus}},
+    services::notification::NotificationService,
+    startup::ApplicationBaseUrl,
+};
+
+const ESCALATIONS_ENABLED_AT_KEY: &str = "escalations_enabled_at";
+
+struct EscalatedTicket {
+    pub id: TicketId,
+    pub title: String,
+    pub priority: i16,
+    pub department_id: i16,
+}
+
+/// A ticket to post to the chat once the escalation is committed.
+#[derive(Debug)]
+pub struct ChatEscalation {
+    pub id: TicketId,
+    pub title: String,
+    pub priority: TicketPriority,
+    pub trigger: EscalationTrigger,
+}
+
+/// Checks tickets against the escalation policies in the background.
+pub fn spawn_escalation_scheduler(
+    pool: PgPool,
+    event_publisher: EventPublisher,
+    base_url: ApplicationBaseUrl,
+    settings: EscalationSettings,
+) {
+    if !settings.enabled || settings.policies.is_empty() {
+        return;
+    }
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(settings.check_interval());
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            ticker.tick().await;
+
+            let escalations = match process_due_escalations(&pool, &settings.policies).await {
+                Ok(escalations) => escalations,
+                Err(e) => {
+                    tracing::error!("Failed to process escalations: {:?}", e);
+                    continue;
+                }
+            };
+
+            for escalation in escalations {
+                let res = event_publisher.publish_event(
+                    Event::TicketEscalated {
+                        id: escalation.id,
+                        title: escalation.title,
+                        priority: escalation.priority,
+                        trigger: escalation.trigger,
+                    },
+                    &base_url
+                )
+                .await;
+
+                if let Err(e) = res {
+                    tracing::error!("{:?}", e);
+                }
+            }
+        }
+    });
+}
+
+/// Applies every policy to the tickets that are due and not escalated by it yet.
+/// Returns the tickets whose policies post to the chat.
+#[tracing::instrument(name = "Process due escalations", skip_all)]
+pub async fn process_due_escalations(
+    pool: &PgPool,
+    policies: &[EscalationPolicy],
+) -> Result<Vec<ChatEscalation>, anyhow::Error> {
+    let mut transaction = pool.begin()
+        .await
+        .context("Failed to begin transaction")?;
+
+    let enabled_at = get_enabled_at(&mut transaction)
+        .await
+        .context("Failed to get escalations start time")?;
+
+    let mut chat_escalations = Vec::new();
+
+    for policy in policies {
+        // A raised priority must not make the next policy of the trigger fire as well.
+        let same_trigger = policies.iter()
+            .filter(|other| other.trigger == policy.trigger && other.name != policy.name)
+            .map(|other| other.name.clone())
+            .collect::<Vec<_>>();
+
+        let tickets = escalate(&mut transaction, policy, &same_trigger, enabled_at)
+            .await
+            .with_context(|| format!("Failed to apply escalation policy {}", policy.name))?;
+
+        if policy.notify_chat {
+            chat_escalations.extend(tickets.into_iter().map(|ticket| ChatEscalation {
+                id: ticket.id,
+                title: ticket.title,
+                priority: TicketPriority::from(ticket.priority),
+                trigger: policy.trigger,
+            }));
+        }
+    }
+
+    transaction.commit()
+        .await
+        .context("Failed to commit transaction")?;
+
+    Ok(chat_escalations)
+}
+
+/// When escalations were first turned on. Older tickets are never escalated,
+/// so enabling them does not escalate the whole history at once.
+async fn get_enabled_at(
+    transaction: &mut Transaction<'_, Postgres>,
+) -> Result<DateTime<Utc>, anyhow::Error> {
+    let now = serde_json::to_value(Utc::now())
+        .context("Failed to serialize escalations start time")?;
+
+    sqlx::query!(
+        "INSERT INTO app_settings (key, value)
+        VALUES ($1, $2)
+        ON CONFLICT (key) DO NOTHING",
+        ESCALATIONS_ENABLED_AT_KEY,
+        now
+    )
+    .execute(&mut **transaction)
+    .await
+    .context("Failed to save escalations start time")?;
+
+    let value = sqlx::query_scalar!(
+        "SELECT value FROM app_settings WHERE key = $1",
+        ESCALATIONS_ENABLED_AT_KEY
+    )
+    .fetch_one(&mut **transaction)
+    .await
+    .context("Failed to get escalations start time")?;
+
+    serde_json::from_value(value).context("Failed to parse escalations start time")
+}
+
+async fn escalate(
+    transaction: &mut Transaction<'_, Postgres>,
+    policy: &EscalationPolicy,
+    same_trigger: &[String],
+    enabled_at: DateTime<Utc>,
+) -> Result<Vec<EscalatedTicket>, sqlx::Error> {
+    let threshold = Utc::now() - chrono::Duration::minutes(policy.after_minutes as i64);
+
+    // Locked rows belong to another instance, which records them itself.
+    let ticket_ids: Vec<TicketId> = sqlx::query_scalar!(
+        "WITH due AS (
+            SELECT t.id
+            FROM tickets t
+            WHERE t.status = ANY($2)
+                AND t.created_at >= $6
+                AND ($3::smallint IS NULL OR t.priority = $3)
+                AND (
+                    (
+                        $4 AND t.first_response_at IS NULL AND t.created_at <= $5
+                        AND NOT EXISTS (SELECT 1 FROM tickets_users tu WHERE tu.ticket_id = t.id)
+                    )
+                    OR (NOT $4 AND t.planned_at <= $5)
+                )
+                AND NOT EXISTS (
+                    SELECT 1
+                    FROM ticket_escalations e
+                    WHERE e.ticket_id = t.id AND e.policy = $1
+                )
+            FOR UPDATE SKIP LOCKED
+        )
+        INSERT INTO ticket_escalations (ticket_id, policy)
+        SELECT id, $1 FROM due
+        ON CONFLICT DO NOTHING
+        RETURNING ticket_id",
+        policy.name,
+        &[TicketStatus::Open as i16, TicketStatus::InProgress as i16],
+        policy.priority.map(|priority| priority as i16),
+        policy.trigger == EscalationTrigger::NoResponse,
+        threshold,
+        enabled_at
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+
+    if ticket_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    if policy.raise_priority {
+        sqlx::query!(
+            "UPDATE tickets
+            SET priority = LEAST(priority + 1, $2)
+            WHERE id = ANY($1)",
+            &ticket_ids,
+            TicketPriority::Critical as i16
+        )
+        .execute(&mut **transaction)
+        .await?;
+
+        sqlx::query!(
+            "INSERT INTO ticket_escalations (ticket_id, policy)
+            SELECT ticket_id, policy
+            FROM UNNEST($1::bigint[]) AS ticket_id
+            CROSS JOIN UNNEST($2::varchar[]) AS policy
+            ON CONFLICT DO NOTHING",
+            &ticket_ids,
+            same_trigger
+        )
+        .execute(&mut **transaction)
+        .await?;
+    }
+
+    let tickets = sqlx::query_as!(
+        EscalatedTicket,
+        "SELECT id, title, priority, department_id
+        FROM tickets
+        WHERE id = ANY($1)
+        ORDER BY id",
+        &ticket_ids
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+
+    if policy.notify_moderators {
+        let notification_service = NotificationService {};
+
+        for ticket in &tickets {
+            let moderator_ids = get_department_moderators(transaction, ticket.department_id).await?;
+
+            if moderator_ids.is_empty() {
+                continue;
+            }
+
+            notification_service.notify(
+                &mut **transaction,
+                ticket.id,
+                &moderator_ids,
+                Notification::Escalated { trigger: policy.trigger }
+            )
+            .await?;
+        }
+    }
+
+    Ok(tickets)
+}
+
+/// Moderators of the department, or moderators and admins of the whole system
+/// when nobody moderates it.
+async fn get_department_moderators(
+    transaction: &mut Transaction<'_, Postgres>,
+    department_id: i16,
+) -> Result<Vec<UserId>, sqlx::Error> {
+    let moderator_ids = sqlx::query_scalar!(
+        "SELECT u.id
+        FROM user_departments ud
+        JOIN users u ON u.id = ud.user_id
+        WHERE ud.department_id = $1 AND ud.role = $2 AND u.is_active",
+        department_id,
+        DepartmentRole::Moderator as i16
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+
+    if !moderator_ids.is_empty() {
+        return Ok(moderator_ids);
+    }
+
+    sqlx::query_scalar!(
+        "SELECT id FROM users WHERE role >= $1 AND is_active ORDER BY id",
+        UserRole::Moderator as i16
+    )
+    .fetch_all(&mut **transaction)
+    .await
+}
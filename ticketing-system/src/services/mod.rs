@@ -1,4 +1,14 @@
 pub mod attachment;
 pub mod action_token;
 pub mod registration_token;
-pub mod notification;
\ No newline at end of file
+pub mod notification;
+pub mod two_factor;
+pub mod oidc;
+pub mod rate_limiter;
+pub mod permission;
+pub mod audit;
+pub mod signup;
+pub mod password_policy;
+pub mod absence;
+pub mod assignment;
+pub mod escalation;
\ No newline at end of file
@@ -0,0 +1,270 @@
+use std::{sync::Arc, time::Duration};
+
+use anyhow::Context;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use jsonwebtoken::{decode_header, jwk::JwkSet, Algorithm, DecodingKey, Validation};
+use moka::future::Cache;
+use rand::{RngExt as _, distr::Alphanumeric, rng};
+use reqwest::{Client, Url};
+use ring::digest::{digest, SHA256};
+use secrecy::{ExposeSecret, SecretString};
+use serde::{Deserialize, Serialize};
+
+use crate::{config::OidcSettings, schema::common::UserId};
+
+const ALLOWED_ALGORITHMS: [Algorithm; 6] = [
+    Algorithm::RS256,
+    Algorithm::RS384,
+    Algorithm::RS512,
+    Algorithm::PS256,
+    Algorithm::ES256,
+    Algorithm::ES384,
+];
+
+#[derive(Deserialize)]
+struct ProviderMetadata {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+}
+
+struct Provider {
+    metadata: ProviderMetadata,
+    jwks: JwkSet,
+}
+
+#[derive(Deserialize)]
+struct TokenEndpointResponse {
+    id_token: String,
+}
+
+/// Kept in Redis between the redirect to the provider and the callback.
+#[derive(Serialize, Deserialize)]
+pub struct OidcLoginState {
+    pub nonce: String,
+    pub code_verifier: String,
+    pub fingerprint: String,
+    /// Set when a signed in user links the identity to their account.
+    #[serde(default)]
+    pub link_user_id: Option<UserId>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IdTokenClaims {
+    pub sub: String,
+    pub nonce: Option<String>,
+    pub email: Option<String>,
+    #[serde(default)]
+    pub email_verified: bool,
+}
+
+pub struct OidcClient {
+    http_client: Client,
+    pub provider_name: String,
+    issuer_url: String,
+    client_id: String,
+    client_secret: SecretString,
+    redirect_url: String,
+    scopes: String,
+    // Discovery document and keys are refetched once an hour or when
+    // a token is signed with an unknown key.
+    provider: Cache<(), Arc<Provider>>,
+}
+
+impl OidcClient {
+    pub fn new(settings: &OidcSettings) -> Self {
+        let http_client = Client::builder()
+            .timeout(settings.timeout())
+            .build()
+            .unwrap();
+
+        Self {
+            http_client,
+            provider_name: settings.provider_name.clone(),
+            issuer_url: settings.issuer_url.trim_end_matches('/').to_owned(),
+            client_id: settings.client_id.clone(),
+            client_secret: settings.client_secret.clone(),
+            redirect_url: settings.redirect_url.clone(),
+            scopes: settings.scopes.clone(),
+            provider: Cache::builder()
+                .max_capacity(1)
+                .time_to_live(Duration::from_secs(60 * 60))
+                .build(),
+        }
+    }
+
+    pub fn new_login_state(&self, fingerprint: String, link_user_id: Option<UserId>) -> OidcLoginState {
+        OidcLoginState {
+            nonce: generate_random_string(32),
+            code_verifier: generate_random_string(64),
+            fingerprint,
+            link_user_id,
+        }
+    }
+
+    pub async fn authorization_url(&self, state: &str, login_state: &OidcLoginState) -> Result<String, anyhow::Error> {
+        let provider = self.get_provider().await?;
+
+        let mut url = Url::parse(&provider.metadata.authorization_endpoint)
+            .context("Invalid authorization endpoint")?;
+
+        url.query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &self.client_id)
+            .append_pair("redirect_uri", &self.redirect_url)
+            .append_pair("scope", &self.scopes)
+            .append_pair("state", state)
+            .append_pair("nonce", &login_state.nonce)
+            .append_pair("code_challenge", &code_challenge(&login_state.code_verifier))
+            .append_pair("code_challenge_method", "S256");
+
+        Ok(url.into())
+    }
+
+    #[tracing::instrument(name = "Exchange OIDC authorization code", skip_all)]
+    pub async fn exchange_code(&self, code: &str, code_verifier: &str) -> Result<String, anyhow::Error> {
+        let provider = self.get_provider().await?;
+
+        let response: TokenEndpointResponse = self.http_client
+            .post(&provider.metadata.token_endpoint)
+            .basic_auth(&self.client_id, Some(self.client_secret.expose_secret()))
+            .form(&[
+                ("grant_type", "authorization_code"),
+                ("code", code),
+                ("redirect_uri", self.redirect_url.as_str()),
+                ("client_id", self.client_id.as_str()),
+                ("code_verifier", code_verifier),
+            ])
+            .send()
+            .await
+            .context("Failed to send token request")?
+            .error_for_status()
+            .context("Token endpoint returned an error")?
+            .json()
+            .await
+            .context("Failed to parse token response")?;
+
+        Ok(response.id_token)
+    }
+
+    #[tracing::instrument(name = "Validate OIDC ID token", skip_all)]
+    pub async fn validate_id_token(&self, id_token: &str, nonce: &str) -> Result<IdTokenClaims, anyhow::Error> {
+        let header = decode_header(id_token).context("Invalid ID token header")?;
+
+        anyhow::ensure!(
+            ALLOWED_ALGORITHMS.contains(&header.alg),
+            "Unsupported ID token algorithm: {:?}", header.alg
+        );
+
+        let mut provider = self.get_provider().await?;
+
+        let key = match find_key(&provider.jwks, header.kid.as_deref()) {
+            Some(key) => key,
+            None => {
+                self.provider.invalidate(&()).await;
+                provider = self.get_provider().await?;
+
+                find_key(&provider.jwks, header.kid.as_deref())
+                    .context("ID token is signed with an unknown key")?
+            }
+        };
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[&provider.metadata.issuer]);
+        validation.set_audience(&[&self.client_id]);
+
+        let claims = jsonwebtoken::decode::<IdTokenClaims>(id_token, &key, &validation)
+            .context("Invalid ID token")?
+            .claims;
+
+        anyhow::ensure!(
+            claims.nonce.as_deref() == Some(nonce),
+            "ID token nonce mismatch"
+        );
+
+        Ok(claims)
+    }
+
+    async fn get_provider(&self) -> Result<Arc<Provider>, anyhow::Error> {
+        self.provider
+            .try_get_with((), self.fetch_provider())
+            .await
+            .map_err(|e| anyhow::anyhow!("{:?}", e))
+    }
+
+    #[tracing::instrument(name = "Fetch OIDC provider metadata", skip(self))]
+    async fn fetch_provider(&self) -> Result<Arc<Provider>, anyhow::Error> {
+        let metadata: ProviderMetadata = self.http_client
+            .get(format!("{}/.well-known/openid-configuration", self.issuer_url))
+            .send()
+            .await
+            .context("Failed to fetch discovery document")?
+            .error_for_status()
+            .context("Discovery endpoint returned an error")?
+            .json()
+            .await
+            .context("Failed to parse discovery document")?;
+
+        anyhow::ensure!(
+            metadata.issuer.trim_end_matches('/') == self.issuer_url,
+            "Issuer mismatch in discovery document: {}", metadata.issuer
+        );
+
+        let jwks: JwkSet = self.http_client
+            .get(&metadata.jwks_uri)
+            .send()
+            .await
+            .context("Failed to fetch JWKS")?
+            .error_for_status()
+            .context("JWKS endpoint returned an error")?
+            .json()
+            .await
+            .context("Failed to parse JWKS")?;
+
+        Ok(Arc::new(Provider { metadata, jwks }))
+    }
+}
+
+fn find_key(jwks: &JwkSet, kid: Option<&str>) -> Option<DecodingKey> {
+    let jwk = match kid {
+        Some(kid) => jwks.find(kid)?,
+        // Providers with a single key may omit kid.
+        None if jwks.keys.len() == 1 => &jwks.keys[0],
+        None => return None,
+    };
+
+    DecodingKey::from_jwk(jwk).ok()
+}
+
+fn code_challenge(code_verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(digest(&SHA256, code_verifier.as_bytes()))
+}
+
+/// Value of the cookie that ties the state to the browser which started
+/// the login.
+pub fn state_binding(state: &str) -> String {
+    URL_SAFE_NO_PAD.encode(digest(&SHA256, state.as_bytes()))
+}
+
+pub fn generate_random_string(len: usize) -> String {
+    let mut rng = rng();
+
+    std::iter::repeat_with(|| rng.sample(Alphanumeric))
+        .map(char::from)
+        .take(len)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::code_challenge;
+
+    #[test]
+    fn code_challenge_is_base64url_sha256_of_verifier() {
+        assert_eq!(
+            code_challenge("dBjftJeZ4CK-1gPBNPlCBDhBvMzmxfBdaUx3MhWl1jk"),
+            "WsGxRFUmWyZbve-VaOoEIRy2mUwfeyQY9WDACKCJ5s0"
+        );
+    }
+}
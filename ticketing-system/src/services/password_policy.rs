@@ -0,0 +1,320 @@
+use std::fs;
+
+use anyhow::Context;
+use chrono::{DateTime, Duration, Utc};
+use ring::digest::{digest, SHA256};
+use sqlx::{PgPool, Postgres, Transaction};
+
+use crate::{auth::types::UserRole, config::PasswordPolicySettings, domain::password::Password, schema::common::UserId, utils::is_password_valid};
+
+// Shorter parts of the login, name or email are too common to reject.
+const MIN_PERSONAL_PART_LEN: usize = 4;
+
+// About 1% false positives.
+const BITS_PER_PASSWORD: usize = 10;
+const HASH_COUNT: u32 = 7;
+
+// A character test and the number of characters it admits.
+type CharClass = (fn(&char) -> bool, u32);
+
+#[derive(Debug, thiserror::Error)]
+pub enum PasswordPolicyError {
+    #[error("Password is too easy to guess")]
+    TooWeak,
+    #[error("Password is in a list of leaked passwords")]
+    Breached,
+    #[error("Password must not contain your login, name or email")]
+    ContainsPersonalInfo,
+    #[error("Password was used recently")]
+    Reused,
+}
+
+/// Rules applied on top of `Password` validation whenever a user sets a password.
+pub struct PasswordPolicy {
+    min_entropy_bits: f64,
+    history_size: u32,
+    admin_max_age: Option<Duration>,
+    breached: Option<BreachedPasswords>,
+}
+
+impl PasswordPolicy {
+    pub fn new(settings: &PasswordPolicySettings) -> Result<Self, anyhow::Error> {
+        let breached = settings.breached_passwords_path
+            .as_deref()
+            .map(BreachedPasswords::load)
+            .transpose()?;
+
+        Ok(Self {
+            min_entropy_bits: settings.min_entropy_bits,
+            history_size: settings.history_size,
+            admin_max_age: settings.admin_max_age_days.map(|days| Duration::days(days.into())),
+            breached,
+        })
+    }
+
+    /// `personal` holds the user's login, name and email.
+    pub fn validate(&self, password: &Password, personal: &[&str]) -> Result<(), PasswordPolicyError> {
+        let password = password.as_ref();
+
+        if estimate_entropy(password) < self.min_entropy_bits {
+            return Err(PasswordPolicyError::TooWeak);
+        }
+
+        if contains_personal_info(password, personal) {
+            return Err(PasswordPolicyError::ContainsPersonalInfo);
+        }
+
+        if self.breached.as_ref().is_some_and(|breached| breached.contains(password)) {
+            return Err(PasswordPolicyError::Breached);
+        }
+
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "Check password history", skip(self, pool, password))]
+    pub async fn is_reused(
+        &self,
+        pool: &PgPool,
+        user_id: UserId,
+        password: &Password,
+    ) -> Result<bool, anyhow::Error> {
+        if self.history_size == 0 {
+            return Ok(false);
+        }
+
+        let hashes = sqlx::query_scalar!(
+            r#"(SELECT password_hash AS "password_hash!" FROM users WHERE id = $1)
+            UNION ALL
+            (SELECT password_hash FROM password_history WHERE user_id = $1 ORDER BY id DESC LIMIT $2)"#,
+            user_id,
+            i64::from(self.history_size - 1)
+        )
+        .fetch_all(pool)
+        .await
+        .context("Failed to get password history")?;
+
+        for hash in hashes {
+            if is_password_valid(password.as_ref(), &hash).context("Failed to check password")? {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// Sets a new password hash and moves the old one to the history.
+    #[tracing::instrument(name = "Update password", skip(self, transaction, password_hash))]
+    pub async fn update_password(
+        &self,
+        transaction: &mut Transaction<'_, Postgres>,
+        user_id: UserId,
+        password_hash: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "INSERT INTO password_history (user_id, password_hash)
+            SELECT id, password_hash FROM users WHERE id = $1",
+            user_id
+        )
+        .execute(&mut **transaction)
+        .await?;
+
+        sqlx::query!(
+            "UPDATE users
+            SET password_hash = $2, password_changed_at = NOW()
+            WHERE id = $1",
+            user_id,
+            password_hash
+        )
+        .execute(&mut **transaction)
+        .await?;
+
+        sqlx::query!(
+            "DELETE FROM password_history
+            WHERE user_id = $1 AND id NOT IN (
+                SELECT id FROM password_history
+                WHERE user_id = $1
+                ORDER BY id DESC
+                LIMIT $2
+            )",
+            user_id,
+            i64::from(self.history_size.saturating_sub(1))
+        )
+        .execute(&mut **transaction)
+        .await?;
+
+        Ok(())
+    }
+
+    pub fn must_change_password(&self, role: UserRole, password_changed_at: DateTime<Utc>) -> bool {
+        role.has_access(UserRole::Admin)
+            && self.admin_max_age.is_some_and(|max_age| Utc::now() - password_changed_at > max_age)
+    }
+}
+
+/// Bits per character for the used character classes, with repeated
+/// and sequential characters counted as nearly free.
+fn estimate_entropy(password: &str) -> f64 {
+    // Non-ASCII letters are sized as the Cyrillic alphabet.
+    let classes: [CharClass; 6] = [
+        (char::is_ascii_lowercase, 26),
+        (char::is_ascii_uppercase, 26),
+        (char::is_ascii_digit, 10),
+        (char::is_ascii_punctuation, 33),
+        (|c| !c.is_ascii() && c.is_lowercase(), 33),
+        (|c| !c.is_ascii() && c.is_uppercase(), 33),
+    ];
+
+    let pool: u32 = classes.iter()
+        .filter(|(matches, _)| password.chars().any(|c| matches(&c)))
+        .map(|(_, size)| size)
+        .sum();
+
+    let char_bits = f64::from(pool.max(1)).log2();
+
+    let mut bits = 0.0;
+    let mut prev = None;
+
+    for c in password.chars() {
+        bits += match prev {
+            Some(p) if p == c => 1.0,
+            Some(p) if u32::abs_diff(p as u32, c as u32) == 1 => 2.0,
+            _ => char_bits,
+        };
+        prev = Some(c);
+    }
+
+    bits
+}
+
+fn contains_personal_info(password: &str, personal: &[&str]) -> bool {
+    let password = password.to_lowercase();
+
+    personal.iter()
+        .flat_map(|value| value.split(|c: char| c.is_whitespace() || c == '@'))
+        .filter(|part| part.chars().count() >= MIN_PERSONAL_PART_LEN)
+        .any(|part| password.contains(&part.to_lowercase()))
+}
+
+/// Bloom filter of leaked passwords. The file holds the hash count as
+/// a little-endian `u32` followed by the bit array.
+pub struct BreachedPasswords {
+    hash_count: u32,
+    bits: Vec<u8>,
+}
+
+impl BreachedPasswords {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            hash_count: HASH_COUNT,
+            bits: vec![0; (capacity.max(1) * BITS_PER_PASSWORD).div_ceil(8)],
+        }
+    }
+
+    pub fn load(path: &str) -> Result<Self, anyhow::Error> {
+        let bytes = fs::read(path)
+            .with_context(|| format!("Failed to read breached passwords file {}", path))?;
+
+        Self::from_bytes(&bytes)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, anyhow::Error> {
+        let Some((header, bits)) = bytes.split_first_chunk::<4>() else {
+            anyhow::bail!("Breached passwords file is too short");
+        };
+
+        let hash_count = u32::from_le_bytes(*header);
+
+        if hash_count == 0 || bits.is_empty() {
+            anyhow::bail!("Breached passwords file is empty");
+        }
+
+        Ok(Self {
+            hash_count,
+            bits: bits.to_vec(),
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(4 + self.bits.len());
+        bytes.extend_from_slice(&self.hash_count.to_le_bytes());
+        bytes.extend_from_slice(&self.bits);
+        bytes
+    }
+
+    pub fn insert(&mut self, password: &str) {
+        for idx in self.indexes(password) {
+            self.bits[idx / 8] |= 1 << (idx % 8);
+        }
+    }
+
+    pub fn contains(&self, password: &str) -> bool {
+        self.indexes(password)
+            .all(|idx| self.bits[idx / 8] & (1 << (idx % 8)) != 0)
+    }
+
+    // Double hashing over two halves of SHA-256.
+    fn indexes(&self, password: &str) -> impl Iterator<Item = usize> + use<> {
+        let hash = digest(&SHA256, password.as_bytes());
+        let (h1, rest) = hash.as_ref().split_first_chunk::<8>().unwrap();
+        let h1 = u64::from_le_bytes(*h1);
+        let h2 = u64::from_le_bytes(*rest.first_chunk::<8>().unwrap()) | 1;
+        let len = self.bits.len() as u64 * 8;
+
+        (0..u64::from(self.hash_count))
+            .map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % len) as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{contains_personal_info, estimate_entropy, BreachedPasswords};
+
+    #[test]
+    fn sequences_and_repeats_have_low_entropy() {
+        assert!(estimate_entropy("abcdefgh1") < 25.0);
+        assert!(estimate_entropy("aaaaaaa1") < 20.0);
+        assert!(estimate_entropy("Tundra-river7") > 60.0);
+        assert!(estimate_entropy("абвгдежз") < 25.0);
+    }
+
+    #[test]
+    fn cyrillic_passphrase_has_high_entropy() {
+        assert!(estimate_entropy("тихий дом у реки") > 40.0);
+        assert!(estimate_entropy("Зелёная-лампа") > 60.0);
+    }
+
+    #[test]
+    fn personal_info_is_found_case_insensitively() {
+        let personal = ["ivanov", "Иван Petrov", "ivan.p@kpfu.ru"];
+
+        assert!(contains_personal_info("MyIvanov99", &personal));
+        assert!(contains_personal_info("petrov2024", &personal));
+        assert!(contains_personal_info("x-ivan.p-1", &personal));
+        assert!(!contains_personal_info("Tundra-river7", &personal));
+    }
+
+    #[test]
+    fn short_personal_parts_are_ignored() {
+        assert!(!contains_personal_info("bob-the-builder1", &["bob"]));
+    }
+
+    #[test]
+    fn breached_passwords_survive_serialization() {
+        let mut filter = BreachedPasswords::with_capacity(3);
+
+        for password in ["password1", "qwerty123", "iloveyou1"] {
+            filter.insert(password);
+        }
+
+        let filter = BreachedPasswords::from_bytes(&filter.to_bytes()).unwrap();
+
+        assert!(filter.contains("password1"));
+        assert!(filter.contains("qwerty123"));
+        assert!(!filter.contains("Tundra-river7"));
+    }
+
+    #[test]
+    fn truncated_filter_is_rejected() {
+        assert!(BreachedPasswords::from_bytes(&[7, 0]).is_err());
+    }
+}
@@ -0,0 +1,65 @@
+use std::{collections::HashSet, str::FromStr as _, time::Duration};
+
+use anyhow::Context;
+use moka::future::Cache;
+use sqlx::PgPool;
+
+use crate::auth::permission::{Permission, Permissions};
+
+/// Resolves role permissions and keeps them in memory, since every
+/// authenticated request needs them.
+pub struct PermissionService {
+    cache: Cache<i16, Permissions>,
+}
+
+impl Default for PermissionService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PermissionService {
+    pub fn new() -> Self {
+        Self {
+            cache: Cache::builder()
+                .max_capacity(1024)
+                // Bounds staleness when roles are edited by another instance.
+                .time_to_live(Duration::from_secs(60))
+                .build(),
+        }
+    }
+
+    pub async fn get_permissions(&self, pool: &PgPool, role_id: i16) -> Result<Permissions, anyhow::Error> {
+        self.cache
+            .try_get_with(role_id, fetch_permissions(pool, role_id))
+            .await
+            .map_err(|e| anyhow::anyhow!("{:?}", e))
+    }
+
+    pub async fn invalidate(&self, role_id: i16) {
+        self.cache.invalidate(&role_id).await;
+    }
+}
+
+#[tracing::instrument(name = "Get role permissions", skip(pool))]
+async fn fetch_permissions(pool: &PgPool, role_id: i16) -> Result<Permissions, anyhow::Error> {
+    let names = sqlx::query_scalar!(
+        "SELECT permission FROM role_permissions WHERE role_id = $1",
+        role_id
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to get role permissions")?;
+
+    let permissions = names.iter()
+        .filter_map(|name| match Permission::from_str(name) {
+            Ok(permission) => Some(permission),
+            Err(_) => {
+                tracing::warn!("Unknown permission {} for role {}", name, role_id);
+                None
+            }
+        })
+        .collect::<HashSet<_>>();
+
+    Ok(Permissions::new(permissions))
+}
@@ -0,0 +1,290 @@
+use anyhow::Context;
+use bb8_redis::{
+    bb8::{Pool, PooledConnection},
+    redis::{self, AsyncCommands, Script},
+    RedisConnectionManager,
+};
+use uuid::Uuid;
+
+use crate::{config::{RateLimitSettings, RouteLimits, WindowLimit}, schema::common::UserId};
+
+const FAILURES_TTL: u64 = 60 * 60 * 24;
+
+// Sliding window log: returns 0 if the request is allowed, otherwise
+// milliseconds until the oldest request leaves the window.
+const SLIDING_WINDOW_SCRIPT: &str = r#"
+local key = KEYS[1]
+local now = tonumber(ARGV[1])
+local window = tonumber(ARGV[2])
+local limit = tonumber(ARGV[3])
+
+redis.call('ZREMRANGEBYSCORE', key, 0, now - window)
+
+if redis.call('ZCARD', key) >= limit then
+    local oldest = redis.call('ZRANGE', key, 0, 0, 'WITHSCORES')
+    return tonumber(oldest[2]) + window - now
+end
+
+redis.call('ZADD', key, now, ARGV[4])
+redis.call('PEXPIRE', key, window)
+
+return 0
+"#;
+
+#[derive(Debug, Clone, Copy)]
+pub enum RateLimitedRoute {
+    Login,
+    AccountRecovery,
+    MagicLink,
+    Signup,
+}
+
+impl RateLimitedRoute {
+    fn as_str(&self) -> &'static str {
+        match self {
+            RateLimitedRoute::Login => "login",
+            RateLimitedRoute::AccountRecovery => "account_recovery",
+            RateLimitedRoute::MagicLink => "magic_link",
+            RateLimitedRoute::Signup => "signup",
+        }
+    }
+}
+
+/// What failed logins are counted against. Known accounts are keyed by id,
+/// so their login and email share one counter.
+#[derive(Debug, Clone, Copy)]
+pub enum LockoutSubject<'a> {
+    User(UserId),
+    Login(&'a str),
+}
+
+impl LockoutSubject<'_> {
+    fn as_key(&self) -> String {
+        match self {
+            LockoutSubject::User(id) => format!("user:{}", id),
+            LockoutSubject::Login(login) => format!("login:{}", normalize(login)),
+        }
+    }
+}
+
+pub struct RateLimiter {
+    redis_pool: Pool<RedisConnectionManager>,
+    settings: RateLimitSettings,
+    script: Script,
+}
+
+impl RateLimiter {
+    pub fn new(redis_pool: Pool<RedisConnectionManager>, settings: RateLimitSettings) -> Self {
+        Self {
+            redis_pool,
+            settings,
+            script: Script::new(SLIDING_WINDOW_SCRIPT),
+        }
+    }
+
+    async fn get_connection(
+        &self,
+    ) -> Result<PooledConnection<'_, RedisConnectionManager>, anyhow::Error> {
+        self.redis_pool
+            .get()
+            .await
+            .context("Failed to get Redis connection")
+    }
+
+    fn limits(&self, route: RateLimitedRoute) -> RouteLimits {
+        match route {
+            RateLimitedRoute::Login => self.settings.login,
+            RateLimitedRoute::AccountRecovery => self.settings.account_recovery,
+            RateLimitedRoute::MagicLink => self.settings.magic_link,
+            RateLimitedRoute::Signup => self.settings.signup,
+        }
+    }
+
+    /// Records the request and returns seconds to wait if the login or
+    /// email, or the client IP, is over the route limits.
+    pub async fn check(
+        &self,
+        route: RateLimitedRoute,
+        key: &str,
+        ip: Option<&str>,
+    ) -> Result<Option<u64>, anyhow::Error> {
+        let mut conn = self.get_connection().await?;
+        let limits = self.limits(route);
+
+        let key_key = format!("rate_limit:{}:key:{}", route.as_str(), normalize(key));
+        let mut retry_after = self.hit(&mut conn, &key_key, limits.per_key).await?;
+
+        if let Some(ip) = ip {
+            let ip_key = format!("rate_limit:{}:ip:{}", route.as_str(), ip);
+            retry_after = retry_after.max(self.hit(&mut conn, &ip_key, limits.per_ip).await?);
+        }
+
+        if let Some(retry_after) = retry_after {
+            tracing::warn!(
+                "Rate limit exceeded on {} for {:?} from {:?}, retry after {}s",
+                route.as_str(), key, ip, retry_after
+            );
+        }
+
+        Ok(retry_after)
+    }
+
+    async fn hit(
+        &self,
+        conn: &mut PooledConnection<'_, RedisConnectionManager>,
+        key: &str,
+        limit: WindowLimit,
+    ) -> Result<Option<u64>, anyhow::Error> {
+        let wait_ms: u64 = self.script
+            .key(key)
+            .arg(chrono::Utc::now().timestamp_millis())
+            .arg(limit.window_seconds * 1000)
+            .arg(limit.max_requests)
+            .arg(Uuid::new_v4().to_string())
+            .invoke_async(&mut **conn)
+            .await
+            .context("Failed to run rate limit script")?;
+
+        Ok((wait_ms > 0).then(|| wait_ms.div_ceil(1000)))
+    }
+
+    /// Returns seconds left if the account or login is locked out.
+    pub async fn get_lockout(&self, subject: LockoutSubject<'_>) -> Result<Option<u64>, anyhow::Error> {
+        let mut conn = self.get_connection().await?;
+
+        let ttl: i64 = conn.ttl(lock_key(subject))
+            .await
+            .context("Failed to get lockout TTL")?;
+
+        Ok((ttl > 0).then_some(ttl as u64))
+    }
+
+    /// Counts a failed login and locks the subject once the threshold is
+    /// reached. Returns the lockout duration in seconds.
+    pub async fn register_login_failure(&self, subject: LockoutSubject<'_>) -> Result<Option<u64>, anyhow::Error> {
+        let mut conn = self.get_connection().await?;
+        let lockout = self.settings.lockout;
+
+        let (failures,): (u32,) = redis::pipe()
+            .incr(failures_key(subject), 1)
+            .expire(failures_key(subject), FAILURES_TTL as i64)
+            .ignore()
+            .query_async(&mut *conn)
+            .await
+            .context("Failed to count login failure")?;
+
+        if failures < lockout.threshold {
+            return Ok(None);
+        }
+
+        let exponent = (failures - lockout.threshold).min(16);
+        let delay = lockout.base_delay_seconds
+            .saturating_mul(1 << exponent)
+            .min(lockout.max_delay_seconds);
+
+        conn.set_ex::<_, _, ()>(lock_key(subject), failures, delay)
+            .await
+            .context("Failed to lock login")?;
+
+        tracing::warn!(
+            "{} locked for {}s after {} failed attempts",
+            subject.as_key(), delay, failures
+        );
+
+        Ok(Some(delay))
+    }
+
+    pub async fn reset_login_failures(&self, subject: LockoutSubject<'_>) -> Result<(), anyhow::Error> {
+        self.unlock(subject).await
+    }
+
+    pub async fn unlock(&self, subject: LockoutSubject<'_>) -> Result<(), anyhow::Error> {
+        let mut conn = self.get_connection().await?;
+
+        conn.del::<_, ()>(vec![failures_key(subject), lock_key(subject)])
+            .await
+            .context("Failed to delete lockout keys")?;
+
+        Ok(())
+    }
+}
+
+fn normalize(key: &str) -> String {
+    key.trim().to_lowercase()
+}
+
+fn failures_key(subject: LockoutSubject<'_>) -> String {
+    format!("login_failures:{}", subject.as_key())
+}
+
+fn lock_key(subject: LockoutSubject<'_>) -> String {
+    format!("login_lock:{}", subject.as_key())
+}
+
+#[cfg(test)]
+mod tests {
+    use bb8_redis::{bb8::Pool, RedisConnectionManager};
+    use uuid::Uuid;
+
+    use crate::config::{LockoutSettings, RateLimitSettings, RouteLimits, WindowLimit};
+
+    use super::{LockoutSubject, RateLimitedRoute, RateLimiter};
+
+    async fn create_limiter() -> RateLimiter {
+        let manager = RedisConnectionManager::new("redis://127.0.0.1:6379")
+            .expect("Failed to create Redis manager");
+
+        let pool = Pool::builder()
+            .build(manager)
+            .await
+            .unwrap();
+
+        let limit = WindowLimit { max_requests: 2, window_seconds: 60 };
+
+        RateLimiter::new(pool, RateLimitSettings {
+            login: RouteLimits { per_key: limit, per_ip: limit },
+            account_recovery: RouteLimits { per_key: limit, per_ip: limit },
+            magic_link: RouteLimits { per_key: limit, per_ip: limit },
+            signup: RouteLimits { per_key: limit, per_ip: limit },
+            lockout: LockoutSettings {
+                threshold: 2,
+                base_delay_seconds: 30,
+                max_delay_seconds: 100,
+            },
+        })
+    }
+
+    #[tokio::test]
+    async fn check_limits_requests_per_key() {
+        let limiter = create_limiter().await;
+        let login = Uuid::new_v4().to_string();
+
+        assert_eq!(limiter.check(RateLimitedRoute::Login, &login, None).await.unwrap(), None);
+        assert_eq!(limiter.check(RateLimitedRoute::Login, &login, None).await.unwrap(), None);
+
+        let retry_after = limiter.check(RateLimitedRoute::Login, &login, None).await.unwrap();
+        assert!(matches!(retry_after, Some(1..=60)));
+
+        // Other routes have their own windows.
+        assert_eq!(limiter.check(RateLimitedRoute::AccountRecovery, &login, None).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn lockout_delay_grows_and_is_capped() {
+        let limiter = create_limiter().await;
+        let login = Uuid::new_v4().to_string();
+        let subject = LockoutSubject::Login(&login);
+
+        assert_eq!(limiter.register_login_failure(subject).await.unwrap(), None);
+        assert_eq!(limiter.register_login_failure(subject).await.unwrap(), Some(30));
+        assert_eq!(limiter.register_login_failure(subject).await.unwrap(), Some(60));
+        assert_eq!(limiter.register_login_failure(subject).await.unwrap(), Some(100));
+
+        assert!(limiter.get_lockout(LockoutSubject::Login(&login.to_uppercase())).await.unwrap().is_some());
+
+        limiter.unlock(subject).await.unwrap();
+
+        assert_eq!(limiter.get_lockout(subject).await.unwrap(), None);
+        assert_eq!(limiter.register_login_failure(subject).await.unwrap(), None);
+    }
+}
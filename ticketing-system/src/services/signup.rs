@@ -0,0 +1,153 @@
+use anyhow::Context;
+use sqlx::{PgPool, Postgres, Transaction};
+
+use crate::config::SignupSettings;
+
+const SIGNUP_ENABLED_KEY: &str = "signup_enabled";
+
+// Well-known disposable email providers, blocked regardless of configuration.
+const DISPOSABLE_DOMAINS: &[&str] = &[
+    "10minutemail.com",
+    "dispostable.com",
+    "guerrillamail.com",
+    "maildrop.cc",
+    "mailinator.com",
+    "mintemail.com",
+    "sharklasers.com",
+    "temp-mail.org",
+    "tempmail.com",
+    "throwawaymail.com",
+    "trashmail.com",
+    "yopmail.com",
+];
+
+/// Decides who may register without an invite.
+pub struct SignupService {
+    allowed_domains: Vec<String>,
+    blocked_domains: Vec<String>,
+}
+
+impl SignupService {
+    pub fn new(settings: &SignupSettings) -> Self {
+        Self {
+            allowed_domains: normalize_domains(&settings.allowed_domains),
+            blocked_domains: normalize_domains(&settings.blocked_domains),
+        }
+    }
+
+    pub fn allowed_domains(&self) -> &[String] {
+        &self.allowed_domains
+    }
+
+    pub fn is_domain_allowed(&self, domain: &str) -> bool {
+        let domain = domain.to_lowercase();
+
+        let is_blocked = DISPOSABLE_DOMAINS.iter()
+            .copied()
+            .chain(self.blocked_domains.iter().map(String::as_str))
+            .any(|pattern| matches_domain(&domain, pattern));
+
+        if is_blocked {
+            return false;
+        }
+
+        // Nothing is allowed until domains are configured, so a missing
+        // configuration never opens self-registration to everyone.
+        self.allowed_domains.iter().any(|pattern| matches_domain(&domain, pattern))
+    }
+
+    // Not cached, so a toggle takes effect on every instance at once.
+    #[tracing::instrument(name = "Check if signup is enabled", skip_all)]
+    pub async fn is_enabled(&self, pool: &PgPool) -> Result<bool, anyhow::Error> {
+        let value = sqlx::query_scalar!(
+            "SELECT value FROM app_settings WHERE key = $1",
+            SIGNUP_ENABLED_KEY
+        )
+        .fetch_optional(pool)
+        .await
+        .context("Failed to get signup setting")?;
+
+        Ok(value.and_then(|value| value.as_bool()).unwrap_or(false))
+    }
+
+    #[tracing::instrument(name = "Set signup enabled", skip(self, transaction))]
+    pub async fn set_enabled(
+        &self,
+        transaction: &mut Transaction<'_, Postgres>,
+        enabled: bool,
+    ) -> Result<(), anyhow::Error> {
+        sqlx::query!(
+            "INSERT INTO app_settings (key, value)
+            VALUES ($1, $2)
+            ON CONFLICT (key) DO UPDATE
+            SET value = EXCLUDED.value, updated_at = NOW()",
+            SIGNUP_ENABLED_KEY,
+            serde_json::Value::Bool(enabled)
+        )
+        .execute(&mut **transaction)
+        .await
+        .context("Failed to update signup setting")?;
+
+        Ok(())
+    }
+}
+
+fn normalize_domains(domains: &[String]) -> Vec<String> {
+    domains.iter()
+        .map(|domain| domain.trim().trim_start_matches('@').to_lowercase())
+        .filter(|domain| !domain.is_empty())
+        .collect()
+}
+
+fn matches_domain(domain: &str, pattern: &str) -> bool {
+    domain == pattern
+        || domain.strip_suffix(pattern).is_some_and(|prefix| prefix.ends_with('.'))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::SignupSettings;
+
+    use super::SignupService;
+
+    fn service(allowed: &[&str], blocked: &[&str]) -> SignupService {
+        SignupService::new(&SignupSettings {
+            allowed_domains: allowed.iter().map(|d| d.to_string()).collect(),
+            blocked_domains: blocked.iter().map(|d| d.to_string()).collect(),
+        })
+    }
+
+    #[test]
+    fn allowed_domain_and_its_subdomains_are_accepted() {
+        let service = service(&["@KPFU.ru"], &[]);
+
+        assert!(service.is_domain_allowed("kpfu.ru"));
+        assert!(service.is_domain_allowed("Stud.Kpfu.ru"));
+        assert!(!service.is_domain_allowed("notkpfu.ru"));
+        assert!(!service.is_domain_allowed("kpfu.ru.example.com"));
+    }
+
+    #[test]
+    fn blocked_domains_take_precedence() {
+        let service = service(&["kpfu.ru"], &["guest.kpfu.ru"]);
+
+        assert!(service.is_domain_allowed("kpfu.ru"));
+        assert!(!service.is_domain_allowed("guest.kpfu.ru"));
+    }
+
+    #[test]
+    fn disposable_domains_are_rejected_even_if_allowed() {
+        let service = service(&["mailinator.com", "yopmail.com"], &[]);
+
+        assert!(!service.is_domain_allowed("mailinator.com"));
+        assert!(!service.is_domain_allowed("eu.yopmail.com"));
+    }
+
+    #[test]
+    fn no_domain_is_allowed_without_allow_list() {
+        let service = service(&[], &[]);
+
+        assert!(!service.is_domain_allowed("example.com"));
+        assert!(!service.is_domain_allowed("kpfu.ru"));
+    }
+}
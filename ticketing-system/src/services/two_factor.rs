@@ -0,0 +1,382 @@
+use anyhow::Context;
+use argon2::{password_hash::{rand_core::OsRng, SaltString}, Argon2, PasswordHasher as _};
+use rand::{RngExt as _, distr::Alphanumeric, rng};
+use sqlx::{PgPool, Postgres, Transaction};
+
+use crate::{auth::{totp, types::UserRole}, schema::{action_token::ActionTokenKind, auth::{TotpSetupResponse, TwoFactorChallenge}, common::UserId}, services::action_token::ActionTokenStore, utils::is_password_valid};
+
+const RECOVERY_CODES_COUNT: usize = 10;
+const RECOVERY_CODE_PART_LEN: usize = 5;
+const CHALLENGE_TTL: u64 = 5 * 60;
+const CHALLENGE_MAX_ATTEMPTS: u8 = 5;
+
+pub struct TwoFactorService {
+    required_role: Option<UserRole>,
+    issuer: String,
+}
+
+impl TwoFactorService {
+    pub fn new(required_role: Option<UserRole>, issuer: String) -> Self {
+        Self {
+            required_role,
+            issuer,
+        }
+    }
+
+    pub fn is_required(&self, role: UserRole) -> bool {
+        self.required_role
+            .is_some_and(|required| role.has_access(required))
+    }
+
+    #[tracing::instrument(name = "Create 2FA challenge", skip_all, fields(user_id = %user_id))]
+    pub async fn create_challenge(
+        &self,
+        store: &ActionTokenStore,
+        user_id: UserId,
+        fingerprint: String,
+    ) -> Result<String, anyhow::Error> {
+        let token = generate_token();
+
+        let challenge = TwoFactorChallenge {
+            user_id,
+            fingerprint,
+            attempts: 0,
+        };
+
+        save_challenge(store, &token, &challenge).await?;
+
+        Ok(token)
+    }
+
+    pub async fn get_challenge(
+        &self,
+        store: &ActionTokenStore,
+        token: &str,
+    ) -> Result<Option<TwoFactorChallenge>, anyhow::Error> {
+        store.get_payload(ActionTokenKind::TwoFactorChallenge, token)
+            .await?
+            .map(|payload| serde_json::from_str(&payload).context("Failed to parse 2FA challenge"))
+            .transpose()
+    }
+
+    /// Consumes the challenge, so it cannot be used concurrently.
+    pub async fn take_challenge(
+        &self,
+        store: &ActionTokenStore,
+        token: &str,
+    ) -> Result<Option<TwoFactorChallenge>, anyhow::Error> {
+        store.get_del_payload(ActionTokenKind::TwoFactorChallenge, token)
+            .await?
+            .map(|payload| serde_json::from_str(&payload).context("Failed to parse 2FA challenge"))
+            .transpose()
+    }
+
+    /// Puts the challenge back after a wrong code until attempts run out.
+    pub async fn retry_challenge(
+        &self,
+        store: &ActionTokenStore,
+        token: &str,
+        mut challenge: TwoFactorChallenge,
+    ) -> Result<(), anyhow::Error> {
+        challenge.attempts += 1;
+
+        if challenge.attempts < CHALLENGE_MAX_ATTEMPTS {
+            save_challenge(store, token, &challenge).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Stores a new pending secret. It is not used for sign in until
+    /// the user confirms it with a valid code.
+    #[tracing::instrument(name = "Start TOTP enrollment", skip(self, pool))]
+    pub async fn start_enrollment(
+        &self,
+        pool: &PgPool,
+        user_id: UserId,
+        account: &str,
+    ) -> Result<TotpSetupResponse, anyhow::Error> {
+        let secret = totp::generate_secret();
+
+        sqlx::query!(
+            "UPDATE users
+            SET totp_secret = $1, totp_last_used_step = NULL
+            WHERE id = $2 AND NOT totp_enabled",
+            secret,
+            user_id
+        )
+        .execute(pool)
+        .await
+        .context("Failed to save TOTP secret")?;
+
+        Ok(TotpSetupResponse {
+            provisioning_uri: totp::provisioning_uri(&secret, &self.issuer, account),
+            secret,
+        })
+    }
+
+    /// Returns recovery codes if the code matches the pending secret.
+    #[tracing::instrument(name = "Complete TOTP enrollment", skip(self, pool, code))]
+    pub async fn complete_enrollment(
+        &self,
+        pool: &PgPool,
+        user_id: UserId,
+        code: &str,
+    ) -> Result<Option<Vec<String>>, anyhow::Error> {
+        if !self.verify_totp(pool, user_id, code).await? {
+            return Ok(None);
+        }
+
+        let mut transaction = pool.begin()
+            .await
+            .context("Failed to begin transaction")?;
+
+        sqlx::query!(
+            "UPDATE users SET totp_enabled = TRUE WHERE id = $1",
+            user_id
+        )
+        .execute(&mut *transaction)
+        .await
+        .context("Failed to enable TOTP")?;
+
+        let codes = replace_recovery_codes(&mut transaction, user_id).await?;
+
+        transaction.commit()
+            .await
+            .context("Failed to commit transaction")?;
+
+        Ok(Some(codes))
+    }
+
+    #[tracing::instrument(name = "Disable TOTP", skip(self, pool))]
+    pub async fn disable(&self, pool: &PgPool, user_id: UserId) -> Result<(), anyhow::Error> {
+        let mut transaction = pool.begin()
+            .await
+            .context("Failed to begin transaction")?;
+
+        sqlx::query!(
+            "UPDATE users
+            SET totp_secret = NULL, totp_enabled = FALSE, totp_last_used_step = NULL
+            WHERE id = $1",
+            user_id
+        )
+        .execute(&mut *transaction)
+        .await
+        .context("Failed to disable TOTP")?;
+
+        sqlx::query!(
+            "DELETE FROM user_recovery_codes WHERE user_id = $1",
+            user_id
+        )
+        .execute(&mut *transaction)
+        .await
+        .context("Failed to delete recovery codes")?;
+
+        transaction.commit()
+            .await
+            .context("Failed to commit transaction")?;
+
+        Ok(())
+    }
+
+    /// Returns `None` when TOTP is not enabled, e.g. only a pending secret is set.
+    #[tracing::instrument(name = "Regenerate recovery codes", skip(self, pool))]
+    pub async fn regenerate_recovery_codes(&self, pool: &PgPool, user_id: UserId) -> Result<Option<Vec<String>>, anyhow::Error> {
+        let mut transaction = pool.begin()
+            .await
+            .context("Failed to begin transaction")?;
+
+        let is_enabled = sqlx::query_scalar!(
+            "SELECT totp_enabled FROM users WHERE id = $1 FOR UPDATE",
+            user_id
+        )
+        .fetch_optional(&mut *transaction)
+        .await
+        .context("Failed to check whether TOTP is enabled")?
+        .unwrap_or(false);
+
+        if !is_enabled {
+            return Ok(None);
+        }
+
+        let codes = replace_recovery_codes(&mut transaction, user_id).await?;
+
+        transaction.commit()
+            .await
+            .context("Failed to commit transaction")?;
+
+        Ok(Some(codes))
+    }
+
+    /// Accepts either a TOTP code or an unused recovery code.
+    pub async fn verify_second_factor(
+        &self,
+        pool: &PgPool,
+        user_id: UserId,
+        code: &str,
+    ) -> Result<bool, anyhow::Error> {
+        let code = code.trim();
+
+        if code.bytes().all(|b| b.is_ascii_digit()) {
+            self.verify_totp(pool, user_id, code).await
+        } else {
+            use_recovery_code(pool, user_id, code).await
+        }
+    }
+
+    #[tracing::instrument(name = "Verify TOTP code", skip(self, pool, code))]
+    pub async fn verify_totp(
+        &self,
+        pool: &PgPool,
+        user_id: UserId,
+        code: &str,
+    ) -> Result<bool, anyhow::Error> {
+        let secret = sqlx::query_scalar!(
+            "SELECT totp_secret FROM users WHERE id = $1",
+            user_id
+        )
+        .fetch_optional(pool)
+        .await
+        .context("Failed to get TOTP secret")?
+        .flatten();
+
+        let step = match secret {
+            Some(secret) => totp::verify(&secret, code.trim(), chrono::Utc::now().timestamp()),
+            None => None,
+        };
+
+        let step = match step {
+            Some(step) => step,
+            None => return Ok(false),
+        };
+
+        // A code can be used only once, even inside its time window.
+        let result = sqlx::query!(
+            "UPDATE users
+            SET totp_last_used_step = $2
+            WHERE id = $1 AND (totp_last_used_step IS NULL OR totp_last_used_step < $2)",
+            user_id,
+            step
+        )
+        .execute(pool)
+        .await
+        .context("Failed to update TOTP last used step")?;
+
+        Ok(result.rows_affected() == 1)
+    }
+}
+
+#[tracing::instrument(name = "Use recovery code", skip(pool, code))]
+async fn use_recovery_code(pool: &PgPool, user_id: UserId, code: &str) -> Result<bool, anyhow::Error> {
+    let code = code.to_lowercase();
+
+    let rows = sqlx::query!(
+        "SELECT id, code_hash
+        FROM user_recovery_codes
+        WHERE user_id = $1 AND used_at IS NULL",
+        user_id
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to get recovery codes")?;
+
+    for row in rows {
+        if !is_password_valid(&code, &row.code_hash).context("Failed to verify recovery code")? {
+            continue;
+        }
+
+        let result = sqlx::query!(
+            "UPDATE user_recovery_codes
+            SET used_at = NOW()
+            WHERE id = $1 AND used_at IS NULL",
+            row.id
+        )
+        .execute(pool)
+        .await
+        .context("Failed to mark recovery code as used")?;
+
+        return Ok(result.rows_affected() == 1);
+    }
+
+    Ok(false)
+}
+
+#[tracing::instrument(name = "Replace recovery codes", skip(transaction))]
+async fn replace_recovery_codes(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: UserId,
+) -> Result<Vec<String>, anyhow::Error> {
+    let codes: Vec<String> = (0..RECOVERY_CODES_COUNT)
+        .map(|_| generate_recovery_code())
+        .collect();
+
+    let argon2 = Argon2::default();
+
+    let hashes = codes.iter()
+        .map(|code| {
+            let salt = SaltString::generate(&mut OsRng);
+            argon2.hash_password(code.as_bytes(), &salt)
+                .map(|hash| hash.to_string())
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .context("Failed to hash recovery codes")?;
+
+    sqlx::query!(
+        "DELETE FROM user_recovery_codes WHERE user_id = $1",
+        user_id
+    )
+    .execute(&mut **transaction)
+    .await
+    .context("Failed to delete recovery codes")?;
+
+    sqlx::query!(
+        "INSERT INTO user_recovery_codes (user_id, code_hash)
+        SELECT $1, code_hash FROM UNNEST($2::text[]) AS code_hash",
+        user_id,
+        &hashes
+    )
+    .execute(&mut **transaction)
+    .await
+    .context("Failed to insert recovery codes")?;
+
+    Ok(codes)
+}
+
+async fn save_challenge(
+    store: &ActionTokenStore,
+    token: &str,
+    challenge: &TwoFactorChallenge,
+) -> Result<(), anyhow::Error> {
+    let payload = serde_json::to_string(challenge)
+        .context("Failed to serialize 2FA challenge")?;
+
+    store.save_token_only(
+        ActionTokenKind::TwoFactorChallenge,
+        token,
+        &payload,
+        Some(CHALLENGE_TTL),
+    )
+    .await
+}
+
+fn generate_token() -> String {
+    let mut rng = rng();
+
+    std::iter::repeat_with(|| rng.sample(Alphanumeric))
+        .map(char::from)
+        .take(32)
+        .collect()
+}
+
+fn generate_recovery_code() -> String {
+    let mut rng = rng();
+
+    let mut part = || -> String {
+        std::iter::repeat_with(|| rng.sample(Alphanumeric))
+            .map(|c| char::from(c).to_ascii_lowercase())
+            .take(RECOVERY_CODE_PART_LEN)
+            .collect()
+    };
+
+    format!("{}-{}", part(), part())
+}
@@ -4,10 +4,10 @@ use actix_multipart::form::MultipartFormConfig;
 use actix_web::{dev::Server, web::{self, Data}, App, HttpResponse, HttpServer};
 use bb8_redis::{bb8::Pool, RedisConnectionManager};
 use moka::future::CacheBuilder;
-use sqlx::{postgres::PgPoolOptions, PgPool};
+use sqlx::{postgres::PgPoolOptions, types::ipnetwork::IpNetwork, PgPool};
 use tracing_actix_web::TracingLogger;
 
-use crate::{auth::{jwt::JwtService, token_store::TokenStore}, cache_expiry::CacheExpiry, config::Settings, email_client::EmailClient, events::event_publisher::EventPublisher, routes::v1::{config, tickets::{metrics::{GetMetricsSchema, TicketsMetrics}, stats::TicketsStats}}, services::{action_token::ActionTokenStore, attachment::AttachmentService, notification::NotificationService, registration_token::RegistrationTokenStore}};
+use crate::{auth::{jwt::JwtService, token_store::TokenStore}, cache_expiry::CacheExpiry, config::{MagicLinkSettings, Settings}, directory::DirectoryProvider, email_client::EmailClient, events::event_publisher::EventPublisher, routes::{jwks::jwks, v1::{config, tickets::{metrics::{GetMetricsSchema, TicketsMetrics}, stats::TicketsStats}}}, services::{absence::spawn_absence_scheduler, action_token::ActionTokenStore, attachment::AttachmentService, escalation::spawn_escalation_scheduler, notification::NotificationService, oidc::OidcClient, password_policy::PasswordPolicy, permission::PermissionService, rate_limiter::RateLimiter, registration_token::RegistrationTokenStore, signup::SignupService, two_factor::TwoFactorService}};
 
 pub struct Application {
     server: Server,
@@ -16,6 +16,17 @@ pub struct Application {
 
 impl Application {
     pub async fn build(config: Settings) -> Result<Self, std::io::Error> {
+        let directory = config.ldap
+            .as_ref()
+            .map(|ldap| ldap.get_directory());
+
+        Self::build_with_directory(config, directory).await
+    }
+
+    pub async fn build_with_directory(
+        config: Settings,
+        directory: Option<Arc<dyn DirectoryProvider>>,
+    ) -> Result<Self, std::io::Error> {
         let connection_pool = PgPoolOptions::new()
             .max_connections(30)
             .connect_lazy_with(config.database.with_db());
@@ -42,6 +53,19 @@ impl Application {
         let email_client = config.email_client.get_email_client();
 
         let jwt_service = JwtService::new(&config.auth).unwrap();
+        let token_store = TokenStore::new(
+            redis_pool.clone(),
+            config.auth.refresh_token_lifetime.as_secs()
+        );
+        let two_factor_service = TwoFactorService::new(
+            config.auth.two_factor_required_role,
+            config.auth.issuer.clone()
+        );
+        let oidc_client = config.oidc.as_ref().map(OidcClient::new);
+        let rate_limiter = RateLimiter::new(redis_pool.clone(), config.rate_limit.clone());
+        let signup_service = SignupService::new(&config.signup);
+        let password_policy = PasswordPolicy::new(&config.password_policy)
+            .expect("Failed to load password policy");
         let attachment_service = AttachmentService::new(storage.clone(), config.storage.bucket());
 
         let timeout = config.event_publisher.timeout();
@@ -56,15 +80,32 @@ impl Application {
 
         let port = listener.local_addr().unwrap().port();
 
+        spawn_absence_scheduler(connection_pool.clone(), config.absences.check_interval());
+        spawn_escalation_scheduler(
+            connection_pool.clone(),
+            event_publisher.clone(),
+            ApplicationBaseUrl(config.application.base_url.clone()),
+            config.escalations
+        );
+
         let server = run(
             listener,
             redis_pool,
             jwt_service,
+            token_store,
+            two_factor_service,
+            rate_limiter,
+            signup_service,
+            password_policy,
             connection_pool,
             attachment_service,
             email_client,
+            directory,
+            oidc_client,
             event_publisher,
-            config.application.base_url
+            config.application.base_url,
+            config.application.trusted_proxies,
+            config.magic_link
         )?;
 
         Ok(Self {
@@ -84,26 +125,46 @@ impl Application {
 
 pub struct ApplicationBaseUrl(pub String);
 
+pub struct TrustedProxies(pub Vec<IpNetwork>);
+
 pub fn run(
     listener: TcpListener,
     redis_pool: Pool<RedisConnectionManager>,
     jwt_service: JwtService,
+    token_store: TokenStore,
+    two_factor_service: TwoFactorService,
+    rate_limiter: RateLimiter,
+    signup_service: SignupService,
+    password_policy: PasswordPolicy,
     pool: PgPool,
     attachment_service: AttachmentService,
     email_client: Arc<dyn EmailClient>,
+    directory: Option<Arc<dyn DirectoryProvider>>,
+    oidc_client: Option<OidcClient>,
     event_publisher: EventPublisher,
     base_url: String,
+    trusted_proxies: Vec<IpNetwork>,
+    magic_link: MagicLinkSettings,
 ) -> Result<Server, std::io::Error> {
-    let token_store = Data::new(TokenStore::new(redis_pool.clone()));
+    let token_store = Data::new(token_store);
     let action_token_store = Data::new(ActionTokenStore::new(redis_pool.clone()));
     let reg_store = Data::new(RegistrationTokenStore::new(redis_pool));
     let jwt_service = Data::new(jwt_service);
+    let two_factor_service = Data::new(two_factor_service);
+    let rate_limiter = Data::new(rate_limiter);
+    let signup_service = Data::new(signup_service);
+    let password_policy = Data::new(password_policy);
     let attachment_service = Data::new(attachment_service);
     let pool = Data::new(pool);
     let email_client = Data::from(email_client);
+    let directory = directory.map(Data::from);
+    let oidc_client = oidc_client.map(Data::new);
     let event_publisher = Data::new(event_publisher);
     let base_url = Data::new(ApplicationBaseUrl(base_url));
+    let trusted_proxies = Data::new(TrustedProxies(trusted_proxies));
+    let magic_link = Data::new(magic_link);
     let notification_service = Data::new(NotificationService {});
+    let permission_service = Data::new(PermissionService::new());
 
     let stats_cache = Data::new(
         CacheBuilder::<(), TicketsStats, _>::new(1)
@@ -118,29 +179,46 @@ pub fn run(
     );
 
     let server = HttpServer::new(move || {
-        App::new()
+        let mut app = App::new()
             .wrap(TracingLogger::default())
             .app_data(token_store.clone())
             .app_data(action_token_store.clone())
             .app_data(reg_store.clone())
             .app_data(jwt_service.clone())
+            .app_data(two_factor_service.clone())
+            .app_data(rate_limiter.clone())
+            .app_data(signup_service.clone())
+            .app_data(password_policy.clone())
             .app_data(attachment_service.clone())
             .app_data(pool.clone())
             .app_data(email_client.clone())
             .app_data(event_publisher.clone())
             .app_data(base_url.clone())
+            .app_data(trusted_proxies.clone())
+            .app_data(magic_link.clone())
             .app_data(notification_service.clone())
+            .app_data(permission_service.clone())
             .app_data(stats_cache.clone())
             .app_data(metrics_cache.clone())
             .app_data(
                 MultipartFormConfig::default()
                     .memory_limit(30 * 1024 * 1024)   
-            )
-            .service(
-                web::scope("")
-                    .route("/health", web::to(HttpResponse::Ok))
-                    .configure(config)
-            )
+            );
+
+        if let Some(directory) = &directory {
+            app = app.app_data(directory.clone());
+        }
+
+        if let Some(oidc_client) = &oidc_client {
+            app = app.app_data(oidc_client.clone());
+        }
+
+        app.service(
+            web::scope("")
+                .route("/health", web::to(HttpResponse::Ok))
+                .route("/.well-known/jwks.json", web::get().to(jwks))
+                .configure(config)
+        )
     })
     .listen(listener)?
     .run();
@@ -20,4 +20,12 @@ pub struct AdminTransferTemplate<'a> {
     pub base_url: &'a str,
     pub link: String,
     pub user_name: &'a str,
+}
+
+#[derive(Template)]
+#[template(path = "magic_link.html")]
+pub struct MagicLinkTemplate<'a> {
+    pub base_url: &'a str,
+    pub link: String,
+    pub ttl_minutes: u64,
 }
\ No newline at end of file
@@ -1,10 +1,10 @@
 use fake::{faker::internet::en::SafeEmail, Fake};
 use sqlx::{Connection, Executor, PgConnection, PgPool, postgres::PgPoolOptions};
-use wiremock::{Mock, MockServer, ResponseTemplate, matchers::{method, path}};
-use std::{borrow::Cow, path::Path, sync::LazyLock};
+use wiremock::{Mock, MockGuard, MockServer, Request, ResponseTemplate, matchers::{method, path}};
+use std::{borrow::Cow, path::Path, sync::{Arc, LazyLock}, time::Duration};
 use uuid::Uuid;
 use ticketing_system::{
-    auth::types::UserRole, config::{DatabaseSettings, get_config}, schema::{assets::{AssetId, CategoryId, ModelId, StatusId}, common::UserId, page::PageId, tickets::TicketId}, startup::Application, telemetry::{get_subscriber, init_subscriber}
+    auth::types::UserRole, config::{DatabaseSettings, RouteLimits, Settings, WindowLimit, get_config}, directory::DirectoryProvider, schema::{assets::{AssetId, CategoryId, ModelId, StatusId}, common::UserId, page::PageId, tickets::TicketId}, startup::Application, telemetry::{get_subscriber, init_subscriber}
 };
 
 static TRACING: LazyLock<()> = LazyLock::new(|| {
@@ -102,6 +102,17 @@ impl TestApp {
         .await
         .unwrap();
 
+        // Staff members work with every department by default
+        sqlx::query!("
+            INSERT INTO user_departments (user_id, department_id)
+            SELECT u.id, d.id FROM users u CROSS JOIN departments d
+            WHERE u.email = $1 AND u.role IN (2, 3)",
+            email
+        )
+        .execute(&self.db_pool)
+        .await
+        .unwrap();
+
         email
     }
 
@@ -205,6 +216,27 @@ impl TestApp {
             .unwrap()
     }
 
+    pub async fn create_ticket_with_title(&self, title: &str, access: &str) -> TicketId {
+        self.create_ticket_with_description(title, "Test description", access).await
+    }
+
+    pub async fn create_ticket_with_description(&self, title: &str, description: &str, access: &str) -> TicketId {
+        let body = serde_json::json!({
+            "title": title,
+            "description": description,
+            "author": "Test author",
+            "author_contacts": "Test contacts",
+            "building_id": 1,
+            "department_id": 1,
+        });
+
+        let resp = self.create_ticket(&body, None, Some(access)).await;
+        assert_eq!(resp.status(), reqwest::StatusCode::CREATED);
+
+        let json: serde_json::Value = resp.json().await.unwrap();
+        json["id"].as_i64().unwrap()
+    }
+
     pub async fn attach_asset(&self, ticket_id: TicketId, body: &serde_json::Value, token: Option<&str>) -> reqwest::Response {
         let mut builder = reqwest::Client::new()
             .post(format!("{}/v1/tickets/{}/assets", self.address, ticket_id))
@@ -655,9 +687,43 @@ impl TestApp {
 
         json["id"].as_i64().unwrap() as StatusId
     }
+
+    pub async fn send(&self, method: reqwest::Method, path: &str, body: Option<serde_json::Value>, access: &str) -> reqwest::Response {
+        let mut builder = reqwest::Client::new()
+            .request(method, format!("{}{}", self.address, path))
+            .bearer_auth(access);
+
+        if let Some(body) = body {
+            builder = builder.json(&body);
+        }
+
+        builder
+            .send()
+            .await
+            .expect("Failed to execute request")
+    }
+
+    pub async fn get(&self, path: &str, access: &str) -> reqwest::Response {
+        self.send(reqwest::Method::GET, path, None, access).await
+    }
 }
 
 pub async fn spawn_app() -> TestApp {
+    spawn_app_with(|_| {}).await
+}
+
+pub async fn spawn_app_with(configure: impl FnOnce(&mut Settings)) -> TestApp {
+    spawn_app_inner(configure, None).await
+}
+
+pub async fn spawn_app_with_directory(directory: Arc<dyn DirectoryProvider>) -> TestApp {
+    spawn_app_inner(|_| {}, Some(directory)).await
+}
+
+async fn spawn_app_inner(
+    configure: impl FnOnce(&mut Settings),
+    directory: Option<Arc<dyn DirectoryProvider>>,
+) -> TestApp {
     LazyLock::force(&TRACING);
 
     let email_server = MockServer::start().await;
@@ -682,13 +748,23 @@ pub async fn spawn_app() -> TestApp {
             always_proxy: true,
             path_style: true,
         };
+
+        // Redis is shared by all tests, so limits are enabled only by tests that check them.
+        let unlimited = WindowLimit { max_requests: u32::MAX, window_seconds: 1 };
+        c.rate_limit.login = RouteLimits { per_key: unlimited, per_ip: unlimited };
+        c.rate_limit.account_recovery = RouteLimits { per_key: unlimited, per_ip: unlimited };
+        c.rate_limit.magic_link = RouteLimits { per_key: unlimited, per_ip: unlimited };
+        c.rate_limit.signup = RouteLimits { per_key: unlimited, per_ip: unlimited };
+        c.rate_limit.lockout.threshold = u32::MAX;
+
+        configure(&mut c);
         
         c
     };
 
     let db_pool = configure_database(&config.database).await;
 
-    let application = Application::build(config)
+    let application = Application::build_with_directory(config, directory)
         .await
         .expect("Failed to build application");
 
@@ -731,6 +807,19 @@ async fn configure_database(config: &DatabaseSettings) -> PgPool {
     connection_pool
 }
 
+// Emails sent after the response arrive a bit later
+pub async fn wait_for_email(mock_guard: &MockGuard) -> Request {
+    for _ in 0..20 {
+        if let Some(request) = mock_guard.received_requests().await.pop() {
+            return request;
+        }
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    panic!("No email was sent");
+}
+
 // Returns email + token
 pub async fn create_invitation(app: &TestApp) -> (String, String) {
     let email = SafeEmail().fake::<String>();
@@ -0,0 +1,35 @@
+use crate::helpers::spawn_app;
+
+#[tokio::test]
+async fn jwks_returns_200() {
+    let app = spawn_app().await;
+
+    let resp = reqwest::Client::new()
+        .get(format!("{}/.well-known/jwks.json", app.address))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), 200);
+}
+
+#[tokio::test]
+async fn jwks_contains_kid_of_issued_tokens() {
+    let app = spawn_app().await;
+
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    let header = jsonwebtoken::decode_header(&access).unwrap();
+    let kid = header.kid.unwrap();
+
+    let jwks: jsonwebtoken::jwk::JwkSet = reqwest::Client::new()
+        .get(format!("{}/.well-known/jwks.json", app.address))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    assert!(jwks.find(&kid).is_some());
+}
@@ -1,2 +1,3 @@
 mod helpers;
-mod v1;
\ No newline at end of file
+mod v1;
+mod jwks;
\ No newline at end of file
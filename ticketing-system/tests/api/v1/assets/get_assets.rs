@@ -209,4 +209,49 @@ async fn get_assets_with_db_err_returns_500() {
     let resp = get_assets(&app, &body, Some(&access)).await;
 
     assert_eq!(resp.status(), 500);
+}
+
+#[tokio::test]
+async fn get_assets_with_cursor_returns_next_cursor() {
+    let app = spawn_app().await;
+
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    let model_id = app.create_test_model().await;
+
+    for _ in 0..11 {
+        app.create_test_asset(model_id).await;
+    }
+
+    let body = serde_json::json!({ "cursor": "", "page_size": 10 });
+
+    let json: serde_json::Value = get_assets(&app, &body, Some(&access)).await
+        .json()
+        .await
+        .unwrap();
+
+    assert_eq!(json["items"].as_array().unwrap().len(), 10);
+
+    let body = serde_json::json!({ "cursor": json["next_cursor"], "page_size": 10 });
+
+    let json: serde_json::Value = get_assets(&app, &body, Some(&access)).await
+        .json()
+        .await
+        .unwrap();
+
+    assert_eq!(json["items"].as_array().unwrap().len(), 1);
+    assert!(json["next_cursor"].is_null());
+}
+
+#[tokio::test]
+async fn get_assets_with_invalid_cursor_returns_400() {
+    let app = spawn_app().await;
+
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    let body = serde_json::json!({ "cursor": "garbage" });
+
+    let resp = get_assets(&app, &body, Some(&access)).await;
+
+    assert_eq!(resp.status(), 400);
 }
\ No newline at end of file
@@ -0,0 +1,80 @@
+use reqwest::StatusCode;
+use ticketing_system::auth::types::UserRole;
+
+use crate::helpers::spawn_app;
+
+#[tokio::test]
+async fn rules_cannot_be_managed_without_permission() {
+    let app = spawn_app().await;
+    let email = app.create_user(UserRole::Moderator).await;
+    let (access, _) = app.get_jwt_tokens(&email, "admin").await;
+
+    let resp = app.send(reqwest::Method::GET, "/v1/assignment_rules", None, &access).await;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn create_update_and_delete_rule() {
+    let app = spawn_app().await;
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    let body = serde_json::json!({
+        "name": "Printers",
+        "department_id": 1,
+        "priority": "high",
+        "keywords": ["printer", "toner"],
+        "strategy": "least_loaded",
+    });
+
+    let resp = app.send(reqwest::Method::POST, "/v1/assignment_rules", Some(body), &access).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+
+    let rule: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(rule["name"], "Printers");
+    assert_eq!(rule["priority"], "high");
+    assert_eq!(rule["strategy"], "least_loaded");
+    assert_eq!(rule["is_active"], true);
+    assert_eq!(rule["assignees"], serde_json::json!([]));
+
+    let id = rule["id"].as_i64().unwrap();
+
+    let body = serde_json::json!({
+        "name": "Printers",
+        "is_active": false,
+        "keywords": ["printer"],
+        "strategy": "round_robin",
+    });
+
+    let resp = app.send(reqwest::Method::PUT, &format!("/v1/assignment_rules/{}", id), Some(body), &access).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let rule: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(rule["is_active"], false);
+    assert_eq!(rule["department_id"], serde_json::Value::Null);
+    assert_eq!(rule["keywords"], serde_json::json!(["printer"]));
+
+    let resp = app.send(reqwest::Method::DELETE, &format!("/v1/assignment_rules/{}", id), None, &access).await;
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+
+    let resp = app.send(reqwest::Method::GET, "/v1/assignment_rules", None, &access).await;
+    let rules: Vec<serde_json::Value> = resp.json().await.unwrap();
+    assert!(rules.is_empty());
+
+    let resp = app.send(reqwest::Method::DELETE, &format!("/v1/assignment_rules/{}", id), None, &access).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn create_rule_with_unknown_department_returns_400() {
+    let app = spawn_app().await;
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    let body = serde_json::json!({
+        "name": "Nowhere",
+        "department_id": 999,
+        "strategy": "round_robin",
+    });
+
+    let resp = app.send(reqwest::Method::POST, "/v1/assignment_rules", Some(body), &access).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
\ No newline at end of file
@@ -0,0 +1,2 @@
+mod crud;
+mod routing;
\ No newline at end of file
@@ -0,0 +1,249 @@
+use reqwest::StatusCode;
+use ticketing_system::auth::types::{UserRole, UserStatus};
+
+use crate::helpers::{spawn_app, TestApp};
+
+async fn create_rule(app: &TestApp, body: serde_json::Value) -> i64 {
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    let resp = reqwest::Client::new()
+        .post(format!("{}/v1/assignment_rules", app.address))
+        .bearer_auth(&access)
+        .json(&body)
+        .send()
+        .await
+        .expect("Failed to execute request");
+
+    assert_eq!(resp.status(), StatusCode::CREATED);
+
+    let rule: serde_json::Value = resp.json().await.unwrap();
+    rule["id"].as_i64().unwrap()
+}
+
+async fn create_employee(app: &TestApp) -> i32 {
+    let email = app.create_user(UserRole::Employee).await;
+
+    sqlx::query_scalar!("SELECT id FROM users WHERE email = $1", email)
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap()
+}
+
+async fn assignees(app: &TestApp, ticket_id: i64) -> Vec<i32> {
+    sqlx::query_scalar!(
+        "SELECT assigned_to FROM tickets_users WHERE ticket_id = $1",
+        ticket_id
+    )
+    .fetch_all(&app.db_pool)
+    .await
+    .unwrap()
+}
+
+async fn first_response_at(app: &TestApp, ticket_id: i64) -> Option<chrono::DateTime<chrono::Utc>> {
+    sqlx::query_scalar!("SELECT first_response_at FROM tickets WHERE id = $1", ticket_id)
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn matching_rule_assigns_new_tickets_in_turn() {
+    let app = spawn_app().await;
+    let first = create_employee(&app).await;
+    let second = create_employee(&app).await;
+
+    create_rule(&app, serde_json::json!({
+        "name": "Printers",
+        "department_id": 1,
+        "keywords": ["printer"],
+        "strategy": "round_robin",
+        "assignees": [first, second],
+    })).await;
+
+    let (admin, _) = app.get_admin_jwt_tokens().await;
+    let ticket_id = app.create_ticket_with_title("Printer is jammed", &admin).await;
+    assert_eq!(assignees(&app, ticket_id).await, [first]);
+
+    let status = sqlx::query_scalar!("SELECT status FROM tickets WHERE id = $1", ticket_id)
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(status, 2);
+
+    let ticket_id = app.create_ticket_with_title("Another PRINTER", &admin).await;
+    assert_eq!(assignees(&app, ticket_id).await, [second]);
+
+    let ticket_id = app.create_ticket_with_title("Printer again", &admin).await;
+    assert_eq!(assignees(&app, ticket_id).await, [first]);
+
+    let ticket_id = app.create_ticket_with_title("No network", &admin).await;
+    assert!(assignees(&app, ticket_id).await.is_empty());
+}
+
+#[tokio::test]
+async fn absent_users_are_skipped() {
+    let app = spawn_app().await;
+    let sick = create_employee(&app).await;
+    let available = create_employee(&app).await;
+
+    sqlx::query!("UPDATE users SET status = $2 WHERE id = $1", sick, UserStatus::Sick as i16)
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+
+    create_rule(&app, serde_json::json!({
+        "name": "Everything",
+        "strategy": "round_robin",
+        "assignees": [sick, available],
+    })).await;
+
+    let (admin, _) = app.get_admin_jwt_tokens().await;
+    let ticket_id = app.create_ticket_with_title("Test", &admin).await;
+    assert_eq!(assignees(&app, ticket_id).await, [available]);
+}
+
+#[tokio::test]
+async fn rule_without_candidates_falls_through() {
+    let app = spawn_app().await;
+    let employee = create_employee(&app).await;
+    let client_email = app.create_user(UserRole::Client).await;
+    let client: i32 = sqlx::query_scalar!("SELECT id FROM users WHERE email = $1", client_email)
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+
+    // The client is not a member of any department.
+    create_rule(&app, serde_json::json!({
+        "name": "Outsiders",
+        "position": 0,
+        "strategy": "round_robin",
+        "assignees": [client],
+    })).await;
+
+    create_rule(&app, serde_json::json!({
+        "name": "Fallback",
+        "position": 1,
+        "strategy": "least_loaded",
+        "assignees": [employee],
+    })).await;
+
+    let (admin, _) = app.get_admin_jwt_tokens().await;
+    let ticket_id = app.create_ticket_with_title("Test", &admin).await;
+    assert_eq!(assignees(&app, ticket_id).await, [employee]);
+}
+
+#[tokio::test]
+async fn dry_run_shows_rule_without_assigning() {
+    let app = spawn_app().await;
+    let employee = create_employee(&app).await;
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    let rule_id = create_rule(&app, serde_json::json!({
+        "name": "Critical",
+        "priority": "critical",
+        "strategy": "least_loaded",
+        "assignees": [employee],
+    })).await;
+
+    let dry_run = |priority: &'static str| {
+        reqwest::Client::new()
+            .post(format!("{}/v1/assignment_rules/dry_run", app.address))
+            .bearer_auth(&access)
+            .json(&serde_json::json!({
+                "department_id": 1,
+                "building_id": 1,
+                "priority": priority,
+                "title": "Server is down",
+            }))
+            .send()
+    };
+
+    let resp = dry_run("critical").await.expect("Failed to execute request");
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let body: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(body["rule_id"], rule_id);
+    assert_eq!(body["rule_name"], "Critical");
+    assert_eq!(body["assignee_id"], employee);
+
+    let resp = dry_run("low").await.expect("Failed to execute request");
+    let body: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(body["rule_id"], serde_json::Value::Null);
+
+    let last_assigned_to = sqlx::query_scalar!(
+        "SELECT last_assigned_to FROM assignment_rules WHERE id = $1",
+        rule_id as i32
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+    assert_eq!(last_assigned_to, None);
+}
+#[tokio::test]
+async fn concurrent_tickets_take_turns() {
+    let app = spawn_app().await;
+    let first = create_employee(&app).await;
+    let second = create_employee(&app).await;
+
+    create_rule(&app, serde_json::json!({
+        "name": "Printers",
+        "department_id": 1,
+        "keywords": ["printer"],
+        "strategy": "round_robin",
+        "assignees": [first, second],
+    })).await;
+
+    let (admin, _) = app.get_admin_jwt_tokens().await;
+    let tickets = tokio::join!(
+        app.create_ticket_with_title("Printer 1", &admin),
+        app.create_ticket_with_title("Printer 2", &admin),
+        app.create_ticket_with_title("Printer 3", &admin),
+        app.create_ticket_with_title("Printer 4", &admin),
+    );
+
+    let mut assigned = Vec::new();
+
+    for ticket_id in [tickets.0, tickets.1, tickets.2, tickets.3] {
+        assigned.extend(assignees(&app, ticket_id).await);
+    }
+
+    assigned.sort();
+    assert_eq!(assigned, [first, first, second, second]);
+}
+
+#[tokio::test]
+async fn first_response_waits_for_the_assignee() {
+    let app = spawn_app().await;
+    let email = app.create_user(UserRole::Employee).await;
+    let employee = sqlx::query_scalar!("SELECT id FROM users WHERE email = $1", email)
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+
+    create_rule(&app, serde_json::json!({
+        "name": "Printers",
+        "department_id": 1,
+        "keywords": ["printer"],
+        "strategy": "round_robin",
+        "assignees": [employee],
+    })).await;
+
+    let (admin, _) = app.get_admin_jwt_tokens().await;
+    let ticket_id = app.create_ticket_with_title("Printer is jammed", &admin).await;
+    assert_eq!(assignees(&app, ticket_id).await, [employee]);
+
+    assert!(first_response_at(&app, ticket_id).await.is_none());
+
+    let (access, _) = app.get_jwt_tokens(&email, "admin").await;
+
+    let resp = reqwest::Client::new()
+        .post(format!("{}/v1/tickets/{}/messages", app.address, ticket_id))
+        .bearer_auth(&access)
+        .json(&serde_json::json!({ "message": "On my way", "is_internal": false }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::CREATED);
+
+    assert!(first_response_at(&app, ticket_id).await.is_some());
+}
@@ -0,0 +1,74 @@
+use ticketing_system::auth::types::UserRole;
+
+use crate::helpers::spawn_app;
+
+#[tokio::test]
+async fn export_audit_log_returns_xlsx() {
+    let app = spawn_app().await;
+
+    app.deactivate_user_account(2).await
+        .error_for_status()
+        .unwrap();
+
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    let resp = reqwest::Client::new()
+        .get(format!("{}/v1/audit/export?action=user_deactivated", app.address))
+        .bearer_auth(access)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), 200);
+    assert_eq!(
+        resp.headers()["content-type"],
+        "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"
+    );
+
+    let body = resp.bytes().await.unwrap();
+
+    // XLSX is a zip archive
+    assert!(body.starts_with(b"PK"));
+}
+
+#[tokio::test]
+async fn export_audit_log_as_employee_returns_403() {
+    let app = spawn_app().await;
+
+    let email = app.create_user(UserRole::Employee).await;
+
+    let (access, _) = app.get_jwt_tokens(&email, "admin").await;
+
+    let resp = reqwest::Client::new()
+        .get(format!("{}/v1/audit/export", app.address))
+        .bearer_auth(access)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), 403);
+}
+
+#[tokio::test]
+async fn export_audit_log_over_limit_returns_400() {
+    let app = spawn_app().await;
+
+    sqlx::query!(
+        "INSERT INTO audit_log (action)
+        SELECT 'user_deactivated' FROM generate_series(1, 50001)"
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    let resp = reqwest::Client::new()
+        .get(format!("{}/v1/audit/export", app.address))
+        .bearer_auth(access)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), 400);
+}
\ No newline at end of file
@@ -0,0 +1,173 @@
+use ticketing_system::auth::types::UserRole;
+
+use crate::helpers::{NEXT_USER_ID, TestApp, spawn_app};
+
+async fn get_audit_log(app: &TestApp, query: &str, access: &str) -> reqwest::Response {
+    reqwest::Client::new()
+        .get(format!("{}/v1/audit?{}", app.address, query))
+        .bearer_auth(access)
+        .send()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn change_user_role_is_recorded() {
+    let app = spawn_app().await;
+
+    app.create_user(UserRole::Employee).await;
+
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    reqwest::Client::new()
+        .patch(format!("{}/v1/user/admin/role", app.address))
+        .bearer_auth(&access)
+        .json(&serde_json::json!({
+            "id": NEXT_USER_ID,
+            "role": "moderator",
+        }))
+        .send()
+        .await
+        .unwrap()
+        .error_for_status()
+        .unwrap();
+
+    let resp = get_audit_log(&app, "action=role_changed", &access).await;
+
+    assert_eq!(resp.status(), 200);
+
+    let json: serde_json::Value = resp.json().await.unwrap();
+    let items = json["items"].as_array().unwrap();
+
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0]["actor_id"], 1);
+    assert_eq!(items[0]["target_id"], NEXT_USER_ID);
+    assert_eq!(items[0]["details"]["role_id"], UserRole::Moderator as i16);
+}
+
+#[tokio::test]
+async fn deactivation_is_recorded() {
+    let app = spawn_app().await;
+
+    app.create_user(UserRole::Employee).await;
+
+    app.deactivate_user_account(NEXT_USER_ID).await
+        .error_for_status()
+        .unwrap();
+
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    let resp = get_audit_log(&app, &format!("target_id={}", NEXT_USER_ID), &access).await;
+
+    let json: serde_json::Value = resp.json().await.unwrap();
+    let items = json["items"].as_array().unwrap();
+
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0]["action"], "user_deactivated");
+}
+
+#[tokio::test]
+async fn change_password_is_recorded() {
+    let app = spawn_app().await;
+
+    let email = app.create_user(UserRole::Client).await;
+
+    let (access, _) = app.get_jwt_tokens(&email, "admin").await;
+
+    reqwest::Client::new()
+        .put(format!("{}/v1/user/password", app.address))
+        .bearer_auth(&access)
+        .json(&serde_json::json!({
+            "current_password": "admin",
+            "new_password": "Password123!",
+        }))
+        .send()
+        .await
+        .unwrap()
+        .error_for_status()
+        .unwrap();
+
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    let resp = get_audit_log(&app, &format!("actor_id={}&action=password_changed", NEXT_USER_ID), &access).await;
+
+    let json: serde_json::Value = resp.json().await.unwrap();
+
+    assert_eq!(json["items"].as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn fingerprint_mismatch_is_recorded() {
+    let app = spawn_app().await;
+
+    let (access, refresh) = app.get_admin_jwt_tokens().await;
+
+    let resp = reqwest::Client::new()
+        .post(format!("{}/v1/auth/token", app.address))
+        .json(&serde_json::json!({
+            "refresh_token": refresh,
+            "fingerprint": "another"
+        }))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), 403);
+
+    let resp = get_audit_log(&app, "action=fingerprint_mismatch", &access).await;
+
+    let json: serde_json::Value = resp.json().await.unwrap();
+    let items = json["items"].as_array().unwrap();
+
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0]["target_id"], 1);
+}
+
+#[tokio::test]
+async fn get_audit_log_with_invalid_range_returns_400() {
+    let app = spawn_app().await;
+
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    let resp = get_audit_log(
+        &app,
+        "from_date=2026-02-01T00:00:00Z&to_date=2026-01-01T00:00:00Z",
+        &access
+    ).await;
+
+    assert_eq!(resp.status(), 400);
+}
+
+#[tokio::test]
+async fn get_audit_log_as_moderator_returns_403() {
+    let app = spawn_app().await;
+
+    let email = app.create_user(UserRole::Moderator).await;
+
+    let (access, _) = app.get_jwt_tokens(&email, "admin").await;
+
+    let resp = get_audit_log(&app, "", &access).await;
+
+    assert_eq!(resp.status(), 403);
+}
+
+#[tokio::test]
+async fn audit_log_is_append_only() {
+    let app = spawn_app().await;
+
+    app.deactivate_user_account(2).await
+        .error_for_status()
+        .unwrap();
+
+    let res = sqlx::query!("UPDATE audit_log SET action = 'user_activated'")
+        .execute(&app.db_pool)
+        .await;
+
+    assert!(res.is_err());
+
+    let res = sqlx::query!("DELETE FROM audit_log")
+        .execute(&app.db_pool)
+        .await;
+
+    assert!(res.is_err());
+}
\ No newline at end of file
@@ -0,0 +1,2 @@
+mod get_audit_log;
+mod export_audit_log;
\ No newline at end of file
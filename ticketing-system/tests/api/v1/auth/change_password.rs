@@ -21,7 +21,7 @@ async fn change_password_returns_200() {
 
     let body = serde_json::json!({
         "current_password": "admin",
-        "new_password": "admin123",
+        "new_password": "Tundra-river7",
     });
 
     let resp = change_password(&app, &body).await;
@@ -35,7 +35,7 @@ async fn change_password_with_wrong_password_returns_400() {
 
     let body = serde_json::json!({
         "current_password": "admin1",
-        "new_password": "admin123",
+        "new_password": "Tundra-river7",
     });
 
     let resp = change_password(&app, &body).await;
@@ -49,7 +49,7 @@ async fn change_password_with_db_error_returns_500() {
 
     let body = serde_json::json!({
         "current_password": "admin",
-        "new_password": "admin123",
+        "new_password": "Tundra-river7",
     });
 
     let (access, _) = app.get_admin_jwt_tokens().await;
@@ -0,0 +1,239 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use ticketing_system::{auth::types::UserRole, directory::{DirectoryError, DirectoryProvider, DirectoryUser}};
+
+use crate::helpers::{spawn_app_with_directory, TestApp};
+
+struct StubDirectory {
+    password: &'static str,
+    user: Option<DirectoryUser>,
+    fail: bool,
+}
+
+#[async_trait]
+impl DirectoryProvider for StubDirectory {
+    async fn authenticate(
+        &self,
+        login: &str,
+        password: &str
+    ) -> Result<Option<DirectoryUser>, DirectoryError> {
+        if self.fail {
+            return Err(anyhow::anyhow!("LDAP server is unavailable").into());
+        }
+
+        match &self.user {
+            Some(user) if user.login == login && password == self.password => Ok(Some(user.clone())),
+            _ => Ok(None),
+        }
+    }
+}
+
+fn directory_user(role: UserRole) -> DirectoryUser {
+    DirectoryUser {
+        login: "ivanov".to_string(),
+        name: "Иванов Иван".to_string(),
+        email: "ivanov@kpfu.ru".to_string(),
+        role,
+        department_ids: vec![1],
+    }
+}
+
+async fn login(app: &TestApp, login: &str, password: &str) -> reqwest::Response {
+    app.login(&serde_json::json!({
+        "login": login,
+        "password": password,
+        "fingerprint": "something",
+    }))
+    .await
+}
+
+#[tokio::test]
+async fn first_directory_login_provisions_user() {
+    let app = spawn_app_with_directory(Arc::new(StubDirectory {
+        password: "secret",
+        user: Some(directory_user(UserRole::Employee)),
+        fail: false,
+    }))
+    .await;
+
+    let resp = login(&app, "ivanov", "secret").await;
+    assert_eq!(resp.status(), 200);
+
+    let user = sqlx::query!(
+        "SELECT id, name, email, role, auth_provider FROM users WHERE login = 'ivanov'"
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+
+    assert_eq!(user.name, "Иванов Иван");
+    assert_eq!(user.email, "ivanov@kpfu.ru");
+    assert_eq!(user.role, UserRole::Employee as i16);
+    assert_eq!(user.auth_provider, "ldap");
+
+    let departments = sqlx::query_scalar!(
+        "SELECT department_id FROM user_departments WHERE user_id = $1",
+        user.id
+    )
+    .fetch_all(&app.db_pool)
+    .await
+    .unwrap();
+
+    assert_eq!(departments, vec![1]);
+}
+
+#[tokio::test]
+async fn directory_login_does_not_take_over_local_account() {
+    let app = spawn_app_with_directory(Arc::new(StubDirectory {
+        password: "secret",
+        user: Some(directory_user(UserRole::Moderator)),
+        fail: false,
+    }))
+    .await;
+
+    sqlx::query!(
+        "INSERT INTO users (name, email, login, password_hash, role)
+        VALUES ('old', 'ivanov@kpfu.ru', 'ivanov_old', 'hash', 1)"
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
+    let resp = login(&app, "ivanov", "secret").await;
+    assert_eq!(resp.status(), 401);
+
+    let user = sqlx::query!(
+        "SELECT login, role, auth_provider FROM users WHERE email = 'ivanov@kpfu.ru'"
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+
+    assert_eq!(user.login, "ivanov_old");
+    assert_eq!(user.role, 1);
+    assert_eq!(user.auth_provider, "local");
+}
+
+#[tokio::test]
+async fn directory_login_refuses_login_and_email_of_different_accounts() {
+    let app = spawn_app_with_directory(Arc::new(StubDirectory {
+        password: "secret",
+        user: Some(directory_user(UserRole::Employee)),
+        fail: false,
+    }))
+    .await;
+
+    sqlx::query!(
+        "INSERT INTO users (name, email, login, password_hash, role, auth_provider)
+        VALUES ('first', 'first@kpfu.ru', 'ivanov', 'hash', 1, 'ldap'),
+            ('second', 'ivanov@kpfu.ru', 'ivanov_ii', 'hash', 1, 'ldap')"
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
+    let resp = login(&app, "ivanov", "secret").await;
+    assert_eq!(resp.status(), 401);
+
+    let users = sqlx::query!(
+        "SELECT login, email FROM users WHERE auth_provider = 'ldap' ORDER BY login"
+    )
+    .fetch_all(&app.db_pool)
+    .await
+    .unwrap();
+
+    assert_eq!(users.len(), 2);
+    assert_eq!((users[0].login.as_str(), users[0].email.as_str()), ("ivanov", "first@kpfu.ru"));
+    assert_eq!((users[1].login.as_str(), users[1].email.as_str()), ("ivanov_ii", "ivanov@kpfu.ru"));
+}
+
+#[tokio::test]
+async fn directory_login_skips_unknown_departments() {
+    let mut user = directory_user(UserRole::Employee);
+    user.department_ids = vec![1, 999];
+
+    let app = spawn_app_with_directory(Arc::new(StubDirectory {
+        password: "secret",
+        user: Some(user),
+        fail: false,
+    }))
+    .await;
+
+    let resp = login(&app, "ivanov", "secret").await;
+    assert_eq!(resp.status(), 200);
+
+    let departments = sqlx::query_scalar!(
+        "SELECT ud.department_id
+        FROM user_departments ud
+        JOIN users u ON u.id = ud.user_id
+        WHERE u.login = 'ivanov'"
+    )
+    .fetch_all(&app.db_pool)
+    .await
+    .unwrap();
+
+    assert_eq!(departments, vec![1]);
+}
+
+#[tokio::test]
+async fn directory_user_cannot_use_local_password() {
+    let app = spawn_app_with_directory(Arc::new(StubDirectory {
+        password: "secret",
+        user: Some(directory_user(UserRole::Employee)),
+        fail: false,
+    }))
+    .await;
+
+    let resp = login(&app, "ivanov", "secret").await;
+    assert_eq!(resp.status(), 200);
+
+    let resp = login(&app, "ivanov", "wrong").await;
+    assert_eq!(resp.status(), 401);
+}
+
+#[tokio::test]
+async fn denied_directory_user_returns_403() {
+    struct DenyingDirectory;
+
+    #[async_trait]
+    impl DirectoryProvider for DenyingDirectory {
+        async fn authenticate(&self, _: &str, _: &str) -> Result<Option<DirectoryUser>, DirectoryError> {
+            Err(DirectoryError::AccessDenied)
+        }
+    }
+
+    let app = spawn_app_with_directory(Arc::new(DenyingDirectory)).await;
+
+    let resp = login(&app, "student", "secret").await;
+
+    assert_eq!(resp.status(), 403);
+}
+
+#[tokio::test]
+async fn local_admin_can_sign_in_when_directory_rejects_credentials() {
+    let app = spawn_app_with_directory(Arc::new(StubDirectory {
+        password: "secret",
+        user: None,
+        fail: false,
+    }))
+    .await;
+
+    let resp = login(&app, "admin@example.com", "admin").await;
+
+    assert_eq!(resp.status(), 200);
+}
+
+#[tokio::test]
+async fn local_admin_can_sign_in_when_directory_is_unavailable() {
+    let app = spawn_app_with_directory(Arc::new(StubDirectory {
+        password: "secret",
+        user: None,
+        fail: true,
+    }))
+    .await;
+
+    let resp = login(&app, "admin@example.com", "admin").await;
+
+    assert_eq!(resp.status(), 200);
+}
@@ -0,0 +1,144 @@
+use reqwest::StatusCode;
+use ticketing_system::{auth::types::UserRole, config::WindowLimit};
+use wiremock::{matchers::{method, path}, Mock, ResponseTemplate};
+
+use crate::helpers::{spawn_app, spawn_app_with, wait_for_email, TestApp};
+
+async fn spawn_app_with_magic_link() -> TestApp {
+    spawn_app_with(|c| c.magic_link.enabled = true).await
+}
+
+fn extract_token(email_request: &wiremock::Request) -> String {
+    let body: serde_json::Value = serde_json::from_slice(&email_request.body).unwrap();
+
+    linkify::LinkFinder::new()
+        .links(body["text"].as_str().unwrap())
+        .filter(|link| *link.kind() == linkify::LinkKind::Url)
+        .filter_map(|link| reqwest::Url::parse(link.as_str()).ok())
+        .find(|url| url.path() == "/magic-login")
+        .and_then(|url| {
+            url.query_pairs()
+                .find(|(key, _)| key == "token")
+                .map(|(_, value)| value.into_owned())
+        })
+        .expect("Failed to extract token from email")
+}
+
+async fn request_magic_link(app: &TestApp, email: &str) -> reqwest::Response {
+    reqwest::Client::new()
+        .post(format!("{}/v1/auth/magic/request", app.address))
+        .json(&serde_json::json!({
+            "email": email,
+            "fingerprint": "something",
+        }))
+        .send()
+        .await
+        .unwrap()
+}
+
+async fn confirm_magic_link(app: &TestApp, token: &str, fingerprint: &str) -> reqwest::Response {
+    reqwest::Client::new()
+        .post(format!("{}/v1/auth/magic/confirm", app.address))
+        .json(&serde_json::json!({
+            "token": token,
+            "fingerprint": fingerprint,
+        }))
+        .send()
+        .await
+        .unwrap()
+}
+
+async fn get_magic_link_token(app: &TestApp, email: &str) -> String {
+    let email_guard = Mock::given(path("/v1/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount_as_scoped(&app.email_server)
+        .await;
+
+    assert_eq!(request_magic_link(app, email).await.status(), StatusCode::OK);
+
+    extract_token(&wait_for_email(&email_guard).await)
+}
+
+#[tokio::test]
+async fn magic_link_returns_token_pair() {
+    let app = spawn_app_with_magic_link().await;
+
+    let email = app.create_user(UserRole::Client).await;
+
+    let token = get_magic_link_token(&app, &email).await;
+
+    let resp = confirm_magic_link(&app, &token, "something").await;
+
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let json: serde_json::Value = resp.json().await.unwrap();
+
+    assert!(json["access_token"].is_string());
+    assert!(json["refresh_token"].is_string());
+}
+
+#[tokio::test]
+async fn magic_link_can_be_used_once() {
+    let app = spawn_app_with_magic_link().await;
+
+    let email = app.create_user(UserRole::Client).await;
+
+    let token = get_magic_link_token(&app, &email).await;
+
+    assert_eq!(confirm_magic_link(&app, &token, "something").await.status(), StatusCode::OK);
+    assert_eq!(confirm_magic_link(&app, &token, "something").await.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn magic_link_from_another_device_returns_403() {
+    let app = spawn_app_with_magic_link().await;
+
+    let email = app.create_user(UserRole::Client).await;
+
+    let token = get_magic_link_token(&app, &email).await;
+
+    assert_eq!(confirm_magic_link(&app, &token, "another").await.status(), StatusCode::FORBIDDEN);
+    assert_eq!(confirm_magic_link(&app, &token, "something").await.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn magic_link_for_unknown_email_sends_nothing() {
+    let app = spawn_app_with_magic_link().await;
+
+    let _email_guard = Mock::given(path("/v1/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount_as_scoped(&app.email_server)
+        .await;
+
+    let resp = request_magic_link(&app, "nobody@example.com").await;
+
+    assert_eq!(resp.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn magic_link_is_rate_limited_per_email() {
+    let app = spawn_app_with(|c| {
+        c.magic_link.enabled = true;
+        c.rate_limit.magic_link.per_key = WindowLimit { max_requests: 1, window_seconds: 60 };
+    })
+    .await;
+
+    let email = format!("{}@example.com", uuid::Uuid::new_v4());
+
+    assert_eq!(request_magic_link(&app, &email).await.status(), StatusCode::OK);
+    assert_eq!(request_magic_link(&app, &email).await.status(), StatusCode::TOO_MANY_REQUESTS);
+}
+
+#[tokio::test]
+async fn magic_link_returns_404_when_not_enabled() {
+    let app = spawn_app().await;
+
+    let email = app.create_user(UserRole::Employee).await;
+
+    assert_eq!(request_magic_link(&app, &email).await.status(), StatusCode::NOT_FOUND);
+    assert_eq!(confirm_magic_link(&app, "token", "something").await.status(), StatusCode::NOT_FOUND);
+}
\ No newline at end of file
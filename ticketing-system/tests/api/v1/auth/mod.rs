@@ -8,4 +8,12 @@ mod request_account_recovery;
 mod confirm_account_recovery;
 mod confirm_admin_transfer;
 mod validate_admin_transfer_token;
-mod validate_recovery_token;
\ No newline at end of file
+mod validate_recovery_token;
+mod two_factor;
+mod ldap_login;
+mod oidc;
+mod sessions;
+mod rate_limit;
+mod magic_link;
+mod signup;
+mod password_policy;
\ No newline at end of file
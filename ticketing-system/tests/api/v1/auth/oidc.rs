@@ -0,0 +1,389 @@
+use jsonwebtoken::{jwk::{Jwk, JwkSet}, Algorithm, EncodingKey, Header};
+use reqwest::{redirect::Policy, StatusCode, Url};
+use ticketing_system::{auth::types::UserRole, config::{get_config, OidcSettings}};
+use wiremock::{matchers::{body_string_contains, method, path}, Mock, MockServer, ResponseTemplate};
+
+use crate::helpers::{spawn_app_with, TestApp};
+
+const CLIENT_ID: &str = "ticketing";
+const KEY_ID: &str = "mock-issuer-key";
+
+struct MockIssuer {
+    server: MockServer,
+    encoding_key: EncodingKey,
+}
+
+impl MockIssuer {
+    async fn start() -> Self {
+        let server = MockServer::start().await;
+
+        let pem = std::fs::read(get_config().unwrap().auth.private_key_path).unwrap();
+        let encoding_key = EncodingKey::from_rsa_pem(&pem).unwrap();
+
+        let mut jwk = Jwk::from_encoding_key(&encoding_key, Algorithm::RS256).unwrap();
+        jwk.common.key_id = Some(KEY_ID.to_string());
+
+        Mock::given(method("GET"))
+            .and(path("/.well-known/openid-configuration"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "issuer": server.uri(),
+                "authorization_endpoint": format!("{}/authorize", server.uri()),
+                "token_endpoint": format!("{}/token", server.uri()),
+                "jwks_uri": format!("{}/jwks", server.uri()),
+            })))
+            .mount(&server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/jwks"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(JwkSet { keys: vec![jwk] }))
+            .mount(&server)
+            .await;
+
+        Self { server, encoding_key }
+    }
+
+    fn settings(&self) -> OidcSettings {
+        OidcSettings {
+            provider_name: "kpfu".to_string(),
+            issuer_url: self.server.uri(),
+            client_id: CLIENT_ID.to_string(),
+            client_secret: "secret".to_string().into(),
+            redirect_url: "http://127.0.0.1/oidc".to_string(),
+            scopes: "openid email".to_string(),
+            timeout_milliseconds: 1000,
+        }
+    }
+
+    async fn issue_id_token(&self, code: &str, claims: serde_json::Value) {
+        let mut header = Header::new(Algorithm::RS256);
+        header.kid = Some(KEY_ID.to_string());
+
+        let id_token = jsonwebtoken::encode(&header, &claims, &self.encoding_key).unwrap();
+
+        Mock::given(method("POST"))
+            .and(path("/token"))
+            .and(body_string_contains(format!("code={}", code)))
+            .and(body_string_contains("code_verifier="))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "access_token": "provider-access-token",
+                "token_type": "Bearer",
+                "id_token": id_token,
+            })))
+            .mount(&self.server)
+            .await;
+    }
+
+    fn claims(&self, sub: &str, email: &str, nonce: &str) -> serde_json::Value {
+        let now = chrono::Utc::now().timestamp();
+
+        serde_json::json!({
+            "iss": self.server.uri(),
+            "aud": CLIENT_ID,
+            "sub": sub,
+            "email": email,
+            "email_verified": true,
+            "nonce": nonce,
+            "iat": now,
+            "exp": now + 300,
+        })
+    }
+}
+
+async fn spawn_app_with_issuer(issuer: &MockIssuer) -> TestApp {
+    let settings = issuer.settings();
+
+    spawn_app_with(|c| c.oidc = Some(settings)).await
+}
+
+fn client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .redirect(Policy::none())
+        .build()
+        .unwrap()
+}
+
+// Returns state and nonce from the redirect to the provider and the
+// state cookie set for the browser
+async fn start_login(app: &TestApp) -> (String, String, String) {
+    let resp = client()
+        .get(format!("{}/v1/auth/oidc/login?fingerprint=something", app.address))
+        .send()
+        .await
+        .expect("Failed to execute request");
+
+    assert_eq!(resp.status(), StatusCode::FOUND);
+
+    let location = Url::parse(
+        resp.headers()["location"].to_str().unwrap()
+    )
+    .unwrap();
+
+    let param = |name: &str| location.query_pairs()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.into_owned())
+        .unwrap();
+
+    assert_eq!(param("client_id"), CLIENT_ID);
+    assert_eq!(param("code_challenge_method"), "S256");
+
+    let cookie = resp.headers()
+        .get_all("set-cookie")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .find(|value| value.starts_with("oidc_state="))
+        .and_then(|value| value.split(';').next())
+        .unwrap()
+        .to_string();
+
+    (param("state"), param("nonce"), cookie)
+}
+
+async fn callback(app: &TestApp, state: &str, cookie: &str, code: &str) -> reqwest::Response {
+    client()
+        .get(format!("{}/v1/auth/oidc/callback", app.address))
+        .query(&[("state", state), ("code", code)])
+        .header("cookie", cookie)
+        .send()
+        .await
+        .expect("Failed to execute request")
+}
+
+async fn link_identity(app: &TestApp, email: &str, subject: &str) {
+    sqlx::query!(
+        "INSERT INTO user_identities (provider, subject, user_id)
+        SELECT 'kpfu', $1, id FROM users WHERE email = $2",
+        subject,
+        email
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+}
+
+// Returns state and nonce from the provider URL and the state cookie
+async fn start_link(app: &TestApp, access_token: &str) -> (String, String, String) {
+    let resp = client()
+        .post(format!("{}/v1/auth/oidc/link", app.address))
+        .bearer_auth(access_token)
+        .json(&serde_json::json!({ "fingerprint": "something" }))
+        .send()
+        .await
+        .expect("Failed to execute request");
+
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let cookie = resp.headers()["set-cookie"]
+        .to_str()
+        .unwrap()
+        .split(';')
+        .next()
+        .unwrap()
+        .to_string();
+
+    let json: serde_json::Value = resp.json().await.unwrap();
+    let url = Url::parse(json["url"].as_str().unwrap()).unwrap();
+
+    let param = |name: &str| url.query_pairs()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.into_owned())
+        .unwrap();
+
+    (param("state"), param("nonce"), cookie)
+}
+
+async fn get_identity_owner(app: &TestApp, subject: &str) -> Option<String> {
+    sqlx::query_scalar!(
+        "SELECT u.email
+        FROM user_identities i
+        JOIN users u ON u.id = i.user_id
+        WHERE i.provider = 'kpfu' AND i.subject = $1",
+        subject
+    )
+    .fetch_optional(&app.db_pool)
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn oidc_login_issues_tokens_for_linked_identity() {
+    let issuer = MockIssuer::start().await;
+    let app = spawn_app_with_issuer(&issuer).await;
+
+    let email = app.create_user(UserRole::Employee).await;
+    link_identity(&app, &email, "sub-1").await;
+
+    let (state, nonce, cookie) = start_login(&app).await;
+    issuer.issue_id_token("code-1", issuer.claims("sub-1", "other@example.com", &nonce)).await;
+
+    let resp = callback(&app, &state, &cookie, "code-1").await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let json: serde_json::Value = resp.json().await.unwrap();
+    assert!(json["access_token"].is_string());
+    assert!(json["refresh_token"].is_string());
+}
+
+#[tokio::test]
+async fn oidc_login_links_identity_by_verified_email() {
+    let issuer = MockIssuer::start().await;
+    let app = spawn_app_with_issuer(&issuer).await;
+
+    let email = app.create_user(UserRole::Employee).await;
+
+    let (state, nonce, cookie) = start_login(&app).await;
+    issuer.issue_id_token("code-1", issuer.claims("sub-1", &email.to_uppercase(), &nonce)).await;
+
+    let resp = callback(&app, &state, &cookie, "code-1").await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    assert_eq!(get_identity_owner(&app, "sub-1").await, Some(email));
+}
+
+#[tokio::test]
+async fn oidc_login_does_not_link_unverified_email() {
+    let issuer = MockIssuer::start().await;
+    let app = spawn_app_with_issuer(&issuer).await;
+
+    let email = app.create_user(UserRole::Employee).await;
+
+    let (state, nonce, cookie) = start_login(&app).await;
+    let mut claims = issuer.claims("sub-1", &email, &nonce);
+    claims["email_verified"] = false.into();
+    issuer.issue_id_token("code-1", claims).await;
+
+    let resp = callback(&app, &state, &cookie, "code-1").await;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    assert_eq!(get_identity_owner(&app, "sub-1").await, None);
+}
+
+#[tokio::test]
+async fn oidc_login_does_not_link_email_of_user_with_identity() {
+    let issuer = MockIssuer::start().await;
+    let app = spawn_app_with_issuer(&issuer).await;
+
+    let email = app.create_user(UserRole::Employee).await;
+    link_identity(&app, &email, "sub-1").await;
+
+    let (state, nonce, cookie) = start_login(&app).await;
+    issuer.issue_id_token("code-1", issuer.claims("sub-2", &email, &nonce)).await;
+
+    let resp = callback(&app, &state, &cookie, "code-1").await;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    assert_eq!(get_identity_owner(&app, "sub-2").await, None);
+}
+
+#[tokio::test]
+async fn oidc_link_links_identity_to_signed_in_user() {
+    let issuer = MockIssuer::start().await;
+    let app = spawn_app_with_issuer(&issuer).await;
+
+    let email = app.create_user(UserRole::Employee).await;
+    let (access_token, _) = app.get_jwt_tokens(&email, "admin").await;
+
+    let (state, nonce, cookie) = start_link(&app, &access_token).await;
+    issuer.issue_id_token("code-1", issuer.claims("sub-1", "other@example.com", &nonce)).await;
+
+    let resp = callback(&app, &state, &cookie, "code-1").await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    assert_eq!(get_identity_owner(&app, "sub-1").await, Some(email));
+}
+
+#[tokio::test]
+async fn oidc_link_rejects_identity_of_another_user() {
+    let issuer = MockIssuer::start().await;
+    let app = spawn_app_with_issuer(&issuer).await;
+
+    let owner = app.create_user(UserRole::Employee).await;
+    link_identity(&app, &owner, "sub-1").await;
+
+    let email = app.create_user(UserRole::Employee).await;
+    let (access_token, _) = app.get_jwt_tokens(&email, "admin").await;
+
+    let (state, nonce, cookie) = start_link(&app, &access_token).await;
+    issuer.issue_id_token("code-1", issuer.claims("sub-1", &email, &nonce)).await;
+
+    let resp = callback(&app, &state, &cookie, "code-1").await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+
+    assert_eq!(get_identity_owner(&app, "sub-1").await, Some(owner));
+}
+
+#[tokio::test]
+async fn oidc_link_requires_authentication() {
+    let issuer = MockIssuer::start().await;
+    let app = spawn_app_with_issuer(&issuer).await;
+
+    let resp = client()
+        .post(format!("{}/v1/auth/oidc/link", app.address))
+        .json(&serde_json::json!({ "fingerprint": "something" }))
+        .send()
+        .await
+        .expect("Failed to execute request");
+
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn oidc_callback_rejects_state_started_in_another_browser() {
+    let issuer = MockIssuer::start().await;
+    let app = spawn_app_with_issuer(&issuer).await;
+
+    let email = app.create_user(UserRole::Employee).await;
+    link_identity(&app, &email, "sub-1").await;
+
+    let (state, nonce, _) = start_login(&app).await;
+    let (_, _, other_cookie) = start_login(&app).await;
+    issuer.issue_id_token("code-1", issuer.claims("sub-1", &email, &nonce)).await;
+
+    let resp = callback(&app, &state, &other_cookie, "code-1").await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn oidc_callback_rejects_wrong_nonce() {
+    let issuer = MockIssuer::start().await;
+    let app = spawn_app_with_issuer(&issuer).await;
+
+    let email = app.create_user(UserRole::Employee).await;
+
+    let (state, _, cookie) = start_login(&app).await;
+    issuer.issue_id_token("code-1", issuer.claims("sub-1", &email, "other-nonce")).await;
+
+    let resp = callback(&app, &state, &cookie, "code-1").await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn oidc_callback_state_can_be_used_once() {
+    let issuer = MockIssuer::start().await;
+    let app = spawn_app_with_issuer(&issuer).await;
+
+    let email = app.create_user(UserRole::Employee).await;
+    link_identity(&app, &email, "sub-1").await;
+
+    let (state, nonce, cookie) = start_login(&app).await;
+    issuer.issue_id_token("code-1", issuer.claims("sub-1", &email, &nonce)).await;
+
+    let resp = callback(&app, &state, &cookie, "code-1").await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let resp = callback(&app, &state, &cookie, "code-1").await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn oidc_endpoints_return_404_when_not_configured() {
+    let app = spawn_app_with(|_| {}).await;
+
+    let resp = client()
+        .get(format!("{}/v1/auth/oidc/login?fingerprint=something", app.address))
+        .send()
+        .await
+        .expect("Failed to execute request");
+
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
\ No newline at end of file
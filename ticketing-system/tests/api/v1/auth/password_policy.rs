@@ -0,0 +1,177 @@
+use reqwest::StatusCode;
+use ticketing_system::{auth::types::UserRole, services::password_policy::BreachedPasswords};
+use uuid::Uuid;
+
+use crate::helpers::{create_invitation, spawn_app, spawn_app_with, TestApp};
+
+async fn change_password(app: &TestApp, email: &str, current: &str, new: &str) -> reqwest::Response {
+    let (access, _) = app.get_jwt_tokens(email, current).await;
+
+    reqwest::Client::new()
+        .put(format!("{}/v1/user/password", app.address))
+        .bearer_auth(access)
+        .json(&serde_json::json!({
+            "current_password": current,
+            "new_password": new,
+        }))
+        .send()
+        .await
+        .unwrap()
+}
+
+async fn get_me(app: &TestApp, email: &str) -> serde_json::Value {
+    let (access, _) = app.get_jwt_tokens(email, "admin").await;
+
+    reqwest::Client::new()
+        .get(format!("{}/v1/auth/me", app.address))
+        .bearer_auth(access)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn weak_password_is_rejected() {
+    let app = spawn_app().await;
+    let email = app.create_user(UserRole::Client).await;
+
+    for password in ["abcdefgh1", "aaaaaaaaaa1", "12345678a"] {
+        let resp = change_password(&app, &email, "admin", password).await;
+
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST, "{}", password);
+    }
+}
+
+#[tokio::test]
+async fn password_containing_login_is_rejected() {
+    let app = spawn_app().await;
+
+    let resp = change_password(&app, "admin@example.com", "admin", "Xadmin-2024!").await;
+
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn recent_passwords_cannot_be_reused() {
+    let app = spawn_app_with(|c| c.password_policy.history_size = 3).await;
+    let email = app.create_user(UserRole::Client).await;
+
+    let passwords = ["Tundra-river7", "Glacier#lake42", "Maple$forest9"];
+    let mut current = "admin";
+
+    for password in passwords {
+        assert_eq!(change_password(&app, &email, current, password).await.status(), StatusCode::OK);
+        current = password;
+    }
+
+    // The current password and the two before it are remembered.
+    for password in passwords {
+        let resp = change_password(&app, &email, current, password).await;
+
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST, "{}", password);
+    }
+
+    let history_len = sqlx::query_scalar!(
+        "SELECT COUNT(*) FROM password_history h JOIN users u ON u.id = h.user_id WHERE u.email = $1",
+        email
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+
+    assert_eq!(history_len, Some(2));
+}
+
+#[tokio::test]
+async fn password_older_than_history_can_be_reused() {
+    let app = spawn_app_with(|c| c.password_policy.history_size = 2).await;
+    let email = app.create_user(UserRole::Client).await;
+
+    let passwords = ["Tundra-river7", "Glacier#lake42", "Maple$forest9", "Tundra-river7"];
+    let mut current = "admin";
+
+    for password in passwords {
+        assert_eq!(change_password(&app, &email, current, password).await.status(), StatusCode::OK);
+        current = password;
+    }
+}
+
+#[tokio::test]
+async fn breached_password_is_rejected() {
+    let path = std::env::temp_dir().join(format!("breached-{}.bin", Uuid::new_v4()));
+
+    let mut filter = BreachedPasswords::with_capacity(2);
+    filter.insert("Tr0ub4dor&3x");
+    filter.insert("Correct-horse9");
+    std::fs::write(&path, filter.to_bytes()).unwrap();
+
+    let app = spawn_app_with(|c| {
+        c.password_policy.breached_passwords_path = Some(path.to_string_lossy().into_owned());
+    })
+    .await;
+    let email = app.create_user(UserRole::Client).await;
+
+    let resp = change_password(&app, &email, "admin", "Tr0ub4dor&3x").await;
+
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let resp = change_password(&app, &email, "admin", "Tundra-river7").await;
+
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[tokio::test]
+async fn register_with_weak_password_keeps_token() {
+    let app = spawn_app().await;
+    let (_, token) = create_invitation(&app).await;
+
+    let register = |password: &'static str| {
+        reqwest::Client::new()
+            .post(format!("{}/v1/auth/register", app.address))
+            .json(&serde_json::json!({
+                "name": "Олег",
+                "login": "oleg_login",
+                "password": password,
+                "token": token,
+            }))
+            .send()
+    };
+
+    assert_eq!(register("oleg_login1").await.unwrap().status(), StatusCode::BAD_REQUEST);
+    assert_eq!(register("Tundra-river7").await.unwrap().status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn old_admin_password_must_be_changed() {
+    let app = spawn_app_with(|c| c.password_policy.admin_max_age_days = Some(30)).await;
+    let admin = app.create_user(UserRole::Admin).await;
+    let employee = app.create_user(UserRole::Employee).await;
+
+    sqlx::query!("UPDATE users SET password_changed_at = NOW() - INTERVAL '31 days'")
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+
+    assert_eq!(get_me(&app, &admin).await["must_change_password"], true);
+    assert_eq!(get_me(&app, &employee).await["must_change_password"], false);
+
+    assert_eq!(change_password(&app, &admin, "admin", "Tundra-river7").await.status(), StatusCode::OK);
+
+    let (access, _) = app.get_jwt_tokens(&admin, "Tundra-river7").await;
+    let me: serde_json::Value = reqwest::Client::new()
+        .get(format!("{}/v1/auth/me", app.address))
+        .bearer_auth(access)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    assert_eq!(me["must_change_password"], false);
+}
\ No newline at end of file
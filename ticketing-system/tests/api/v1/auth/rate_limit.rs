@@ -0,0 +1,161 @@
+use reqwest::StatusCode;
+use ticketing_system::{auth::types::UserRole, config::{LockoutSettings, Settings, WindowLimit}};
+
+use crate::helpers::{spawn_app_with, TestApp};
+
+// The test client connects from localhost, acting as the reverse proxy.
+fn trust_local_proxy(c: &mut Settings) {
+    c.application.trusted_proxies = vec!["127.0.0.1".parse().unwrap()];
+}
+
+fn enable_lockout(c: &mut Settings) {
+    trust_local_proxy(c);
+    c.rate_limit.lockout = LockoutSettings {
+        threshold: 3,
+        base_delay_seconds: 60,
+        max_delay_seconds: 600,
+    };
+}
+
+// Requests come from a random address, so tests do not share IP windows.
+async fn login_from(app: &TestApp, ip: &str, login: &str, password: &str) -> reqwest::Response {
+    reqwest::Client::new()
+        .post(format!("{}/v1/auth/login", app.address))
+        .header("X-Forwarded-For", ip)
+        .json(&serde_json::json!({
+            "login": login,
+            "password": password,
+            "fingerprint": "something",
+        }))
+        .send()
+        .await
+        .expect("Failed to execute request")
+}
+
+fn random_ip() -> String {
+    format!(
+        "10.{}.{}.{}",
+        rand::random::<u8>(),
+        rand::random::<u8>(),
+        rand::random::<u8>()
+    )
+}
+
+#[tokio::test]
+async fn unknown_login_and_wrong_password_get_same_response() {
+    let app = spawn_app_with(trust_local_proxy).await;
+    let email = app.create_user(UserRole::Employee).await;
+    let ip = random_ip();
+
+    let wrong_password = login_from(&app, &ip, &email, "wrong").await;
+    let unknown_login = login_from(&app, &ip, "nobody@example.com", "wrong").await;
+
+    assert_eq!(wrong_password.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(unknown_login.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(wrong_password.text().await.unwrap(), unknown_login.text().await.unwrap());
+}
+
+#[tokio::test]
+async fn login_is_locked_after_repeated_failures() {
+    let app = spawn_app_with(enable_lockout).await;
+    let email = app.create_user(UserRole::Employee).await;
+    let ip = random_ip();
+
+    for _ in 0..3 {
+        let resp = login_from(&app, &ip, &email, "wrong").await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    // Even the right password is rejected while the login is locked.
+    let resp = login_from(&app, &ip, &email, "admin").await;
+
+    assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert!(resp.headers().contains_key("retry-after"));
+}
+
+#[tokio::test]
+async fn login_and_email_share_lockout() {
+    let app = spawn_app_with(enable_lockout).await;
+    let email = app.create_user(UserRole::Employee).await;
+    let login = email.split('@').next().unwrap();
+    let ip = random_ip();
+
+    for identifier in [email.as_str(), login, &email.to_uppercase()] {
+        let resp = login_from(&app, &ip, identifier, "wrong").await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    let resp = login_from(&app, &ip, login, "admin").await;
+    assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+}
+
+#[tokio::test]
+async fn admin_can_unlock_account() {
+    let app = spawn_app_with(enable_lockout).await;
+    let email = app.create_user(UserRole::Employee).await;
+    let ip = random_ip();
+
+    for _ in 0..3 {
+        login_from(&app, &ip, &email, "wrong").await;
+    }
+
+    let user_id = sqlx::query_scalar!("SELECT id FROM users WHERE email = $1", email)
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    let resp = reqwest::Client::new()
+        .post(format!("{}/v1/user/{}/unlock", app.address, user_id))
+        .bearer_auth(access)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let resp = login_from(&app, &ip, &email, "admin").await;
+    assert_eq!(resp.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn login_is_rate_limited_per_ip() {
+    let app = spawn_app_with(|c| {
+        trust_local_proxy(c);
+        c.rate_limit.login.per_ip = WindowLimit { max_requests: 2, window_seconds: 60 };
+    })
+    .await;
+    let ip = random_ip();
+
+    for i in 0..2 {
+        let resp = login_from(&app, &ip, &format!("user{}@example.com", i), "wrong").await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    let resp = login_from(&app, &ip, "user3@example.com", "wrong").await;
+    assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+
+    let resp = login_from(&app, &random_ip(), "user3@example.com", "wrong").await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn account_recovery_is_rate_limited_per_email() {
+    let app = spawn_app_with(|c| {
+        trust_local_proxy(c);
+        c.rate_limit.account_recovery.per_key = WindowLimit { max_requests: 1, window_seconds: 60 };
+    })
+    .await;
+
+    let email = format!("{}@example.com", uuid::Uuid::new_v4());
+
+    let request = || reqwest::Client::new()
+        .post(format!("{}/v1/auth/recovery/request", app.address))
+        .header("X-Forwarded-For", random_ip())
+        .json(&serde_json::json!({ "email": email }))
+        .send();
+
+    assert_eq!(request().await.unwrap().status(), StatusCode::OK);
+    assert_eq!(request().await.unwrap().status(), StatusCode::TOO_MANY_REQUESTS);
+}
\ No newline at end of file
@@ -0,0 +1,233 @@
+use reqwest::StatusCode;
+use ticketing_system::{auth::types::UserRole, schema::common::UserId};
+
+use crate::helpers::{spawn_app, TestApp};
+
+// Refresh tokens live in a Redis shared by all tests, so session tests
+// use ids that do not collide with users of other test databases.
+async fn create_user_with_unique_id(app: &TestApp, role: UserRole) -> (String, UserId) {
+    let id: UserId = rand::random_range(1_000_000..i32::MAX);
+    let login = format!("user{}", id);
+    let email = format!("{}@example.com", login);
+
+    // Identity columns can't be updated, so the id is set on insert. Password: admin
+    sqlx::query!("
+        INSERT INTO users (id, name, email, login, password_hash, role)
+        OVERRIDING SYSTEM VALUE
+        VALUES (
+            $1,
+            'user',
+            $2,
+            $3,
+            '$argon2id$v=19$m=19456,t=2,p=1$842ILagOz0rdwfNELPZhPg$KobLaelwC6ZPo2X0555H1rbyPlBo/+N7G+N2NOvKS7w',
+            $4
+        )",
+        id,
+        email,
+        login,
+        role as i16
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
+    (email, id)
+}
+
+async fn login(app: &TestApp, email: &str, user_agent: &str) -> (String, String) {
+    let resp = reqwest::Client::new()
+        .post(format!("{}/v1/auth/login", app.address))
+        .header("User-Agent", user_agent)
+        .json(&serde_json::json!({
+            "login": email,
+            "password": "admin",
+            "fingerprint": "something",
+        }))
+        .send()
+        .await
+        .unwrap()
+        .error_for_status()
+        .unwrap();
+
+    let json: serde_json::Value = resp.json().await.unwrap();
+
+    (
+        json["access_token"].as_str().unwrap().to_string(),
+        json["refresh_token"].as_str().unwrap().to_string(),
+    )
+}
+
+async fn refresh(app: &TestApp, refresh_token: &str) -> reqwest::Response {
+    reqwest::Client::new()
+        .post(format!("{}/v1/auth/token", app.address))
+        .json(&serde_json::json!({
+            "refresh_token": refresh_token,
+            "fingerprint": "something"
+        }))
+        .send()
+        .await
+        .unwrap()
+}
+
+async fn get_sessions(app: &TestApp, url: &str, access: &str) -> Vec<serde_json::Value> {
+    reqwest::Client::new()
+        .get(format!("{}{}", app.address, url))
+        .bearer_auth(access)
+        .send()
+        .await
+        .unwrap()
+        .error_for_status()
+        .unwrap()
+        .json()
+        .await
+        .unwrap()
+}
+
+async fn delete(app: &TestApp, url: &str, access: &str) -> reqwest::Response {
+    reqwest::Client::new()
+        .delete(format!("{}{}", app.address, url))
+        .bearer_auth(access)
+        .send()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn sessions_are_listed_with_device_info() {
+    let app = spawn_app().await;
+    let (email, _) = create_user_with_unique_id(&app, UserRole::Employee).await;
+
+    let (access, _) = login(&app, &email, "Firefox").await;
+    login(&app, &email, "Chrome").await;
+
+    let sessions = get_sessions(&app, "/v1/auth/sessions", &access).await;
+
+    assert_eq!(sessions.len(), 2);
+
+    let current: Vec<_> = sessions.iter()
+        .filter(|s| s["current"] == true)
+        .collect();
+
+    assert_eq!(current.len(), 1);
+    assert_eq!(current[0]["user_agent"], "Firefox");
+    assert!(current[0]["ip"].is_string());
+    assert!(current[0]["created_at"].is_string());
+}
+
+#[tokio::test]
+async fn forwarded_for_from_untrusted_peer_is_not_recorded() {
+    let app = spawn_app().await;
+    let (email, _) = create_user_with_unique_id(&app, UserRole::Employee).await;
+
+    let resp = reqwest::Client::new()
+        .post(format!("{}/v1/auth/login", app.address))
+        .header("X-Forwarded-For", "203.0.113.7")
+        .json(&serde_json::json!({
+            "login": email,
+            "password": "admin",
+            "fingerprint": "something",
+        }))
+        .send()
+        .await
+        .unwrap()
+        .error_for_status()
+        .unwrap();
+
+    let json: serde_json::Value = resp.json().await.unwrap();
+    let sessions = get_sessions(&app, "/v1/auth/sessions", json["access_token"].as_str().unwrap()).await;
+
+    assert_eq!(sessions[0]["ip"], "127.0.0.1");
+}
+
+#[tokio::test]
+async fn refresh_keeps_session() {
+    let app = spawn_app().await;
+    let (email, _) = create_user_with_unique_id(&app, UserRole::Employee).await;
+
+    let (access, refresh_token) = login(&app, &email, "Firefox").await;
+    let before = get_sessions(&app, "/v1/auth/sessions", &access).await;
+
+    let json: serde_json::Value = refresh(&app, &refresh_token).await
+        .error_for_status()
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    let after = get_sessions(&app, "/v1/auth/sessions", json["access_token"].as_str().unwrap()).await;
+
+    assert_eq!(after.len(), 1);
+    assert_eq!(after[0]["id"], before[0]["id"]);
+    assert_eq!(after[0]["current"], true);
+}
+
+#[tokio::test]
+async fn revoked_session_cannot_be_refreshed() {
+    let app = spawn_app().await;
+    let (email, _) = create_user_with_unique_id(&app, UserRole::Employee).await;
+
+    let (access, _) = login(&app, &email, "Firefox").await;
+    let (_, other_refresh) = login(&app, &email, "Chrome").await;
+
+    let sessions = get_sessions(&app, "/v1/auth/sessions", &access).await;
+    let other = sessions.iter()
+        .find(|s| s["current"] == false)
+        .unwrap();
+
+    let resp = delete(&app, &format!("/v1/auth/sessions/{}", other["id"].as_str().unwrap()), &access).await;
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+
+    assert_eq!(refresh(&app, &other_refresh).await.status(), StatusCode::UNAUTHORIZED);
+
+    let resp = delete(&app, &format!("/v1/auth/sessions/{}", other["id"].as_str().unwrap()), &access).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn logout_everywhere_else_keeps_current_session() {
+    let app = spawn_app().await;
+    let (email, _) = create_user_with_unique_id(&app, UserRole::Employee).await;
+
+    let (access, refresh_token) = login(&app, &email, "Firefox").await;
+    let (_, other_refresh) = login(&app, &email, "Chrome").await;
+
+    let resp = delete(&app, "/v1/auth/sessions", &access).await;
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+
+    assert_eq!(refresh(&app, &other_refresh).await.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(refresh(&app, &refresh_token).await.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn admin_can_revoke_user_sessions() {
+    let app = spawn_app().await;
+    let (email, id) = create_user_with_unique_id(&app, UserRole::Employee).await;
+
+    let (_, refresh_token) = login(&app, &email, "Firefox").await;
+    let (admin_access, _) = app.get_admin_jwt_tokens().await;
+
+    let sessions = get_sessions(&app, &format!("/v1/user/{}/sessions", id), &admin_access).await;
+    assert_eq!(sessions.len(), 1);
+
+    let resp = delete(&app, &format!("/v1/user/{}/sessions", id), &admin_access).await;
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+
+    assert_eq!(refresh(&app, &refresh_token).await.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn non_admin_cannot_list_user_sessions() {
+    let app = spawn_app().await;
+    let (email, id) = create_user_with_unique_id(&app, UserRole::Moderator).await;
+
+    let (access, _) = login(&app, &email, "Firefox").await;
+
+    let resp = reqwest::Client::new()
+        .get(format!("{}/v1/user/{}/sessions", app.address, id))
+        .bearer_auth(&access)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+}
\ No newline at end of file
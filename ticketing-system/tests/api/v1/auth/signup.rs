@@ -0,0 +1,212 @@
+use std::borrow::Cow;
+
+use reqwest::StatusCode;
+use ticketing_system::{auth::types::UserRole, config::{Settings, WindowLimit}};
+use uuid::Uuid;
+use wiremock::{matchers::{method, path}, Mock, ResponseTemplate};
+
+use crate::helpers::{spawn_app_with, wait_for_email, TestApp};
+
+fn allow_kpfu(c: &mut Settings) {
+    c.signup.allowed_domains = vec!["kpfu.ru".into()];
+    c.signup.blocked_domains = vec!["guest.kpfu.ru".into()];
+}
+
+fn random_email(domain: &str) -> String {
+    format!("{}@{}", Uuid::new_v4().simple(), domain)
+}
+
+async fn signup(app: &TestApp, email: &str) -> reqwest::Response {
+    reqwest::Client::new()
+        .post(format!("{}/v1/auth/signup", app.address))
+        .json(&serde_json::json!({ "email": email }))
+        .send()
+        .await
+        .unwrap()
+}
+
+async fn set_signup_enabled(app: &TestApp, enabled: bool, token: &str) -> reqwest::Response {
+    reqwest::Client::new()
+        .put(format!("{}/v1/user/admin/signup", app.address))
+        .bearer_auth(token)
+        .json(&serde_json::json!({ "enabled": enabled }))
+        .send()
+        .await
+        .unwrap()
+}
+
+async fn enable_signup(app: &TestApp) {
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    assert_eq!(set_signup_enabled(app, true, &access).await.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn signup_is_disabled_by_default() {
+    let app = spawn_app_with(allow_kpfu).await;
+
+    let resp = signup(&app, &random_email("kpfu.ru")).await;
+
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn signup_status_shows_toggle_and_domains() {
+    let app = spawn_app_with(allow_kpfu).await;
+
+    enable_signup(&app).await;
+
+    let resp = reqwest::Client::new()
+        .get(format!("{}/v1/auth/signup", app.address))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let body: serde_json::Value = resp.json().await.unwrap();
+
+    assert_eq!(body["enabled"], true);
+    assert_eq!(body["allowed_domains"], serde_json::json!(["kpfu.ru"]));
+}
+
+#[tokio::test]
+async fn toggling_signup_requires_permission() {
+    let app = spawn_app_with(allow_kpfu).await;
+    let email = app.create_user(UserRole::Employee).await;
+    let (access, _) = app.get_jwt_tokens(&email, "admin").await;
+
+    let resp = set_signup_enabled(&app, true, &access).await;
+
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn signup_creates_client_account_after_confirmation() {
+    let app = spawn_app_with(allow_kpfu).await;
+    let email = random_email("stud.kpfu.ru");
+
+    enable_signup(&app).await;
+
+    let mock_guard = Mock::given(path("/v1/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount_as_scoped(&app.email_server)
+        .await;
+
+    assert_eq!(signup(&app, &email).await.status(), StatusCode::OK);
+
+    let email_request = wait_for_email(&mock_guard).await;
+    let url = app.get_invitation_links(&email_request).html;
+    let (_, token) = url.query_pairs()
+        .find(|(key, _)| key == &Cow::Borrowed("token"))
+        .unwrap();
+
+    let resp = reqwest::Client::new()
+        .post(format!("{}/v1/auth/register", app.address))
+        .json(&serde_json::json!({
+            "name": "Олег",
+            "login": "signup_login",
+            "password": "some_pass1",
+            "token": token
+        }))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let role = sqlx::query_scalar!("SELECT role FROM users WHERE email = $1", email)
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+
+    assert_eq!(UserRole::from(role), UserRole::Client);
+}
+
+#[tokio::test]
+async fn signup_with_not_allowed_domain_returns_400() {
+    let app = spawn_app_with(allow_kpfu).await;
+
+    enable_signup(&app).await;
+
+    for email in [
+        random_email("example.com"),
+        random_email("notkpfu.ru"),
+        random_email("guest.kpfu.ru"),
+    ] {
+        let resp = signup(&app, &email).await;
+
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST, "{}", email);
+    }
+}
+
+#[tokio::test]
+async fn signup_with_disposable_domain_returns_400() {
+    let app = spawn_app_with(|c| c.signup.allowed_domains = vec!["mailinator.com".into()]).await;
+
+    enable_signup(&app).await;
+
+    let resp = signup(&app, &random_email("mailinator.com")).await;
+
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn signup_without_allowed_domains_returns_400() {
+    let app = spawn_app_with(|c| c.signup.allowed_domains = vec![]).await;
+
+    enable_signup(&app).await;
+
+    let resp = signup(&app, &random_email("kpfu.ru")).await;
+
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn signup_with_existing_email_returns_200_without_email() {
+    let app = spawn_app_with(|c| {
+        c.signup.allowed_domains = vec!["example.com".into(), "example.net".into(), "example.org".into()];
+    })
+    .await;
+    let email = app.create_user(UserRole::Client).await;
+
+    enable_signup(&app).await;
+
+    let _mock_guard = Mock::given(path("/v1/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount_as_scoped(&app.email_server)
+        .await;
+
+    assert_eq!(signup(&app, &email).await.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn signup_is_rate_limited_per_email() {
+    let app = spawn_app_with(|c| {
+        allow_kpfu(c);
+        c.rate_limit.signup.per_key = WindowLimit { max_requests: 2, window_seconds: 60 };
+    })
+    .await;
+    let email = random_email("kpfu.ru");
+
+    enable_signup(&app).await;
+
+    Mock::given(path("/v1/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.email_server)
+        .await;
+
+    for _ in 0..2 {
+        assert_eq!(signup(&app, &email).await.status(), StatusCode::OK);
+    }
+
+    let resp = signup(&app, &email).await;
+
+    assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert!(resp.headers().contains_key("retry-after"));
+}
\ No newline at end of file
@@ -0,0 +1,270 @@
+use ticketing_system::auth::{totp, types::UserRole};
+
+use crate::helpers::{spawn_app, spawn_app_with, TestApp};
+
+async fn post(app: &TestApp, path: &str, body: &serde_json::Value, access: Option<&str>) -> reqwest::Response {
+    let mut builder = reqwest::Client::new()
+        .post(format!("{}/v1/auth{}", app.address, path))
+        .json(body);
+
+    if let Some(access) = access {
+        builder = builder.bearer_auth(access);
+    }
+
+    builder.send()
+        .await
+        .expect("Failed to execute request")
+}
+
+fn code_for(secret: &str, offset: i64) -> String {
+    totp::generate(secret, chrono::Utc::now().timestamp() + offset).unwrap()
+}
+
+// Returns secret and recovery codes
+async fn enable_two_factor(app: &TestApp, access: &str) -> (String, Vec<String>) {
+    let resp = post(app, "/2fa/setup", &serde_json::json!({}), Some(access)).await;
+    assert_eq!(resp.status(), 200);
+
+    let json: serde_json::Value = resp.json().await.unwrap();
+    let secret = json["secret"].as_str().unwrap().to_string();
+    assert!(json["provisioning_uri"].as_str().unwrap().starts_with("otpauth://totp/"));
+
+    let resp = post(app, "/2fa/enable", &serde_json::json!({ "code": code_for(&secret, 0) }), Some(access)).await;
+    assert_eq!(resp.status(), 200);
+
+    let json: serde_json::Value = resp.json().await.unwrap();
+    let codes = json["recovery_codes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| c.as_str().unwrap().to_string())
+        .collect();
+
+    (secret, codes)
+}
+
+async fn login_challenge(app: &TestApp, login: &str) -> serde_json::Value {
+    let resp = app.login(&serde_json::json!({
+        "login": login,
+        "password": "admin",
+        "fingerprint": "something",
+    }))
+    .await;
+
+    assert_eq!(resp.status(), 200);
+
+    resp.json().await.unwrap()
+}
+
+#[tokio::test]
+async fn enable_with_invalid_code_returns_400() {
+    let app = spawn_app().await;
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    let resp = post(&app, "/2fa/setup", &serde_json::json!({}), Some(&access)).await;
+    assert_eq!(resp.status(), 200);
+
+    let resp = post(&app, "/2fa/enable", &serde_json::json!({ "code": "000000" }), Some(&access)).await;
+    assert_eq!(resp.status(), 400);
+}
+
+#[tokio::test]
+async fn setup_without_token_returns_401() {
+    let app = spawn_app().await;
+
+    let resp = post(&app, "/2fa/setup", &serde_json::json!({}), None).await;
+
+    assert_eq!(resp.status(), 401);
+}
+
+#[tokio::test]
+async fn login_with_enabled_two_factor_returns_challenge() {
+    let app = spawn_app().await;
+    let email = app.create_user(UserRole::Employee).await;
+    let (access, _) = app.get_jwt_tokens(&email, "admin").await;
+
+    let (secret, _) = enable_two_factor(&app, &access).await;
+
+    let json = login_challenge(&app, &email).await;
+    assert!(json.get("access_token").is_none());
+    assert_eq!(json["setup_required"], false);
+
+    let resp = post(&app, "/login/2fa", &serde_json::json!({
+        "challenge_token": json["challenge_token"],
+        "code": code_for(&secret, 30),
+    }), None).await;
+
+    assert_eq!(resp.status(), 200);
+
+    let json: serde_json::Value = resp.json().await.unwrap();
+    assert!(json["access_token"].is_string());
+    assert!(json["refresh_token"].is_string());
+}
+
+#[tokio::test]
+async fn login_two_factor_rejects_reused_code() {
+    let app = spawn_app().await;
+    let email = app.create_user(UserRole::Employee).await;
+    let (access, _) = app.get_jwt_tokens(&email, "admin").await;
+
+    let (secret, _) = enable_two_factor(&app, &access).await;
+
+    let used_step = sqlx::query_scalar!(
+        "SELECT totp_last_used_step FROM users WHERE email = $1",
+        email
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap()
+    .unwrap();
+
+    let json = login_challenge(&app, &email).await;
+
+    let resp = post(&app, "/login/2fa", &serde_json::json!({
+        "challenge_token": json["challenge_token"],
+        "code": totp::generate(&secret, used_step * 30).unwrap(),
+    }), None).await;
+
+    assert_eq!(resp.status(), 401);
+}
+
+#[tokio::test]
+async fn recovery_code_can_be_used_only_once() {
+    let app = spawn_app().await;
+    let email = app.create_user(UserRole::Employee).await;
+    let (access, _) = app.get_jwt_tokens(&email, "admin").await;
+
+    let (_, codes) = enable_two_factor(&app, &access).await;
+    assert_eq!(codes.len(), 10);
+
+    let json = login_challenge(&app, &email).await;
+    let resp = post(&app, "/login/2fa", &serde_json::json!({
+        "challenge_token": json["challenge_token"],
+        "code": codes[0],
+    }), None).await;
+    assert_eq!(resp.status(), 200);
+
+    let json = login_challenge(&app, &email).await;
+    let resp = post(&app, "/login/2fa", &serde_json::json!({
+        "challenge_token": json["challenge_token"],
+        "code": codes[0],
+    }), None).await;
+    assert_eq!(resp.status(), 401);
+}
+
+#[tokio::test]
+async fn challenge_is_revoked_after_too_many_attempts() {
+    let app = spawn_app().await;
+    let email = app.create_user(UserRole::Employee).await;
+    let (access, _) = app.get_jwt_tokens(&email, "admin").await;
+
+    let (secret, _) = enable_two_factor(&app, &access).await;
+
+    let json = login_challenge(&app, &email).await;
+
+    for _ in 0..5 {
+        let resp = post(&app, "/login/2fa", &serde_json::json!({
+            "challenge_token": json["challenge_token"],
+            "code": "000000",
+        }), None).await;
+        assert_eq!(resp.status(), 401);
+    }
+
+    let resp = post(&app, "/login/2fa", &serde_json::json!({
+        "challenge_token": json["challenge_token"],
+        "code": code_for(&secret, 30),
+    }), None).await;
+
+    assert_eq!(resp.status(), 401);
+}
+
+#[tokio::test]
+async fn required_two_factor_is_enrolled_during_login() {
+    let app = spawn_app_with(|c| c.auth.two_factor_required_role = Some(UserRole::Admin)).await;
+
+    let json = login_challenge(&app, "admin@example.com").await;
+    assert_eq!(json["setup_required"], true);
+
+    let resp = post(&app, "/login/2fa/setup", &serde_json::json!({
+        "challenge_token": json["challenge_token"],
+    }), None).await;
+    assert_eq!(resp.status(), 200);
+
+    let setup: serde_json::Value = resp.json().await.unwrap();
+    let secret = setup["secret"].as_str().unwrap();
+
+    let resp = post(&app, "/login/2fa", &serde_json::json!({
+        "challenge_token": json["challenge_token"],
+        "code": code_for(secret, 0),
+    }), None).await;
+    assert_eq!(resp.status(), 200);
+
+    let json: serde_json::Value = resp.json().await.unwrap();
+    assert!(json["access_token"].is_string());
+    assert_eq!(json["recovery_codes"].as_array().unwrap().len(), 10);
+}
+
+#[tokio::test]
+async fn required_two_factor_does_not_apply_to_lower_roles() {
+    let app = spawn_app_with(|c| c.auth.two_factor_required_role = Some(UserRole::Admin)).await;
+    let email = app.create_user(UserRole::Employee).await;
+
+    let json = login_challenge(&app, &email).await;
+
+    assert!(json["access_token"].is_string());
+}
+
+#[tokio::test]
+async fn disable_required_two_factor_returns_403() {
+    let app = spawn_app_with(|c| c.auth.two_factor_required_role = Some(UserRole::Moderator)).await;
+    let email = app.create_user(UserRole::Employee).await;
+    let (access, _) = app.get_jwt_tokens(&email, "admin").await;
+    let (secret, _) = enable_two_factor(&app, &access).await;
+
+    sqlx::query!("UPDATE users SET role = 4 WHERE email = $1", email)
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+
+    let resp = post(&app, "/2fa/disable", &serde_json::json!({
+        "password": "admin",
+        "code": code_for(&secret, 30),
+    }), Some(&access)).await;
+
+    assert_eq!(resp.status(), 403);
+}
+
+#[tokio::test]
+async fn disable_two_factor_restores_single_step_login() {
+    let app = spawn_app().await;
+    let email = app.create_user(UserRole::Employee).await;
+    let (access, _) = app.get_jwt_tokens(&email, "admin").await;
+    let (secret, _) = enable_two_factor(&app, &access).await;
+
+    let resp = post(&app, "/2fa/disable", &serde_json::json!({
+        "password": "admin",
+        "code": code_for(&secret, 30),
+    }), Some(&access)).await;
+    assert_eq!(resp.status(), 200);
+
+    let json = login_challenge(&app, &email).await;
+    assert!(json["access_token"].is_string());
+}
+
+#[tokio::test]
+async fn regenerate_recovery_codes_with_pending_secret_returns_400() {
+    let app = spawn_app().await;
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    let resp = post(&app, "/2fa/setup", &serde_json::json!({}), Some(&access)).await;
+    assert_eq!(resp.status(), 200);
+
+    let json: serde_json::Value = resp.json().await.unwrap();
+    let secret = json["secret"].as_str().unwrap();
+
+    let resp = post(&app, "/2fa/recovery_codes", &serde_json::json!({
+        "code": code_for(secret, 0),
+    }), Some(&access)).await;
+
+    assert_eq!(resp.status(), 400);
+}
\ No newline at end of file
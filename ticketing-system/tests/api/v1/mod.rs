@@ -11,3 +11,7 @@ mod assets;
 mod reports;
 
 mod attachments;
+
+mod roles;
+mod audit;
+mod assignment_rules;
\ No newline at end of file
@@ -109,4 +109,34 @@ async fn get_pages_returns_only_public_pages_for_client() {
     let items_count = json["items"].as_array().unwrap().len();
 
     assert_eq!(items_count, 5);
+}
+
+#[tokio::test]
+async fn get_pages_with_cursor_returns_pages_in_order() {
+    let app = spawn_app().await;
+
+    for _ in 0..15 {
+        app.create_test_page().await;
+    }
+
+    let json: serde_json::Value = app.get_pages(&serde_json::json!({ "cursor": "", "sort_order": "desc" }), None).await
+        .json()
+        .await
+        .unwrap();
+
+    let items = json["items"].as_array().unwrap();
+    assert_eq!(items.len(), 10);
+    assert_eq!(items[0]["id"], 15);
+
+    let cursor = json["next_cursor"].as_str().unwrap();
+
+    let json: serde_json::Value = app.get_pages(&serde_json::json!({ "cursor": cursor, "sort_order": "desc" }), None).await
+        .json()
+        .await
+        .unwrap();
+
+    let items = json["items"].as_array().unwrap();
+    assert_eq!(items.len(), 5);
+    assert_eq!(items[0]["id"], 5);
+    assert!(json["next_cursor"].is_null());
 }
\ No newline at end of file
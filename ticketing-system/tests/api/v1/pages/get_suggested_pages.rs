@@ -0,0 +1,89 @@
+use reqwest::StatusCode;
+
+use crate::helpers::{spawn_app, TestApp};
+
+async fn create_page(app: &TestApp, title: &str, text: &str, is_public: bool) {
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    let body = serde_json::json!({
+        "data": { "text": text },
+        "title": title,
+        "tags": [],
+        "related": [],
+        "is_public": is_public
+    });
+
+    let resp = app.create_page(&body, Some(&access)).await;
+    assert!(resp.status().is_success());
+}
+
+async fn get_suggestions(app: &TestApp, query: &[(&str, &str)], access: &str) -> reqwest::Response {
+    reqwest::Client::new()
+        .get(format!("{}/v1/pages/suggestions", app.address))
+        .query(query)
+        .bearer_auth(access)
+        .send()
+        .await
+        .expect("Failed to execute request")
+}
+
+#[tokio::test]
+async fn matching_public_pages_are_suggested_for_draft() {
+    let app = spawn_app().await;
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    create_page(&app, "Не работает принтер", "Перезагрузите принтер и проверьте картридж", true).await;
+    create_page(&app, "Принтер в деканате", "Внутренняя инструкция по принтеру", false).await;
+    create_page(&app, "Настройка почты", "Как подключить почтовый ящик", true).await;
+
+    let resp = get_suggestions(&app, &[("title", "Не работает принтер"), ("description", "картридж закончился")], &access).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let pages: Vec<serde_json::Value> = resp.json().await.unwrap();
+    assert_eq!(pages.len(), 1);
+    assert_eq!(pages[0]["title"], "Не работает принтер");
+}
+
+#[tokio::test]
+async fn suggestions_use_existing_ticket() {
+    let app = spawn_app().await;
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    create_page(&app, "Test title", "Some text", true).await;
+
+    let resp = app.create_test_ticket().await;
+    let ticket: serde_json::Value = resp.json().await.unwrap();
+    let ticket_id = ticket["id"].as_i64().unwrap().to_string();
+
+    let resp = get_suggestions(&app, &[("ticket_id", &ticket_id)], &access).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let pages: Vec<serde_json::Value> = resp.json().await.unwrap();
+    assert_eq!(pages.len(), 1);
+}
+
+#[tokio::test]
+async fn suggestions_without_query_return_400() {
+    let app = spawn_app().await;
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    let resp = get_suggestions(&app, &[], &access).await;
+
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn suggestions_for_foreign_ticket_return_403() {
+    let app = spawn_app().await;
+
+    let resp = app.create_test_ticket().await;
+    let ticket: serde_json::Value = resp.json().await.unwrap();
+    let ticket_id = ticket["id"].as_i64().unwrap().to_string();
+
+    let login = app.create_user(ticketing_system::auth::types::UserRole::Client).await;
+    let (access, _) = app.get_jwt_tokens(&login, "admin").await;
+
+    let resp = get_suggestions(&app, &[("ticket_id", &ticket_id)], &access).await;
+
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+}
\ No newline at end of file
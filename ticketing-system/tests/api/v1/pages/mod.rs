@@ -2,4 +2,5 @@ mod create_page;
 mod get_pages;
 mod delete_page;
 mod get_page;
-mod update_page;
\ No newline at end of file
+mod update_page;
+mod get_suggested_pages;
\ No newline at end of file
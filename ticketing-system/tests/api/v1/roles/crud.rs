@@ -0,0 +1,191 @@
+use reqwest::StatusCode;
+use ticketing_system::auth::types::UserRole;
+
+use crate::helpers::{spawn_app, TestApp};
+
+async fn create_role(app: &TestApp, body: serde_json::Value, access: &str) -> reqwest::Response {
+    app.send(reqwest::Method::POST, "/v1/roles", Some(body), access).await
+}
+
+#[tokio::test]
+async fn get_roles_returns_seeded_roles() {
+    let app = spawn_app().await;
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    let resp = app.send(reqwest::Method::GET, "/v1/roles", None, &access).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let roles: Vec<serde_json::Value> = resp.json().await.unwrap();
+    let names: Vec<&str> = roles.iter().map(|r| r["name"].as_str().unwrap()).collect();
+    assert_eq!(names, ["anonym", "client", "employee", "moderator", "admin"]);
+    assert!(roles.iter().all(|r| r["is_system"] == true));
+
+    let permissions = |i: usize| roles[i]["permissions"].as_array().unwrap().clone();
+    assert!(permissions(0).is_empty());
+    assert!(permissions(1).contains(&"tickets.read".into()));
+    assert!(!permissions(1).contains(&"tickets.update".into()));
+    assert!(permissions(3).contains(&"tickets.assign_others".into()));
+    assert!(!permissions(3).contains(&"roles.manage".into()));
+    assert!(permissions(4).contains(&"roles.manage".into()));
+}
+
+#[tokio::test]
+async fn get_permissions_lists_known_permissions() {
+    let app = spawn_app().await;
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    let resp = app.send(reqwest::Method::GET, "/v1/roles/permissions", None, &access).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let permissions: Vec<String> = resp.json().await.unwrap();
+    assert!(permissions.contains(&"assets.write".to_string()));
+    assert!(permissions.contains(&"reports.generate".to_string()));
+}
+
+#[tokio::test]
+async fn roles_cannot_be_managed_without_permission() {
+    let app = spawn_app().await;
+    let email = app.create_user(UserRole::Moderator).await;
+    let (access, _) = app.get_jwt_tokens(&email, "admin").await;
+
+    let resp = app.send(reqwest::Method::GET, "/v1/roles", None, &access).await;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn create_and_update_role() {
+    let app = spawn_app().await;
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    let body = serde_json::json!({
+        "name": "asset_keeper",
+        "level": "employee",
+        "permissions": ["assets.write", "assets.read", "assets.read"],
+    });
+
+    let resp = create_role(&app, body.clone(), &access).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+
+    let role: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(role["level"], "employee");
+    assert_eq!(role["is_system"], false);
+    assert_eq!(role["permissions"], serde_json::json!(["assets.read", "assets.write"]));
+
+    let resp = create_role(&app, body, &access).await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+
+    let resp = app.send(
+        reqwest::Method::PUT,
+        &format!("/v1/roles/{}", role["id"]),
+        Some(serde_json::json!({ "name": "inventory", "permissions": ["reports.generate"] })),
+        &access
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let role: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(role["name"], "inventory");
+    assert_eq!(role["permissions"], serde_json::json!(["reports.generate"]));
+
+    let resp = app.send(reqwest::Method::PUT, "/v1/roles/1000", Some(serde_json::json!({ "name": "x" })), &access).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn create_role_with_unknown_permission_returns_400() {
+    let app = spawn_app().await;
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    let body = serde_json::json!({
+        "name": "flyer",
+        "level": "client",
+        "permissions": ["tickets.fly"],
+    });
+
+    let resp = create_role(&app, body, &access).await;
+    assert!(resp.status().is_client_error());
+}
+
+#[tokio::test]
+async fn delete_role() {
+    let app = spawn_app().await;
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    let resp = app.send(reqwest::Method::DELETE, "/v1/roles/2", None, &access).await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+
+    let role: serde_json::Value = create_role(
+        &app,
+        serde_json::json!({ "name": "temporary", "level": "client" }),
+        &access
+    )
+    .await
+    .json()
+    .await
+    .unwrap();
+
+    let email = app.create_user(UserRole::Client).await;
+
+    sqlx::query!("UPDATE users SET role_id = $1 WHERE email = $2", role["id"].as_i64().unwrap() as i16, email)
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+
+    let path = format!("/v1/roles/{}", role["id"]);
+
+    let resp = app.send(reqwest::Method::DELETE, &path, None, &access).await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+
+    sqlx::query!("UPDATE users SET role_id = 1 WHERE email = $1", email)
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+
+    let resp = app.send(reqwest::Method::DELETE, &path, None, &access).await;
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+
+    let resp = app.send(reqwest::Method::DELETE, &path, None, &access).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn system_role_permissions_cannot_be_changed() {
+    let app = spawn_app().await;
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    let resp = app.send(reqwest::Method::PUT, "/v1/roles/4", Some(serde_json::json!({ "permissions": [] })), &access).await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+
+    let resp = app.send(reqwest::Method::GET, "/v1/roles", None, &access).await;
+    let roles: Vec<serde_json::Value> = resp.json().await.unwrap();
+    assert!(roles[4]["permissions"].as_array().unwrap().contains(&"roles.manage".into()));
+}
+
+#[tokio::test]
+async fn role_above_own_level_cannot_be_updated() {
+    let app = spawn_app().await;
+    let (admin, _) = app.get_admin_jwt_tokens().await;
+
+    let body = serde_json::json!({
+        "name": "role_keeper",
+        "level": "employee",
+        "permissions": ["roles.manage"],
+    });
+
+    let role: serde_json::Value = create_role(&app, body, &admin).await.json().await.unwrap();
+
+    let email = app.create_user(UserRole::Employee).await;
+
+    sqlx::query!("UPDATE users SET role_id = $1 WHERE email = $2", role["id"].as_i64().unwrap() as i16, email)
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+
+    let (access, _) = app.get_jwt_tokens(&email, "admin").await;
+
+    let resp = app.send(reqwest::Method::PUT, "/v1/roles/3", Some(serde_json::json!({ "name": "boss" })), &access).await;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    let resp = app.send(reqwest::Method::PUT, &format!("/v1/roles/{}", role["id"]), Some(serde_json::json!({ "name": "keeper" })), &access).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+}
\ No newline at end of file
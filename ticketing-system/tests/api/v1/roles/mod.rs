@@ -0,0 +1,2 @@
+mod crud;
+mod permissions;
\ No newline at end of file
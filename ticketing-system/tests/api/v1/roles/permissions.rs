@@ -0,0 +1,178 @@
+use reqwest::StatusCode;
+use ticketing_system::auth::types::UserRole;
+
+use crate::helpers::{spawn_app, TestApp};
+
+async fn create_role(app: &TestApp, name: &str, permissions: &[&str]) -> i64 {
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    let role: serde_json::Value = reqwest::Client::new()
+        .post(format!("{}/v1/roles", app.address))
+        .bearer_auth(access)
+        .json(&serde_json::json!({
+            "name": name,
+            "level": "employee",
+            "permissions": permissions,
+        }))
+        .send()
+        .await
+        .unwrap()
+        .error_for_status()
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    role["id"].as_i64().unwrap()
+}
+
+// Returns access token of a new user with the role
+async fn login_with_role(app: &TestApp, role_id: i64) -> String {
+    let email = app.create_user(UserRole::Client).await;
+    let id = sqlx::query_scalar!("SELECT id FROM users WHERE email = $1", email)
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+
+    let (admin, _) = app.get_admin_jwt_tokens().await;
+
+    let resp = reqwest::Client::new()
+        .patch(format!("{}/v1/user/admin/role", app.address))
+        .bearer_auth(admin)
+        .json(&serde_json::json!({ "id": id, "role_id": role_id }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    app.get_jwt_tokens(&email, "admin").await.0
+}
+
+#[tokio::test]
+async fn custom_role_grants_only_its_permissions() {
+    let app = spawn_app().await;
+    let role_id = create_role(&app, "asset_viewer", &["assets.read"]).await;
+    let access = login_with_role(&app, role_id).await;
+
+    assert_eq!(app.get("/v1/assets/statuses", &access).await.status(), StatusCode::OK);
+    assert_eq!(app.get("/v1/tickets/metrics", &access).await.status(), StatusCode::FORBIDDEN);
+
+    let resp = app.create_status(
+        &serde_json::json!({ "name": "Broken", "color": "#ff0000" }),
+        Some(&access)
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    let me: serde_json::Value = app.get("/v1/auth/me", &access).await.json().await.unwrap();
+    assert_eq!(me["role"], "employee");
+    assert_eq!(me["role_id"], role_id);
+    assert_eq!(me["permissions"], serde_json::json!(["assets.read"]));
+}
+
+#[tokio::test]
+async fn role_changes_apply_to_issued_tokens() {
+    let app = spawn_app().await;
+    let role_id = create_role(&app, "asset_viewer", &["assets.read"]).await;
+    let access = login_with_role(&app, role_id).await;
+
+    assert_eq!(app.get("/v1/assets/statuses", &access).await.status(), StatusCode::OK);
+
+    let (admin, _) = app.get_admin_jwt_tokens().await;
+
+    reqwest::Client::new()
+        .put(format!("{}/v1/roles/{}", app.address, role_id))
+        .bearer_auth(admin)
+        .json(&serde_json::json!({ "permissions": [] }))
+        .send()
+        .await
+        .unwrap()
+        .error_for_status()
+        .unwrap();
+
+    assert_eq!(app.get("/v1/assets/statuses", &access).await.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn publishing_page_requires_permission() {
+    let app = spawn_app().await;
+    let role_id = create_role(&app, "writer", &["pages.write"]).await;
+    let access = login_with_role(&app, role_id).await;
+
+    let body = |is_public: bool| serde_json::json!({
+        "data": {
+            "text": "Some text"
+        },
+        "title": "Test title",
+        "tags": [],
+        "related": [],
+        "is_public": is_public
+    });
+
+    let resp = app.create_page(&body(true), Some(&access)).await;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    let resp = app.create_page(&body(false), Some(&access)).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+}
+
+#[tokio::test]
+async fn reading_private_page_requires_permission() {
+    let app = spawn_app().await;
+    let (admin, _) = app.get_admin_jwt_tokens().await;
+
+    let page: serde_json::Value = app.create_page(
+        &serde_json::json!({
+            "data": {
+                "text": "Some text"
+            },
+            "title": "Test title",
+            "tags": [],
+            "related": [],
+            "is_public": false
+        }),
+        Some(&admin)
+    )
+    .await
+    .json()
+    .await
+    .unwrap();
+    let path = format!("/v1/pages/{}", page["id"]);
+
+    let role_id = create_role(&app, "writer", &["pages.write"]).await;
+    let access = login_with_role(&app, role_id).await;
+    assert_eq!(app.get(&path, &access).await.status(), StatusCode::NOT_FOUND);
+
+    let role_id = create_role(&app, "reader", &["pages.read_private"]).await;
+    let access = login_with_role(&app, role_id).await;
+    assert_eq!(app.get(&path, &access).await.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn cannot_assign_role_above_own_level() {
+    let app = spawn_app().await;
+    let (admin, _) = app.get_admin_jwt_tokens().await;
+    let email = app.create_user(UserRole::Client).await;
+    let id = sqlx::query_scalar!("SELECT id FROM users WHERE email = $1", email)
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+
+    let resp = reqwest::Client::new()
+        .patch(format!("{}/v1/user/admin/role", app.address))
+        .bearer_auth(&admin)
+        .json(&serde_json::json!({ "id": id, "role_id": 4 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    let resp = reqwest::Client::new()
+        .patch(format!("{}/v1/user/admin/role", app.address))
+        .bearer_auth(&admin)
+        .json(&serde_json::json!({ "id": id, "role_id": 1000 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
\ No newline at end of file
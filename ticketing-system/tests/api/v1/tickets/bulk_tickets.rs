@@ -0,0 +1,208 @@
+use reqwest::StatusCode;
+use ticketing_system::auth::types::UserRole;
+
+use crate::helpers::{spawn_app, TestApp};
+
+async fn bulk(app: &TestApp, body: serde_json::Value, access: &str) -> reqwest::Response {
+    reqwest::Client::new()
+        .post(format!("{}/v1/tickets/bulk", app.address))
+        .bearer_auth(access)
+        .json(&body)
+        .send()
+        .await
+        .expect("Failed to execute request")
+}
+
+async fn bulk_outcomes(app: &TestApp, body: serde_json::Value, access: &str) -> Vec<(i64, String)> {
+    let resp = bulk(app, body, access).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let json: serde_json::Value = resp.json().await.unwrap();
+
+    json.as_array().unwrap().iter()
+        .map(|result| (
+            result["ticket_id"].as_i64().unwrap(),
+            result["outcome"].as_str().unwrap().to_string()
+        ))
+        .collect()
+}
+
+#[tokio::test]
+async fn bulk_closes_tickets_and_notifies_authors() {
+    let app = spawn_app().await;
+    let (admin_access, _) = app.get_admin_jwt_tokens().await;
+
+    let login = app.create_user(UserRole::Client).await;
+    let (access, _) = app.get_jwt_tokens(&login, "admin").await;
+
+    let first = app.create_ticket_with_title("First", &access).await;
+    let second = app.create_ticket_with_title("Second", &access).await;
+
+    let body = serde_json::json!({
+        "ticket_ids": [second, first],
+        "operation": { "type": "set_status", "status": "closed" },
+    });
+
+    let outcomes = bulk_outcomes(&app, body, &admin_access).await;
+
+    assert_eq!(outcomes, vec![(first, "updated".to_string()), (second, "updated".to_string())]);
+
+    let closed = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "count!" FROM tickets WHERE id = ANY($1) AND status = 1 AND closed_at IS NOT NULL"#,
+        &[first, second]
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+
+    assert_eq!(closed, 2);
+
+    let notifications = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "count!" FROM notifications WHERE ticket_id = ANY($1) AND payload->>'type' = 'status_changed'"#,
+        &[first, second]
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+
+    assert_eq!(notifications, 2);
+}
+
+#[tokio::test]
+async fn bulk_reports_result_per_ticket() {
+    let app = spawn_app().await;
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    let id = app.create_ticket_with_title("Printer", &access).await;
+
+    let body = serde_json::json!({
+        "ticket_ids": [id, 999999],
+        "operation": { "type": "set_priority", "priority": "high" },
+    });
+
+    let outcomes = bulk_outcomes(&app, body.clone(), &access).await;
+
+    assert_eq!(outcomes, vec![(id, "updated".to_string()), (999999, "not_found".to_string())]);
+
+    let outcomes = bulk_outcomes(&app, body, &access).await;
+
+    assert_eq!(outcomes[0], (id, "unchanged".to_string()));
+}
+
+#[tokio::test]
+async fn atomic_bulk_changes_nothing_when_a_ticket_is_rejected() {
+    let app = spawn_app().await;
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    let id = app.create_ticket_with_title("Printer", &access).await;
+
+    let body = serde_json::json!({
+        "ticket_ids": [id, 999999],
+        "operation": { "type": "set_priority", "priority": "critical" },
+        "atomic": true,
+    });
+
+    let outcomes = bulk_outcomes(&app, body, &access).await;
+
+    assert_eq!(outcomes, vec![(id, "skipped".to_string()), (999999, "not_found".to_string())]);
+
+    let priority = sqlx::query_scalar!("SELECT priority FROM tickets WHERE id = $1", id)
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+
+    assert_eq!(priority, 0);
+}
+
+#[tokio::test]
+async fn bulk_assigns_and_adds_internal_messages_by_filters() {
+    let app = spawn_app().await;
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    let first = app.create_ticket_with_title("First", &access).await;
+    let second = app.create_ticket_with_title("Second", &access).await;
+
+    let login = app.create_user(UserRole::Employee).await;
+    let employee_id = sqlx::query_scalar!("SELECT id FROM users WHERE login = $1", login)
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+
+    let body = serde_json::json!({
+        "filters": { "statuses": ["open"] },
+        "operation": { "type": "assign", "user_id": employee_id },
+    });
+
+    let outcomes = bulk_outcomes(&app, body, &access).await;
+
+    assert_eq!(outcomes, vec![(first, "updated".to_string()), (second, "updated".to_string())]);
+
+    let body = serde_json::json!({
+        "filters": { "assigned_to": employee_id },
+        "operation": { "type": "add_internal_message", "message": "Network is back" },
+    });
+
+    let outcomes = bulk_outcomes(&app, body, &access).await;
+
+    assert_eq!(outcomes.len(), 2);
+
+    let messages = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "count!" FROM ticket_messages WHERE ticket_id = ANY($1) AND is_internal"#,
+        &[first, second]
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+
+    assert_eq!(messages, 2);
+
+    let notified = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "count!" FROM notifications WHERE user_id = $1"#,
+        employee_id
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+
+    assert_eq!(notified, 2);
+}
+
+#[tokio::test]
+async fn bulk_rejects_filters_matching_too_many_tickets() {
+    let app = spawn_app().await;
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    sqlx::query!(
+        "INSERT INTO tickets (title, description, author, author_contacts, building_id, department_id)
+        SELECT 'Ticket ' || n, 'Test description', 'Test author', 'Test contacts', 1, 1
+        FROM generate_series(1, 101) AS n"
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
+    let body = serde_json::json!({
+        "filters": {},
+        "operation": { "type": "set_status", "status": "closed" },
+    });
+
+    let resp = bulk(&app, body, &access).await;
+
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn bulk_requires_exactly_one_target() {
+    let app = spawn_app().await;
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    let operation = serde_json::json!({ "type": "set_priority", "priority": "high" });
+
+    let resp = bulk(&app, serde_json::json!({ "operation": operation }), &access).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let body = serde_json::json!({ "ticket_ids": [1], "filters": {}, "operation": operation });
+
+    let resp = bulk(&app, body, &access).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
\ No newline at end of file
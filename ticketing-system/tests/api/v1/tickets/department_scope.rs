@@ -0,0 +1,301 @@
+use ticketing_system::auth::types::UserRole;
+
+use crate::helpers::{NEXT_USER_ID, TestApp, spawn_app};
+
+async fn leave_departments(app: &TestApp, user_id: i32) {
+    sqlx::query!("DELETE FROM user_departments WHERE user_id = $1", user_id)
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+}
+
+async fn set_member(app: &TestApp, department_id: i16, user_id: i32, role: &str, access: &str) -> reqwest::Response {
+    reqwest::Client::new()
+        .put(format!("{}/v1/departments/{}/members/{}", app.address, department_id, user_id))
+        .json(&serde_json::json!({ "role": role }))
+        .bearer_auth(access)
+        .send()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn get_tickets_hides_tickets_of_other_departments() {
+    let app = spawn_app().await;
+
+    app.create_test_ticket().await
+        .error_for_status()
+        .unwrap();
+
+    let email = app.create_user(UserRole::Employee).await;
+    leave_departments(&app, NEXT_USER_ID).await;
+
+    let (access, _) = app.get_jwt_tokens(&email, "admin").await;
+
+    let resp = reqwest::Client::new()
+        .get(format!("{}/v1/tickets/", app.address))
+        .bearer_auth(access)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), 200);
+
+    let json: serde_json::Value = resp.json().await.unwrap();
+
+    assert!(json["items"].as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn get_tickets_shows_tickets_of_member_departments() {
+    let app = spawn_app().await;
+
+    app.create_test_ticket().await
+        .error_for_status()
+        .unwrap();
+
+    let email = app.create_user(UserRole::Employee).await;
+
+    let (access, _) = app.get_jwt_tokens(&email, "admin").await;
+
+    let resp = reqwest::Client::new()
+        .get(format!("{}/v1/tickets/", app.address))
+        .bearer_auth(access)
+        .send()
+        .await
+        .unwrap();
+
+    let json: serde_json::Value = resp.json().await.unwrap();
+
+    assert_eq!(json["items"].as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn ticket_of_other_department_returns_403() {
+    let app = spawn_app().await;
+
+    app.create_test_ticket().await
+        .error_for_status()
+        .unwrap();
+
+    let email = app.create_user(UserRole::Moderator).await;
+    leave_departments(&app, NEXT_USER_ID).await;
+
+    let (access, _) = app.get_jwt_tokens(&email, "admin").await;
+
+    let resp = reqwest::Client::new()
+        .get(format!("{}/v1/tickets/1", app.address))
+        .bearer_auth(&access)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), 403);
+
+    let resp = app.update_ticket(
+        1,
+        &serde_json::json!({
+            "title": "Some title for tests"
+        }),
+        None,
+        Some(&access)
+    ).await;
+
+    assert_eq!(resp.status(), 403);
+
+    let resp = reqwest::Client::new()
+        .patch(format!("{}/v1/tickets/1/assign", app.address))
+        .bearer_auth(&access)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), 403);
+}
+
+#[tokio::test]
+async fn ticket_messages_and_assets_of_other_department_return_403() {
+    let app = spawn_app().await;
+
+    app.create_test_ticket().await
+        .error_for_status()
+        .unwrap();
+
+    let email = app.create_user(UserRole::Moderator).await;
+    leave_departments(&app, NEXT_USER_ID).await;
+
+    let (access, _) = app.get_jwt_tokens(&email, "admin").await;
+
+    let resp = reqwest::Client::new()
+        .get(format!("{}/v1/tickets/1/messages", app.address))
+        .bearer_auth(&access)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), 403);
+
+    let resp = reqwest::Client::new()
+        .post(format!("{}/v1/tickets/1/messages", app.address))
+        .json(&serde_json::json!({ "message": "Hello", "is_internal": true }))
+        .bearer_auth(&access)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), 403);
+
+    let resp = reqwest::Client::new()
+        .get(format!("{}/v1/tickets/1/assets", app.address))
+        .bearer_auth(&access)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), 403);
+
+    let resp = reqwest::Client::new()
+        .post(format!("{}/v1/tickets/1/assets", app.address))
+        .json(&serde_json::json!({ "asset_id": 1 }))
+        .bearer_auth(&access)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), 403);
+}
+
+#[tokio::test]
+async fn admin_can_access_ticket_without_membership() {
+    let app = spawn_app().await;
+
+    app.create_test_ticket().await
+        .error_for_status()
+        .unwrap();
+
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    let resp = reqwest::Client::new()
+        .get(format!("{}/v1/tickets/1", app.address))
+        .bearer_auth(access)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), 200);
+}
+
+#[tokio::test]
+async fn assign_ticket_to_non_member_returns_400() {
+    let app = spawn_app().await;
+
+    app.create_test_ticket().await
+        .error_for_status()
+        .unwrap();
+
+    app.create_user(UserRole::Employee).await;
+    leave_departments(&app, NEXT_USER_ID).await;
+
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    let resp = reqwest::Client::new()
+        .post(format!("{}/v1/tickets/1/assign/{}", app.address, NEXT_USER_ID))
+        .bearer_auth(access)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), 400);
+}
+
+#[tokio::test]
+async fn department_moderator_can_assign_others() {
+    let app = spawn_app().await;
+
+    app.create_test_ticket().await
+        .error_for_status()
+        .unwrap();
+
+    let email = app.create_user(UserRole::Employee).await;
+    app.create_user(UserRole::Employee).await;
+
+    let (admin_access, _) = app.get_admin_jwt_tokens().await;
+    let (access, _) = app.get_jwt_tokens(&email, "admin").await;
+
+    let assign = || reqwest::Client::new()
+        .post(format!("{}/v1/tickets/1/assign/{}", app.address, NEXT_USER_ID + 1))
+        .bearer_auth(&access)
+        .send();
+
+    let resp = assign().await.unwrap();
+
+    assert_eq!(resp.status(), 403);
+
+    let resp = set_member(&app, 1, NEXT_USER_ID, "moderator", &admin_access).await;
+
+    assert_eq!(resp.status(), 200);
+
+    let resp = assign().await.unwrap();
+
+    assert_eq!(resp.status(), 200);
+}
+
+#[tokio::test]
+async fn department_members_can_be_managed() {
+    let app = spawn_app().await;
+
+    app.create_user(UserRole::Employee).await;
+
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    let resp = set_member(&app, 1, NEXT_USER_ID, "moderator", &access).await;
+
+    assert_eq!(resp.status(), 200);
+
+    let json: serde_json::Value = reqwest::Client::new()
+        .get(format!("{}/v1/departments/1/members", app.address))
+        .bearer_auth(&access)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    let member = json.as_array()
+        .unwrap()
+        .iter()
+        .find(|member| member["id"] == NEXT_USER_ID)
+        .unwrap();
+
+    assert_eq!(member["role"], "moderator");
+
+    let resp = reqwest::Client::new()
+        .delete(format!("{}/v1/departments/1/members/{}", app.address, NEXT_USER_ID))
+        .bearer_auth(&access)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), 204);
+
+    let resp = reqwest::Client::new()
+        .delete(format!("{}/v1/departments/1/members/{}", app.address, NEXT_USER_ID))
+        .bearer_auth(&access)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), 404);
+}
+
+#[tokio::test]
+async fn set_member_of_non_existent_department_returns_404() {
+    let app = spawn_app().await;
+
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    let resp = set_member(&app, 100, 1, "member", &access).await;
+
+    assert_eq!(resp.status(), 404);
+}
\ No newline at end of file
@@ -0,0 +1,317 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use ticketing_system::{auth::types::UserRole, config::EscalationPolicy, schema::tickets::{EscalationTrigger, TicketPriority}};
+use wiremock::{matchers::{method, path_regex}, Mock, MockServer, ResponseTemplate};
+
+use crate::helpers::{spawn_app_with, TestApp};
+
+fn policy(name: &str, trigger: EscalationTrigger, priority: Option<TicketPriority>) -> EscalationPolicy {
+    EscalationPolicy {
+        name: name.to_string(),
+        trigger,
+        priority,
+        after_minutes: 30,
+        raise_priority: true,
+        notify_moderators: true,
+        notify_chat: true,
+    }
+}
+
+async fn spawn_app(telegram_server: &MockServer, policies: Vec<EscalationPolicy>) -> TestApp {
+    let telegram_uri = telegram_server.uri();
+
+    let app = spawn_app_with(move |c| {
+        c.escalations.enabled = true;
+        c.escalations.check_interval_seconds = 1;
+        c.escalations.policies = policies;
+        c.event_publisher.base_url = telegram_uri;
+    })
+    .await;
+
+    // Tests backdate their tickets, which must not predate escalations.
+    set_escalations_enabled_at(&app, Utc::now() - chrono::Duration::days(1)).await;
+
+    app
+}
+
+async fn set_escalations_enabled_at(app: &TestApp, enabled_at: DateTime<Utc>) {
+    sqlx::query!(
+        "INSERT INTO app_settings (key, value)
+        VALUES ('escalations_enabled_at', $1)
+        ON CONFLICT (key) DO UPDATE SET value = EXCLUDED.value",
+        serde_json::to_value(enabled_at).unwrap()
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+}
+
+async fn escalations(app: &TestApp, ticket_id: i64) -> Vec<String> {
+    sqlx::query_scalar!(
+        "SELECT policy FROM ticket_escalations WHERE ticket_id = $1 ORDER BY policy",
+        ticket_id
+    )
+    .fetch_all(&app.db_pool)
+    .await
+    .unwrap()
+}
+
+async fn wait_for_escalation(app: &TestApp, ticket_id: i64) -> Vec<String> {
+    let mut policies = Vec::new();
+
+    for _ in 0..20 {
+        tokio::time::sleep(Duration::from_millis(500)).await;
+
+        policies = escalations(app, ticket_id).await;
+
+        if !policies.is_empty() {
+            break;
+        }
+    }
+
+    policies
+}
+
+async fn get_priority(app: &TestApp, ticket_id: i64) -> i16 {
+    sqlx::query_scalar!("SELECT priority FROM tickets WHERE id = $1", ticket_id)
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn unanswered_ticket_is_escalated_once() {
+    let telegram_server = MockServer::start().await;
+
+    Mock::given(path_regex("/bot.*/sendMessage"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&telegram_server)
+        .await;
+
+    let app = spawn_app(&telegram_server, vec![
+        policy("high_no_response", EscalationTrigger::NoResponse, Some(TicketPriority::High)),
+    ]).await;
+
+    let email = app.create_user(UserRole::Moderator).await;
+    sqlx::query!(
+        "UPDATE user_departments SET role = 1
+        WHERE user_id = (SELECT id FROM users WHERE email = $1) AND department_id = 1",
+        email
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
+    let (access, _) = app.get_admin_jwt_tokens().await;
+    let ticket_id = app.create_ticket_with_title("Test", &access).await;
+
+    sqlx::query!(
+        "UPDATE tickets SET priority = $2, created_at = NOW() - INTERVAL '1 hour' WHERE id = $1",
+        ticket_id,
+        TicketPriority::High as i16
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
+    assert_eq!(wait_for_escalation(&app, ticket_id).await, ["high_no_response"]);
+    assert_eq!(get_priority(&app, ticket_id).await, TicketPriority::Critical as i16);
+
+    let notifications = sqlx::query_scalar!(
+        "SELECT payload FROM notifications n
+        JOIN users u ON u.id = n.user_id
+        WHERE n.ticket_id = $1 AND u.email = $2",
+        ticket_id,
+        email
+    )
+    .fetch_all(&app.db_pool)
+    .await
+    .unwrap();
+
+    assert_eq!(notifications.len(), 1);
+    assert_eq!(notifications[0]["type"], "escalated");
+    assert_eq!(notifications[0]["data"]["trigger"], "no_response");
+
+    // Further ticks must not escalate it again.
+    tokio::time::sleep(Duration::from_millis(2500)).await;
+
+    assert_eq!(escalations(&app, ticket_id).await, ["high_no_response"]);
+}
+
+#[tokio::test]
+async fn answered_ticket_is_not_escalated() {
+    let telegram_server = MockServer::start().await;
+
+    let app = spawn_app(&telegram_server, vec![
+        policy("no_response", EscalationTrigger::NoResponse, None),
+    ]).await;
+
+    let (access, _) = app.get_admin_jwt_tokens().await;
+    let ticket_id = app.create_ticket_with_title("Test", &access).await;
+
+    sqlx::query!(
+        "UPDATE tickets
+        SET created_at = NOW() - INTERVAL '1 hour', first_response_at = NOW()
+        WHERE id = $1",
+        ticket_id
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
+    tokio::time::sleep(Duration::from_millis(2500)).await;
+
+    assert!(escalations(&app, ticket_id).await.is_empty());
+}
+
+#[tokio::test]
+async fn ticket_created_before_escalations_were_enabled_is_not_escalated() {
+    let telegram_server = MockServer::start().await;
+
+    let app = spawn_app(&telegram_server, vec![
+        policy("no_response", EscalationTrigger::NoResponse, None),
+    ]).await;
+
+    set_escalations_enabled_at(&app, Utc::now() - chrono::Duration::minutes(30)).await;
+
+    let (access, _) = app.get_admin_jwt_tokens().await;
+    let ticket_id = app.create_ticket_with_title("Test", &access).await;
+
+    sqlx::query!(
+        "UPDATE tickets SET created_at = NOW() - INTERVAL '1 hour' WHERE id = $1",
+        ticket_id
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
+    tokio::time::sleep(Duration::from_millis(2500)).await;
+
+    assert!(escalations(&app, ticket_id).await.is_empty());
+}
+
+#[tokio::test]
+async fn ticket_past_planned_date_is_escalated() {
+    let telegram_server = MockServer::start().await;
+
+    Mock::given(path_regex("/bot.*/sendMessage"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&telegram_server)
+        .await;
+
+    let app = spawn_app(&telegram_server, vec![
+        policy("planned_at_passed", EscalationTrigger::PlannedAtPassed, None),
+    ]).await;
+
+    let (access, _) = app.get_admin_jwt_tokens().await;
+    let overdue = app.create_ticket_with_title("Test", &access).await;
+    let closed = app.create_ticket_with_title("Test", &access).await;
+
+    sqlx::query!(
+        "UPDATE tickets SET planned_at = NOW() - INTERVAL '1 hour' WHERE id = ANY($1)",
+        &[overdue, closed]
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
+    sqlx::query!("UPDATE tickets SET status = 1 WHERE id = $1", closed)
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+
+    assert_eq!(wait_for_escalation(&app, overdue).await, ["planned_at_passed"]);
+    assert_eq!(get_priority(&app, overdue).await, TicketPriority::Medium as i16);
+    assert!(escalations(&app, closed).await.is_empty());
+}
+#[tokio::test]
+async fn assigned_ticket_is_not_escalated_for_no_response() {
+    let telegram_server = MockServer::start().await;
+
+    let app = spawn_app(&telegram_server, vec![
+        policy("no_response", EscalationTrigger::NoResponse, None),
+    ]).await;
+
+    let email = app.create_user(UserRole::Employee).await;
+    let (access, _) = app.get_admin_jwt_tokens().await;
+    let ticket_id = app.create_ticket_with_title("Test", &access).await;
+
+    sqlx::query!(
+        "INSERT INTO tickets_users (assigned_to, ticket_id)
+        SELECT id, $1 FROM users WHERE email = $2",
+        ticket_id,
+        email
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
+    sqlx::query!(
+        "UPDATE tickets SET created_at = NOW() - INTERVAL '1 hour' WHERE id = $1",
+        ticket_id
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
+    tokio::time::sleep(Duration::from_millis(2500)).await;
+
+    assert!(escalations(&app, ticket_id).await.is_empty());
+}
+
+#[tokio::test]
+async fn raised_priority_does_not_trigger_next_policy() {
+    let telegram_server = MockServer::start().await;
+
+    Mock::given(path_regex("/bot.*/sendMessage"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&telegram_server)
+        .await;
+
+    let app = spawn_app(&telegram_server, vec![
+        policy("critical_no_response", EscalationTrigger::NoResponse, Some(TicketPriority::Critical)),
+        policy("high_no_response", EscalationTrigger::NoResponse, Some(TicketPriority::High)),
+    ]).await;
+
+    // Nobody moderates the department, so the global moderator is notified.
+    let email = app.create_user(UserRole::Moderator).await;
+    let (access, _) = app.get_admin_jwt_tokens().await;
+    let ticket_id = app.create_ticket_with_title("Test", &access).await;
+
+    sqlx::query!(
+        "UPDATE tickets SET priority = $2, created_at = NOW() - INTERVAL '1 hour' WHERE id = $1",
+        ticket_id,
+        TicketPriority::High as i16
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
+    wait_for_escalation(&app, ticket_id).await;
+
+    // Further ticks see the critical ticket as already escalated.
+    tokio::time::sleep(Duration::from_millis(2500)).await;
+
+    assert_eq!(escalations(&app, ticket_id).await, ["critical_no_response", "high_no_response"]);
+    assert_eq!(get_priority(&app, ticket_id).await, TicketPriority::Critical as i16);
+
+    let notifications = sqlx::query_scalar!(
+        "SELECT COUNT(*) AS \"count!\" FROM notifications n
+        JOIN users u ON u.id = n.user_id
+        WHERE n.ticket_id = $1 AND u.email = $2",
+        ticket_id,
+        email
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+
+    assert_eq!(notifications, 1);
+}
@@ -0,0 +1,139 @@
+use reqwest::StatusCode;
+use ticketing_system::auth::types::UserRole;
+
+use crate::helpers::{spawn_app, TestApp};
+
+async fn export(app: &TestApp, query: &[(&str, &str)], access: &str) -> reqwest::Response {
+    reqwest::Client::new()
+        .get(format!("{}/v1/tickets/export", app.address))
+        .query(query)
+        .bearer_auth(access)
+        .send()
+        .await
+        .expect("Failed to execute request")
+}
+
+async fn export_csv_rows(app: &TestApp, query: &[(&str, &str)], access: &str) -> Vec<String> {
+    let resp = export(app, query, access).await;
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert!(resp.headers()["content-type"].to_str().unwrap().starts_with("text/csv"));
+
+    let text = resp.text().await.unwrap();
+
+    text.split("\r\n")
+        .skip(1)
+        .filter(|line| !line.is_empty())
+        .map(ToString::to_string)
+        .collect()
+}
+
+#[tokio::test]
+async fn csv_export_applies_filters_and_includes_assignees() {
+    let app = spawn_app().await;
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    let id = app.create_ticket_with_title("Printer", &access).await;
+    app.create_ticket_with_title("Network", &access).await;
+
+    sqlx::query!(
+        "UPDATE tickets SET priority = 2 WHERE id = $1",
+        id
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
+    sqlx::query!(
+        "INSERT INTO tickets_users (ticket_id, assigned_to)
+        SELECT $1, id FROM users WHERE email = 'admin@example.com'",
+        id
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
+    let admin_name: String = sqlx::query_scalar!("SELECT name FROM users WHERE email = 'admin@example.com'")
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+
+    let department_name: String = sqlx::query_scalar!("SELECT name FROM departments WHERE id = 1")
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+
+    let rows = export_csv_rows(&app, &[("format", "csv"), ("priorities[]", "high")], &access).await;
+
+    assert_eq!(rows.len(), 1);
+    assert!(rows[0].starts_with(&format!("{},Printer,", id)));
+    assert!(rows[0].contains(&admin_name));
+    assert!(rows[0].contains(&department_name));
+    assert!(rows[0].contains("Высокий"));
+}
+
+#[tokio::test]
+async fn csv_export_fetches_all_batches() {
+    let app = spawn_app().await;
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    sqlx::query!(
+        "INSERT INTO tickets (title, description, author, author_contacts, building_id, department_id)
+        SELECT 'Ticket ' || n, 'Test description', 'Test author', 'Test contacts', 1, 1
+        FROM generate_series(1, 1200) AS n"
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
+    let rows = export_csv_rows(&app, &[("format", "csv"), ("order_by", "1"), ("sort_order", "asc")], &access).await;
+
+    assert_eq!(rows.len(), 1200);
+}
+
+#[tokio::test]
+async fn client_exports_only_own_tickets() {
+    let app = spawn_app().await;
+    let (admin_access, _) = app.get_admin_jwt_tokens().await;
+
+    let login = app.create_user(UserRole::Client).await;
+    let (access, _) = app.get_jwt_tokens(&login, "admin").await;
+
+    app.create_ticket_with_title("Admin ticket", &admin_access).await;
+    let id = app.create_ticket_with_title("Client ticket", &access).await;
+
+    let rows = export_csv_rows(&app, &[("format", "csv")], &access).await;
+
+    assert_eq!(rows.len(), 1);
+    assert!(rows[0].starts_with(&format!("{},Client ticket,", id)));
+}
+
+#[tokio::test]
+async fn xlsx_export_returns_workbook() {
+    let app = spawn_app().await;
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    app.create_ticket_with_title("Printer", &access).await;
+
+    let resp = export(&app, &[("format", "xlsx")], &access).await;
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(
+        resp.headers()["content-type"],
+        "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"
+    );
+    assert!(resp.headers()["content-disposition"].to_str().unwrap().contains(".xlsx"));
+
+    let bytes = resp.bytes().await.unwrap();
+    assert!(bytes.starts_with(b"PK"));
+}
+
+#[tokio::test]
+async fn export_requires_known_format() {
+    let app = spawn_app().await;
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    let resp = export(&app, &[("format", "pdf")], &access).await;
+
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
\ No newline at end of file
@@ -269,4 +269,105 @@ async fn get_tickets_for_client_returns_only_his_tickets() {
     let data = json["items"].as_array().unwrap();
 
     assert_eq!(data.len(), 5);
+}
+
+#[tokio::test]
+async fn get_tickets_with_cursor_walks_every_ticket_once() {
+    let app = spawn_app().await;
+
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    insert_random_tickets(&app.db_pool).await;
+
+    sqlx::query!("UPDATE tickets SET priority = id % 4, planned_at = CASE WHEN id % 3 = 0 THEN NULL ELSE NOW() + (id % 5) * INTERVAL '1 day' END")
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+
+    for order_by in [0, 1, 2] {
+        for sort_order in ["asc", "desc"] {
+            let mut ids = Vec::new();
+            let mut cursor = String::new();
+
+            loop {
+                let json: serde_json::Value = reqwest::Client::new()
+                    .get(format!("{}/v1/tickets/", app.address))
+                    .query(&[
+                        ("order_by", order_by.to_string().as_str()),
+                        ("sort_order", sort_order),
+                        ("page_size", "30"),
+                        ("cursor", cursor.as_str()),
+                    ])
+                    .bearer_auth(&access)
+                    .send()
+                    .await
+                    .unwrap()
+                    .json()
+                    .await
+                    .unwrap();
+
+                ids.extend(json["items"].as_array().unwrap().iter().map(|item| item["id"].as_i64().unwrap()));
+
+                match json["next_cursor"].as_str() {
+                    Some(next) => cursor = next.to_string(),
+                    None => break,
+                }
+            }
+
+            assert_eq!(ids.len(), 100);
+            ids.sort_unstable();
+            ids.dedup();
+            assert_eq!(ids.len(), 100);
+        }
+    }
+}
+
+#[tokio::test]
+async fn get_tickets_with_cursor_counts_total_on_request() {
+    let app = spawn_app().await;
+
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    insert_random_tickets(&app.db_pool).await;
+
+    let json: serde_json::Value = reqwest::Client::new()
+        .get(format!("{}/v1/tickets/?cursor=&page_size=10", app.address))
+        .bearer_auth(&access)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    assert_eq!(json["items"].as_array().unwrap().len(), 10);
+    assert!(json.get("total_items").is_none());
+
+    let json: serde_json::Value = reqwest::Client::new()
+        .get(format!("{}/v1/tickets/?cursor=&with_total=true", app.address))
+        .bearer_auth(&access)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    assert_eq!(json["total_items"], 100);
+}
+
+#[tokio::test]
+async fn get_tickets_with_invalid_cursor_returns_400() {
+    let app = spawn_app().await;
+
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    let resp = reqwest::Client::new()
+        .get(format!("{}/v1/tickets/?cursor=garbage", app.address))
+        .bearer_auth(&access)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), 400);
 }
\ No newline at end of file
@@ -0,0 +1,98 @@
+use reqwest::StatusCode;
+
+use crate::helpers::{spawn_app, TestApp};
+
+async fn create_link(app: &TestApp, ticket_id: i64, body: serde_json::Value, access: &str) -> reqwest::Response {
+    reqwest::Client::new()
+        .post(format!("{}/v1/tickets/{}/links", app.address, ticket_id))
+        .bearer_auth(access)
+        .json(&body)
+        .send()
+        .await
+        .expect("Failed to execute request")
+}
+
+async fn get_links(app: &TestApp, ticket_id: i64, access: &str) -> Vec<serde_json::Value> {
+    reqwest::Client::new()
+        .get(format!("{}/v1/tickets/{}/links", app.address, ticket_id))
+        .bearer_auth(access)
+        .send()
+        .await
+        .expect("Failed to execute request")
+        .json()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn link_is_visible_from_both_tickets() {
+    let app = spawn_app().await;
+    let (access, _) = app.get_admin_jwt_tokens().await;
+    let blocker = app.create_ticket_with_title("Test", &access).await;
+    let blocked = app.create_ticket_with_title("Test", &access).await;
+
+    let resp = create_link(&app, blocker, serde_json::json!({ "ticket_id": blocked, "kind": "blocks" }), &access).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+
+    let links = get_links(&app, blocker, &access).await;
+    assert_eq!(links.len(), 1);
+    assert_eq!(links[0]["ticket_id"], blocked);
+    assert_eq!(links[0]["kind"], "blocks");
+    assert_eq!(links[0]["outgoing"], true);
+
+    let links = get_links(&app, blocked, &access).await;
+    assert_eq!(links.len(), 1);
+    assert_eq!(links[0]["ticket_id"], blocker);
+    assert_eq!(links[0]["outgoing"], false);
+}
+
+#[tokio::test]
+async fn pair_can_be_linked_once() {
+    let app = spawn_app().await;
+    let (access, _) = app.get_admin_jwt_tokens().await;
+    let first = app.create_ticket_with_title("Test", &access).await;
+    let second = app.create_ticket_with_title("Test", &access).await;
+
+    let resp = create_link(&app, first, serde_json::json!({ "ticket_id": second, "kind": "related" }), &access).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+
+    let resp = create_link(&app, second, serde_json::json!({ "ticket_id": first, "kind": "related" }), &access).await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn invalid_links_are_rejected() {
+    let app = spawn_app().await;
+    let (access, _) = app.get_admin_jwt_tokens().await;
+    let ticket = app.create_ticket_with_title("Test", &access).await;
+
+    let resp = create_link(&app, ticket, serde_json::json!({ "ticket_id": ticket, "kind": "related" }), &access).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let resp = create_link(&app, ticket, serde_json::json!({ "ticket_id": i64::MAX, "kind": "related" }), &access).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn link_can_be_deleted_from_either_side() {
+    let app = spawn_app().await;
+    let (access, _) = app.get_admin_jwt_tokens().await;
+    let first = app.create_ticket_with_title("Test", &access).await;
+    let second = app.create_ticket_with_title("Test", &access).await;
+
+    create_link(&app, first, serde_json::json!({ "ticket_id": second, "kind": "related" }), &access).await
+        .error_for_status()
+        .unwrap();
+
+    let delete = || reqwest::Client::new()
+        .delete(format!("{}/v1/tickets/{}/links/{}", app.address, second, first))
+        .bearer_auth(&access)
+        .send();
+
+    let resp = delete().await.expect("Failed to execute request");
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+    assert!(get_links(&app, first, &access).await.is_empty());
+
+    let resp = delete().await.expect("Failed to execute request");
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
\ No newline at end of file
@@ -0,0 +1,133 @@
+use reqwest::StatusCode;
+use ticketing_system::auth::types::UserRole;
+
+use crate::helpers::{spawn_app, TestApp};
+
+async fn merge(app: &TestApp, primary_id: i64, ticket_ids: &[i64], access: &str) -> reqwest::Response {
+    reqwest::Client::new()
+        .post(format!("{}/v1/tickets/{}/merge", app.address, primary_id))
+        .bearer_auth(access)
+        .json(&serde_json::json!({ "ticket_ids": ticket_ids }))
+        .send()
+        .await
+        .expect("Failed to execute request")
+}
+
+async fn insert_message(app: &TestApp, ticket_id: i64) {
+    sqlx::query!(
+        "INSERT INTO ticket_messages (ticket_id, user_id, message_text)
+        SELECT $1, id, 'Still broken' FROM users WHERE email = 'admin@example.com'",
+        ticket_id
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn merge_moves_contents_and_cancels_duplicates() {
+    let app = spawn_app().await;
+    let (admin_access, _) = app.get_admin_jwt_tokens().await;
+    let client = app.create_user(UserRole::Client).await;
+    let (client_access, _) = app.get_jwt_tokens(&client, "admin").await;
+
+    let primary = app.create_ticket_with_title("Projector is broken", &admin_access).await;
+    let duplicate = app.create_ticket_with_title("Projector is broken", &client_access).await;
+
+    insert_message(&app, duplicate).await;
+
+    sqlx::query!(
+        "INSERT INTO ticket_attachments (ticket_id, key) VALUES ($1, 'photo.webp')",
+        duplicate
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
+    let resp = merge(&app, primary, &[duplicate], &admin_access).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let messages = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "count!" FROM ticket_messages WHERE ticket_id = $1"#,
+        primary
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+    assert_eq!(messages, 1);
+
+    let attachment_ticket = sqlx::query_scalar!(
+        "SELECT ticket_id FROM ticket_attachments WHERE key = 'photo.webp'"
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+    assert_eq!(attachment_ticket, primary);
+
+    let status = sqlx::query_scalar!("SELECT status FROM tickets WHERE id = $1", duplicate)
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(status, 3);
+
+    let resp = reqwest::Client::new()
+        .get(format!("{}/v1/tickets/{}/links", app.address, duplicate))
+        .bearer_auth(&admin_access)
+        .send()
+        .await
+        .expect("Failed to execute request");
+
+    let links: Vec<serde_json::Value> = resp.json().await.unwrap();
+    assert_eq!(links.len(), 1);
+    assert_eq!(links[0]["ticket_id"], primary);
+    assert_eq!(links[0]["kind"], "duplicates");
+    assert_eq!(links[0]["outgoing"], true);
+
+    let payload = sqlx::query_scalar!(
+        "SELECT n.payload FROM notifications n
+        JOIN users u ON u.id = n.user_id
+        WHERE n.ticket_id = $1 AND u.email = $2",
+        duplicate,
+        client
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+    assert_eq!(payload["type"], "merged");
+    assert_eq!(payload["data"]["into"], primary);
+}
+
+#[tokio::test]
+async fn merging_into_itself_returns_400() {
+    let app = spawn_app().await;
+    let (access, _) = app.get_admin_jwt_tokens().await;
+    let primary = app.create_ticket_with_title("Projector is broken", &access).await;
+
+    let resp = merge(&app, primary, &[primary], &access).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn merging_cancelled_ticket_returns_409() {
+    let app = spawn_app().await;
+    let (access, _) = app.get_admin_jwt_tokens().await;
+    let primary = app.create_ticket_with_title("Projector is broken", &access).await;
+    let duplicate = app.create_ticket_with_title("Projector is broken", &access).await;
+    let other = app.create_ticket_with_title("Projector is broken", &access).await;
+
+    let resp = merge(&app, primary, &[duplicate], &access).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let resp = merge(&app, other, &[duplicate], &access).await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn merging_unknown_ticket_returns_404() {
+    let app = spawn_app().await;
+    let (access, _) = app.get_admin_jwt_tokens().await;
+    let primary = app.create_ticket_with_title("Projector is broken", &access).await;
+
+    let resp = merge(&app, primary, &[i64::MAX], &access).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
\ No newline at end of file
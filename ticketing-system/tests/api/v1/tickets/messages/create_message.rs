@@ -50,6 +50,16 @@ async fn create_message_forces_not_internal_for_client() {
         .await;
     let (access, _) = app.get_jwt_tokens(&login, "admin").await;
 
+    // Clients only write to their own tickets.
+    sqlx::query!(
+        "UPDATE tickets SET author_id = (SELECT id FROM users WHERE email = $2) WHERE id = $1",
+        ticket_id,
+        login
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
     let body = serde_json::json!({
         "message": "client message",
         "is_internal": true
@@ -46,6 +46,16 @@ async fn get_messages_hides_internal_for_client() {
         .await;
     let (client_access, _) = app.get_jwt_tokens(&client_login, "admin").await;
 
+    // Clients only see messages of their own tickets.
+    sqlx::query!(
+        "UPDATE tickets SET author_id = (SELECT id FROM users WHERE email = $2) WHERE id = $1",
+        ticket_id,
+        client_login
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
     let resp = get_messages(&app, ticket_id, Some(&client_access)).await;
 
     assert_eq!(resp.status(), 200);
@@ -9,4 +9,14 @@ mod messages;
 mod assign_ticket;
 mod get_ticket;
 mod assets;
-mod unassign_ticket;
\ No newline at end of file
+mod unassign_ticket;
+mod department_scope;
+mod escalations;
+mod merge_tickets;
+mod links;
+mod similar_tickets;
+mod ticket_pages;
+mod search_tickets;
+mod views;
+mod export_tickets;
+mod bulk_tickets;
\ No newline at end of file
@@ -0,0 +1,124 @@
+use reqwest::StatusCode;
+use ticketing_system::auth::types::UserRole;
+
+use crate::helpers::{spawn_app, TestApp};
+
+async fn search(app: &TestApp, query: &str, access: &str) -> Vec<serde_json::Value> {
+    let resp = reqwest::Client::new()
+        .get(format!("{}/v1/tickets/", app.address))
+        .query(&[("search", query)])
+        .bearer_auth(access)
+        .send()
+        .await
+        .expect("Failed to execute request");
+
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let json: serde_json::Value = resp.json().await.unwrap();
+    json["items"].as_array().unwrap().clone()
+}
+
+#[tokio::test]
+async fn search_matches_word_forms_and_highlights_them() {
+    let app = spawn_app().await;
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    let id = app.create_ticket_with_description("Не работает принтер", "Принтер в 101 не печатает", &access).await;
+    app.create_ticket_with_description("Нет интернета", "Кабель на месте", &access).await;
+
+    let items = search(&app, "принтера", &access).await;
+
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0]["id"], id);
+    assert!(items[0]["search_snippet"].as_str().unwrap().contains("<b>Принтер</b>"));
+}
+
+#[tokio::test]
+async fn search_orders_by_relevance() {
+    let app = spawn_app().await;
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    let in_description = app.create_ticket_with_description("Проблема в аудитории", "Сломался проектор", &access).await;
+    let in_title = app.create_ticket_with_description("Сломался проектор", "Проектор не включается", &access).await;
+
+    let items = search(&app, "проектор", &access).await;
+
+    let ids = items.iter().map(|item| item["id"].as_i64().unwrap()).collect::<Vec<_>>();
+    assert_eq!(ids, vec![in_title, in_description]);
+}
+
+#[tokio::test]
+async fn search_falls_back_to_ticket_number() {
+    let app = spawn_app().await;
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    app.create_ticket_with_description("Первая заявка", "Описание", &access).await;
+    let id = app.create_ticket_with_description("Вторая заявка", "Описание", &access).await;
+
+    let items = search(&app, &format!("#{}", id), &access).await;
+
+    assert_eq!(items[0]["id"], id);
+}
+
+#[tokio::test]
+async fn search_without_query_has_no_snippets() {
+    let app = spawn_app().await;
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    app.create_ticket_with_description("Не работает принтер", "Принтер не печатает", &access).await;
+
+    let items = search(&app, "  ", &access).await;
+
+    assert_eq!(items.len(), 1);
+    assert!(items[0].get("search_snippet").is_none());
+}
+
+#[tokio::test]
+async fn clients_do_not_match_internal_messages() {
+    let app = spawn_app().await;
+    let (admin_access, _) = app.get_admin_jwt_tokens().await;
+
+    let login = app.create_user(UserRole::Client).await;
+    let (access, _) = app.get_jwt_tokens(&login, "admin").await;
+
+    let id = app.create_ticket_with_description("Не работает принтер", "Принтер не печатает", &access).await;
+
+    sqlx::query!(
+        "INSERT INTO ticket_messages (ticket_id, user_id, is_internal, message_text)
+        SELECT $1, id, TRUE, 'Заказали новые картриджи' FROM users WHERE email = 'admin@example.com'",
+        id
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
+    assert_eq!(search(&app, "картридж", &admin_access).await.len(), 1);
+    assert!(search(&app, "картридж", &access).await.is_empty());
+}
+#[tokio::test]
+async fn staff_without_permission_do_not_match_internal_messages() {
+    let app = spawn_app().await;
+    let (admin_access, _) = app.get_admin_jwt_tokens().await;
+
+    sqlx::query!("DELETE FROM role_permissions WHERE role_id = 2 AND permission = 'messages.read_internal'")
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+
+    let login = app.create_user(UserRole::Employee).await;
+    let (access, _) = app.get_jwt_tokens(&login, "admin").await;
+
+    let id = app.create_ticket_with_description("Не работает принтер", "Принтер не печатает", &admin_access).await;
+
+    sqlx::query!(
+        "INSERT INTO ticket_messages (ticket_id, user_id, is_internal, message_text)
+        SELECT $1, id, TRUE, 'Заказали новые картриджи' FROM users WHERE email = 'admin@example.com'",
+        id
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
+    assert_eq!(search(&app, "принтер", &access).await.len(), 1);
+    assert!(search(&app, "картридж", &access).await.is_empty());
+}
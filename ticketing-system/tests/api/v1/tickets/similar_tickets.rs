@@ -0,0 +1,120 @@
+use reqwest::StatusCode;
+use ticketing_system::auth::types::UserRole;
+
+use crate::helpers::{spawn_app, TestApp};
+
+fn ticket_body(title: &str, cabinet: &str) -> serde_json::Value {
+    serde_json::json!({
+        "title": title,
+        "description": "Nothing is shown on the screen",
+        "author": "Test author",
+        "author_contacts": "Test contacts",
+        "building_id": 1,
+        "department_id": 1,
+        "cabinet": cabinet,
+    })
+}
+
+async fn get_similar(app: &TestApp, query: &[(&str, &str)]) -> Vec<serde_json::Value> {
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    get_similar_as(app, query, &access).await
+}
+
+async fn get_similar_as(app: &TestApp, query: &[(&str, &str)], access: &str) -> Vec<serde_json::Value> {
+    let resp = reqwest::Client::new()
+        .get(format!("{}/v1/tickets/similar", app.address))
+        .bearer_auth(access)
+        .query(query)
+        .send()
+        .await
+        .expect("Failed to execute request");
+
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    resp.json().await.unwrap()
+}
+
+#[tokio::test]
+async fn similar_open_tickets_are_suggested() {
+    let app = spawn_app().await;
+
+    let resp = app.create_ticket_from_admin(&ticket_body("Projector does not work", "1201"), None).await;
+    let body: serde_json::Value = resp.json().await.unwrap();
+    let id = body["id"].as_i64().unwrap();
+
+    app.create_ticket_from_admin(&ticket_body("Printer is out of toner", "1201"), None).await;
+
+    let similar = get_similar(&app, &[("title", "Projector doesn't work"), ("building_id", "1")]).await;
+    assert_eq!(similar.len(), 1);
+    assert_eq!(similar[0]["id"], id);
+
+    let similar = get_similar(&app, &[("title", "Projector doesn't work"), ("building_id", "1"), ("cabinet", "1305")]).await;
+    assert!(similar.is_empty());
+
+    let similar = get_similar(&app, &[("title", "Projector doesn't work"), ("building_id", "2")]).await;
+    assert!(similar.is_empty());
+}
+
+#[tokio::test]
+async fn closed_tickets_are_not_suggested() {
+    let app = spawn_app().await;
+
+    let resp = app.create_ticket_from_admin(&ticket_body("Projector does not work", "1201"), None).await;
+    let body: serde_json::Value = resp.json().await.unwrap();
+
+    sqlx::query!("UPDATE tickets SET status = 1 WHERE id = $1", body["id"].as_i64().unwrap())
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+
+    let similar = get_similar(&app, &[("title", "Projector does not work"), ("building_id", "1")]).await;
+    assert!(similar.is_empty());
+}
+
+#[tokio::test]
+async fn create_ticket_with_check_returns_409_for_duplicates() {
+    let app = spawn_app().await;
+
+    app.create_ticket_from_admin(&ticket_body("Projector does not work", "1201"), None).await;
+
+    let mut body = ticket_body("Projector does not work", "1201");
+    body["check_similar"] = true.into();
+
+    let resp = app.create_ticket_from_admin(&body, None).await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+
+    let similar: Vec<serde_json::Value> = resp.json().await.unwrap();
+    assert_eq!(similar.len(), 1);
+
+    body["check_similar"] = false.into();
+
+    let resp = app.create_ticket_from_admin(&body, None).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+}
+#[tokio::test]
+async fn tickets_of_others_are_reduced_to_similarity() {
+    let app = spawn_app().await;
+
+    let resp = app.create_ticket_from_admin(&ticket_body("Projector does not work", "1201"), None).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+
+    let email = app.create_user(UserRole::Client).await;
+    let (access, _) = app.get_jwt_tokens(&email, "admin").await;
+
+    let similar = get_similar_as(&app, &[("title", "Projector doesn't work"), ("building_id", "1")], &access).await;
+    assert_eq!(similar.len(), 1);
+    assert!(similar[0]["similarity"].is_number());
+    assert!(similar[0].get("id").is_none());
+    assert!(similar[0].get("title").is_none());
+
+    let mut body = ticket_body("Projector does not work", "1201");
+    body["check_similar"] = true.into();
+
+    let resp = app.create_ticket(&body, None, Some(&access)).await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+
+    let similar: Vec<serde_json::Value> = resp.json().await.unwrap();
+    assert_eq!(similar.len(), 1);
+    assert!(similar[0].get("title").is_none());
+}
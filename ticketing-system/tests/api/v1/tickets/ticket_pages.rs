@@ -0,0 +1,93 @@
+use reqwest::StatusCode;
+use ticketing_system::auth::types::UserRole;
+
+use crate::helpers::{spawn_app, TestApp};
+
+async fn attach_page(app: &TestApp, ticket_id: i64, page_id: i32, access: &str) -> reqwest::Response {
+    reqwest::Client::new()
+        .post(format!("{}/v1/tickets/{}/pages", app.address, ticket_id))
+        .bearer_auth(access)
+        .json(&serde_json::json!({ "page_id": page_id }))
+        .send()
+        .await
+        .expect("Failed to execute request")
+}
+
+async fn detach_page(app: &TestApp, ticket_id: i64, page_id: i32, access: &str) -> reqwest::Response {
+    reqwest::Client::new()
+        .delete(format!("{}/v1/tickets/{}/pages/{}", app.address, ticket_id, page_id))
+        .bearer_auth(access)
+        .send()
+        .await
+        .expect("Failed to execute request")
+}
+
+#[tokio::test]
+async fn client_sees_public_solution_pages() {
+    let app = spawn_app().await;
+    let (admin_access, _) = app.get_admin_jwt_tokens().await;
+
+    let login = app.create_user(UserRole::Client).await;
+    let (access, _) = app.get_jwt_tokens(&login, "admin").await;
+
+    let ticket_id = app.create_ticket_with_title("Test", &access).await;
+
+    app.create_test_page().await;
+    app.create_private_page().await;
+
+    for page_id in [1, 2] {
+        let resp = attach_page(&app, ticket_id, page_id, &admin_access).await;
+        assert_eq!(resp.status(), StatusCode::CREATED);
+    }
+
+    let ticket: serde_json::Value = app.get_ticket(ticket_id, Some(&access)).await.json().await.unwrap();
+    assert_eq!(ticket["solution_pages"], serde_json::json!([{ "id": 1, "title": "Test title" }]));
+
+    let ticket: serde_json::Value = app.get_ticket(ticket_id, Some(&admin_access)).await.json().await.unwrap();
+    assert_eq!(ticket["solution_pages"].as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn page_can_be_attached_once() {
+    let app = spawn_app().await;
+    let (access, _) = app.get_admin_jwt_tokens().await;
+    let ticket_id = app.create_ticket_with_title("Test", &access).await;
+
+    app.create_test_page().await;
+
+    let resp = attach_page(&app, ticket_id, 1, &access).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+
+    let resp = attach_page(&app, ticket_id, 1, &access).await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn attach_missing_page_returns_404() {
+    let app = spawn_app().await;
+    let (access, _) = app.get_admin_jwt_tokens().await;
+    let ticket_id = app.create_ticket_with_title("Test", &access).await;
+
+    let resp = attach_page(&app, ticket_id, 42, &access).await;
+
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn detach_page_removes_it_from_ticket() {
+    let app = spawn_app().await;
+    let (access, _) = app.get_admin_jwt_tokens().await;
+    let ticket_id = app.create_ticket_with_title("Test", &access).await;
+
+    app.create_test_page().await;
+    attach_page(&app, ticket_id, 1, &access).await;
+
+    let resp = detach_page(&app, ticket_id, 1, &access).await;
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+
+    let resp = detach_page(&app, ticket_id, 1, &access).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    let ticket: serde_json::Value = app.get_ticket(ticket_id, Some(&access)).await.json().await.unwrap();
+    assert_eq!(ticket["solution_pages"], serde_json::json!([]));
+}
\ No newline at end of file
@@ -0,0 +1,148 @@
+use reqwest::StatusCode;
+use ticketing_system::auth::types::UserRole;
+
+use crate::helpers::{spawn_app, TestApp};
+
+async fn create_ticket_with_priority(app: &TestApp, title: &str, priority: i16, access: &str) {
+    let id = app.create_ticket_with_title(title, access).await;
+
+    sqlx::query!("UPDATE tickets SET priority = $1 WHERE id = $2", priority, id)
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+}
+
+async fn create_view(app: &TestApp, body: serde_json::Value, access: &str) -> reqwest::Response {
+    reqwest::Client::new()
+        .post(format!("{}/v1/tickets/views", app.address))
+        .bearer_auth(access)
+        .json(&body)
+        .send()
+        .await
+        .expect("Failed to execute request")
+}
+
+async fn create_high_priority_view(app: &TestApp, department_id: Option<i16>, access: &str) -> i64 {
+    let body = serde_json::json!({
+        "name": "Urgent",
+        "filters": { "priorities": ["high", "critical"], "sort_order": "desc", "page": 3 },
+        "department_id": department_id,
+    });
+
+    let resp = create_view(app, body, access).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+
+    let body: serde_json::Value = resp.json().await.unwrap();
+    body["id"].as_i64().unwrap()
+}
+
+#[tokio::test]
+async fn view_executes_stored_filters() {
+    let app = spawn_app().await;
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    create_ticket_with_priority(&app, "Low", 0, &access).await;
+    create_ticket_with_priority(&app, "High", 2, &access).await;
+    create_ticket_with_priority(&app, "Critical", 3, &access).await;
+
+    let id = create_high_priority_view(&app, None, &access).await;
+
+    let json: serde_json::Value = app.get(&format!("/v1/tickets/views/{}", id), &access).await.json().await.unwrap();
+
+    let titles = json["items"].as_array().unwrap().iter()
+        .map(|item| item["title"].as_str().unwrap())
+        .collect::<Vec<_>>();
+
+    // The stored page is dropped, the order is kept.
+    assert_eq!(titles, vec!["Critical", "High"]);
+    assert_eq!(json["total_items"], 2);
+}
+
+#[tokio::test]
+async fn badge_counts_tickets_created_since_last_visit() {
+    let app = spawn_app().await;
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    let id = create_high_priority_view(&app, None, &access).await;
+
+    create_ticket_with_priority(&app, "High", 2, &access).await;
+    create_ticket_with_priority(&app, "Low", 0, &access).await;
+
+    let badges: serde_json::Value = app.get("/v1/tickets/views/badges", &access).await.json().await.unwrap();
+    assert_eq!(badges, serde_json::json!([{ "id": id, "new_tickets": 1 }]));
+
+    app.get(&format!("/v1/tickets/views/{}", id), &access).await;
+
+    let badges: serde_json::Value = app.get("/v1/tickets/views/badges", &access).await.json().await.unwrap();
+    assert_eq!(badges[0]["new_tickets"], 0);
+
+    create_ticket_with_priority(&app, "Critical", 3, &access).await;
+
+    let json: serde_json::Value = app.get(&format!("/v1/tickets/views/{}?only_new=true", id), &access).await.json().await.unwrap();
+    assert_eq!(json["items"].as_array().unwrap().len(), 1);
+    assert_eq!(json["items"][0]["title"], "Critical");
+}
+
+#[tokio::test]
+async fn shared_view_is_visible_to_department_members_only() {
+    let app = spawn_app().await;
+    let (admin_access, _) = app.get_admin_jwt_tokens().await;
+
+    let id = create_high_priority_view(&app, Some(1), &admin_access).await;
+
+    let login = app.create_user(UserRole::Employee).await;
+    let (employee_access, _) = app.get_jwt_tokens(&login, "admin").await;
+
+    let views: serde_json::Value = app.get("/v1/tickets/views", &employee_access).await.json().await.unwrap();
+    assert_eq!(views[0]["id"], id);
+    assert_eq!(app.get(&format!("/v1/tickets/views/{}", id), &employee_access).await.status(), StatusCode::OK);
+
+    // Only the owner can delete it.
+    let resp = reqwest::Client::new()
+        .delete(format!("{}/v1/tickets/views/{}", app.address, id))
+        .bearer_auth(&employee_access)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    let login = app.create_user(UserRole::Client).await;
+    let (client_access, _) = app.get_jwt_tokens(&login, "admin").await;
+
+    let views: serde_json::Value = app.get("/v1/tickets/views", &client_access).await.json().await.unwrap();
+    assert_eq!(views, serde_json::json!([]));
+    assert_eq!(app.get(&format!("/v1/tickets/views/{}", id), &client_access).await.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn view_with_invalid_filters_returns_400() {
+    let app = spawn_app().await;
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    let body = serde_json::json!({
+        "name": "Broken",
+        "filters": { "statuses": ["unknown"] },
+    });
+
+    let resp = create_view(&app, body, &access).await;
+
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn client_cannot_share_view() {
+    let app = spawn_app().await;
+
+    let login = app.create_user(UserRole::Client).await;
+    let (access, _) = app.get_jwt_tokens(&login, "admin").await;
+
+    let body = serde_json::json!({
+        "name": "Mine",
+        "filters": {},
+        "department_id": 1,
+    });
+
+    let resp = create_view(&app, body, &access).await;
+
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+}
\ No newline at end of file
@@ -0,0 +1,347 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use reqwest::StatusCode;
+use ticketing_system::auth::types::{UserRole, UserStatus};
+
+use crate::helpers::{spawn_app, spawn_app_with, TestApp};
+
+async fn get_user_id(app: &TestApp, email: &str) -> i32 {
+    sqlx::query_scalar!("SELECT id FROM users WHERE email = $1", email)
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap()
+}
+
+async fn get_status(app: &TestApp, user_id: i32) -> i16 {
+    sqlx::query_scalar!("SELECT status FROM users WHERE id = $1", user_id)
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap()
+}
+
+async fn create_absence(
+    app: &TestApp,
+    user_id: i32,
+    body: &serde_json::Value,
+    access: &str,
+) -> reqwest::Response {
+    reqwest::Client::new()
+        .post(format!("{}/v1/user/{}/absences", app.address, user_id))
+        .bearer_auth(access)
+        .json(body)
+        .send()
+        .await
+        .unwrap()
+}
+
+fn absence_body(starts_in: chrono::Duration, ends_in: chrono::Duration) -> serde_json::Value {
+    let now = Utc::now();
+
+    serde_json::json!({
+        "kind": "vacation",
+        "starts_at": now + starts_in,
+        "ends_at": now + ends_in,
+    })
+}
+
+// Ticket in progress, assigned only to the given user.
+async fn create_assigned_ticket(app: &TestApp, user_id: i32) -> i64 {
+    app.create_test_ticket().await
+        .error_for_status()
+        .unwrap();
+
+    let ticket_id = sqlx::query_scalar!("SELECT id FROM tickets ORDER BY id DESC LIMIT 1")
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+
+    sqlx::query!("INSERT INTO tickets_users (ticket_id, assigned_to) VALUES ($1, $2)", ticket_id, user_id)
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+
+    sqlx::query!("UPDATE tickets SET status = 2 WHERE id = $1", ticket_id)
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+
+    ticket_id
+}
+
+async fn get_assignees(app: &TestApp, ticket_id: i64) -> Vec<i32> {
+    sqlx::query_scalar!("SELECT assigned_to FROM tickets_users WHERE ticket_id = $1", ticket_id)
+        .fetch_all(&app.db_pool)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn absence_starting_now_changes_status() {
+    let app = spawn_app().await;
+    let email = app.create_user(UserRole::Employee).await;
+    let user_id = get_user_id(&app, &email).await;
+    let (access, _) = app.get_jwt_tokens(&email, "admin").await;
+
+    let body = absence_body(chrono::Duration::minutes(-1), chrono::Duration::days(7));
+    let resp = create_absence(&app, user_id, &body, &access).await;
+
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    assert_eq!(get_status(&app, user_id).await, UserStatus::Vacation as i16);
+
+    let absences: serde_json::Value = reqwest::Client::new()
+        .get(format!("{}/v1/user/{}/absences", app.address, user_id))
+        .bearer_auth(&access)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    assert_eq!(absences[0]["kind"], "vacation");
+    assert_eq!(absences[0]["started"], true);
+}
+
+#[tokio::test]
+async fn planned_absence_does_not_change_status() {
+    let app = spawn_app().await;
+    let email = app.create_user(UserRole::Employee).await;
+    let user_id = get_user_id(&app, &email).await;
+    let (access, _) = app.get_jwt_tokens(&email, "admin").await;
+
+    let body = absence_body(chrono::Duration::days(1), chrono::Duration::days(7));
+    let resp = create_absence(&app, user_id, &body, &access).await;
+
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    assert_eq!(get_status(&app, user_id).await, UserStatus::Available as i16);
+}
+
+#[tokio::test]
+async fn scheduler_restores_status_when_absence_ends() {
+    let app = spawn_app_with(|c| c.absences.check_interval_seconds = 1).await;
+    let email = app.create_user(UserRole::Employee).await;
+    let user_id = get_user_id(&app, &email).await;
+    let (access, _) = app.get_jwt_tokens(&email, "admin").await;
+
+    let body = absence_body(chrono::Duration::minutes(-1), chrono::Duration::seconds(2));
+    create_absence(&app, user_id, &body, &access).await
+        .error_for_status()
+        .unwrap();
+
+    assert_eq!(get_status(&app, user_id).await, UserStatus::Vacation as i16);
+
+    let mut status = UserStatus::Vacation as i16;
+
+    for _ in 0..20 {
+        tokio::time::sleep(Duration::from_millis(500)).await;
+
+        status = get_status(&app, user_id).await;
+
+        if status == UserStatus::Available as i16 {
+            break;
+        }
+    }
+
+    assert_eq!(status, UserStatus::Available as i16);
+}
+
+#[tokio::test]
+async fn open_tickets_are_reassigned_to_deputy() {
+    let app = spawn_app().await;
+    let email = app.create_user(UserRole::Employee).await;
+    let user_id = get_user_id(&app, &email).await;
+    let deputy_email = app.create_user(UserRole::Employee).await;
+    let deputy_id = get_user_id(&app, &deputy_email).await;
+    let (access, _) = app.get_jwt_tokens(&email, "admin").await;
+
+    let ticket_id = create_assigned_ticket(&app, user_id).await;
+
+    let mut body = absence_body(chrono::Duration::minutes(-1), chrono::Duration::days(7));
+    body["deputy_id"] = deputy_id.into();
+    body["reassign_tickets"] = true.into();
+
+    create_absence(&app, user_id, &body, &access).await
+        .error_for_status()
+        .unwrap();
+
+    assert_eq!(get_assignees(&app, ticket_id).await, vec![deputy_id]);
+}
+
+#[tokio::test]
+async fn tickets_outside_deputy_departments_return_to_queue() {
+    let app = spawn_app().await;
+    let email = app.create_user(UserRole::Employee).await;
+    let user_id = get_user_id(&app, &email).await;
+    let deputy_email = app.create_user(UserRole::Employee).await;
+    let deputy_id = get_user_id(&app, &deputy_email).await;
+    let (access, _) = app.get_jwt_tokens(&email, "admin").await;
+
+    sqlx::query!("DELETE FROM user_departments WHERE user_id = $1", deputy_id)
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+
+    let ticket_id = create_assigned_ticket(&app, user_id).await;
+
+    let mut body = absence_body(chrono::Duration::minutes(-1), chrono::Duration::days(7));
+    body["deputy_id"] = deputy_id.into();
+    body["reassign_tickets"] = true.into();
+
+    create_absence(&app, user_id, &body, &access).await
+        .error_for_status()
+        .unwrap();
+
+    assert!(get_assignees(&app, ticket_id).await.is_empty());
+
+    let status = sqlx::query_scalar!("SELECT status FROM tickets WHERE id = $1", ticket_id)
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+
+    assert_eq!(status, 0);
+}
+
+#[tokio::test]
+async fn open_tickets_return_to_queue_without_deputy() {
+    let app = spawn_app().await;
+    let email = app.create_user(UserRole::Employee).await;
+    let user_id = get_user_id(&app, &email).await;
+    let (access, _) = app.get_jwt_tokens(&email, "admin").await;
+
+    let ticket_id = create_assigned_ticket(&app, user_id).await;
+
+    let mut body = absence_body(chrono::Duration::minutes(-1), chrono::Duration::days(7));
+    body["reassign_tickets"] = true.into();
+
+    create_absence(&app, user_id, &body, &access).await
+        .error_for_status()
+        .unwrap();
+
+    assert!(get_assignees(&app, ticket_id).await.is_empty());
+
+    let status = sqlx::query_scalar!("SELECT status FROM tickets WHERE id = $1", ticket_id)
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+
+    assert_eq!(status, 0);
+}
+
+#[tokio::test]
+async fn overlapping_absence_returns_409() {
+    let app = spawn_app().await;
+    let email = app.create_user(UserRole::Employee).await;
+    let user_id = get_user_id(&app, &email).await;
+    let (access, _) = app.get_jwt_tokens(&email, "admin").await;
+
+    let body = absence_body(chrono::Duration::days(1), chrono::Duration::days(7));
+    create_absence(&app, user_id, &body, &access).await
+        .error_for_status()
+        .unwrap();
+
+    let body = absence_body(chrono::Duration::days(6), chrono::Duration::days(10));
+    let resp = create_absence(&app, user_id, &body, &access).await;
+
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn concurrent_overlapping_absences_are_not_both_created() {
+    let app = spawn_app().await;
+    let email = app.create_user(UserRole::Employee).await;
+    let user_id = get_user_id(&app, &email).await;
+    let (access, _) = app.get_jwt_tokens(&email, "admin").await;
+
+    let first = absence_body(chrono::Duration::days(1), chrono::Duration::days(7));
+    let second = absence_body(chrono::Duration::days(6), chrono::Duration::days(10));
+
+    let (first, second) = tokio::join!(
+        create_absence(&app, user_id, &first, &access),
+        create_absence(&app, user_id, &second, &access),
+    );
+
+    let mut statuses = [first.status(), second.status()];
+    statuses.sort();
+    assert_eq!(statuses, [StatusCode::CREATED, StatusCode::CONFLICT]);
+}
+
+#[tokio::test]
+async fn invalid_absence_returns_400() {
+    let app = spawn_app().await;
+    let email = app.create_user(UserRole::Employee).await;
+    let user_id = get_user_id(&app, &email).await;
+    let (access, _) = app.get_jwt_tokens(&email, "admin").await;
+
+    let mut available = absence_body(chrono::Duration::days(1), chrono::Duration::days(7));
+    available["kind"] = "available".into();
+
+    let mut self_deputy = absence_body(chrono::Duration::days(1), chrono::Duration::days(7));
+    self_deputy["deputy_id"] = user_id.into();
+
+    let client_email = app.create_user(UserRole::Client).await;
+    let mut client_deputy = absence_body(chrono::Duration::days(1), chrono::Duration::days(7));
+    client_deputy["deputy_id"] = get_user_id(&app, &client_email).await.into();
+
+    for body in [
+        available,
+        self_deputy,
+        client_deputy,
+        absence_body(chrono::Duration::days(7), chrono::Duration::days(1)),
+        absence_body(chrono::Duration::days(-7), chrono::Duration::days(-1)),
+    ] {
+        let resp = create_absence(&app, user_id, &body, &access).await;
+
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST, "{}", body);
+    }
+}
+
+#[tokio::test]
+async fn managing_absences_of_others_requires_permission() {
+    let app = spawn_app().await;
+    let email = app.create_user(UserRole::Employee).await;
+    let other_email = app.create_user(UserRole::Employee).await;
+    let other_id = get_user_id(&app, &other_email).await;
+    let body = absence_body(chrono::Duration::days(1), chrono::Duration::days(7));
+
+    let (access, _) = app.get_jwt_tokens(&email, "admin").await;
+    let resp = create_absence(&app, other_id, &body, &access).await;
+
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    let (admin_access, _) = app.get_admin_jwt_tokens().await;
+    let resp = create_absence(&app, other_id, &body, &admin_access).await;
+
+    assert_eq!(resp.status(), StatusCode::CREATED);
+}
+
+#[tokio::test]
+async fn cancelling_ongoing_absence_restores_status() {
+    let app = spawn_app().await;
+    let email = app.create_user(UserRole::Employee).await;
+    let user_id = get_user_id(&app, &email).await;
+    let (access, _) = app.get_jwt_tokens(&email, "admin").await;
+
+    let body = absence_body(chrono::Duration::minutes(-1), chrono::Duration::days(7));
+    let json: serde_json::Value = create_absence(&app, user_id, &body, &access).await
+        .json()
+        .await
+        .unwrap();
+
+    let resp = reqwest::Client::new()
+        .delete(format!("{}/v1/user/{}/absences/{}", app.address, user_id, json["id"]))
+        .bearer_auth(&access)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+    assert_eq!(get_status(&app, user_id).await, UserStatus::Available as i16);
+
+    let finished = sqlx::query_scalar!("SELECT finished FROM user_absences WHERE user_id = $1", user_id)
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+
+    assert!(finished);
+}
\ No newline at end of file
@@ -138,4 +138,44 @@ async fn get_users_without_users_returns_200() {
         .unwrap();
 
     assert_eq!(resp.status(), 200);
+}
+
+#[tokio::test]
+async fn get_users_with_cursor_returns_next_batch() {
+    let app = spawn_app().await;
+
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    for _ in 0..12 {
+        app.create_user(ticketing_system::auth::types::UserRole::Client).await;
+    }
+
+    let json: serde_json::Value = reqwest::Client::new()
+        .get(format!("{}/v1/user/list?cursor=&page_size=10", app.address))
+        .bearer_auth(&access)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    assert_eq!(json["items"].as_array().unwrap().len(), 10);
+
+    let cursor = json["next_cursor"].as_str().unwrap();
+
+    let json: serde_json::Value = reqwest::Client::new()
+        .get(format!("{}/v1/user/list?cursor={}&page_size=10&with_total=true", app.address, cursor))
+        .bearer_auth(&access)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    // Seeded admin and client with the created users
+    assert_eq!(json["items"].as_array().unwrap().len(), 4);
+    assert_eq!(json["total_items"], 14);
+    assert!(json["next_cursor"].is_null());
 }
\ No newline at end of file
@@ -0,0 +1,144 @@
+use ticketing_system::auth::types::UserRole;
+
+use crate::helpers::{NEXT_USER_ID, TestApp, spawn_app};
+
+async fn impersonate(app: &TestApp, user_id: i32, access: &str) -> reqwest::Response {
+    reqwest::Client::new()
+        .post(format!("{}/v1/user/admin/impersonate/{}", app.address, user_id))
+        .bearer_auth(access)
+        .send()
+        .await
+        .unwrap()
+}
+
+async fn get_impersonation_token(app: &TestApp, user_id: i32) -> String {
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    let json: serde_json::Value = impersonate(app, user_id, &access).await
+        .error_for_status()
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    json["access_token"].as_str().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn impersonate_returns_token_of_the_user() {
+    let app = spawn_app().await;
+
+    app.create_user(UserRole::Client).await;
+
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    let resp = impersonate(&app, NEXT_USER_ID, &access).await;
+
+    assert_eq!(resp.status(), 200);
+
+    let json: serde_json::Value = resp.json().await.unwrap();
+
+    assert!(json.get("refresh_token").is_none());
+    assert!(json["expires_in"].as_i64().unwrap() <= 15 * 60);
+
+    let me: serde_json::Value = reqwest::Client::new()
+        .get(format!("{}/v1/auth/me", app.address))
+        .bearer_auth(json["access_token"].as_str().unwrap())
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    assert_eq!(me["id"], NEXT_USER_ID);
+    assert_eq!(me["impersonated_by"], 1);
+}
+
+#[tokio::test]
+async fn write_operations_while_impersonating_return_403() {
+    let app = spawn_app().await;
+
+    app.create_user(UserRole::Client).await;
+
+    let token = get_impersonation_token(&app, NEXT_USER_ID).await;
+
+    let resp = reqwest::Client::new()
+        .put(format!("{}/v1/user/profile", app.address))
+        .bearer_auth(&token)
+        .json(&serde_json::json!({ "name": "Impersonated" }))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), 403);
+
+    let resp = reqwest::Client::new()
+        .get(format!("{}/v1/tickets/", app.address))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), 200);
+}
+
+#[tokio::test]
+async fn impersonate_admin_returns_403() {
+    let app = spawn_app().await;
+
+    app.create_user(UserRole::Admin).await;
+
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    let resp = impersonate(&app, NEXT_USER_ID, &access).await;
+
+    assert_eq!(resp.status(), 403);
+}
+
+#[tokio::test]
+async fn impersonate_non_existent_user_returns_404() {
+    let app = spawn_app().await;
+
+    let (access, _) = app.get_admin_jwt_tokens().await;
+
+    let resp = impersonate(&app, 100, &access).await;
+
+    assert_eq!(resp.status(), 404);
+}
+
+#[tokio::test]
+async fn impersonate_as_moderator_returns_403() {
+    let app = spawn_app().await;
+
+    let email = app.create_user(UserRole::Moderator).await;
+    app.create_user(UserRole::Client).await;
+
+    let (access, _) = app.get_jwt_tokens(&email, "admin").await;
+
+    let resp = impersonate(&app, NEXT_USER_ID + 1, &access).await;
+
+    assert_eq!(resp.status(), 403);
+}
+
+#[tokio::test]
+async fn impersonation_is_recorded_in_audit_log() {
+    let app = spawn_app().await;
+
+    app.create_user(UserRole::Client).await;
+
+    get_impersonation_token(&app, NEXT_USER_ID).await;
+
+    let exists = sqlx::query_scalar!(
+        r#"SELECT EXISTS(
+            SELECT 1 FROM audit_log
+            WHERE action = 'impersonation_started' AND actor_id = 1 AND target_id = $1
+        ) AS "exists!""#,
+        NEXT_USER_ID
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+
+    assert!(exists);
+}
\ No newline at end of file
@@ -5,4 +5,7 @@ mod change_user_status;
 mod toggle_user_active;
 mod update_profile;
 mod update_avatar;
-mod get_stats;
\ No newline at end of file
+mod get_stats;
+mod tokens;
+mod impersonate;
+mod absences;
\ No newline at end of file
@@ -0,0 +1,147 @@
+use reqwest::StatusCode;
+use ticketing_system::auth::types::UserRole;
+
+use crate::helpers::{spawn_app, TestApp};
+
+async fn create_token(app: &TestApp, access: &str, body: serde_json::Value) -> reqwest::Response {
+    reqwest::Client::new()
+        .post(format!("{}/v1/user/tokens", app.address))
+        .bearer_auth(access)
+        .json(&body)
+        .send()
+        .await
+        .expect("Failed to execute request")
+}
+
+fn token_body(role: Option<&str>, scopes: &[&str]) -> serde_json::Value {
+    serde_json::json!({
+        "name": "monitoring",
+        "expires_at": chrono::Utc::now() + chrono::Duration::days(30),
+        "role": role,
+        "scopes": scopes,
+    })
+}
+
+// Returns id and token
+async fn create_employee_token(app: &TestApp, role: Option<&str>, scopes: &[&str]) -> (i64, String) {
+    let email = app.create_user(UserRole::Employee).await;
+    let (access, _) = app.get_jwt_tokens(&email, "admin").await;
+
+    let json: serde_json::Value = create_token(app, &access, token_body(role, scopes)).await
+        .error_for_status()
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    (json["id"].as_i64().unwrap(), json["token"].as_str().unwrap().to_string())
+}
+
+#[tokio::test]
+async fn token_is_shown_once_and_authenticates_requests() {
+    let app = spawn_app().await;
+    let email = app.create_user(UserRole::Employee).await;
+    let (access, _) = app.get_jwt_tokens(&email, "admin").await;
+
+    let resp = create_token(&app, &access, token_body(None, &[])).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+
+    let json: serde_json::Value = resp.json().await.unwrap();
+    let token = json["token"].as_str().unwrap();
+    assert!(token.starts_with("pat_"));
+    assert_eq!(json["role"], "employee");
+
+    let resp = app.get("/v1/auth/me", token).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let me: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(me["email"], email);
+
+    let list: Vec<serde_json::Value> = app.get("/v1/user/tokens", &access).await
+        .json()
+        .await
+        .unwrap();
+
+    assert_eq!(list.len(), 1);
+    assert!(list[0].get("token").is_none());
+    assert!(list[0]["last_used_at"].is_string());
+}
+
+#[tokio::test]
+async fn token_role_cannot_exceed_owner_role() {
+    let app = spawn_app().await;
+    let email = app.create_user(UserRole::Employee).await;
+    let (access, _) = app.get_jwt_tokens(&email, "admin").await;
+
+    let resp = create_token(&app, &access, token_body(Some("admin"), &[])).await;
+
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn token_with_lower_role_is_limited_by_it() {
+    let app = spawn_app().await;
+    let (_, token) = create_employee_token(&app, Some("client"), &[]).await;
+
+    let resp = app.get("/v1/user/list", &token).await;
+
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn token_is_limited_to_its_scopes() {
+    let app = spawn_app().await;
+    let (_, token) = create_employee_token(&app, None, &["/v1/auth/me"]).await;
+
+    assert_eq!(app.get("/v1/auth/me", &token).await.status(), StatusCode::OK);
+    assert_eq!(app.get("/v1/user/list", &token).await.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn revoked_token_is_rejected() {
+    let app = spawn_app().await;
+    let email = app.create_user(UserRole::Employee).await;
+    let (access, _) = app.get_jwt_tokens(&email, "admin").await;
+
+    let json: serde_json::Value = create_token(&app, &access, token_body(None, &[])).await
+        .json()
+        .await
+        .unwrap();
+
+    let resp = reqwest::Client::new()
+        .delete(format!("{}/v1/user/tokens/{}", app.address, json["id"]))
+        .bearer_auth(&access)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+
+    let resp = app.get("/v1/auth/me", json["token"].as_str().unwrap()).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn token_cannot_manage_tokens() {
+    let app = spawn_app().await;
+    let (_, token) = create_employee_token(&app, None, &[]).await;
+
+    let resp = create_token(&app, &token, token_body(None, &[])).await;
+
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn expired_token_cannot_be_created() {
+    let app = spawn_app().await;
+    let email = app.create_user(UserRole::Employee).await;
+    let (access, _) = app.get_jwt_tokens(&email, "admin").await;
+
+    let resp = create_token(&app, &access, serde_json::json!({
+        "name": "monitoring",
+        "expires_at": chrono::Utc::now() - chrono::Duration::days(1),
+    }))
+    .await;
+
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
\ No newline at end of file